@@ -1,9 +1,19 @@
+use crate::api::error::Result as BiliResult;
 use reqwest::{header, Client, ClientBuilder, RequestBuilder, Response};
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Semaphore;
 use tokio::time::sleep;
 
+/// 翻页钩子
+///
+/// 分页模块在请求每一页之前调用,返回错误时中止翻页。
+/// 调用方借此在长列表的翻页过程中插入自己的逻辑 (如后台任务响应暂停和取消)。
+pub type PageHook =
+    Arc<dyn Fn() -> Pin<Box<dyn Future<Output = BiliResult<()>> + Send>> + Send + Sync>;
+
 /// B站API HTTP客户端
 ///
 /// 提供以下功能:
@@ -35,9 +45,11 @@ pub struct BiliClient {
     client: Client,
     cookie: Option<String>,
     rate_limiter: Arc<Semaphore>,
+    concurrency: usize,
     max_retries: usize,
     min_delay_ms: u64,
     max_delay_ms: u64,
+    page_hook: Option<PageHook>,
 }
 
 impl BiliClient {
@@ -90,9 +102,11 @@ impl BiliClient {
             client,
             cookie: None,
             rate_limiter: Arc::new(Semaphore::new(2)), // 每秒最多2个请求
+            concurrency: 2,
             max_retries: 3,
             min_delay_ms: 1000,
             max_delay_ms: 3000,
+            page_hook: None,
         }
     }
 
@@ -110,9 +124,25 @@ impl BiliClient {
     /// ```
     pub fn with_rate_limit(mut self, permits: usize) -> Self {
         self.rate_limiter = Arc::new(Semaphore::new(permits));
+        self.concurrency = permits;
         self
     }
 
+    /// 获取并发请求数量限制
+    ///
+    /// 并发翻页时以此作为同时工作的请求数,确保不超出共享的限流额度。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// # use bilibili_backup_tauri::api::BiliClient;
+    /// let client = BiliClient::new().with_rate_limit(3);
+    /// assert_eq!(client.max_concurrency(), 3);
+    /// ```
+    pub fn max_concurrency(&self) -> usize {
+        self.concurrency
+    }
+
     /// 设置最大重试次数
     ///
     /// # 参数
@@ -400,6 +430,30 @@ impl BiliClient {
         unreachable!()
     }
 
+    /// 设置翻页钩子
+    ///
+    /// # 参数
+    ///
+    /// * `hook` - 每请求一页之前调用的钩子
+    pub fn with_page_hook(mut self, hook: PageHook) -> Self {
+        self.page_hook = Some(hook);
+        self
+    }
+
+    /// 运行翻页钩子
+    ///
+    /// 未设置钩子时直接返回。
+    ///
+    /// # 错误
+    ///
+    /// - 钩子返回的错误 (如任务已取消)
+    pub async fn before_page(&self) -> BiliResult<()> {
+        match self.page_hook {
+            Some(ref hook) => hook().await,
+            None => Ok(()),
+        }
+    }
+
     /// 随机延迟 (防风控)
     ///
     /// 延迟时间在 min_delay_ms 到 max_delay_ms 之间随机选择
@@ -494,6 +548,7 @@ mod tests {
         let client = BiliClient::new().with_rate_limit(5);
         // Semaphore的available_permits()方法需要获取许可才能检查,所以我们只验证构建成功
         assert!(client.get_cookie().is_none());
+        assert_eq!(client.max_concurrency(), 5);
     }
 
     #[test]
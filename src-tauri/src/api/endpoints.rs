@@ -249,7 +249,7 @@ pub const API_BANGUMI_UNFOLLOW: &str = "https://api.bilibili.com/pgc/web/follow/
 /// - `view_at` - 查看时间戳 (第一页不传)
 /// - `business` - 业务类型
 ///
-/// 返回: `CursorPageData<History>`
+/// 返回: `{cursor: {max, view_at, business}, list: Vec<History>}`
 pub const API_HISTORY_LIST: &str = "https://api.bilibili.com/x/web-interface/history/cursor";
 
 /// 删除历史记录
@@ -337,6 +337,7 @@ pub const API_VIDEO_PAGELIST: &str = "https://api.bilibili.com/x/player/pagelist
 /// 参数:
 /// - `session_type` - 会话类型 (1:私信)
 /// - `begin_seqno` - 起始序号
+/// - `end_ts` - 翻页游标 (上一页最后一个会话的 `session_ts`)
 /// - `size` - 数量
 pub const API_SESSION_LIST: &str = "https://api.vc.bilibili.com/session_svr/v1/session_svr/get_sessions";
 
@@ -349,6 +350,30 @@ pub const API_SESSION_LIST: &str = "https://api.vc.bilibili.com/session_svr/v1/s
 /// - `size` - 数量
 pub const API_SESSION_MSGS: &str = "https://api.vc.bilibili.com/svr_sync/v1/svr_sync/fetch_session_msgs";
 
+// ==================== 动态相关 ====================
+
+/// 获取用户空间动态 (游标分页)
+///
+/// 参数:
+/// - `host_mid` - 用户ID
+/// - `offset` - 翻页游标 (第一页不传)
+///
+/// 返回: `{items, has_more, offset}`
+pub const API_DYNAMIC_SPACE: &str = "https://api.bilibili.com/x/polymer/web-dynamic/v1/feed/space";
+
+// ==================== 评论相关 ====================
+
+/// 获取评论区评论 (游标分页)
+///
+/// 参数:
+/// - `oid` - 目标ID
+/// - `type` - 评论区类型 (1:视频)
+/// - `mode` - 排序方式 (2:按时间 3:按热度)
+/// - `pagination_str` - 翻页游标 (`{"offset":"..."}`)
+///
+/// 返回: `{replies, cursor: {is_end, pagination_reply: {next_offset}}}`
+pub const API_REPLY_MAIN: &str = "https://api.bilibili.com/x/v2/reply/wbi/main";
+
 // ==================== 弹幕相关 ====================
 
 /// 获取弹幕列表 (protobuf格式)
@@ -30,6 +30,22 @@ pub enum BiliError {
     /// IO错误
     #[error("IO错误: {0}")]
     IoError(#[from] std::io::Error),
+
+    /// 数据库错误
+    #[error("数据库错误: {0}")]
+    DatabaseError(#[from] rusqlite::Error),
+
+    /// 加密或解密失败
+    #[error("加密错误: {0}")]
+    CryptoError(String),
+
+    /// 数据由更新版本的应用创建
+    #[error("版本不兼容: {0}")]
+    NewerVersion(String),
+
+    /// 任务被用户取消
+    #[error("任务已取消")]
+    Cancelled,
 }
 
 /// 统一的Result类型
@@ -61,6 +77,26 @@ impl BiliError {
         Self::IoError(std::io::Error::new(std::io::ErrorKind::Other, msg.into()))
     }
 
+    /// 创建加密错误
+    pub fn crypto(msg: impl Into<String>) -> Self {
+        Self::CryptoError(msg.into())
+    }
+
+    /// 创建版本不兼容错误 (数据由更新版本的应用创建)
+    pub fn newer_version(msg: impl Into<String>) -> Self {
+        Self::NewerVersion(msg.into())
+    }
+
+    /// 是否为版本不兼容 (数据由更新版本的应用创建)
+    pub fn is_newer_version(&self) -> bool {
+        matches!(self, Self::NewerVersion(_))
+    }
+
+    /// 是否为任务取消
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled)
+    }
+
     /// 创建解析错误
     pub fn parse(msg: impl Into<String>) -> Self {
         Self::ParamError(format!("解析错误: {}", msg.into()))
@@ -66,26 +66,6 @@ impl<T> PageData<T> {
     }
 }
 
-/// 游标分页数据结构
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CursorPageData<T> {
-    /// 数据列表
-    pub list: Vec<T>,
-    /// 下一页游标
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub cursor: Option<String>,
-    /// 是否还有更多数据
-    #[serde(default)]
-    pub has_more: bool,
-}
-
-impl<T> CursorPageData<T> {
-    /// 获取数据项
-    pub fn items(&self) -> &[T] {
-        &self.list
-    }
-}
-
 /// 普通分页数据包装(兼容B站API)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NormalPageData<T> {
@@ -354,6 +334,16 @@ pub struct Media {
     pub bv_id: Option<String>,
 }
 
+/// 失效收藏项的占位标题
+pub const INVALID_MEDIA_TITLE: &str = "已失效视频";
+
+impl Media {
+    /// 是否已失效 (被删除或下架)
+    pub fn is_invalid(&self) -> bool {
+        self.attr.is_some_and(|attr| attr & 1 == 1) || self.title == INVALID_MEDIA_TITLE
+    }
+}
+
 // ==================== 追番追剧 ====================
 
 /// 番剧/追剧
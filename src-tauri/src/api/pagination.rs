@@ -1,8 +1,9 @@
 /// 分页数据获取模块
 ///
 /// 提供通用的分页数据获取功能,支持:
-/// - 普通分页 (页码分页)
-/// - 游标分页 (cursor分页)
+/// - 普通分页 (页码分页, 已知总数时并发获取剩余页)
+/// - 游标分页 (cursor分页, 通过 [`CursorSpec`] 描述不同接口的游标规则)
+/// - 翻页钩子 (每请求一页之前调用客户端上设置的 [`PageHook`](crate::api::client::PageHook))
 ///
 /// # 示例
 ///
@@ -29,12 +30,143 @@
 
 use crate::api::client::BiliClient;
 use crate::api::error::{BiliError, Result};
-use crate::api::models::{ApiResult, CursorPageData, PageData};
+use crate::api::models::{ApiResult, PageData};
 use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+/// 翻页接口的页数上限错误码
+///
+/// B站会静默限制部分列表的可访问页数 (如他人的关注列表只能访问前5页),
+/// 超出后返回该错误码。遇到时视为数据到此为止,由 [`Completeness`] 标记截断。
+pub const PAGE_LIMIT_CODE: i32 = 22007;
+
+/// 分页获取的完整性统计
+///
+/// 对比接口报告的总数 (`total`/`media_count`) 与实际获取的数量,
+/// 用于发现被B站静默截断的列表,并写入备份清单。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Completeness {
+    /// 接口报告的总数 (未知时为None)
+    pub expected: Option<usize>,
+    /// 实际获取的数量
+    pub fetched: usize,
+    /// 是否被截断 (实际获取数量少于接口报告的总数,或翻页提前停止)
+    pub truncated: bool,
+}
+
+impl Completeness {
+    /// 根据预期数量和实际数量创建统计
+    pub fn new(expected: Option<usize>, fetched: usize) -> Self {
+        Self {
+            expected,
+            fetched,
+            truncated: expected.is_some_and(|expected| fetched < expected),
+        }
+    }
+
+    /// 翻页提前停止时的统计
+    ///
+    /// 游标分页的接口不报告总数,但翻页因达到上限、游标重复或缺少下一页游标而停止时,
+    /// 可以确定数据不完整。
+    pub fn stopped_early(fetched: usize) -> Self {
+        Self {
+            expected: None,
+            fetched,
+            truncated: true,
+        }
+    }
+
+    /// 合并另一组统计 (如多个收藏夹汇总)
+    ///
+    /// 任一方总数未知时,合并后的总数也未知;任一方被截断时,合并后也视为截断。
+    pub fn merge(&mut self, other: &Completeness) {
+        self.expected = self.expected.zip(other.expected).map(|(a, b)| a + b);
+        self.fetched += other.fetched;
+        self.truncated |= other.truncated;
+    }
+
+    /// 生成可读的摘要 (如 "预期 120 条，实际获取 100 条（不完整）")
+    pub fn summary(&self) -> String {
+        match self.expected {
+            Some(expected) if self.truncated => {
+                format!("预期 {} 条，实际获取 {} 条（不完整）", expected, self.fetched)
+            }
+            Some(expected) => format!("预期 {} 条，实际获取 {} 条", expected, self.fetched),
+            None if self.truncated => format!("实际获取 {} 条（不完整）", self.fetched),
+            None => format!("实际获取 {} 条", self.fetched),
+        }
+    }
+}
+
+/// 分页获取结果 (数据项 + 完整性统计)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchOutcome<T> {
+    /// 获取到的数据项
+    pub items: Vec<T>,
+    /// 完整性统计
+    pub completeness: Completeness,
+}
+
+impl<T> FetchOutcome<T> {
+    /// 根据数据项和接口报告的总数创建结果
+    pub fn new(items: Vec<T>, expected: Option<usize>) -> Self {
+        let completeness = Completeness::new(expected, items.len());
+        Self { items, completeness }
+    }
+
+    /// 是否被截断
+    pub fn is_truncated(&self) -> bool {
+        self.completeness.truncated
+    }
+
+    /// 取出数据项
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+}
+
+/// 可分页响应
+///
+/// 描述页码分页接口单页响应中的数据项和总数,
+/// 使不同结构的响应 (如 `PageData`、收藏夹内容) 可以共用并发翻页逻辑。
+pub trait PageItems<T> {
+    /// 接口报告的总数 (未知时返回None)
+    fn total(&self) -> Option<usize>;
+
+    /// 接口实际使用的每页大小 (未知时返回None)
+    fn page_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// 取出本页数据项
+    fn into_items(self) -> Vec<T>;
+}
+
+impl<T> PageItems<T> for PageData<T> {
+    fn total(&self) -> Option<usize> {
+        Some(self.total)
+    }
+
+    fn page_size(&self) -> Option<usize> {
+        self.ps
+    }
+
+    fn into_items(self) -> Vec<T> {
+        self.list
+    }
+}
 
 /// 获取所有分页数据 (普通分页)
 ///
-/// 自动处理分页逻辑,获取所有数据直到没有更多数据为止。
+/// 先请求第一页获取 `total`,再在共享的限流额度内并发获取剩余页,
+/// 最后按页码顺序重新组装。
 ///
 /// # 类型参数
 ///
@@ -79,125 +211,334 @@ pub async fn fetch_all_pages<T>(
     max_pages: Option<usize>,
 ) -> Result<Vec<T>>
 where
-    T: DeserializeOwned,
+    T: DeserializeOwned + Send + 'static,
 {
-    let mut all_items = Vec::new();
-    let mut page = 1;
+    fetch_all_pages_with_outcome(client, base_url, page_size, max_pages)
+        .await
+        .map(FetchOutcome::into_items)
+}
 
-    loop {
-        // 检查是否达到最大页数
-        if let Some(max) = max_pages {
-            if page > max {
-                break;
-            }
-        }
+/// 获取所有分页数据,并返回完整性统计
+///
+/// 与 [`fetch_all_pages`] 相同,但会对比 `total` 与实际获取的数量,
+/// 在列表被截断时 (页数上限、失效条目被过滤等) 通过 [`FetchOutcome`] 标记。
+pub async fn fetch_all_pages_with_outcome<T>(
+    client: &BiliClient,
+    base_url: &str,
+    page_size: usize,
+    max_pages: Option<usize>,
+) -> Result<FetchOutcome<T>>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    fetch_pages_from::<PageData<T>, T>(client, base_url, page_size, 1, max_pages).await
+}
 
-        // 构建URL
-        let separator = if base_url.contains('?') { '&' } else { '?' };
-        let url = format!("{}{}pn={}&ps={}", base_url, separator, page, page_size);
+/// 从指定页开始获取所有分页数据
+///
+/// 第一页顺序请求,得到总数后剩余页交给 [`fetch_pages_concurrent`]。
+/// 总数未知时退化为逐页请求,直到返回空页为止。
+/// 返回结果中的完整性统计以第一页报告的总数为准。
+pub async fn fetch_pages_from<D, T>(
+    client: &BiliClient,
+    base_url: &str,
+    page_size: usize,
+    start_page: usize,
+    max_pages: Option<usize>,
+) -> Result<FetchOutcome<T>>
+where
+    D: DeserializeOwned + PageItems<T> + Send + 'static,
+    T: Send + 'static,
+{
+    if max_pages == Some(0) {
+        return Ok(FetchOutcome::new(Vec::new(), None));
+    }
+    let last_allowed = max_pages.map(|max| start_page + max - 1);
 
-        // 发送请求
-        let response = client.get_with_retry(&url).await?;
+    // 1. 获取第一页
+    let first = match fetch_page::<D>(client, &page_url(base_url, start_page, page_size)).await? {
+        PageResponse::Data(Some(first)) => first,
+        PageResponse::Data(None) | PageResponse::Limited => {
+            return Ok(FetchOutcome::new(Vec::new(), None))
+        }
+    };
+    let total = first.total();
+    let effective_size = first.page_size().filter(|&ps| ps > 0).unwrap_or(page_size);
+    let mut all_items = first.into_items();
+
+    // 2. 总数已知: 并发获取剩余页
+    if let Some(total) = total {
+        let mut last_page = start_page - 1 + total.div_ceil(effective_size.max(1));
+        if let Some(max) = last_allowed {
+            last_page = last_page.min(max);
+        }
+        if last_page > start_page {
+            client.delay_random().await;
+            let rest = fetch_pages_concurrent::<D, T>(
+                client,
+                base_url,
+                page_size,
+                start_page + 1..=last_page,
+            )
+            .await?;
+            all_items.extend(rest);
+        }
 
-        // 解析响应
-        let api_result: ApiResult<PageData<T>> = response.json().await?;
+        let outcome = FetchOutcome::new(all_items, Some(total));
+        if outcome.is_truncated() {
+            tracing::warn!("列表不完整: {} ({})", outcome.completeness.summary(), base_url);
+        }
+        return Ok(outcome);
+    }
 
-        // 检查API错误
-        if !api_result.is_success() {
-            return Err(BiliError::api(format!(
-                "API错误 [{}]: {}",
-                api_result.code, api_result.message
-            )));
+    // 3. 总数未知: 逐页获取直到空页
+    let mut page = start_page;
+    while !all_items.is_empty() {
+        client.before_page().await?;
+        page += 1;
+        if last_allowed.is_some_and(|max| page > max) {
+            break;
         }
 
-        // 获取数据
-        if let Some(page_data) = api_result.data {
-            let items = page_data.list;
-            let current_total = all_items.len() + items.len();
-            let has_more = current_total < page_data.total;
+        client.delay_random().await;
+        let items = match fetch_page::<D>(client, &page_url(base_url, page, page_size)).await? {
+            PageResponse::Data(Some(data)) => data.into_items(),
+            PageResponse::Data(None) | PageResponse::Limited => break,
+        };
+        if items.is_empty() {
+            break;
+        }
+        all_items.extend(items);
+    }
+
+    Ok(FetchOutcome::new(all_items, None))
+}
 
-            all_items.extend(items);
+/// 并发获取指定范围内的页
+///
+/// 同时工作的请求数等于客户端的并发限制 ([`BiliClient::max_concurrency`]),
+/// 每个工作者在两次请求之间随机延迟,结果按页码顺序返回。
+/// 任一页失败时中止其余请求并返回该错误;
+/// 触及页数上限 ([`PAGE_LIMIT_CODE`]) 的页按空页处理。
+///
+/// # 参数
+///
+/// * `client` - HTTP客户端
+/// * `base_url` - 基础URL (不含分页参数)
+/// * `page_size` - 每页大小
+/// * `pages` - 页码范围
+pub async fn fetch_pages_concurrent<D, T>(
+    client: &BiliClient,
+    base_url: &str,
+    page_size: usize,
+    pages: RangeInclusive<usize>,
+) -> Result<Vec<T>>
+where
+    D: DeserializeOwned + PageItems<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let (first_page, last_page) = (*pages.start(), *pages.end());
+    if first_page > last_page {
+        return Ok(Vec::new());
+    }
 
-            if !has_more {
-                break;
+    let page_count = last_page - first_page + 1;
+    let workers = client.max_concurrency().clamp(1, page_count);
+    let next_page = Arc::new(AtomicUsize::new(first_page));
+    let mut tasks = JoinSet::new();
+
+    for worker in 0..workers {
+        let client = client.clone();
+        let base_url = base_url.to_string();
+        let next_page = next_page.clone();
+
+        tasks.spawn(async move {
+            let mut fetched: Vec<(usize, Vec<T>)> = Vec::new();
+            loop {
+                client.before_page().await?;
+                let page = next_page.fetch_add(1, Ordering::SeqCst);
+                if page > last_page {
+                    break;
+                }
+                // 第一个工作者的首个请求紧接在第一页之后,其余请求均随机延迟,防风控
+                if worker > 0 || !fetched.is_empty() {
+                    client.delay_random().await;
+                }
+
+                let url = page_url(&base_url, page, page_size);
+                let items = match fetch_page::<D>(&client, &url).await? {
+                    PageResponse::Data(data) => {
+                        data.map(|data| data.into_items()).unwrap_or_default()
+                    }
+                    PageResponse::Limited => {
+                        tracing::warn!("第 {} 页超出接口页数上限", page);
+                        Vec::new()
+                    }
+                };
+                tracing::debug!("第 {} 页获取 {} 条", page, items.len());
+                fetched.push((page, items));
+            }
+            Ok::<_, BiliError>(fetched)
+        });
+    }
+
+    let mut pages_data: Vec<(usize, Vec<T>)> = Vec::with_capacity(page_count);
+    while let Some(joined) = tasks.join_next().await {
+        let fetched = joined
+            .map_err(|e| BiliError::business(format!("分页任务异常退出: {}", e)))
+            .and_then(|result| result);
+        match fetched {
+            Ok(fetched) => pages_data.extend(fetched),
+            Err(e) => {
+                tasks.abort_all();
+                return Err(e);
             }
-        } else {
-            break;
         }
+    }
 
-        page += 1;
+    pages_data.sort_by_key(|(page, _)| *page);
+    Ok(pages_data.into_iter().flat_map(|(_, items)| items).collect())
+}
 
-        // 随机延迟,防风控
-        client.delay_random().await;
+/// 单页请求结果
+enum PageResponse<D> {
+    /// 正常返回 (data为空时为None)
+    Data(Option<D>),
+    /// 超出接口页数上限
+    Limited,
+}
+
+/// 请求单页并检查API错误
+async fn fetch_page<D>(client: &BiliClient, url: &str) -> Result<PageResponse<D>>
+where
+    D: DeserializeOwned,
+{
+    let response = client.get_with_retry(url).await?;
+    let api_result: ApiResult<D> = response.json().await?;
+
+    if api_result.code == PAGE_LIMIT_CODE {
+        return Ok(PageResponse::Limited);
+    }
+    if !api_result.is_success() {
+        return Err(BiliError::api(format!(
+            "API错误 [{}]: {}",
+            api_result.code, api_result.message
+        )));
     }
 
-    Ok(all_items)
+    Ok(PageResponse::Data(api_result.data))
+}
+
+/// 构建带页码参数的URL
+fn page_url(base_url: &str, page: usize, page_size: usize) -> String {
+    let separator = if base_url.contains('?') { '&' } else { '?' };
+    format!("{}{}pn={}&ps={}", base_url, separator, page, page_size)
+}
+
+/// 游标分页的单页解析结果
+#[derive(Debug, Clone)]
+pub struct CursorPage<T, C> {
+    /// 本页数据项
+    pub items: Vec<T>,
+    /// 下一页游标 (None表示没有下一页)
+    pub next: Option<C>,
+    /// 是否还有更多数据
+    pub has_more: bool,
+}
+
+/// 游标分页规则
+///
+/// 描述如何从某个接口的响应中提取数据项和下一页游标,
+/// 以及如何把游标转换为下一次请求的查询参数。
+/// 不同接口的游标字段各不相同 (历史记录的 `max`+`view_at`+`business`,
+/// 动态的 `offset`, 评论的 `pagination_str`, 私信的 `end_ts`),
+/// 由实现方自行描述,翻页、终止和重复页检测统一由 [`fetch_cursor_pages`] 处理。
+pub trait CursorSpec {
+    /// 响应中 `data` 字段的类型
+    type Data: DeserializeOwned;
+    /// 数据项类型
+    type Item;
+    /// 游标类型
+    type Cursor: Clone + Eq + Hash + std::fmt::Debug;
+
+    /// 解析单页响应
+    fn parse_page(&self, data: Self::Data) -> CursorPage<Self::Item, Self::Cursor>;
+
+    /// 将游标转换为查询参数
+    fn cursor_params(&self, cursor: &Self::Cursor) -> Vec<(String, String)>;
 }
 
 /// 获取所有游标分页数据
 ///
-/// 自动处理游标分页逻辑,获取所有数据直到没有更多数据为止。
+/// 按照 `spec` 描述的规则翻页,直到满足以下任一条件:
+/// - 返回空页
+/// - `has_more` 为 false
+/// - `has_more` 为 true 但没有下一页游标
+/// - 下一页游标与已请求过的游标重复 (重复页)
+/// - 达到最大迭代次数
 ///
-/// # 类型参数
-///
-/// * `T` - 数据项类型 (必须实现 DeserializeOwned)
+/// 后三种情况说明还有数据没有获取,结果的完整性统计会标记为截断。
 ///
 /// # 参数
 ///
 /// * `client` - HTTP客户端
-/// * `base_url` - 基础URL
+/// * `base_url` - 基础URL (不含游标参数)
+/// * `spec` - 游标分页规则
 /// * `max_iterations` - 最大迭代次数 (防止无限循环)
 ///
 /// # 返回
 ///
-/// Result<Vec<T>, BiliError> - 所有数据项的列表
+/// Result<FetchOutcome<S::Item>, BiliError> - 所有数据项及完整性统计
 ///
 /// # 示例
 ///
 /// ```rust
-/// # use bilibili_backup_tauri::api::{BiliClient, pagination::fetch_cursor_pages};
+/// # use bilibili_backup_tauri::api::{BiliClient, pagination::{fetch_cursor_pages, DynamicCursorSpec}};
+/// # use bilibili_backup_tauri::api::endpoints::API_DYNAMIC_SPACE;
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 /// let client = BiliClient::new();
-/// let data = fetch_cursor_pages::<serde_json::Value>(
-///     &client,
-///     "https://api.bilibili.com/x/web-interface/history/cursor",
-///     100 // 最多100次迭代
-/// ).await?;
+/// let url = format!("{}?host_mid=123", API_DYNAMIC_SPACE);
+/// let dynamics = fetch_cursor_pages(&client, &url, &DynamicCursorSpec, 100).await?;
+/// println!("获取到 {} 条动态 ({})", dynamics.items.len(), dynamics.completeness.summary());
 /// # Ok(())
 /// # }
 /// ```
-pub async fn fetch_cursor_pages<T>(
+pub async fn fetch_cursor_pages<S>(
     client: &BiliClient,
     base_url: &str,
+    spec: &S,
     max_iterations: usize,
-) -> Result<Vec<T>>
+) -> Result<FetchOutcome<S::Item>>
 where
-    T: DeserializeOwned,
+    S: CursorSpec,
 {
     let mut all_items = Vec::new();
-    let mut cursor: Option<String> = None;
+    let mut cursor: Option<S::Cursor> = None;
+    let mut seen_cursors: HashSet<S::Cursor> = HashSet::new();
     let mut iteration = 0;
+    let mut stopped_early = false;
 
     loop {
+        client.before_page().await?;
+
         // 检查迭代次数
         if iteration >= max_iterations {
             tracing::warn!("达到最大迭代次数: {}", max_iterations);
+            stopped_early = true;
             break;
         }
 
         // 构建URL
-        let url = if let Some(ref c) = cursor {
-            let separator = if base_url.contains('?') { '&' } else { '?' };
-            format!("{}{}cursor={}", base_url, separator, c)
-        } else {
-            base_url.to_string()
+        let url = match cursor {
+            Some(ref c) => append_query(base_url, &spec.cursor_params(c)),
+            None => base_url.to_string(),
         };
 
         // 发送请求
         let response = client.get_with_retry(&url).await?;
 
         // 解析响应
-        let api_result: ApiResult<CursorPageData<T>> = response.json().await?;
+        let api_result: ApiResult<S::Data> = response.json().await?;
 
         // 检查API错误
         if !api_result.is_success() {
@@ -208,18 +549,33 @@ where
         }
 
         // 获取数据
-        if let Some(page_data) = api_result.data {
-            all_items.extend(page_data.list);
+        let page = match api_result.data {
+            Some(data) => spec.parse_page(data),
+            None => break,
+        };
 
-            // 检查是否还有更多数据
-            if !page_data.has_more || page_data.cursor.is_none() {
-                break;
-            }
+        if page.items.is_empty() {
+            break;
+        }
+        all_items.extend(page.items);
+
+        // 检查是否还有更多数据
+        if !page.has_more {
+            break;
+        }
+        let Some(next) = page.next else {
+            tracing::warn!("接口报告还有更多数据但没有下一页游标,停止翻页");
+            stopped_early = true;
+            break;
+        };
 
-            cursor = page_data.cursor;
-        } else {
+        // 重复页检测: 游标没有前进说明接口在返回同一页
+        if !seen_cursors.insert(next.clone()) {
+            tracing::warn!("游标重复 {:?},停止翻页", next);
+            stopped_early = true;
             break;
         }
+        cursor = Some(next);
 
         iteration += 1;
 
@@ -227,7 +583,153 @@ where
         client.delay_random().await;
     }
 
-    Ok(all_items)
+    let completeness = if stopped_early {
+        Completeness::stopped_early(all_items.len())
+    } else {
+        Completeness::new(None, all_items.len())
+    };
+    Ok(FetchOutcome {
+        items: all_items,
+        completeness,
+    })
+}
+
+/// 在URL后追加查询参数 (参数值会进行URL编码)
+fn append_query(base_url: &str, params: &[(String, String)]) -> String {
+    if params.is_empty() {
+        return base_url.to_string();
+    }
+
+    let query = params
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, urlencoding::encode(v)))
+        .collect::<Vec<_>>()
+        .join("&");
+    let separator = if base_url.contains('?') { '&' } else { '?' };
+    format!("{}{}{}", base_url, separator, query)
+}
+
+// ==================== 常用游标规则 ====================
+
+/// 空间动态游标规则
+///
+/// 响应: `{items, has_more, offset}`,下一页通过 `offset` 参数请求
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DynamicCursorSpec;
+
+impl CursorSpec for DynamicCursorSpec {
+    type Data = Value;
+    type Item = Value;
+    type Cursor = String;
+
+    fn parse_page(&self, mut data: Value) -> CursorPage<Value, String> {
+        let items = take_array(&mut data, "items");
+        let has_more = data.get("has_more").map(value_as_bool).unwrap_or(false);
+        let next = data
+            .get("offset")
+            .and_then(value_as_string)
+            .filter(|s| !s.is_empty());
+
+        CursorPage { items, next, has_more }
+    }
+
+    fn cursor_params(&self, cursor: &String) -> Vec<(String, String)> {
+        vec![("offset".to_string(), cursor.clone())]
+    }
+}
+
+/// 评论区游标规则
+///
+/// 响应: `{replies, cursor: {is_end, pagination_reply: {next_offset}}}`,
+/// 下一页通过 `pagination_str={"offset":"..."}` 参数请求
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReplyCursorSpec;
+
+impl CursorSpec for ReplyCursorSpec {
+    type Data = Value;
+    type Item = Value;
+    type Cursor = String;
+
+    fn parse_page(&self, mut data: Value) -> CursorPage<Value, String> {
+        let items = take_array(&mut data, "replies");
+        let cursor = data.get("cursor");
+        let is_end = cursor
+            .and_then(|c| c.get("is_end"))
+            .map(value_as_bool)
+            .unwrap_or(true);
+        let next = cursor
+            .and_then(|c| c.get("pagination_reply"))
+            .and_then(|p| p.get("next_offset"))
+            .and_then(value_as_string)
+            .filter(|s| !s.is_empty());
+
+        CursorPage {
+            items,
+            next,
+            has_more: !is_end,
+        }
+    }
+
+    fn cursor_params(&self, cursor: &String) -> Vec<(String, String)> {
+        let pagination = serde_json::json!({ "offset": cursor });
+        vec![("pagination_str".to_string(), pagination.to_string())]
+    }
+}
+
+/// 私信会话列表游标规则
+///
+/// 响应: `{session_list, has_more}`,下一页以本页最后一个会话的
+/// `session_ts` 作为 `end_ts` 参数请求
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SessionCursorSpec;
+
+impl CursorSpec for SessionCursorSpec {
+    type Data = Value;
+    type Item = Value;
+    type Cursor = i64;
+
+    fn parse_page(&self, mut data: Value) -> CursorPage<Value, i64> {
+        let items = take_array(&mut data, "session_list");
+        let has_more = data.get("has_more").map(value_as_bool).unwrap_or(false);
+        let next = items
+            .last()
+            .and_then(|s| s.get("session_ts"))
+            .and_then(|ts| ts.as_i64());
+
+        CursorPage { items, next, has_more }
+    }
+
+    fn cursor_params(&self, cursor: &i64) -> Vec<(String, String)> {
+        vec![("end_ts".to_string(), cursor.to_string())]
+    }
+}
+
+/// 取出JSON对象中的数组字段 (不存在或不是数组时返回空列表)
+///
+/// 数组从 `data` 中移出而不是复制,字段原来的位置留下null。
+fn take_array(data: &mut Value, field: &str) -> Vec<Value> {
+    match data.get_mut(field).map(Value::take) {
+        Some(Value::Array(items)) => items,
+        _ => Vec::new(),
+    }
+}
+
+/// 宽松解析布尔值 (B站部分接口用0/1表示布尔值)
+fn value_as_bool(value: &Value) -> bool {
+    match value {
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_i64().unwrap_or(0) != 0,
+        _ => false,
+    }
+}
+
+/// 宽松解析字符串 (兼容数字形式的游标)
+fn value_as_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
 }
 
 /// 获取单页数据
@@ -338,61 +840,19 @@ where
     }
 
     /// 获取所有数据
-    pub async fn fetch_all(self) -> Result<Vec<T>> {
-        let mut all_items = Vec::new();
-        let mut page = self.start_page;
-
-        loop {
-            // 检查是否达到最大页数
-            if let Some(max) = self.max_pages {
-                if page >= self.start_page + max {
-                    break;
-                }
-            }
-
-            // 构建URL
-            let separator = if self.base_url.contains('?') { '&' } else { '?' };
-            let url = format!(
-                "{}{}pn={}&ps={}",
-                self.base_url, separator, page, self.page_size
-            );
-
-            // 发送请求
-            let response = self.client.get_with_retry(&url).await?;
-
-            // 解析响应
-            let api_result: ApiResult<PageData<T>> = response.json().await?;
-
-            // 检查API错误
-            if !api_result.is_success() {
-                return Err(BiliError::api(format!(
-                    "API错误 [{}]: {}",
-                    api_result.code, api_result.message
-                )));
-            }
-
-            // 获取数据
-            if let Some(page_data) = api_result.data {
-                let items = page_data.list;
-                let current_total = all_items.len() + items.len();
-                let has_more = current_total < page_data.total;
-
-                all_items.extend(items);
-
-                if !has_more {
-                    break;
-                }
-            } else {
-                break;
-            }
-
-            page += 1;
-
-            // 随机延迟,防风控
-            self.client.delay_random().await;
-        }
-
-        Ok(all_items)
+    pub async fn fetch_all(self) -> Result<Vec<T>>
+    where
+        T: Send + 'static,
+    {
+        fetch_pages_from::<PageData<T>, T>(
+            self.client,
+            &self.base_url,
+            self.page_size,
+            self.start_page,
+            self.max_pages,
+        )
+        .await
+        .map(FetchOutcome::into_items)
     }
 }
 
@@ -422,4 +882,139 @@ mod tests {
         assert_eq!(fetcher.max_pages, None);
         assert_eq!(fetcher.start_page, 1);
     }
+
+    #[test]
+    fn test_page_url() {
+        assert_eq!(page_url("https://example.com/x", 2, 50), "https://example.com/x?pn=2&ps=50");
+        assert_eq!(
+            page_url("https://example.com/x?vmid=1", 3, 20),
+            "https://example.com/x?vmid=1&pn=3&ps=20"
+        );
+    }
+
+    #[test]
+    fn test_page_data_page_items() {
+        let page = PageData {
+            list: vec![1, 2, 3],
+            total: 45,
+            pn: Some(1),
+            ps: Some(3),
+        };
+        assert_eq!(PageItems::<i32>::total(&page), Some(45));
+        assert_eq!(PageItems::<i32>::page_size(&page), Some(3));
+        assert_eq!(page.into_items(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_completeness_truncated() {
+        let complete = Completeness::new(Some(100), 100);
+        assert!(!complete.truncated);
+        assert_eq!(complete.summary(), "预期 100 条，实际获取 100 条");
+
+        let truncated = Completeness::new(Some(2000), 1000);
+        assert!(truncated.truncated);
+        assert_eq!(truncated.summary(), "预期 2000 条，实际获取 1000 条（不完整）");
+
+        let unknown = Completeness::new(None, 30);
+        assert!(!unknown.truncated);
+        assert_eq!(unknown.summary(), "实际获取 30 条");
+
+        let stopped = Completeness::stopped_early(30);
+        assert!(stopped.truncated);
+        assert_eq!(stopped.summary(), "实际获取 30 条（不完整）");
+    }
+
+    #[test]
+    fn test_completeness_merge() {
+        let mut total = Completeness::new(Some(10), 10);
+        total.merge(&Completeness::new(Some(20), 18));
+        assert_eq!(total.expected, Some(30));
+        assert_eq!(total.fetched, 28);
+        assert!(total.truncated);
+
+        total.merge(&Completeness::new(None, 5));
+        assert_eq!(total.expected, None);
+        assert_eq!(total.fetched, 33);
+    }
+
+    #[test]
+    fn test_fetch_outcome_new() {
+        let outcome = FetchOutcome::new(vec![1, 2, 3], Some(5));
+        assert!(outcome.is_truncated());
+        assert_eq!(outcome.completeness.fetched, 3);
+        assert_eq!(outcome.into_items(), vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_pages_concurrent_empty_range() {
+        let client = BiliClient::new();
+        #[allow(clippy::reversed_empty_ranges)]
+        let items = fetch_pages_concurrent::<PageData<i32>, i32>(&client, "https://example.com", 20, 3..=2)
+            .await
+            .unwrap();
+        assert!(items.is_empty());
+    }
+
+    #[test]
+    fn test_append_query_encodes_values() {
+        let params = vec![("pagination_str".to_string(), "{\"offset\":\"a b\"}".to_string())];
+        let url = append_query("https://example.com/x?oid=1", &params);
+        assert_eq!(
+            url,
+            "https://example.com/x?oid=1&pagination_str=%7B%22offset%22%3A%22a%20b%22%7D"
+        );
+        assert_eq!(append_query("https://example.com/x", &[]), "https://example.com/x");
+    }
+
+    #[test]
+    fn test_dynamic_cursor_spec() {
+        let data = serde_json::json!({
+            "items": [{"id_str": "1"}, {"id_str": "2"}],
+            "has_more": true,
+            "offset": "987654321"
+        });
+        let page = DynamicCursorSpec.parse_page(data);
+        assert_eq!(page.items.len(), 2);
+        assert!(page.has_more);
+        assert_eq!(page.next.as_deref(), Some("987654321"));
+        assert_eq!(
+            DynamicCursorSpec.cursor_params(&"987654321".to_string()),
+            vec![("offset".to_string(), "987654321".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_reply_cursor_spec() {
+        let data = serde_json::json!({
+            "replies": [{"rpid": 1}],
+            "cursor": {"is_end": false, "pagination_reply": {"next_offset": "abc"}}
+        });
+        let page = ReplyCursorSpec.parse_page(data);
+        assert_eq!(page.items.len(), 1);
+        assert!(page.has_more);
+        assert_eq!(page.next.as_deref(), Some("abc"));
+
+        let end = ReplyCursorSpec.parse_page(serde_json::json!({
+            "replies": null,
+            "cursor": {"is_end": true}
+        }));
+        assert!(end.items.is_empty());
+        assert!(!end.has_more);
+        assert!(end.next.is_none());
+    }
+
+    #[test]
+    fn test_session_cursor_spec() {
+        let data = serde_json::json!({
+            "session_list": [{"session_ts": 300}, {"session_ts": 200}],
+            "has_more": 1
+        });
+        let page = SessionCursorSpec.parse_page(data);
+        assert!(page.has_more);
+        assert_eq!(page.next, Some(200));
+        assert_eq!(
+            SessionCursorSpec.cursor_params(&200),
+            vec![("end_ts".to_string(), "200".to_string())]
+        );
+    }
 }
@@ -0,0 +1,664 @@
+use crate::api::error::{BiliError, Result};
+use crate::api::models::{Bangumi, History, Relation, RelationTag, ToView, User};
+use crate::api::pagination::Completeness;
+use crate::backup::crypto;
+use crate::backup::images::ImageIndex;
+use crate::backup::manifest::{
+    BackupManifest, BackupModule, BackupSource, CatalogEntry, ManifestEntry,
+    ARCHIVE_SCHEMA_VERSION, IMAGES_FILE_NAME, MANIFEST_FILE_NAME, VIDEOS_FILE_NAME,
+};
+use crate::backup::schema::{decode_payload, encode_payload};
+use crate::backup::videos::VideoCatalog;
+use crate::services::favorites::FavFolderWithMedia;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// 备份数据
+///
+/// 每个字段对应一个备份模块,未备份的模块为None。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupData {
+    /// 关注分组
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relation_tags: Option<Vec<RelationTag>>,
+    /// 关注列表
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub following: Option<Vec<Relation>>,
+    /// 粉丝列表
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub followers: Option<Vec<Relation>>,
+    /// 黑名单
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blacklist: Option<Vec<User>>,
+    /// 收藏夹
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favorites: Option<Vec<FavFolderWithMedia>>,
+    /// 历史记录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<Vec<History>>,
+    /// 追番追剧
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bangumi: Option<Vec<Bangumi>>,
+    /// 稍后再看
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toview: Option<Vec<ToView>>,
+}
+
+impl BackupData {
+    /// 已包含的模块
+    pub fn modules(&self) -> Vec<BackupModule> {
+        BackupModule::ALL
+            .into_iter()
+            .filter(|&module| self.count(module).is_some())
+            .collect()
+    }
+
+    /// 合并另一份备份数据 (对方包含的模块覆盖当前数据)
+    pub fn merge(&mut self, other: BackupData) {
+        fn replace<T>(field: &mut Option<T>, other: Option<T>) {
+            if other.is_some() {
+                *field = other;
+            }
+        }
+
+        replace(&mut self.relation_tags, other.relation_tags);
+        replace(&mut self.following, other.following);
+        replace(&mut self.followers, other.followers);
+        replace(&mut self.blacklist, other.blacklist);
+        replace(&mut self.favorites, other.favorites);
+        replace(&mut self.history, other.history);
+        replace(&mut self.bangumi, other.bangumi);
+        replace(&mut self.toview, other.toview);
+    }
+
+    /// 模块的数据项数量 (未包含该模块时返回None)
+    ///
+    /// 收藏夹按收藏夹数量计数。
+    pub fn count(&self, module: BackupModule) -> Option<usize> {
+        match module {
+            BackupModule::RelationTags => self.relation_tags.as_ref().map(Vec::len),
+            BackupModule::Following => self.following.as_ref().map(Vec::len),
+            BackupModule::Followers => self.followers.as_ref().map(Vec::len),
+            BackupModule::Blacklist => self.blacklist.as_ref().map(Vec::len),
+            BackupModule::Favorites => self.favorites.as_ref().map(Vec::len),
+            BackupModule::History => self.history.as_ref().map(Vec::len),
+            BackupModule::Bangumi => self.bangumi.as_ref().map(Vec::len),
+            BackupModule::ToView => self.toview.as_ref().map(Vec::len),
+        }
+    }
+
+    /// 序列化模块数据为当前版本的载荷 (未包含该模块时返回None)
+    pub fn module_to_json(&self, module: BackupModule) -> Result<Option<Vec<u8>>> {
+        let bytes = match module {
+            BackupModule::RelationTags => to_json(module, &self.relation_tags)?,
+            BackupModule::Following => to_json(module, &self.following)?,
+            BackupModule::Followers => to_json(module, &self.followers)?,
+            BackupModule::Blacklist => to_json(module, &self.blacklist)?,
+            BackupModule::Favorites => to_json(module, &self.favorites)?,
+            BackupModule::History => to_json(module, &self.history)?,
+            BackupModule::Bangumi => to_json(module, &self.bangumi)?,
+            BackupModule::ToView => to_json(module, &self.toview)?,
+        };
+        Ok(bytes)
+    }
+
+    /// 从任意版本的载荷反序列化模块数据
+    pub fn set_module_json(&mut self, module: BackupModule, bytes: &[u8]) -> Result<()> {
+        match module {
+            BackupModule::RelationTags => self.relation_tags = Some(decode_payload(module, bytes)?),
+            BackupModule::Following => self.following = Some(decode_payload(module, bytes)?),
+            BackupModule::Followers => self.followers = Some(decode_payload(module, bytes)?),
+            BackupModule::Blacklist => self.blacklist = Some(decode_payload(module, bytes)?),
+            BackupModule::Favorites => self.favorites = Some(decode_payload(module, bytes)?),
+            BackupModule::History => self.history = Some(decode_payload(module, bytes)?),
+            BackupModule::Bangumi => self.bangumi = Some(decode_payload(module, bytes)?),
+            BackupModule::ToView => self.toview = Some(decode_payload(module, bytes)?),
+        }
+        Ok(())
+    }
+}
+
+/// 序列化可选的模块数据
+fn to_json<T: Serialize>(module: BackupModule, value: &Option<Vec<T>>) -> Result<Option<Vec<u8>>> {
+    value
+        .as_ref()
+        .map(|items| encode_payload(module, items))
+        .transpose()
+}
+
+/// 备份归档
+///
+/// 一次备份的完整内容: 来源账号、备份时间、各模块数据及其完整性统计。
+/// 通过 [`write_archive`] 写入单个zip文件,通过 [`read_archive`] 读回。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupArchive {
+    /// 来源账号
+    pub source: BackupSource,
+    /// 备份开始时间 (时间戳,秒)
+    pub created_at: i64,
+    /// 各模块数据
+    pub data: BackupData,
+    /// 各模块的完整性统计
+    #[serde(default)]
+    pub completeness: BTreeMap<BackupModule, Completeness>,
+    /// 已归档到本地的图片 (原始URL → 本地图片)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub images: ImageIndex,
+}
+
+impl BackupArchive {
+    /// 创建空的备份归档 (备份时间为当前时间)
+    pub fn new(source: BackupSource) -> Self {
+        Self {
+            source,
+            created_at: chrono::Utc::now().timestamp(),
+            data: BackupData::default(),
+            completeness: BTreeMap::new(),
+            images: ImageIndex::new(),
+        }
+    }
+}
+
+/// 计算SHA-256 (十六进制小写)
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// 将备份归档写入zip文件
+///
+/// 归档包含 `manifest.json` 和每个模块一个JSON条目。
+/// 先写入同目录下的临时文件,成功后再重命名为目标文件,避免留下写了一半的归档。
+/// 提供口令时整个归档文件会被加密。
+///
+/// # 参数
+///
+/// * `path` - 归档文件路径
+/// * `archive` - 备份归档
+/// * `passphrase` - 加密口令,None表示不加密
+///
+/// # 返回
+///
+/// 写入的清单
+pub async fn write_archive(
+    path: impl AsRef<Path>,
+    archive: &BackupArchive,
+    passphrase: Option<&str>,
+) -> Result<BackupManifest> {
+    let path = path.as_ref().to_path_buf();
+    let archive = archive.clone();
+    let passphrase = passphrase.map(str::to_string);
+    run_blocking(move || write_archive_blocking(&path, &archive, passphrase.as_deref())).await
+}
+
+/// 从zip文件读取备份归档
+///
+/// 会校验每个条目的SHA-256,并拒绝由更新版本写入的归档。
+/// 加密的归档会自动解密。
+///
+/// # 参数
+///
+/// * `path` - 归档文件路径
+/// * `passphrase` - 解密口令,归档未加密时忽略
+pub async fn read_archive(
+    path: impl AsRef<Path>,
+    passphrase: Option<&str>,
+) -> Result<BackupArchive> {
+    let path = path.as_ref().to_path_buf();
+    let passphrase = passphrase.map(str::to_string);
+    run_blocking(move || read_archive_blocking(&path, passphrase.as_deref())).await
+}
+
+/// 只读取归档的清单
+///
+/// # 参数
+///
+/// * `path` - 归档文件路径
+/// * `passphrase` - 解密口令,归档未加密时忽略
+pub async fn read_manifest(
+    path: impl AsRef<Path>,
+    passphrase: Option<&str>,
+) -> Result<BackupManifest> {
+    let path = path.as_ref().to_path_buf();
+    let passphrase = passphrase.map(str::to_string);
+    run_blocking(move || {
+        let mut zip = open_zip(&path, passphrase.as_deref())?;
+        read_manifest_entry(&mut zip)
+    })
+    .await
+}
+
+/// 在阻塞线程池中执行同步的归档操作
+pub(crate) async fn run_blocking<T, F>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| BiliError::business(format!("归档任务异常退出: {}", e)))?
+}
+
+fn write_archive_blocking(
+    path: &Path,
+    archive: &BackupArchive,
+    passphrase: Option<&str>,
+) -> Result<BackupManifest> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    // 视频元数据只在目录中保存一份
+    let catalog = VideoCatalog::from_data(&archive.data);
+
+    let mut entries = Vec::new();
+    for module in BackupModule::ALL {
+        let Some(bytes) = archive.data.module_to_json(module)? else {
+            continue;
+        };
+        let bytes = catalog.compact_payload(module, bytes)?;
+
+        let path = module.file_name();
+        zip.start_file(path.as_str(), options).map_err(zip_error)?;
+        zip.write_all(&bytes)
+            .map_err(|e| BiliError::io(format!("写入归档条目失败: {}", e)))?;
+
+        entries.push(ManifestEntry {
+            module,
+            path,
+            count: archive.data.count(module).unwrap_or(0),
+            completeness: archive.completeness.get(&module).cloned(),
+            sha256: sha256_hex(&bytes),
+            size: bytes.len() as u64,
+        });
+    }
+
+    let videos = if catalog.is_empty() {
+        None
+    } else {
+        let bytes = serde_json::to_vec_pretty(&catalog)
+            .map_err(|e| BiliError::parse(format!("序列化视频目录失败: {}", e)))?;
+        zip.start_file(VIDEOS_FILE_NAME, options)
+            .map_err(zip_error)?;
+        zip.write_all(&bytes)
+            .map_err(|e| BiliError::io(format!("写入视频目录失败: {}", e)))?;
+        Some(CatalogEntry {
+            path: VIDEOS_FILE_NAME.to_string(),
+            count: catalog.len(),
+            sha256: sha256_hex(&bytes),
+            size: bytes.len() as u64,
+        })
+    };
+
+    let images = if archive.images.is_empty() {
+        None
+    } else {
+        let bytes = serde_json::to_vec_pretty(&archive.images)
+            .map_err(|e| BiliError::parse(format!("序列化图片索引失败: {}", e)))?;
+        zip.start_file(IMAGES_FILE_NAME, options)
+            .map_err(zip_error)?;
+        zip.write_all(&bytes)
+            .map_err(|e| BiliError::io(format!("写入图片索引失败: {}", e)))?;
+        Some(CatalogEntry {
+            path: IMAGES_FILE_NAME.to_string(),
+            count: archive.images.len(),
+            sha256: sha256_hex(&bytes),
+            size: bytes.len() as u64,
+        })
+    };
+
+    let manifest = BackupManifest {
+        schema_version: ARCHIVE_SCHEMA_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        source: archive.source.clone(),
+        created_at: archive.created_at,
+        written_at: chrono::Utc::now().timestamp(),
+        entries,
+        videos,
+        images,
+    };
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| BiliError::parse(format!("序列化清单失败: {}", e)))?;
+    zip.start_file(MANIFEST_FILE_NAME, options)
+        .map_err(zip_error)?;
+    zip.write_all(&manifest_bytes)
+        .map_err(|e| BiliError::io(format!("写入清单失败: {}", e)))?;
+
+    let mut bytes = zip.finish().map_err(zip_error)?.into_inner();
+    if let Some(passphrase) = passphrase {
+        bytes = crypto::encrypt(&bytes, passphrase)?;
+    }
+    write_file_atomic(path, &bytes)?;
+
+    tracing::info!(
+        "备份归档已写入: {} ({} 个模块)",
+        path.display(),
+        manifest.entries.len()
+    );
+    Ok(manifest)
+}
+
+fn read_archive_blocking(path: &Path, passphrase: Option<&str>) -> Result<BackupArchive> {
+    let mut zip = open_zip(path, passphrase)?;
+    let manifest = read_manifest_entry(&mut zip)?;
+    let catalog = read_catalog(&mut zip, &manifest)?;
+    let images = read_image_index(&mut zip, &manifest)?;
+
+    let mut data = BackupData::default();
+    let mut completeness = BTreeMap::new();
+    for entry in &manifest.entries {
+        let bytes = read_entry(&mut zip, &entry.path)?;
+        if sha256_hex(&bytes) != entry.sha256 {
+            return Err(BiliError::parse(format!(
+                "归档条目 {} 校验失败，文件可能已损坏",
+                entry.path
+            )));
+        }
+
+        let bytes = catalog.expand_payload(entry.module, bytes)?;
+        data.set_module_json(entry.module, &bytes)?;
+        if let Some(ref c) = entry.completeness {
+            completeness.insert(entry.module, c.clone());
+        }
+    }
+
+    tracing::info!(
+        "读取备份归档: {} ({} 个模块)",
+        path.display(),
+        manifest.entries.len()
+    );
+    Ok(BackupArchive {
+        source: manifest.source,
+        created_at: manifest.created_at,
+        data,
+        completeness,
+        images,
+    })
+}
+
+/// 先写入临时文件再重命名,保证目标文件要么是旧内容要么是完整的新内容
+pub(crate) fn write_file_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let tmp_path = temp_path(path);
+    let mut file =
+        File::create(&tmp_path).map_err(|e| BiliError::io(format!("创建文件失败: {}", e)))?;
+    file.write_all(bytes)
+        .map_err(|e| BiliError::io(format!("写入文件失败: {}", e)))?;
+    file.sync_all()
+        .map_err(|e| BiliError::io(format!("同步文件失败: {}", e)))?;
+    drop(file);
+
+    std::fs::rename(&tmp_path, path).map_err(|e| BiliError::io(format!("保存文件失败: {}", e)))
+}
+
+/// 打开zip归档 (加密的归档先解密)
+fn open_zip(path: &Path, passphrase: Option<&str>) -> Result<ZipArchive<Cursor<Vec<u8>>>> {
+    let mut bytes =
+        std::fs::read(path).map_err(|e| BiliError::io(format!("打开归档文件失败: {}", e)))?;
+    if crypto::is_encrypted(&bytes) {
+        let passphrase =
+            passphrase.ok_or_else(|| BiliError::crypto("备份归档已加密，请提供口令"))?;
+        bytes = crypto::decrypt(&bytes, passphrase)?;
+    }
+    ZipArchive::new(Cursor::new(bytes)).map_err(zip_error)
+}
+
+/// 读取并检查清单
+fn read_manifest_entry(zip: &mut ZipArchive<Cursor<Vec<u8>>>) -> Result<BackupManifest> {
+    let bytes = read_entry(zip, MANIFEST_FILE_NAME)?;
+    let manifest: BackupManifest = serde_json::from_slice(&bytes)
+        .map_err(|e| BiliError::parse(format!("清单解析失败: {}", e)))?;
+
+    if manifest.schema_version > ARCHIVE_SCHEMA_VERSION {
+        return Err(BiliError::newer_version(format!(
+            "备份归档由更新版本 ({}) 创建 (格式版本 {}，当前支持 {})，请升级后再导入",
+            manifest.app_version, manifest.schema_version, ARCHIVE_SCHEMA_VERSION
+        )));
+    }
+
+    Ok(manifest)
+}
+
+/// 读取并校验视频元数据目录 (第3版之前的归档没有目录)
+pub(crate) fn read_catalog(
+    zip: &mut ZipArchive<Cursor<Vec<u8>>>,
+    manifest: &BackupManifest,
+) -> Result<VideoCatalog> {
+    let Some(ref entry) = manifest.videos else {
+        return Ok(VideoCatalog::default());
+    };
+
+    let bytes = read_entry(zip, &entry.path)?;
+    if sha256_hex(&bytes) != entry.sha256 {
+        return Err(BiliError::parse(format!(
+            "归档条目 {} 校验失败，文件可能已损坏",
+            entry.path
+        )));
+    }
+    serde_json::from_slice(&bytes).map_err(|e| BiliError::parse(format!("视频目录解析失败: {}", e)))
+}
+
+/// 读取并校验本地图片索引 (未归档图片的备份没有索引)
+fn read_image_index(
+    zip: &mut ZipArchive<Cursor<Vec<u8>>>,
+    manifest: &BackupManifest,
+) -> Result<ImageIndex> {
+    let Some(ref entry) = manifest.images else {
+        return Ok(ImageIndex::new());
+    };
+
+    let bytes = read_entry(zip, &entry.path)?;
+    if sha256_hex(&bytes) != entry.sha256 {
+        return Err(BiliError::parse(format!(
+            "归档条目 {} 校验失败，文件可能已损坏",
+            entry.path
+        )));
+    }
+    serde_json::from_slice(&bytes).map_err(|e| BiliError::parse(format!("图片索引解析失败: {}", e)))
+}
+
+/// 读取归档条目的全部内容
+pub(crate) fn read_entry(zip: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> Result<Vec<u8>> {
+    let mut file = zip
+        .by_name(name)
+        .map_err(|e| BiliError::parse(format!("归档缺少条目 {}: {}", name, e)))?;
+    let mut bytes = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut bytes)
+        .map_err(|e| BiliError::io(format!("读取归档条目 {} 失败: {}", name, e)))?;
+    Ok(bytes)
+}
+
+/// 临时文件路径 (与目标文件位于同一目录,保证重命名是原子操作)
+pub(crate) fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// 转换zip错误
+pub(crate) fn zip_error(e: zip::result::ZipError) -> BiliError {
+    BiliError::io(format!("归档格式错误: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::images::ImageRef;
+
+    fn sample_archive() -> BackupArchive {
+        let mut archive = BackupArchive::new(BackupSource {
+            uid: 123456,
+            uname: Some("测试用户".to_string()),
+        });
+        archive.data.relation_tags = Some(vec![RelationTag::new(1, "特别关注".to_string())]);
+        archive.data.following = Some(vec![Relation {
+            mid: 1,
+            uname: "UP主".to_string(),
+            face: String::new(),
+            sign: None,
+            mtime: 1700000000,
+            attribute: Some(2),
+            special: None,
+            tag: Some(vec![1]),
+            vip: None,
+        }]);
+        archive.data.toview = Some(vec![]);
+        archive
+            .completeness
+            .insert(BackupModule::Following, Completeness::new(Some(3), 1));
+        archive
+    }
+
+    #[tokio::test]
+    async fn test_archive_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.zip");
+
+        let mut archive = sample_archive();
+        archive.images.insert(
+            "http://i0.hdslb.com/face.jpg".to_string(),
+            ImageRef {
+                sha256: "ab".repeat(32),
+                path: format!("ab/{}.jpg", "ab".repeat(32)),
+                size: 10,
+                content_type: Some("image/jpeg".to_string()),
+            },
+        );
+        let manifest = write_archive(&path, &archive, None).await.unwrap();
+        assert_eq!(manifest.schema_version, ARCHIVE_SCHEMA_VERSION);
+        assert_eq!(manifest.images.as_ref().unwrap().count, 1);
+        assert_eq!(manifest.source.uid, 123456);
+        assert_eq!(
+            manifest
+                .entries
+                .iter()
+                .map(|e| e.module)
+                .collect::<Vec<_>>(),
+            vec![
+                BackupModule::RelationTags,
+                BackupModule::Following,
+                BackupModule::ToView
+            ]
+        );
+        assert!(manifest
+            .entry(BackupModule::Following)
+            .unwrap()
+            .is_truncated());
+        assert!(!temp_path(&path).exists());
+
+        let loaded = read_archive(&path, None).await.unwrap();
+        assert_eq!(loaded.source, archive.source);
+        assert_eq!(loaded.created_at, archive.created_at);
+        assert_eq!(loaded.data.modules(), archive.data.modules());
+        assert_eq!(loaded.data.following.unwrap()[0].uname, "UP主");
+        assert_eq!(loaded.completeness, archive.completeness);
+        assert_eq!(loaded.images, archive.images);
+
+        assert_eq!(read_manifest(&path, None).await.unwrap(), manifest);
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_archive_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.zip");
+
+        let manifest = write_archive(&path, &sample_archive(), Some("secret"))
+            .await
+            .unwrap();
+        assert!(crypto::is_encrypted(&std::fs::read(&path).unwrap()));
+
+        let err = read_manifest(&path, None).await.unwrap_err();
+        assert!(err.to_string().contains("请提供口令"));
+        let err = read_archive(&path, Some("wrong")).await.unwrap_err();
+        assert!(err.to_string().contains("口令错误"));
+
+        assert_eq!(
+            read_manifest(&path, Some("secret")).await.unwrap(),
+            manifest
+        );
+        let loaded = read_archive(&path, Some("secret")).await.unwrap();
+        assert_eq!(loaded.source.uid, 123456);
+    }
+
+    #[tokio::test]
+    async fn test_archive_extracts_video_catalog() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.zip");
+
+        let video: ToView = serde_json::from_value(serde_json::json!({
+            "aid": 100,
+            "bvid": "BV1xx411c7mD",
+            "cid": 7,
+            "title": "测试视频",
+            "pic": "http://i0.hdslb.com/cover.jpg",
+            "add_at": 1700000000
+        }))
+        .unwrap();
+        let mut archive = sample_archive();
+        archive.data.toview = Some(vec![video]);
+
+        let manifest = write_archive(&path, &archive, None).await.unwrap();
+        assert_eq!(manifest.videos.as_ref().unwrap().count, 1);
+
+        // 条目中不再重复保存标题
+        let mut zip = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let mut entry = String::new();
+        zip.by_name("toview.json")
+            .unwrap()
+            .read_to_string(&mut entry)
+            .unwrap();
+        assert!(!entry.contains("测试视频"));
+
+        let loaded = read_archive(&path, None).await.unwrap();
+        let toview = loaded.data.toview.unwrap();
+        assert_eq!(toview[0].title, "测试视频");
+        assert_eq!(toview[0].pic, "http://i0.hdslb.com/cover.jpg");
+        assert_eq!(toview[0].add_at, Some(1700000000));
+    }
+
+    #[tokio::test]
+    async fn test_read_archive_rejects_corrupted_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.zip");
+        let mut manifest = write_archive(&path, &sample_archive(), None).await.unwrap();
+
+        // 重写一个校验和不匹配的归档
+        manifest.entries[0].sha256 = "0".repeat(64);
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        let options = FileOptions::default();
+        zip.start_file(manifest.entries[0].path.as_str(), options)
+            .unwrap();
+        zip.write_all(b"[]").unwrap();
+        manifest.entries.truncate(1);
+        zip.start_file(MANIFEST_FILE_NAME, options).unwrap();
+        zip.write_all(&serde_json::to_vec(&manifest).unwrap())
+            .unwrap();
+        zip.finish().unwrap();
+
+        let err = read_archive(&path, None).await.unwrap_err();
+        assert!(err.to_string().contains("校验失败"));
+    }
+
+    #[tokio::test]
+    async fn test_read_archive_rejects_newer_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.zip");
+        let mut manifest = write_archive(&path, &sample_archive(), None).await.unwrap();
+        manifest.schema_version = ARCHIVE_SCHEMA_VERSION + 1;
+        manifest.entries.clear();
+
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        zip.start_file(MANIFEST_FILE_NAME, FileOptions::default())
+            .unwrap();
+        zip.write_all(&serde_json::to_vec(&manifest).unwrap())
+            .unwrap();
+        zip.finish().unwrap();
+
+        let err = read_manifest(&path, None).await.unwrap_err();
+        assert!(err.is_newer_version());
+        assert!(err.to_string().contains("更新版本"));
+    }
+}
@@ -0,0 +1,257 @@
+//! 备份文件加密
+//!
+//! 使用 AES-256-GCM 认证加密,密钥由口令经 Argon2id 派生。
+//! 加密文件格式 (整数均为小端):
+//!
+//! | 字段       | 长度 | 说明                       |
+//! |------------|------|----------------------------|
+//! | magic      | 8    | `BBKCRYPT`                 |
+//! | version    | 1    | 加密格式版本 (当前为1)     |
+//! | m_cost     | 4    | Argon2 内存开销 (KiB)      |
+//! | t_cost     | 4    | Argon2 迭代次数            |
+//! | p_cost     | 4    | Argon2 并行度              |
+//! | salt       | 16   | 随机盐                     |
+//! | nonce      | 12   | 随机nonce                  |
+//! | key_check  | 8    | 密钥校验值 (用于识别口令错误) |
+//! | ciphertext | 余下 | 密文及16字节认证标签       |
+//!
+//! 文件头作为附加认证数据参与认证,任何篡改都会导致解密失败。
+
+use crate::api::error::{BiliError, Result};
+use crate::backup::archive::run_blocking;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// 加密文件标识
+const MAGIC: &[u8; 8] = b"BBKCRYPT";
+
+/// 加密格式版本
+const FORMAT_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_CHECK_LEN: usize = 8;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 12 + SALT_LEN + NONCE_LEN + KEY_CHECK_LEN;
+
+/// Argon2id 参数 (m_cost KiB, t_cost, p_cost),与 OWASP 推荐的最低配置一致
+const KDF_PARAMS: (u32, u32, u32) = (19 * 1024, 2, 1);
+
+/// 解密时接受的最高 Argon2id 参数 (每项为 [`KDF_PARAMS`] 的8倍)
+///
+/// 参数来自不可信的文件头,超出上限的文件在派生密钥前就被拒绝,
+/// 避免构造的文件耗尽内存或长时间占用CPU。
+const MAX_KDF_PARAMS: (u32, u32, u32) = (KDF_PARAMS.0 * 8, KDF_PARAMS.1 * 8, KDF_PARAMS.2 * 8);
+
+/// 判断数据是否为加密格式
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// 由口令派生256位密钥
+fn derive_key(passphrase: &str, salt: &[u8], (m, t, p): (u32, u32, u32)) -> Result<[u8; 32]> {
+    let params = Params::new(m, t, p, Some(32))
+        .map_err(|e| BiliError::crypto(format!("无效的密钥派生参数: {}", e)))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| BiliError::crypto(format!("密钥派生失败: {}", e)))?;
+    Ok(key)
+}
+
+/// 密钥校验值
+fn key_check(key: &[u8; 32]) -> [u8; KEY_CHECK_LEN] {
+    let digest = Sha256::new()
+        .chain_update(b"bilibili-backup key check")
+        .chain_update(key)
+        .finalize();
+    let mut check = [0u8; KEY_CHECK_LEN];
+    check.copy_from_slice(&digest[..KEY_CHECK_LEN]);
+    check
+}
+
+/// 使用口令加密
+///
+/// # 参数
+///
+/// * `plaintext` - 明文
+/// * `passphrase` - 口令 (不能为空)
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    if passphrase.is_empty() {
+        return Err(BiliError::param("加密口令不能为空"));
+    }
+
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt, KDF_PARAMS)?;
+    let (m, t, p) = KDF_PARAMS;
+
+    let mut output = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+    output.extend_from_slice(MAGIC);
+    output.push(FORMAT_VERSION);
+    output.extend_from_slice(&m.to_le_bytes());
+    output.extend_from_slice(&t.to_le_bytes());
+    output.extend_from_slice(&p.to_le_bytes());
+    output.extend_from_slice(&salt);
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&key_check(&key));
+
+    let cipher = Aes256Gcm::new_from_slice(&key)
+        .map_err(|e| BiliError::crypto(format!("初始化加密器失败: {}", e)))?;
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &output,
+            },
+        )
+        .map_err(|_| BiliError::crypto("加密失败"))?;
+
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+/// 使用口令解密
+///
+/// # 错误
+///
+/// - 数据不是加密格式或格式版本过新
+/// - 文件头中的密钥派生参数超过上限
+/// - 口令错误
+/// - 数据被篡改或损坏
+pub fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    if !is_encrypted(data) || data.len() < HEADER_LEN {
+        return Err(BiliError::crypto("不是有效的加密备份文件"));
+    }
+
+    let version = data[MAGIC.len()];
+    if version > FORMAT_VERSION {
+        return Err(BiliError::crypto(format!(
+            "加密格式版本 {} 过新，请升级后再导入",
+            version
+        )));
+    }
+
+    let read_u32 = |offset: usize| {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&data[offset..offset + 4]);
+        u32::from_le_bytes(bytes)
+    };
+    let params_offset = MAGIC.len() + 1;
+    let params = (
+        read_u32(params_offset),
+        read_u32(params_offset + 4),
+        read_u32(params_offset + 8),
+    );
+    let (max_m, max_t, max_p) = MAX_KDF_PARAMS;
+    if params.0 > max_m || params.1 > max_t || params.2 > max_p {
+        return Err(BiliError::crypto(format!(
+            "密钥派生参数过高 (m={}, t={}, p={})，拒绝解密",
+            params.0, params.1, params.2
+        )));
+    }
+    let salt_offset = params_offset + 12;
+    let nonce_offset = salt_offset + SALT_LEN;
+    let check_offset = nonce_offset + NONCE_LEN;
+    let (header, ciphertext) = data.split_at(HEADER_LEN);
+
+    let key = derive_key(passphrase, &header[salt_offset..nonce_offset], params)?;
+    if key_check(&key)[..] != header[check_offset..HEADER_LEN] {
+        return Err(BiliError::crypto("口令错误，无法解密备份文件"));
+    }
+
+    let cipher = Aes256Gcm::new_from_slice(&key)
+        .map_err(|e| BiliError::crypto(format!("初始化解密器失败: {}", e)))?;
+    cipher
+        .decrypt(
+            Nonce::from_slice(&header[nonce_offset..check_offset]),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| BiliError::crypto("备份文件已损坏或被篡改，解密失败"))
+}
+
+/// 按需加密 (未提供口令时原样返回)
+///
+/// 密钥派生开销较大,在阻塞线程池中执行。
+pub async fn seal(data: Vec<u8>, passphrase: Option<&str>) -> Result<Vec<u8>> {
+    match passphrase {
+        None => Ok(data),
+        Some(passphrase) => {
+            let passphrase = passphrase.to_string();
+            run_blocking(move || encrypt(&data, &passphrase)).await
+        }
+    }
+}
+
+/// 按需解密 (未加密的数据原样返回)
+///
+/// 数据已加密但未提供口令时返回明确的错误。
+pub async fn open(data: Vec<u8>, passphrase: Option<&str>) -> Result<Vec<u8>> {
+    if !is_encrypted(&data) {
+        return Ok(data);
+    }
+
+    let passphrase = passphrase
+        .ok_or_else(|| BiliError::crypto("备份文件已加密，请提供口令"))?
+        .to_string();
+    run_blocking(move || decrypt(&data, &passphrase)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let encrypted = encrypt(b"hello", "secret").unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert_eq!(decrypt(&encrypted, "secret").unwrap(), b"hello");
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let encrypted = encrypt(b"hello", "secret").unwrap();
+        let err = decrypt(&encrypted, "wrong").unwrap_err();
+        assert!(err.to_string().contains("口令错误"));
+    }
+
+    #[test]
+    fn test_tampered_data() {
+        let mut encrypted = encrypt(b"hello", "secret").unwrap();
+        let last = encrypted.len() - 1;
+        encrypted[last] ^= 1;
+        let err = decrypt(&encrypted, "secret").unwrap_err();
+        assert!(err.to_string().contains("损坏"));
+    }
+
+    #[test]
+    fn test_inflated_kdf_params_rejected() {
+        let mut encrypted = encrypt(b"hello", "secret").unwrap();
+        let offset = MAGIC.len() + 1;
+        encrypted[offset..offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = decrypt(&encrypted, "secret").unwrap_err();
+        assert!(err.to_string().contains("参数过高"));
+
+        let mut encrypted = encrypt(b"hello", "secret").unwrap();
+        encrypted[offset + 4..offset + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decrypt(&encrypted, "secret").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_open_requires_passphrase() {
+        assert_eq!(open(b"[]".to_vec(), None).await.unwrap(), b"[]");
+
+        let sealed = seal(b"[]".to_vec(), Some("secret")).await.unwrap();
+        assert!(open(sealed.clone(), None).await.is_err());
+        assert_eq!(open(sealed, Some("secret")).await.unwrap(), b"[]");
+    }
+}
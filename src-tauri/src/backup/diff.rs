@@ -0,0 +1,581 @@
+//! 备份差异比较
+//!
+//! 按稳定ID (mid / tagid / 收藏夹ID / 媒体ID / season_id) 比较两次备份,
+//! 生成结构化的差异报告和可读的摘要。只比较两次备份都包含的模块。
+
+use crate::api::models::{Bangumi, Media, Relation, RelationTag, User};
+use crate::backup::archive::BackupData;
+use crate::backup::manifest::BackupModule;
+use crate::services::favorites::FavFolderWithMedia;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// 用户 (关注、黑名单)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserRef {
+    /// 用户ID
+    pub mid: u64,
+    /// 用户名
+    pub uname: String,
+}
+
+/// 关注分组变化
+///
+/// 新增分组时 `old_name` 为None,删除分组时 `new_name` 为None,其余为改名。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagChange {
+    /// 分组ID
+    pub tag_id: i64,
+    /// 原名称
+    pub old_name: Option<String>,
+    /// 新名称
+    pub new_name: Option<String>,
+}
+
+/// 关注用户所在分组的变化
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FollowingTagChange {
+    /// 用户
+    pub user: UserRef,
+    /// 加入的分组
+    pub added_tags: Vec<i64>,
+    /// 移出的分组
+    pub removed_tags: Vec<i64>,
+}
+
+/// 收藏夹
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FolderRef {
+    /// 收藏夹ID
+    pub id: u64,
+    /// 标题
+    pub title: String,
+}
+
+/// 收藏夹改名
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FolderRename {
+    /// 收藏夹ID
+    pub id: u64,
+    /// 原标题
+    pub old_title: String,
+    /// 新标题
+    pub new_title: String,
+}
+
+/// 收藏的媒体
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MediaRef {
+    /// 媒体ID
+    pub id: u64,
+    /// 媒体类型
+    pub item_type: u32,
+    /// BV号
+    pub bvid: Option<String>,
+    /// 标题
+    pub title: String,
+}
+
+/// 单个收藏夹内容的变化
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FolderDiff {
+    /// 收藏夹
+    pub folder: FolderRef,
+    /// 新增的收藏
+    pub added: Vec<MediaRef>,
+    /// 移除的收藏
+    pub removed: Vec<MediaRef>,
+}
+
+/// 番剧
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BangumiRef {
+    /// 剧集season_id
+    pub season_id: u64,
+    /// 标题
+    pub title: String,
+}
+
+/// 追番状态变化
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BangumiStatusChange {
+    /// 番剧
+    pub bangumi: BangumiRef,
+    /// 原状态
+    pub old_status: Option<i32>,
+    /// 新状态
+    pub new_status: Option<i32>,
+}
+
+/// 两次备份的差异
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BackupDiff {
+    /// 参与比较的模块 (两次备份都包含的模块)
+    pub compared_modules: Vec<BackupModule>,
+    /// 新增的关注
+    pub following_gained: Vec<UserRef>,
+    /// 取消的关注
+    pub following_lost: Vec<UserRef>,
+    /// 关注分组的新增、删除和改名
+    pub tag_changes: Vec<TagChange>,
+    /// 关注用户所在分组的变化
+    pub following_tag_changes: Vec<FollowingTagChange>,
+    /// 新建的收藏夹
+    pub folders_added: Vec<FolderRef>,
+    /// 删除的收藏夹
+    pub folders_removed: Vec<FolderRef>,
+    /// 改名的收藏夹
+    pub folders_renamed: Vec<FolderRename>,
+    /// 各收藏夹内容的变化 (只包含两次备份中都存在且有变化的收藏夹)
+    pub favorites: Vec<FolderDiff>,
+    /// 新追的番剧
+    pub bangumi_followed: Vec<BangumiRef>,
+    /// 取消追的番剧
+    pub bangumi_unfollowed: Vec<BangumiRef>,
+    /// 追番状态变化
+    pub bangumi_status_changed: Vec<BangumiStatusChange>,
+    /// 新拉黑的用户
+    pub blacklist_added: Vec<UserRef>,
+    /// 移出黑名单的用户
+    pub blacklist_removed: Vec<UserRef>,
+}
+
+/// 差异报告 (结构化差异 + 可读摘要)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupDiffReport {
+    /// 结构化差异
+    pub diff: BackupDiff,
+    /// 可读摘要
+    pub summary: String,
+}
+
+impl From<BackupDiff> for BackupDiffReport {
+    fn from(diff: BackupDiff) -> Self {
+        let summary = diff.summary();
+        Self { diff, summary }
+    }
+}
+
+/// 比较两次备份
+///
+/// # 参数
+///
+/// * `old` - 较早的备份
+/// * `new` - 较新的备份
+///
+/// # 返回
+///
+/// 从 `old` 到 `new` 的差异
+///
+/// # 示例
+///
+/// ```rust
+/// use bilibili_backup_tauri::backup::{diff_backups, BackupData};
+///
+/// let diff = diff_backups(&BackupData::default(), &BackupData::default());
+/// assert!(diff.is_empty());
+/// ```
+pub fn diff_backups(old: &BackupData, new: &BackupData) -> BackupDiff {
+    let new_modules: HashSet<BackupModule> = new.modules().into_iter().collect();
+    let mut diff = BackupDiff {
+        compared_modules: old
+            .modules()
+            .into_iter()
+            .filter(|m| new_modules.contains(m))
+            .collect(),
+        ..Default::default()
+    };
+
+    if let (Some(old), Some(new)) = (&old.following, &new.following) {
+        diff_following(&mut diff, old, new);
+    }
+    if let (Some(old), Some(new)) = (&old.relation_tags, &new.relation_tags) {
+        diff.tag_changes = diff_tags(old, new);
+    }
+    if let (Some(old), Some(new)) = (&old.favorites, &new.favorites) {
+        diff_favorites(&mut diff, old, new);
+    }
+    if let (Some(old), Some(new)) = (&old.bangumi, &new.bangumi) {
+        diff_bangumi(&mut diff, old, new);
+    }
+    if let (Some(old), Some(new)) = (&old.blacklist, &new.blacklist) {
+        let (added, removed) = diff_by_key(old, new, |u| u.mid);
+        diff.blacklist_added = added.into_iter().map(user_ref).collect();
+        diff.blacklist_removed = removed.into_iter().map(user_ref).collect();
+    }
+
+    diff
+}
+
+/// 按键比较两个列表,返回 (新增, 删除),保持各自列表中的顺序
+fn diff_by_key<'a, T, K, F>(old: &'a [T], new: &'a [T], key: F) -> (Vec<&'a T>, Vec<&'a T>)
+where
+    K: Eq + std::hash::Hash,
+    F: Fn(&T) -> K,
+{
+    let old_keys: HashSet<K> = old.iter().map(&key).collect();
+    let new_keys: HashSet<K> = new.iter().map(&key).collect();
+
+    let added = new.iter().filter(|x| !old_keys.contains(&key(x))).collect();
+    let removed = old.iter().filter(|x| !new_keys.contains(&key(x))).collect();
+    (added, removed)
+}
+
+fn user_ref(user: &User) -> UserRef {
+    UserRef {
+        mid: user.mid,
+        uname: user.uname.clone(),
+    }
+}
+
+fn relation_ref(relation: &Relation) -> UserRef {
+    UserRef {
+        mid: relation.mid,
+        uname: relation.uname.clone(),
+    }
+}
+
+fn diff_following(diff: &mut BackupDiff, old: &[Relation], new: &[Relation]) {
+    let (gained, lost) = diff_by_key(old, new, |r| r.mid);
+    diff.following_gained = gained.into_iter().map(relation_ref).collect();
+    diff.following_lost = lost.into_iter().map(relation_ref).collect();
+
+    let old_map: HashMap<u64, &Relation> = old.iter().map(|r| (r.mid, r)).collect();
+    for relation in new {
+        let Some(previous) = old_map.get(&relation.mid) else {
+            continue;
+        };
+        let old_tags: BTreeSet<i64> = previous.tag.iter().flatten().copied().collect();
+        let new_tags: BTreeSet<i64> = relation.tag.iter().flatten().copied().collect();
+        if old_tags == new_tags {
+            continue;
+        }
+
+        diff.following_tag_changes.push(FollowingTagChange {
+            user: relation_ref(relation),
+            added_tags: new_tags.difference(&old_tags).copied().collect(),
+            removed_tags: old_tags.difference(&new_tags).copied().collect(),
+        });
+    }
+}
+
+fn diff_tags(old: &[RelationTag], new: &[RelationTag]) -> Vec<TagChange> {
+    let old_map: HashMap<i64, &RelationTag> = old.iter().map(|t| (t.tag_id, t)).collect();
+    let new_ids: HashSet<i64> = new.iter().map(|t| t.tag_id).collect();
+
+    let mut changes: Vec<TagChange> = new
+        .iter()
+        .filter_map(|tag| match old_map.get(&tag.tag_id) {
+            None => Some(TagChange {
+                tag_id: tag.tag_id,
+                old_name: None,
+                new_name: Some(tag.name.clone()),
+            }),
+            Some(previous) if previous.name != tag.name => Some(TagChange {
+                tag_id: tag.tag_id,
+                old_name: Some(previous.name.clone()),
+                new_name: Some(tag.name.clone()),
+            }),
+            Some(_) => None,
+        })
+        .collect();
+
+    changes.extend(
+        old.iter()
+            .filter(|tag| !new_ids.contains(&tag.tag_id))
+            .map(|tag| TagChange {
+                tag_id: tag.tag_id,
+                old_name: Some(tag.name.clone()),
+                new_name: None,
+            }),
+    );
+    changes
+}
+
+fn folder_ref(folder: &FavFolderWithMedia) -> FolderRef {
+    FolderRef {
+        id: folder.folder.id,
+        title: folder.folder.title.clone(),
+    }
+}
+
+fn media_ref(media: &Media) -> MediaRef {
+    MediaRef {
+        id: media.id,
+        item_type: media.item_type,
+        bvid: media.bvid.clone().or_else(|| media.bv_id.clone()),
+        title: media.title.clone(),
+    }
+}
+
+fn diff_favorites(diff: &mut BackupDiff, old: &[FavFolderWithMedia], new: &[FavFolderWithMedia]) {
+    let (added, removed) = diff_by_key(old, new, |f| f.folder.id);
+    diff.folders_added = added.into_iter().map(folder_ref).collect();
+    diff.folders_removed = removed.into_iter().map(folder_ref).collect();
+
+    let old_map: HashMap<u64, &FavFolderWithMedia> = old.iter().map(|f| (f.folder.id, f)).collect();
+    for folder in new {
+        let Some(previous) = old_map.get(&folder.folder.id) else {
+            continue;
+        };
+
+        if previous.folder.title != folder.folder.title {
+            diff.folders_renamed.push(FolderRename {
+                id: folder.folder.id,
+                old_title: previous.folder.title.clone(),
+                new_title: folder.folder.title.clone(),
+            });
+        }
+
+        let (added, removed) = diff_by_key(&previous.media_list, &folder.media_list, |m| {
+            (m.id, m.item_type)
+        });
+        if !added.is_empty() || !removed.is_empty() {
+            diff.favorites.push(FolderDiff {
+                folder: folder_ref(folder),
+                added: added.into_iter().map(media_ref).collect(),
+                removed: removed.into_iter().map(media_ref).collect(),
+            });
+        }
+    }
+}
+
+fn bangumi_ref(bangumi: &Bangumi) -> BangumiRef {
+    BangumiRef {
+        season_id: bangumi.season_id,
+        title: bangumi.title.clone(),
+    }
+}
+
+fn diff_bangumi(diff: &mut BackupDiff, old: &[Bangumi], new: &[Bangumi]) {
+    let (followed, unfollowed) = diff_by_key(old, new, |b| b.season_id);
+    diff.bangumi_followed = followed.into_iter().map(bangumi_ref).collect();
+    diff.bangumi_unfollowed = unfollowed.into_iter().map(bangumi_ref).collect();
+
+    let old_map: HashMap<u64, &Bangumi> = old.iter().map(|b| (b.season_id, b)).collect();
+    diff.bangumi_status_changed = new
+        .iter()
+        .filter_map(|bangumi| {
+            let previous = old_map.get(&bangumi.season_id)?;
+            (previous.follow_status != bangumi.follow_status).then(|| BangumiStatusChange {
+                bangumi: bangumi_ref(bangumi),
+                old_status: previous.follow_status,
+                new_status: bangumi.follow_status,
+            })
+        })
+        .collect();
+}
+
+/// 追番状态名称
+fn follow_status_name(status: Option<i32>) -> &'static str {
+    match status {
+        Some(1) => "想看",
+        Some(2) => "在看",
+        Some(3) => "看过",
+        _ => "未知",
+    }
+}
+
+/// 列出若干名称 (超过5个时省略)
+fn name_list<'a>(names: impl Iterator<Item = &'a str>) -> String {
+    let names: Vec<&str> = names.collect();
+    if names.len() <= 5 {
+        names.join("、")
+    } else {
+        format!("{} 等", names[..5].join("、"))
+    }
+}
+
+impl BackupDiff {
+    /// 是否没有任何变化
+    pub fn is_empty(&self) -> bool {
+        self.following_gained.is_empty()
+            && self.following_lost.is_empty()
+            && self.tag_changes.is_empty()
+            && self.following_tag_changes.is_empty()
+            && self.folders_added.is_empty()
+            && self.folders_removed.is_empty()
+            && self.folders_renamed.is_empty()
+            && self.favorites.is_empty()
+            && self.bangumi_followed.is_empty()
+            && self.bangumi_unfollowed.is_empty()
+            && self.bangumi_status_changed.is_empty()
+            && self.blacklist_added.is_empty()
+            && self.blacklist_removed.is_empty()
+    }
+
+    /// 可读摘要 (每项变化一行)
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return "两次备份没有差异".to_string();
+        }
+
+        let mut lines = Vec::new();
+        let users = |users: &[UserRef]| name_list(users.iter().map(|u| u.uname.as_str()));
+
+        if !self.following_gained.is_empty() {
+            lines.push(format!(
+                "新增关注 {} 个: {}",
+                self.following_gained.len(),
+                users(&self.following_gained)
+            ));
+        }
+        if !self.following_lost.is_empty() {
+            lines.push(format!(
+                "取消关注 {} 个: {}",
+                self.following_lost.len(),
+                users(&self.following_lost)
+            ));
+        }
+        for change in &self.tag_changes {
+            lines.push(match (&change.old_name, &change.new_name) {
+                (None, Some(name)) => format!("新建关注分组「{}」", name),
+                (Some(name), None) => format!("删除关注分组「{}」", name),
+                (Some(old), Some(new)) => format!("关注分组「{}」改名为「{}」", old, new),
+                (None, None) => continue,
+            });
+        }
+        if !self.following_tag_changes.is_empty() {
+            lines.push(format!(
+                "{} 个关注的分组有变化",
+                self.following_tag_changes.len()
+            ));
+        }
+        for folder in &self.folders_added {
+            lines.push(format!("新建收藏夹「{}」", folder.title));
+        }
+        for folder in &self.folders_removed {
+            lines.push(format!("删除收藏夹「{}」", folder.title));
+        }
+        for rename in &self.folders_renamed {
+            lines.push(format!(
+                "收藏夹「{}」改名为「{}」",
+                rename.old_title, rename.new_title
+            ));
+        }
+        for folder in &self.favorites {
+            lines.push(format!(
+                "收藏夹「{}」: 新增 {} 个，移除 {} 个",
+                folder.folder.title,
+                folder.added.len(),
+                folder.removed.len()
+            ));
+        }
+        if !self.bangumi_followed.is_empty() {
+            lines.push(format!(
+                "新追番剧 {} 部: {}",
+                self.bangumi_followed.len(),
+                name_list(self.bangumi_followed.iter().map(|b| b.title.as_str()))
+            ));
+        }
+        if !self.bangumi_unfollowed.is_empty() {
+            lines.push(format!(
+                "取消追番 {} 部: {}",
+                self.bangumi_unfollowed.len(),
+                name_list(self.bangumi_unfollowed.iter().map(|b| b.title.as_str()))
+            ));
+        }
+        for change in &self.bangumi_status_changed {
+            lines.push(format!(
+                "「{}」: {} → {}",
+                change.bangumi.title,
+                follow_status_name(change.old_status),
+                follow_status_name(change.new_status)
+            ));
+        }
+        if !self.blacklist_added.is_empty() {
+            lines.push(format!(
+                "新拉黑 {} 个: {}",
+                self.blacklist_added.len(),
+                users(&self.blacklist_added)
+            ));
+        }
+        if !self.blacklist_removed.is_empty() {
+            lines.push(format!(
+                "移出黑名单 {} 个: {}",
+                self.blacklist_removed.len(),
+                users(&self.blacklist_removed)
+            ));
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn data(value: serde_json::Value) -> BackupData {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_diff_following_and_tags() {
+        let old = data(json!({
+            "relation_tags": [{"tagid": 1, "name": "游戏"}, {"tagid": 2, "name": "音乐"}],
+            "following": [
+                {"mid": 1, "uname": "a", "face": "", "mtime": 0, "tag": [1]},
+                {"mid": 2, "uname": "b", "face": "", "mtime": 0}
+            ]
+        }));
+        let new = data(json!({
+            "relation_tags": [{"tagid": 1, "name": "游戏区"}, {"tagid": 3, "name": "学习"}],
+            "following": [
+                {"mid": 1, "uname": "a", "face": "", "mtime": 0, "tag": [3]},
+                {"mid": 4, "uname": "d", "face": "", "mtime": 0}
+            ]
+        }));
+
+        let diff = diff_backups(&old, &new);
+        assert_eq!(diff.following_gained[0].mid, 4);
+        assert_eq!(diff.following_lost[0].mid, 2);
+        assert_eq!(diff.tag_changes.len(), 3);
+        assert_eq!(diff.following_tag_changes[0].added_tags, vec![3]);
+        assert_eq!(diff.following_tag_changes[0].removed_tags, vec![1]);
+
+        let summary = diff.summary();
+        assert!(summary.contains("关注分组「游戏」改名为「游戏区」"));
+        assert!(summary.contains("删除关注分组「音乐」"));
+    }
+
+    #[test]
+    fn test_diff_favorites_bangumi_blacklist() {
+        let folder = |title: &str, ids: &[u64]| {
+            json!({
+                "folder": {"id": 7, "mid": 1, "attr": 0, "title": title, "mediaCount": ids.len()},
+                "media_list": ids.iter().map(|id| json!({"id": id, "type": 2, "title": format!("视频{}", id)})).collect::<Vec<_>>()
+            })
+        };
+        let old = data(json!({
+            "favorites": [folder("默认", &[1, 2])],
+            "bangumi": [{"seasonId": 1, "mediaId": 1, "title": "番剧", "cover": "", "followStatus": 2}],
+            "blacklist": []
+        }));
+        let new = data(json!({
+            "favorites": [folder("稍后看", &[2, 3])],
+            "bangumi": [{"seasonId": 1, "mediaId": 1, "title": "番剧", "cover": "", "followStatus": 3}],
+            "blacklist": [{"mid": 9, "uname": "x", "face": ""}]
+        }));
+
+        let diff = diff_backups(&old, &new);
+        assert_eq!(diff.folders_renamed[0].new_title, "稍后看");
+        assert_eq!(diff.favorites[0].added[0].id, 3);
+        assert_eq!(diff.favorites[0].removed[0].id, 1);
+        assert_eq!(diff.bangumi_status_changed[0].new_status, Some(3));
+        assert_eq!(diff.blacklist_added[0].mid, 9);
+        assert!(diff.summary().contains("「番剧」: 在看 → 看过"));
+    }
+
+    #[test]
+    fn test_only_common_modules_are_compared() {
+        let old = data(json!({"following": [{"mid": 1, "uname": "a", "face": "", "mtime": 0}]}));
+        let new = data(json!({"blacklist": []}));
+
+        let diff = diff_backups(&old, &new);
+        assert!(diff.compared_modules.is_empty());
+        assert!(diff.is_empty());
+        assert_eq!(diff.summary(), "两次备份没有差异");
+    }
+}
@@ -0,0 +1,427 @@
+//! 本地图片归档
+//!
+//! 备份中只保存封面、头像等图片的URL,视频或账号消失后这些链接会失效。
+//! 图片归档把备份引用的所有图片下载到按内容寻址的目录
+//! (`<哈希前两位>/<sha256>.<扩展名>`),相同内容只保存一份,
+//! 并在备份归档中记录原始URL与本地文件的对应关系。
+
+use crate::api::error::{BiliError, Result};
+use crate::api::BiliClient;
+use crate::backup::archive::{
+    run_blocking, sha256_hex, write_file_atomic, BackupArchive, BackupData,
+};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+/// 默认的同时下载数
+pub const DEFAULT_IMAGE_CONCURRENCY: usize = 8;
+
+/// 本地图片记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageRef {
+    /// 图片内容的SHA-256 (十六进制)
+    pub sha256: String,
+    /// 相对于图片归档目录的路径
+    pub path: String,
+    /// 文件大小 (字节)
+    pub size: u64,
+    /// 下载时的Content-Type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
+
+/// 图片索引 (原始URL → 本地图片)
+pub type ImageIndex = BTreeMap<String, ImageRef>;
+
+/// 图片归档统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageArchiveReport {
+    /// 备份引用的图片数量
+    pub total: usize,
+    /// 本次下载的图片数量
+    pub downloaded: usize,
+    /// 已归档、无需下载的图片数量
+    pub skipped: usize,
+    /// 下载失败的图片URL
+    pub failed: Vec<String>,
+}
+
+/// 图片归档结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageArchiveResult {
+    /// 记录了本地图片的备份归档
+    pub archive: BackupArchive,
+    /// 归档统计
+    pub report: ImageArchiveReport,
+}
+
+/// 默认的图片归档目录
+pub fn default_image_dir(data_dir: impl AsRef<Path>) -> PathBuf {
+    data_dir.as_ref().join("images")
+}
+
+/// 收集备份数据引用的所有图片URL
+///
+/// 包括关注、粉丝和黑名单的头像,收藏夹封面和收藏项封面、UP主头像,
+/// 历史记录封面,追番封面以及稍后再看的封面。
+pub fn collect_image_urls(data: &BackupData) -> BTreeSet<String> {
+    let mut urls = BTreeSet::new();
+    let mut add = |url: &str| {
+        if normalize_url(url).is_some() {
+            urls.insert(url.to_string());
+        }
+    };
+
+    for relation in [&data.following, &data.followers]
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        add(&relation.face);
+    }
+    for user in data.blacklist.iter().flatten() {
+        add(&user.face);
+    }
+    for folder in data.favorites.iter().flatten() {
+        add(folder.folder.cover.as_deref().unwrap_or_default());
+        for media in &folder.media_list {
+            add(media.cover.as_deref().unwrap_or_default());
+            if let Some(ref upper) = media.upper {
+                add(&upper.face);
+            }
+        }
+    }
+    for item in data.history.iter().flatten() {
+        add(item.cover.as_deref().unwrap_or_default());
+    }
+    for bangumi in data.bangumi.iter().flatten() {
+        add(&bangumi.cover);
+    }
+    for video in data.toview.iter().flatten() {
+        add(&video.pic);
+        if let Some(ref owner) = video.owner {
+            add(&owner.face);
+        }
+    }
+    urls
+}
+
+/// 图片索引中本地文件存在的图片 (原始URL → 本地文件路径)
+///
+/// # 参数
+///
+/// * `images` - 图片索引
+/// * `dir` - 图片归档目录
+pub fn local_paths(images: &ImageIndex, dir: &Path) -> HashMap<String, PathBuf> {
+    images
+        .iter()
+        .map(|(url, image)| (url.clone(), dir.join(&image.path)))
+        .filter(|(_, path)| path.is_file())
+        .collect()
+}
+
+/// 补全协议并过滤非HTTP地址
+fn normalize_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    if url.starts_with("//") {
+        Some(format!("https:{}", url))
+    } else if url.starts_with("http://") || url.starts_with("https://") {
+        Some(url.to_string())
+    } else {
+        None
+    }
+}
+
+/// 根据Content-Type或URL确定文件扩展名
+fn extension(content_type: Option<&str>, url: &str) -> &'static str {
+    const KNOWN: [(&str, &str); 6] = [
+        ("image/jpeg", "jpg"),
+        ("image/png", "png"),
+        ("image/webp", "webp"),
+        ("image/gif", "gif"),
+        ("image/avif", "avif"),
+        ("image/bmp", "bmp"),
+    ];
+
+    if let Some(content_type) = content_type {
+        let mime = content_type.split(';').next().unwrap_or_default().trim();
+        if let Some((_, ext)) = KNOWN.iter().find(|(m, _)| mime.eq_ignore_ascii_case(m)) {
+            return ext;
+        }
+    }
+
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let suffix = path.rsplit_once('.').map(|(_, s)| s.to_ascii_lowercase());
+    match suffix.as_deref() {
+        Some("jpg" | "jpeg") => "jpg",
+        Some("png") => "png",
+        Some("webp") => "webp",
+        Some("gif") => "gif",
+        Some("avif") => "avif",
+        Some("bmp") => "bmp",
+        _ => "img",
+    }
+}
+
+/// 把图片内容保存到按内容寻址的目录
+///
+/// 相同内容的图片只保存一份,已存在时直接返回记录。
+///
+/// # 参数
+///
+/// * `dir` - 图片归档目录
+/// * `bytes` - 图片内容
+/// * `content_type` - 下载时的Content-Type
+/// * `url` - 图片URL (无法从Content-Type确定扩展名时使用)
+pub fn store_image(
+    dir: &Path,
+    bytes: &[u8],
+    content_type: Option<&str>,
+    url: &str,
+) -> Result<ImageRef> {
+    let sha256 = sha256_hex(bytes);
+    let path = format!(
+        "{}/{}.{}",
+        &sha256[..2],
+        sha256,
+        extension(content_type, url)
+    );
+
+    let full_path = dir.join(&path);
+    if !full_path.is_file() {
+        if let Some(parent) = full_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| BiliError::io(format!("创建图片目录失败: {}", e)))?;
+        }
+        write_file_atomic(&full_path, bytes)?;
+    }
+
+    Ok(ImageRef {
+        sha256,
+        path,
+        size: bytes.len() as u64,
+        content_type: content_type.map(str::to_string),
+    })
+}
+
+/// 图片归档器
+///
+/// 按固定的并发数下载图片,单张图片失败不会中断整个归档。
+#[derive(Clone)]
+pub struct ImageArchiver {
+    client: Client,
+    dir: PathBuf,
+    concurrency: usize,
+}
+
+impl ImageArchiver {
+    /// 创建图片归档器
+    ///
+    /// # 参数
+    ///
+    /// * `client` - HTTP客户端 (图片下载无需登录)
+    /// * `dir` - 图片归档目录
+    pub fn new(client: &BiliClient, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            client: client.client().clone(),
+            dir: dir.into(),
+            concurrency: DEFAULT_IMAGE_CONCURRENCY,
+        }
+    }
+
+    /// 设置同时下载数 (至少为1)
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// 图片归档目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 下载备份引用的图片并记录到归档的图片索引中
+    ///
+    /// 索引中已有且本地文件存在的图片会被跳过,下载失败的图片记录在统计中。
+    ///
+    /// # 参数
+    ///
+    /// * `archive` - 备份归档
+    ///
+    /// # 返回
+    ///
+    /// 归档统计
+    pub async fn archive(&self, archive: &mut BackupArchive) -> Result<ImageArchiveReport> {
+        let urls = collect_image_urls(&archive.data);
+        let mut report = ImageArchiveReport {
+            total: urls.len(),
+            ..Default::default()
+        };
+
+        let pending: Vec<String> = urls
+            .into_iter()
+            .filter(|url| {
+                let archived = archive
+                    .images
+                    .get(url)
+                    .is_some_and(|image| self.dir.join(&image.path).is_file());
+                !archived
+            })
+            .collect();
+        report.skipped = report.total - pending.len();
+        if pending.is_empty() {
+            return Ok(report);
+        }
+
+        let pending = Arc::new(pending);
+        let next = Arc::new(AtomicUsize::new(0));
+        let workers = self.concurrency.clamp(1, pending.len());
+        let mut tasks = JoinSet::new();
+        for _ in 0..workers {
+            let archiver = self.clone();
+            let pending = pending.clone();
+            let next = next.clone();
+            tasks.spawn(async move {
+                let mut results = Vec::new();
+                loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(url) = pending.get(index) else {
+                        break;
+                    };
+                    let result = archiver.download(url).await;
+                    results.push((url.clone(), result));
+                }
+                results
+            });
+        }
+
+        while let Some(joined) = tasks.join_next().await {
+            let results =
+                joined.map_err(|e| BiliError::business(format!("图片下载任务异常退出: {}", e)))?;
+            for (url, result) in results {
+                match result {
+                    Ok(image) => {
+                        archive.images.insert(url, image);
+                        report.downloaded += 1;
+                    }
+                    Err(e) => {
+                        tracing::warn!("下载图片失败 {}: {}", url, e);
+                        report.failed.push(url);
+                    }
+                }
+            }
+        }
+        report.failed.sort();
+
+        tracing::info!(
+            "图片归档完成: 共 {} 张，下载 {} 张，跳过 {} 张，失败 {} 张",
+            report.total,
+            report.downloaded,
+            report.skipped,
+            report.failed.len()
+        );
+        Ok(report)
+    }
+
+    /// 下载单张图片并保存
+    async fn download(&self, url: &str) -> Result<ImageRef> {
+        let request_url = normalize_url(url)
+            .ok_or_else(|| BiliError::param(format!("无效的图片地址: {}", url)))?;
+        let response = self
+            .client
+            .get(&request_url)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        if let Some(ref content_type) = content_type {
+            if !content_type.starts_with("image/") {
+                return Err(BiliError::business(format!(
+                    "返回的内容不是图片: {}",
+                    content_type
+                )));
+            }
+        }
+        let bytes = response.bytes().await?;
+        if bytes.is_empty() {
+            return Err(BiliError::business("图片内容为空"));
+        }
+
+        let dir = self.dir.clone();
+        run_blocking(move || store_image(&dir, &bytes, content_type.as_deref(), &request_url)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_store_image_deduplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = store_image(dir.path(), b"png-bytes", Some("image/png"), "http://a/1").unwrap();
+        let second = store_image(dir.path(), b"png-bytes", None, "http://b/2.png").unwrap();
+
+        assert_eq!(first.path, second.path);
+        assert_eq!(
+            first.path,
+            format!("{}/{}.png", &first.sha256[..2], first.sha256)
+        );
+        assert_eq!(
+            std::fs::read(dir.path().join(&first.path)).unwrap(),
+            b"png-bytes"
+        );
+
+        let index = ImageIndex::from([
+            ("http://a/1".to_string(), first),
+            (
+                "http://c/3.jpg".to_string(),
+                store_image(dir.path(), b"other", None, "http://c/3.jpg").unwrap(),
+            ),
+        ]);
+        std::fs::remove_file(dir.path().join(&index["http://c/3.jpg"].path)).unwrap();
+        let paths = local_paths(&index, dir.path());
+        assert_eq!(paths.len(), 1);
+        assert!(paths.contains_key("http://a/1"));
+    }
+
+    #[test]
+    fn test_extension() {
+        assert_eq!(extension(Some("image/jpeg; charset=binary"), ""), "jpg");
+        assert_eq!(
+            extension(None, "http://i0.hdslb.com/bfs/a.webp?x=1"),
+            "webp"
+        );
+        assert_eq!(extension(None, "http://i0.hdslb.com/bfs/a"), "img");
+    }
+
+    #[test]
+    fn test_collect_image_urls() {
+        let data: BackupData = serde_json::from_value(json!({
+            "following": [{"mid": 1, "uname": "a", "face": "http://face/1.jpg", "mtime": 0}],
+            "bangumi": [{"seasonId": 1, "mediaId": 2, "title": "番", "cover": "//cover/2.jpg"}],
+            "toview": [{"aid": 1, "cid": 1, "title": "v", "pic": "http://face/1.jpg"}],
+            "blacklist": [{"mid": 2, "uname": "b", "face": ""}]
+        }))
+        .unwrap();
+
+        let urls: Vec<String> = collect_image_urls(&data).into_iter().collect();
+        assert_eq!(urls, vec!["//cover/2.jpg", "http://face/1.jpg"]);
+        assert_eq!(
+            normalize_url("//cover/2.jpg").as_deref(),
+            Some("https://cover/2.jpg")
+        );
+    }
+}
@@ -0,0 +1,480 @@
+//! 增量备份
+//!
+//! 将模块数据展开为以稳定ID为键的行 (mid / 媒体ID / season_id / kid 等),
+//! 与上一次快照比较后只保存新增、删除、修改和移动的行。
+//! 还原时从完整快照开始依次应用各增量快照的变更即可得到完整视图。
+
+use crate::api::error::{BiliError, Result};
+use crate::backup::archive::{sha256_hex, BackupData};
+use crate::backup::manifest::BackupModule;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// 以稳定ID为键的一行数据
+pub type KeyedRow = (String, Value);
+
+/// 变更类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeOp {
+    /// 新增
+    Add,
+    /// 修改
+    Modify,
+    /// 删除
+    Remove,
+    /// 移动 (行的内容不变,相对顺序变化)
+    Move,
+}
+
+impl ChangeOp {
+    /// 存储用的标识
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Modify => "modify",
+            Self::Remove => "remove",
+            Self::Move => "move",
+        }
+    }
+
+    /// 从存储标识解析
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "add" => Some(Self::Add),
+            "modify" => Some(Self::Modify),
+            "remove" => Some(Self::Remove),
+            "move" => Some(Self::Move),
+            _ => None,
+        }
+    }
+}
+
+/// 单行变更
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemChange {
+    /// 变更类型
+    pub op: ChangeOp,
+    /// 行的稳定键
+    pub key: String,
+    /// 新增或移动的行在新列表中的位置 (修改和删除时忽略)
+    pub position: usize,
+    /// 新增或修改后的数据 (删除和移动时为None)
+    pub data: Option<Value>,
+}
+
+/// 将模块数据序列化为JSON列表 (未包含该模块时返回None)
+pub fn module_values(data: &BackupData, module: BackupModule) -> Result<Option<Vec<Value>>> {
+    fn to_values<T: Serialize>(items: &Option<Vec<T>>) -> Result<Option<Vec<Value>>> {
+        items
+            .as_ref()
+            .map(|items| {
+                items
+                    .iter()
+                    .map(serde_json::to_value)
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .map_err(|e| BiliError::parse(format!("序列化失败: {}", e)))
+            })
+            .transpose()
+    }
+
+    match module {
+        BackupModule::RelationTags => to_values(&data.relation_tags),
+        BackupModule::Following => to_values(&data.following),
+        BackupModule::Followers => to_values(&data.followers),
+        BackupModule::Blacklist => to_values(&data.blacklist),
+        BackupModule::Favorites => to_values(&data.favorites),
+        BackupModule::History => to_values(&data.history),
+        BackupModule::Bangumi => to_values(&data.bangumi),
+        BackupModule::ToView => to_values(&data.toview),
+    }
+}
+
+/// 模块数据的摘要 (用于校验重建结果)
+pub fn digest(items: &[Value]) -> String {
+    sha256_hex(&serde_json::to_vec(items).unwrap_or_default())
+}
+
+/// 读取对象中的ID字段 (数字或字符串)
+fn id_field(value: &Value, field: &str) -> Option<String> {
+    match value.get(field)? {
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        _ => None,
+    }
+}
+
+/// 单个条目的稳定ID
+fn item_id(module: BackupModule, item: &Value) -> Option<String> {
+    match module {
+        BackupModule::RelationTags => id_field(item, "tagid"),
+        BackupModule::Following | BackupModule::Followers | BackupModule::Blacklist => {
+            id_field(item, "mid")
+        }
+        BackupModule::History => id_field(item, "kid").or_else(|| {
+            let history = item.get("history")?;
+            Some(format!(
+                "{}:{}",
+                id_field(history, "business").unwrap_or_default(),
+                id_field(history, "oid")?
+            ))
+        }),
+        BackupModule::Bangumi => id_field(item, "seasonId"),
+        BackupModule::ToView => id_field(item, "aid"),
+        BackupModule::Favorites => item.get("folder").and_then(|f| id_field(f, "id")),
+    }
+}
+
+/// 为键去重 (同一ID出现多次时追加序号)
+fn unique_key(seen: &mut HashMap<String, usize>, key: String) -> String {
+    let count = seen.entry(key.clone()).or_insert(0);
+    *count += 1;
+    if *count == 1 {
+        key
+    } else {
+        format!("{}#{}", key, count)
+    }
+}
+
+/// 没有ID的条目以内容摘要作为键
+fn content_key(value: &Value) -> String {
+    let digest = digest(std::slice::from_ref(value));
+    format!("sha:{}", &digest[..16])
+}
+
+/// 将模块数据展开为带键的行
+///
+/// 收藏夹展开为"收藏夹行 + 其中每个媒体一行",
+/// 这样收藏夹内增删单个视频时只需要记录一行变更。
+pub fn flatten(module: BackupModule, items: Vec<Value>) -> Vec<KeyedRow> {
+    let mut seen = HashMap::new();
+    let mut rows = Vec::with_capacity(items.len());
+
+    for mut item in items {
+        let id = item_id(module, &item).unwrap_or_else(|| content_key(&item));
+
+        if module != BackupModule::Favorites {
+            rows.push((unique_key(&mut seen, id), item));
+            continue;
+        }
+
+        let media = match item.get_mut("media_list").map(Value::take) {
+            Some(Value::Array(media)) => media,
+            _ => Vec::new(),
+        };
+        item["media_list"] = Value::Array(Vec::new());
+        let folder_key = unique_key(&mut seen, format!("folder:{}", id));
+        rows.push((folder_key.clone(), item));
+
+        for media in media {
+            let media_id = id_field(&media, "id").unwrap_or_else(|| content_key(&media));
+            let key = unique_key(&mut seen, format!("media:{}:{}", folder_key, media_id));
+            rows.push((key, media));
+        }
+    }
+
+    rows
+}
+
+/// 将带键的行还原为模块数据 ([`flatten`] 的逆操作)
+pub fn unflatten(module: BackupModule, rows: Vec<KeyedRow>) -> Result<Vec<Value>> {
+    if module != BackupModule::Favorites {
+        return Ok(rows.into_iter().map(|(_, value)| value).collect());
+    }
+
+    let mut folders: Vec<(String, Value)> = Vec::new();
+    for (key, value) in rows {
+        if key.starts_with("folder:") {
+            folders.push((key, value));
+            continue;
+        }
+
+        let folder = folders
+            .iter_mut()
+            .rev()
+            .find(|(folder_key, _)| key.starts_with(&format!("media:{}:", folder_key)))
+            .ok_or_else(|| BiliError::parse(format!("媒体 {} 找不到所属的收藏夹", key)))?;
+        if let Some(Value::Array(media)) = folder.1.get_mut("media_list") {
+            media.push(value);
+        }
+    }
+
+    Ok(folders.into_iter().map(|(_, value)| value).collect())
+}
+
+/// 比较两次备份的行,得到变更列表
+///
+/// 两次都存在的行中,相对顺序保持不变的最多的一组行原地不动,
+/// 其余的行记录为移动 (例如重新观看的视频移到历史记录最前面只需要一条移动)。
+pub fn compute_changes(base: &[KeyedRow], current: &[KeyedRow]) -> Vec<ItemChange> {
+    let base_map: HashMap<&str, &Value> = base.iter().map(|(k, v)| (k.as_str(), v)).collect();
+    let current_positions: HashMap<&str, usize> = current
+        .iter()
+        .enumerate()
+        .map(|(position, (k, _))| (k.as_str(), position))
+        .collect();
+
+    // 保留下来的行按基础顺序排列时在新列表中的位置,其中最长递增子序列不需要移动
+    let kept: Vec<usize> = base
+        .iter()
+        .filter_map(|(key, _)| current_positions.get(key.as_str()).copied())
+        .collect();
+    let in_order = longest_increasing(&kept);
+
+    let mut changes: Vec<ItemChange> = base
+        .iter()
+        .filter(|(key, _)| !current_positions.contains_key(key.as_str()))
+        .map(|(key, _)| ItemChange {
+            op: ChangeOp::Remove,
+            key: key.clone(),
+            position: 0,
+            data: None,
+        })
+        .collect();
+
+    for (position, (key, value)) in current.iter().enumerate() {
+        match base_map.get(key.as_str()) {
+            None => changes.push(ItemChange {
+                op: ChangeOp::Add,
+                key: key.clone(),
+                position,
+                data: Some(value.clone()),
+            }),
+            Some(old) => {
+                if *old != value {
+                    changes.push(ItemChange {
+                        op: ChangeOp::Modify,
+                        key: key.clone(),
+                        position,
+                        data: Some(value.clone()),
+                    });
+                }
+                if !in_order.contains(&position) {
+                    changes.push(ItemChange {
+                        op: ChangeOp::Move,
+                        key: key.clone(),
+                        position,
+                        data: None,
+                    });
+                }
+            }
+        }
+    }
+
+    changes
+}
+
+/// 最长递增子序列中的元素
+fn longest_increasing(values: &[usize]) -> HashSet<usize> {
+    // tails[n] 是长度为 n + 1 的递增子序列中末尾最小的那个的末尾下标
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous = vec![None; values.len()];
+
+    for (i, &value) in values.iter().enumerate() {
+        let len = tails.partition_point(|&t| values[t] < value);
+        if len > 0 {
+            previous[i] = Some(tails[len - 1]);
+        }
+        if len == tails.len() {
+            tails.push(i);
+        } else {
+            tails[len] = i;
+        }
+    }
+
+    let mut result = HashSet::new();
+    let mut current = tails.last().copied();
+    while let Some(i) = current {
+        result.insert(values[i]);
+        current = previous[i];
+    }
+    result
+}
+
+/// 在基础行上应用变更
+///
+/// 删除和修改就地进行,新增和移动的行按记录的位置 (从前往后) 插入。
+pub fn apply_changes(base: Vec<KeyedRow>, changes: Vec<ItemChange>) -> Result<Vec<KeyedRow>> {
+    let mut removed = HashSet::new();
+    let mut modified = HashMap::new();
+    let mut moved = HashMap::new();
+    let mut added = Vec::new();
+
+    for change in changes {
+        match change.op {
+            ChangeOp::Remove => {
+                removed.insert(change.key);
+            }
+            ChangeOp::Modify => {
+                let data = change
+                    .data
+                    .ok_or_else(|| BiliError::parse(format!("变更 {} 缺少数据", change.key)))?;
+                modified.insert(change.key, data);
+            }
+            ChangeOp::Add => {
+                let data = change
+                    .data
+                    .ok_or_else(|| BiliError::parse(format!("变更 {} 缺少数据", change.key)))?;
+                added.push((change.position, change.key, data));
+            }
+            ChangeOp::Move => {
+                moved.insert(change.key, change.position);
+            }
+        }
+    }
+
+    let mut rows: Vec<KeyedRow> = Vec::with_capacity(base.len());
+    for (key, value) in base {
+        if removed.contains(&key) {
+            continue;
+        }
+        let value = modified.remove(&key).unwrap_or(value);
+        match moved.remove(&key) {
+            Some(position) => added.push((position, key, value)),
+            None => rows.push((key, value)),
+        }
+    }
+
+    if let Some(key) = modified.into_keys().next() {
+        return Err(BiliError::parse(format!("要修改的条目 {} 不存在", key)));
+    }
+    if let Some(key) = moved.into_keys().next() {
+        return Err(BiliError::parse(format!("要移动的条目 {} 不存在", key)));
+    }
+
+    added.sort_by_key(|(position, _, _)| *position);
+    for (position, key, value) in added {
+        let position = position.min(rows.len());
+        rows.insert(position, (key, value));
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rows(module: BackupModule, items: Value) -> Vec<KeyedRow> {
+        flatten(module, serde_json::from_value(items).unwrap())
+    }
+
+    #[test]
+    fn test_compute_and_apply_changes() {
+        let base = rows(
+            BackupModule::Following,
+            json!([{"mid": 1, "uname": "a"}, {"mid": 2, "uname": "b"}, {"mid": 3, "uname": "c"}]),
+        );
+        let current = rows(
+            BackupModule::Following,
+            json!([{"mid": 4, "uname": "d"}, {"mid": 1, "uname": "a"}, {"mid": 3, "uname": "c2"}]),
+        );
+
+        let changes = compute_changes(&base, &current);
+        let ops: Vec<_> = changes.iter().map(|c| (c.op, c.key.as_str())).collect();
+        assert_eq!(
+            ops,
+            vec![
+                (ChangeOp::Remove, "2"),
+                (ChangeOp::Add, "4"),
+                (ChangeOp::Modify, "3")
+            ]
+        );
+
+        assert_eq!(apply_changes(base, changes).unwrap(), current);
+    }
+
+    #[test]
+    fn test_reorder_is_recorded_as_moves() {
+        let base = rows(
+            BackupModule::History,
+            json!([{"kid": 1}, {"kid": 2}, {"kid": 3}, {"kid": 4}, {"kid": 5}]),
+        );
+        // 重新观看的视频移到最前面,同时有新增和删除
+        let current = rows(
+            BackupModule::History,
+            json!([{"kid": 4, "view_at": 9}, {"kid": 6}, {"kid": 1}, {"kid": 3}, {"kid": 2}]),
+        );
+
+        let changes = compute_changes(&base, &current);
+        let ops: Vec<_> = changes.iter().map(|c| (c.op, c.key.as_str())).collect();
+        assert_eq!(
+            ops,
+            vec![
+                (ChangeOp::Remove, "5"),
+                (ChangeOp::Modify, "4"),
+                (ChangeOp::Move, "4"),
+                (ChangeOp::Add, "6"),
+                (ChangeOp::Move, "2"),
+            ]
+        );
+        assert_eq!(apply_changes(base, changes).unwrap(), current);
+    }
+
+    #[test]
+    fn test_favorites_flatten_roundtrip() {
+        let items: Vec<Value> = serde_json::from_value(json!([
+            {"folder": {"id": 1}, "media_list": [{"id": 10}, {"id": 11}]},
+            {"folder": {"id": 2}, "media_list": [{"id": 10}]}
+        ]))
+        .unwrap();
+
+        let flat = flatten(BackupModule::Favorites, items.clone());
+        let keys: Vec<_> = flat.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "folder:1",
+                "media:folder:1:10",
+                "media:folder:1:11",
+                "folder:2",
+                "media:folder:2:10"
+            ]
+        );
+        assert_eq!(unflatten(BackupModule::Favorites, flat).unwrap(), items);
+    }
+
+    #[test]
+    fn test_favorite_media_change_is_single_row() {
+        let base = rows(
+            BackupModule::Favorites,
+            json!([{"folder": {"id": 1}, "media_list": [{"id": 10}, {"id": 11}]}]),
+        );
+        let current = rows(
+            BackupModule::Favorites,
+            json!([{"folder": {"id": 1}, "media_list": [{"id": 12}, {"id": 10}, {"id": 11}]}]),
+        );
+
+        let changes = compute_changes(&base, &current);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].op, ChangeOp::Add);
+        assert_eq!(changes[0].position, 1);
+        assert_eq!(apply_changes(base, changes).unwrap(), current);
+    }
+
+    #[test]
+    fn test_history_key_falls_back_to_oid() {
+        let flat = rows(
+            BackupModule::History,
+            json!([{"title": "a", "history": {"oid": 5, "business": "archive"}}, {"title": "b", "kid": 9}]),
+        );
+        let keys: Vec<_> = flat.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["archive:5", "9"]);
+    }
+
+    #[test]
+    fn test_items_without_id_use_content_key() {
+        let flat = rows(BackupModule::History, json!([{"title": "a"}]));
+        assert!(flat[0].0.starts_with("sha:"));
+        assert_eq!(flat, rows(BackupModule::History, json!([{"title": "a"}])));
+    }
+
+    #[test]
+    fn test_duplicate_ids_get_unique_keys() {
+        let flat = rows(BackupModule::ToView, json!([{"aid": 1}, {"aid": 1}]));
+        let keys: Vec<_> = flat.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, vec!["1", "1#2"]);
+    }
+}
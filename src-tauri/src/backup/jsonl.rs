@@ -0,0 +1,682 @@
+//! JSON Lines 导出格式
+//!
+//! 历史记录等大模块整体序列化为一个JSON文档时,需要在内存中同时保存数据和完整的JSON文本。
+//! JSON Lines 格式第一行是文件头,之后每行一个数据项,写入和读取都可以逐项进行。
+//! 收藏夹的媒体数量可能很多,因此每个收藏夹先写一行基础信息 (媒体列表为空),
+//! 其中的每个媒体再各占一行 `{"folder_id": .., "media": ..}`,读取时放回所属的收藏夹。
+//! 写入时先写同目录下的临时文件,完成后再重命名,中途失败不会破坏已有的文件。
+//!
+//! 数据行按文件头记录的格式版本逐行迁移到当前格式;读取时也兼容旧版的整体JSON导出文件。
+
+use crate::api::error::{BiliError, Result};
+use crate::api::models::{FavInfo, History, Media, ToView};
+use crate::backup::archive::{run_blocking, temp_path, write_file_atomic};
+use crate::backup::crypto;
+use crate::backup::manifest::BackupModule;
+use crate::backup::schema::{decode_items, decode_payload, SCHEMA_VERSION};
+use crate::services::favorites::FavFolderWithMedia;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+use tokio::io::AsyncWriteExt;
+
+/// 文件头中的格式标识
+pub const JSONL_FORMAT: &str = "jsonl";
+
+/// 写入文件时每次提交的数据量 (字节)
+const WRITE_CHUNK_SIZE: usize = 256 * 1024;
+
+/// JSON Lines 文件头 (文件的第一行)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonlHeader {
+    /// 格式标识,固定为 `jsonl`
+    pub format: String,
+    /// 数据格式版本
+    pub schema_version: u32,
+    /// 所属模块
+    pub module: BackupModule,
+    /// 数据行数 (不含文件头),用于发现没有写完的文件
+    pub count: usize,
+}
+
+impl JsonlHeader {
+    /// 创建当前版本的文件头
+    pub fn new(module: BackupModule, count: usize) -> Self {
+        Self {
+            format: JSONL_FORMAT.to_string(),
+            schema_version: SCHEMA_VERSION,
+            module,
+            count,
+        }
+    }
+
+    /// 解析文件头 (不是 JSON Lines 文件头时返回None)
+    pub fn parse(line: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(line.trim())
+            .ok()
+            .filter(|header| header.format == JSONL_FORMAT)
+    }
+}
+
+/// 可以写入 JSON Lines 的数据项
+///
+/// 大多数数据项占一行;收藏夹的每个媒体单独占一行。
+pub trait JsonlItem: Serialize {
+    /// 数据项占用的行数
+    fn line_count(&self) -> usize {
+        1
+    }
+
+    /// 写入数据项的所有行
+    fn write_lines<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_line(writer, self)
+    }
+}
+
+impl JsonlItem for History {}
+
+impl JsonlItem for ToView {}
+
+/// 收藏夹的基础信息行
+#[derive(Serialize)]
+struct FolderLine<'a> {
+    folder: &'a FavInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    intro: Option<&'a String>,
+    media_list: &'a [Media],
+}
+
+/// 收藏夹中的一个媒体
+#[derive(Serialize)]
+struct MediaLine<'a> {
+    folder_id: u64,
+    media: &'a Media,
+}
+
+impl JsonlItem for FavFolderWithMedia {
+    fn line_count(&self) -> usize {
+        1 + self.media_list.len()
+    }
+
+    fn write_lines<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let folder = FolderLine {
+            folder: &self.folder,
+            intro: self.intro.as_ref(),
+            media_list: &[],
+        };
+        write_line(writer, &folder)?;
+        for media in &self.media_list {
+            let line = MediaLine {
+                folder_id: self.folder.id,
+                media,
+            };
+            write_line(writer, &line)?;
+        }
+        Ok(())
+    }
+}
+
+/// 逐项写入 JSON Lines
+///
+/// # 参数
+///
+/// * `writer` - 输出
+/// * `module` - 所属模块
+/// * `items` - 数据项
+pub fn write_jsonl<W: Write, T: JsonlItem>(
+    mut writer: W,
+    module: BackupModule,
+    items: &[T],
+) -> Result<()> {
+    write_line(&mut writer, &header_for(module, items))?;
+    for item in items {
+        item.write_lines(&mut writer)?;
+    }
+    writer
+        .flush()
+        .map_err(|e| BiliError::io(format!("写入文件失败: {}", e)))
+}
+
+fn header_for<T: JsonlItem>(module: BackupModule, items: &[T]) -> JsonlHeader {
+    JsonlHeader::new(module, items.iter().map(JsonlItem::line_count).sum())
+}
+
+fn write_line<W: Write, T: Serialize + ?Sized>(writer: &mut W, value: &T) -> Result<()> {
+    serde_json::to_writer(&mut *writer, value).map_err(|e| {
+        if e.is_io() {
+            BiliError::io(format!("写入文件失败: {}", e))
+        } else {
+            BiliError::parse(format!("序列化失败: {}", e))
+        }
+    })?;
+    writer
+        .write_all(b"\n")
+        .map_err(|e| BiliError::io(format!("写入文件失败: {}", e)))
+}
+
+/// 收藏夹媒体行所属的收藏夹 (不是媒体行时返回None)
+fn media_line_folder(line: &Value) -> Option<&Value> {
+    line.get("media")?;
+    line.get("folder_id")
+}
+
+/// 把收藏夹的媒体行放回收藏夹
+///
+/// # 错误
+///
+/// - 媒体行不在所属收藏夹的基础信息行之后
+fn attach_media(folder: &mut Value, mut line: Value) -> Result<()> {
+    let folder_id = media_line_folder(&line).cloned().unwrap_or_default();
+    if folder.pointer("/folder/id") != Some(&folder_id) {
+        return Err(BiliError::parse(format!(
+            "收藏夹 {} 的媒体不在该收藏夹之后",
+            folder_id
+        )));
+    }
+
+    let media = line["media"].take();
+    let folder = folder
+        .as_object_mut()
+        .ok_or_else(|| BiliError::parse("收藏夹数据应为JSON对象"))?;
+    match folder.get_mut("media_list") {
+        Some(Value::Array(list)) => list.push(media),
+        _ => {
+            folder.insert("media_list".to_string(), Value::Array(vec![media]));
+        }
+    }
+    Ok(())
+}
+
+/// 把数据行组合为数据项 (收藏夹的媒体行放回所属的收藏夹)
+///
+/// # 错误
+///
+/// - 收藏夹的媒体行找不到所属的收藏夹
+pub(crate) fn group_lines(module: BackupModule, lines: Vec<Value>) -> Result<Vec<Value>> {
+    if module != BackupModule::Favorites {
+        return Ok(lines);
+    }
+
+    let mut items: Vec<Value> = Vec::new();
+    for line in lines {
+        match (media_line_folder(&line), items.last_mut()) {
+            (None, _) => items.push(line),
+            (Some(_), Some(folder)) => attach_media(folder, line)?,
+            (Some(folder_id), None) => {
+                return Err(BiliError::parse(format!(
+                    "收藏夹 {} 的媒体不在该收藏夹之后",
+                    folder_id
+                )))
+            }
+        }
+    }
+    Ok(items)
+}
+
+/// 逐项读取 JSON Lines
+///
+/// 迭代器逐项产出数据项,每项按文件头记录的格式版本迁移到当前格式;
+/// 读取到的行数少于文件头记录的行数时,最后会产出一个错误,说明文件没有写完。
+pub struct JsonlReader<R, T> {
+    lines: std::io::Lines<R>,
+    header: JsonlHeader,
+    line: usize,
+    read: usize,
+    /// 读取收藏夹的媒体行时多读到的下一行
+    pending: Option<Value>,
+    finished: bool,
+    _marker: PhantomData<T>,
+}
+
+impl<R: BufRead, T: DeserializeOwned> JsonlReader<R, T> {
+    /// 读取文件头并创建读取器
+    ///
+    /// # 错误
+    ///
+    /// - 文件不是 JSON Lines 格式
+    /// - 文件所属模块与期望的模块不一致
+    /// - 文件由更新版本的应用创建
+    pub fn new(mut reader: R, module: BackupModule) -> Result<Self> {
+        let mut first = String::new();
+        reader
+            .read_line(&mut first)
+            .map_err(|e| BiliError::io(format!("读取文件失败: {}", e)))?;
+        let header = JsonlHeader::parse(&first)
+            .ok_or_else(|| BiliError::parse("文件不是 JSON Lines 格式的导出文件"))?;
+        Self::with_header(reader, header, module)
+    }
+
+    fn with_header(reader: R, header: JsonlHeader, module: BackupModule) -> Result<Self> {
+        if header.schema_version > SCHEMA_VERSION {
+            return Err(BiliError::newer_version(format!(
+                "{}数据由更新版本的应用创建 (格式版本 {}，当前支持 {})，请升级后再导入",
+                module.display_name(),
+                header.schema_version,
+                SCHEMA_VERSION
+            )));
+        }
+        if header.module != module {
+            return Err(BiliError::parse(format!(
+                "数据模块不匹配: 期望 {}，实际为 {}",
+                module.key(),
+                header.module.key()
+            )));
+        }
+
+        Ok(Self {
+            lines: reader.lines(),
+            header,
+            line: 1,
+            read: 0,
+            pending: None,
+            finished: false,
+            _marker: PhantomData,
+        })
+    }
+
+    /// 文件头
+    pub fn header(&self) -> &JsonlHeader {
+        &self.header
+    }
+
+    /// 读取下一个非空行 (文件结束时返回None)
+    fn read_line(&mut self) -> Result<Option<Value>> {
+        for line in self.lines.by_ref() {
+            self.line += 1;
+            let line = line.map_err(|e| BiliError::io(format!("读取文件失败: {}", e)))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            self.read += 1;
+            return serde_json::from_str(&line).map(Some).map_err(|e| {
+                BiliError::parse(format!(
+                    "{}数据第 {} 行解析失败: {}",
+                    self.header.module.display_name(),
+                    self.line,
+                    e
+                ))
+            });
+        }
+        Ok(None)
+    }
+
+    /// 读取下一个数据项 (收藏夹连同其后的媒体行)
+    fn read_item(&mut self) -> Result<Option<Value>> {
+        let item = match self.pending.take() {
+            Some(item) => item,
+            None => match self.read_line()? {
+                Some(item) => item,
+                None => return Ok(None),
+            },
+        };
+        if self.header.module != BackupModule::Favorites {
+            return Ok(Some(item));
+        }
+
+        let mut lines = vec![item];
+        while let Some(line) = self.read_line()? {
+            if media_line_folder(&line).is_none() {
+                self.pending = Some(line);
+                break;
+            }
+            lines.push(line);
+        }
+        Ok(group_lines(BackupModule::Favorites, lines)?.pop())
+    }
+
+    /// 把数据项迁移到当前格式并反序列化
+    fn decode(&self, item: Value) -> Result<T> {
+        let module = self.header.module;
+        decode_items(module, self.header.schema_version, vec![item])?
+            .pop()
+            .ok_or_else(|| BiliError::parse(format!("{}数据迁移后为空", module.display_name())))
+    }
+}
+
+impl<R: BufRead, T: DeserializeOwned> Iterator for JsonlReader<R, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let item = match self.read_item() {
+            Ok(Some(item)) => self.decode(item),
+            Ok(None) => {
+                self.finished = true;
+                if self.read < self.header.count {
+                    return Some(Err(BiliError::parse(format!(
+                        "{}数据不完整: 应有 {} 行，只读取到 {} 行，文件可能没有写完",
+                        self.header.module.display_name(),
+                        self.header.count,
+                        self.read
+                    ))));
+                }
+                return None;
+            }
+            Err(e) => Err(e),
+        };
+        if item.is_err() {
+            self.finished = true;
+        }
+        Some(item)
+    }
+}
+
+/// 导出为 JSON Lines 文件
+///
+/// 未加密时数据分块写入临时文件,完成后重命名为目标文件,
+/// 内存中只保留当前的一块;提供口令时整个文件会被加密,加密需要完整的明文,因此先在内存中生成。
+///
+/// # 参数
+///
+/// * `path` - 导出文件路径
+/// * `module` - 所属模块
+/// * `items` - 数据项
+/// * `passphrase` - 加密口令,None表示不加密
+pub async fn export_jsonl<T: JsonlItem + Sync>(
+    path: impl AsRef<Path>,
+    module: BackupModule,
+    items: &[T],
+    passphrase: Option<&str>,
+) -> Result<()> {
+    let path = path.as_ref();
+    match passphrase {
+        None => write_jsonl_file(path, module, items).await,
+        Some(passphrase) => {
+            let mut buffer = Vec::new();
+            write_jsonl(&mut buffer, module, items)?;
+            let bytes = crypto::seal(buffer, Some(passphrase)).await?;
+            let path = path.to_path_buf();
+            run_blocking(move || write_file_atomic(&path, &bytes)).await
+        }
+    }
+}
+
+/// 分块写入临时文件,完成后重命名为目标文件
+async fn write_jsonl_file<T: JsonlItem + Sync>(
+    path: &Path,
+    module: BackupModule,
+    items: &[T],
+) -> Result<()> {
+    let tmp_path = temp_path(path);
+    let mut result = write_chunks(&tmp_path, module, items).await;
+    if result.is_ok() {
+        result = tokio::fs::rename(&tmp_path, path)
+            .await
+            .map_err(|e| BiliError::io(format!("保存文件失败: {}", e)));
+    }
+
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+    }
+    result
+}
+
+async fn write_chunks<T: JsonlItem + Sync>(
+    path: &Path,
+    module: BackupModule,
+    items: &[T],
+) -> Result<()> {
+    let write_error = |e: std::io::Error| BiliError::io(format!("写入文件失败: {}", e));
+    let mut file = tokio::fs::File::create(path)
+        .await
+        .map_err(|e| BiliError::io(format!("创建文件失败: {}", e)))?;
+
+    let mut buffer = Vec::with_capacity(WRITE_CHUNK_SIZE);
+    write_line(&mut buffer, &header_for(module, items))?;
+    for item in items {
+        item.write_lines(&mut buffer)?;
+        if buffer.len() >= WRITE_CHUNK_SIZE {
+            file.write_all(&buffer).await.map_err(write_error)?;
+            buffer.clear();
+        }
+    }
+    file.write_all(&buffer).await.map_err(write_error)?;
+    file.sync_all()
+        .await
+        .map_err(|e| BiliError::io(format!("同步文件失败: {}", e)))
+}
+
+/// 从导出文件导入
+///
+/// 支持 JSON Lines 格式和旧版的整体JSON格式,加密的文件会自动解密。
+/// 旧版本导出的数据会迁移到当前格式,由更新版本的应用导出的文件会被拒绝。
+///
+/// # 参数
+///
+/// * `path` - 导入文件路径
+/// * `module` - 所属模块
+/// * `passphrase` - 解密口令,文件未加密时忽略
+pub async fn import_jsonl<T: DeserializeOwned + Send + 'static>(
+    path: impl AsRef<Path>,
+    module: BackupModule,
+    passphrase: Option<&str>,
+) -> Result<Vec<T>> {
+    let path = path.as_ref().to_path_buf();
+    let passphrase = passphrase.map(str::to_string);
+    run_blocking(move || read_export_file(&path, module, passphrase.as_deref())).await
+}
+
+fn read_export_file<T: DeserializeOwned>(
+    path: &Path,
+    module: BackupModule,
+    passphrase: Option<&str>,
+) -> Result<Vec<T>> {
+    let file = File::open(path).map_err(|e| BiliError::io(format!("读取文件失败: {}", e)))?;
+    let mut reader = BufReader::new(file);
+
+    let encrypted = reader
+        .fill_buf()
+        .map(crypto::is_encrypted)
+        .map_err(|e| BiliError::io(format!("读取文件失败: {}", e)))?;
+    if encrypted {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .map_err(|e| BiliError::io(format!("读取文件失败: {}", e)))?;
+        let passphrase =
+            passphrase.ok_or_else(|| BiliError::crypto("备份文件已加密，请提供口令"))?;
+        let bytes = crypto::decrypt(&bytes, passphrase)?;
+        return read_items(Cursor::new(bytes), module);
+    }
+
+    read_items(reader, module)
+}
+
+/// 按文件头判断格式,逐项读取 JSON Lines 或整体解析旧版JSON
+fn read_items<R: BufRead, T: DeserializeOwned>(
+    mut reader: R,
+    module: BackupModule,
+) -> Result<Vec<T>> {
+    let mut first = String::new();
+    reader
+        .read_line(&mut first)
+        .map_err(|e| BiliError::io(format!("读取文件失败: {}", e)))?;
+
+    match JsonlHeader::parse(&first) {
+        Some(header) => JsonlReader::with_header(reader, header, module)?.collect(),
+        None => {
+            let mut bytes = first.into_bytes();
+            reader
+                .read_to_end(&mut bytes)
+                .map_err(|e| BiliError::io(format!("读取文件失败: {}", e)))?;
+            decode_payload(module, &bytes)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::models::ToView;
+    use crate::backup::schema::encode_payload;
+
+    fn toview(aid: u64) -> ToView {
+        serde_json::from_value(serde_json::json!({
+            "aid": aid,
+            "cid": aid,
+            "title": format!("视频{}", aid),
+            "pic": ""
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_jsonl_roundtrip() {
+        let items: Vec<ToView> = (1..=3).map(toview).collect();
+        let mut buffer = Vec::new();
+        write_jsonl(&mut buffer, BackupModule::ToView, &items).unwrap();
+
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        assert_eq!(text.lines().count(), 4);
+        assert!(text
+            .lines()
+            .next()
+            .unwrap()
+            .contains("\"format\":\"jsonl\""));
+
+        let reader = JsonlReader::new(Cursor::new(buffer), BackupModule::ToView).unwrap();
+        assert_eq!(reader.header().count, 3);
+        let read: Vec<ToView> = reader.collect::<Result<_>>().unwrap();
+        assert_eq!(
+            read.iter().map(|t| t.aid).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_jsonl_reader_detects_truncation() {
+        let items: Vec<ToView> = (1..=3).map(toview).collect();
+        let mut buffer = Vec::new();
+        write_jsonl(&mut buffer, BackupModule::ToView, &items).unwrap();
+
+        // 在最后一行中间截断
+        buffer.truncate(buffer.len() - 5);
+        let result: Result<Vec<ToView>> =
+            read_items(Cursor::new(buffer.clone()), BackupModule::ToView);
+        assert!(result.unwrap_err().to_string().contains("第 4 行"));
+
+        // 在行边界截断
+        let cut = buffer.iter().rposition(|b| *b == b'\n').unwrap() + 1;
+        buffer.truncate(cut);
+        let result: Result<Vec<ToView>> = read_items(Cursor::new(buffer), BackupModule::ToView);
+        assert!(result.unwrap_err().to_string().contains("不完整"));
+    }
+
+    #[test]
+    fn test_jsonl_rejects_other_module_and_newer_version() {
+        let mut buffer = Vec::new();
+        write_jsonl(&mut buffer, BackupModule::History, &[toview(1)]).unwrap();
+        assert!(JsonlReader::<_, ToView>::new(Cursor::new(buffer), BackupModule::ToView).is_err());
+
+        let header = JsonlHeader {
+            schema_version: SCHEMA_VERSION + 1,
+            ..JsonlHeader::new(BackupModule::ToView, 0)
+        };
+        let line = serde_json::to_string(&header).unwrap();
+        let error = JsonlReader::<_, ToView>::new(Cursor::new(line), BackupModule::ToView)
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("更新版本"));
+    }
+
+    #[test]
+    fn test_jsonl_v1_lines_are_migrated() {
+        let fixture = concat!(
+            r#"{"format":"jsonl","schema_version":1,"module":"toview","count":2}"#,
+            "\n",
+            r#"{"aid": 8, "cid": 8, "title": "第1版", "pic": ""}"#,
+            "\n",
+            r#"{"aid": 9, "cid": 9, "title": "第1版", "pic": ""}"#,
+            "\n"
+        );
+        let read: Vec<ToView> = read_items(Cursor::new(fixture), BackupModule::ToView).unwrap();
+        assert_eq!(read.iter().map(|t| t.aid).collect::<Vec<_>>(), vec![8, 9]);
+    }
+
+    #[test]
+    fn test_favorites_media_on_own_lines() {
+        let folder = |id: u64, media: Vec<u64>| -> FavFolderWithMedia {
+            serde_json::from_value(serde_json::json!({
+                "folder": {"id": id, "mid": 1, "attr": 0, "title": format!("收藏夹{}", id),
+                           "mediaCount": media.len()},
+                "media_list": media.iter().map(|m| serde_json::json!({
+                    "id": m, "type": 2, "title": format!("视频{}", m)
+                })).collect::<Vec<_>>()
+            }))
+            .unwrap()
+        };
+        let folders = vec![folder(1, vec![10, 11, 12]), folder(2, vec![])];
+        let mut buffer = Vec::new();
+        write_jsonl(&mut buffer, BackupModule::Favorites, &folders).unwrap();
+
+        let text = String::from_utf8(buffer.clone()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].contains("\"count\":5"));
+        assert!(lines[1].contains("\"media_list\":[]"));
+        assert!(lines[2].starts_with("{\"folder_id\":1,"));
+
+        let read: Vec<FavFolderWithMedia> =
+            read_items(Cursor::new(buffer.clone()), BackupModule::Favorites).unwrap();
+        let media: Vec<Vec<u64>> = read
+            .iter()
+            .map(|f| f.media_list.iter().map(|m| m.id).collect())
+            .collect();
+        assert_eq!(media, vec![vec![10, 11, 12], vec![]]);
+
+        // 截断在收藏夹的媒体行中间
+        let cut = text.match_indices('\n').nth(2).unwrap().0 + 1;
+        buffer.truncate(cut);
+        let result: Result<Vec<FavFolderWithMedia>> =
+            read_items(Cursor::new(buffer), BackupModule::Favorites);
+        assert!(result.unwrap_err().to_string().contains("不完整"));
+    }
+
+    #[test]
+    fn test_read_legacy_export() {
+        let items = vec![toview(7)];
+        let payload = encode_payload(BackupModule::ToView, &items).unwrap();
+        let read: Vec<ToView> = read_items(Cursor::new(payload), BackupModule::ToView).unwrap();
+        assert_eq!(read[0].aid, 7);
+
+        let legacy = r#"[{"aid": 8, "cid": 8, "title": "旧版", "pic": ""}]"#;
+        let read: Vec<ToView> = read_items(Cursor::new(legacy), BackupModule::ToView).unwrap();
+        assert_eq!(read[0].aid, 8);
+    }
+
+    #[tokio::test]
+    async fn test_export_import_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let items: Vec<ToView> = (1..=2).map(toview).collect();
+
+        let plain = dir.path().join("toview.jsonl");
+        export_jsonl(&plain, BackupModule::ToView, &items, None)
+            .await
+            .unwrap();
+        assert!(!temp_path(&plain).exists());
+        let read: Vec<ToView> = import_jsonl(&plain, BackupModule::ToView, None)
+            .await
+            .unwrap();
+        assert_eq!(read.len(), 2);
+
+        let sealed = dir.path().join("toview.enc.jsonl");
+        export_jsonl(&sealed, BackupModule::ToView, &items, Some("口令"))
+            .await
+            .unwrap();
+        assert!(import_jsonl::<ToView>(&sealed, BackupModule::ToView, None)
+            .await
+            .is_err());
+        let read: Vec<ToView> = import_jsonl(&sealed, BackupModule::ToView, Some("口令"))
+            .await
+            .unwrap();
+        assert_eq!(read.len(), 2);
+    }
+}
@@ -0,0 +1,800 @@
+//! 原版 bilibili-backup (Java) 备份导入
+//!
+//! 原版工具为每个账号生成一个备份目录,目录中每类数据一个JSON文件
+//! (例如 `关注/关注分组.json`、`关注/关注.json`、`收藏夹/创建的收藏夹.json`),
+//! 字段名与本项目的模型不同。本模块读取该目录 (或其zip压缩包),
+//! 按文件名识别模块,并转换为本项目的模型,结果可直接交给各 `restore_*` 服务。
+
+use crate::api::error::{BiliError, Result};
+use crate::api::models::{
+    Bangumi, CntInfo, FavInfo, History, HistoryItem, Media, Relation, RelationTag, ToView, Upper,
+    User, Vip,
+};
+use crate::backup::archive::{run_blocking, zip_error, BackupArchive, BackupData};
+use crate::backup::manifest::{BackupModule, BackupSource};
+use crate::services::favorites::FavFolderWithMedia;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+
+/// 原版备份中各模块对应的文件名 (不含扩展名)
+///
+/// 同一模块可能对应多个文件 (例如追番和追剧分开保存),导入时会合并。
+const LEGACY_FILE_NAMES: [(BackupModule, &[&str]); 8] = [
+    (
+        BackupModule::RelationTags,
+        &["关注分组", "relation_tags", "relationtags"],
+    ),
+    (
+        BackupModule::Following,
+        &["关注", "following", "followings"],
+    ),
+    (BackupModule::Followers, &["粉丝", "followers", "fans"]),
+    (BackupModule::Blacklist, &["黑名单", "blacklist"]),
+    (
+        BackupModule::Favorites,
+        &["创建的收藏夹", "收藏夹", "favorites", "fav_folders"],
+    ),
+    (BackupModule::History, &["历史记录", "history"]),
+    (
+        BackupModule::Bangumi,
+        &["追番", "追剧", "追番追剧", "bangumi"],
+    ),
+    (BackupModule::ToView, &["稍后再看", "toview"]),
+];
+
+/// 原版备份导入结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegacyImport {
+    /// 转换后的备份归档
+    pub archive: BackupArchive,
+    /// 已导入的文件 (相对路径)
+    pub imported_files: Vec<String>,
+    /// 未识别而跳过的JSON文件 (相对路径)
+    pub skipped_files: Vec<String>,
+}
+
+/// 导入原版 bilibili-backup 的备份
+///
+/// # 参数
+///
+/// * `path` - 账号备份目录,或该目录的zip压缩包
+///
+/// # 返回
+///
+/// 转换后的备份归档及导入的文件列表
+///
+/// # 示例
+///
+/// ```rust,no_run
+/// # use bilibili_backup_tauri::backup::import_legacy_backup;
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let imported = import_legacy_backup("备份数据/123456_用户名").await?;
+/// println!("导入了 {} 个文件", imported.imported_files.len());
+/// # Ok(())
+/// # }
+/// ```
+pub async fn import_legacy_backup(path: impl AsRef<Path>) -> Result<LegacyImport> {
+    let path = path.as_ref().to_path_buf();
+    run_blocking(move || {
+        let files = if path.is_dir() {
+            read_dir_files(&path)?
+        } else {
+            read_zip_files(&path)?
+        };
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        convert_files(&name, files)
+    })
+    .await
+}
+
+/// 递归读取目录中的所有JSON文件
+fn read_dir_files(root: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| BiliError::io(format!("读取目录 {} 失败: {}", dir.display(), e)))?;
+        for entry in entries {
+            let path = entry
+                .map_err(|e| BiliError::io(format!("读取目录失败: {}", e)))?
+                .path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if is_json(&path.to_string_lossy()) {
+                let bytes = std::fs::read(&path).map_err(|e| {
+                    BiliError::io(format!("读取文件 {} 失败: {}", path.display(), e))
+                })?;
+                let relative = path.strip_prefix(root).unwrap_or(&path);
+                files.push((relative.to_string_lossy().replace('\\', "/"), bytes));
+            }
+        }
+    }
+
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(files)
+}
+
+/// 读取zip压缩包中的所有JSON文件
+fn read_zip_files(path: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    let file = File::open(path).map_err(|e| BiliError::io(format!("打开压缩包失败: {}", e)))?;
+    let mut zip = ZipArchive::new(file).map_err(zip_error)?;
+
+    let mut files = Vec::new();
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i).map_err(zip_error)?;
+        let name = entry.name().to_string();
+        if entry.is_dir() || name.starts_with("__MACOSX/") || !is_json(&name) {
+            continue;
+        }
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        entry
+            .read_to_end(&mut bytes)
+            .map_err(|e| BiliError::io(format!("读取压缩包条目 {} 失败: {}", name, e)))?;
+        files.push((name, bytes));
+    }
+
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(files)
+}
+
+fn is_json(name: &str) -> bool {
+    name.to_lowercase().ends_with(".json")
+}
+
+/// 根据文件名识别模块
+fn module_for_file(path: &str) -> Option<BackupModule> {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let stem = file_name
+        .rsplit_once('.')
+        .map_or(file_name, |(stem, _)| stem)
+        .to_lowercase();
+
+    LEGACY_FILE_NAMES
+        .iter()
+        .find(|(_, names)| names.contains(&stem.as_str()))
+        .map(|(module, _)| *module)
+}
+
+/// 将识别出的文件转换为备份归档
+fn convert_files(name: &str, files: Vec<(String, Vec<u8>)>) -> Result<LegacyImport> {
+    let mut grouped: BTreeMap<BackupModule, Vec<Value>> = BTreeMap::new();
+    let mut imported_files = Vec::new();
+    let mut skipped_files = Vec::new();
+
+    for (path, bytes) in files {
+        let Some(module) = module_for_file(&path) else {
+            skipped_files.push(path);
+            continue;
+        };
+
+        let value: Value = serde_json::from_slice(&bytes)
+            .map_err(|e| BiliError::parse(format!("{} 不是有效的JSON: {}", path, e)))?;
+        let items = extract_list(value)
+            .ok_or_else(|| BiliError::parse(format!("{} 中没有找到数据列表", path)))?;
+
+        tracing::debug!("{} → {} ({} 条)", path, module.display_name(), items.len());
+        grouped.entry(module).or_default().extend(items);
+        imported_files.push(path);
+    }
+
+    if imported_files.is_empty() {
+        return Err(BiliError::param("没有找到原版 bilibili-backup 的备份文件"));
+    }
+
+    let mut data = BackupData::default();
+    for (module, items) in grouped {
+        convert_module(&mut data, module, items)?;
+    }
+
+    let mut archive = BackupArchive::new(BackupSource {
+        uid: parse_uid(name).unwrap_or(0),
+        uname: None,
+    });
+    archive.data = data;
+
+    tracing::info!(
+        "导入原版备份: {} 个文件，跳过 {} 个",
+        imported_files.len(),
+        skipped_files.len()
+    );
+    Ok(LegacyImport {
+        archive,
+        imported_files,
+        skipped_files,
+    })
+}
+
+/// 取出数据列表 (支持裸数组以及 `list`/`data` 包装)
+fn extract_list(value: Value) -> Option<Vec<Value>> {
+    match value {
+        Value::Array(items) => Some(items),
+        Value::Object(mut map) => ["list", "data", "items"]
+            .iter()
+            .find_map(|key| map.remove(*key))
+            .and_then(extract_list),
+        _ => None,
+    }
+}
+
+/// 从备份目录名中解析UID (例如 `123456_用户名`)
+fn parse_uid(name: &str) -> Option<u64> {
+    name.split(|c: char| !c.is_ascii_digit())
+        .find(|part| part.len() >= 3)
+        .and_then(|part| part.parse().ok())
+}
+
+/// 转换单个模块
+fn convert_module(data: &mut BackupData, module: BackupModule, items: Vec<Value>) -> Result<()> {
+    match module {
+        BackupModule::RelationTags => {
+            data.relation_tags = Some(convert::<LegacyRelationTag, _>(module, items)?)
+        }
+        BackupModule::Following => {
+            data.following = Some(convert::<LegacyRelation, _>(module, items)?)
+        }
+        BackupModule::Followers => {
+            data.followers = Some(convert::<LegacyRelation, _>(module, items)?)
+        }
+        BackupModule::Blacklist => {
+            data.blacklist = Some(convert::<LegacyRelation, _>(module, items)?)
+        }
+        BackupModule::Favorites => {
+            data.favorites = Some(convert::<LegacyFavFolder, _>(module, items)?)
+        }
+        BackupModule::History => data.history = Some(convert::<LegacyHistory, _>(module, items)?),
+        BackupModule::Bangumi => data.bangumi = Some(convert::<LegacyBangumi, _>(module, items)?),
+        BackupModule::ToView => data.toview = Some(convert::<LegacyToView, _>(module, items)?),
+    }
+    Ok(())
+}
+
+/// 统一字段名后反序列化为原版模型,再转换为本项目的模型
+fn convert<L, T>(module: BackupModule, items: Vec<Value>) -> Result<Vec<T>>
+where
+    L: DeserializeOwned + Into<T>,
+{
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(i, item)| {
+            serde_json::from_value::<L>(normalize_keys(item))
+                .map(Into::into)
+                .map_err(|e| {
+                    BiliError::parse(format!(
+                        "{}第 {} 条数据转换失败: {}",
+                        module.display_name(),
+                        i + 1,
+                        e
+                    ))
+                })
+        })
+        .collect()
+}
+
+/// 将所有对象键转换为snake_case (原版混用camelCase和接口原始字段名)
+fn normalize_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (to_snake_case(&key), normalize_keys(value)))
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(normalize_keys).collect()),
+        other => other,
+    }
+}
+
+fn to_snake_case(key: &str) -> String {
+    let mut result = String::with_capacity(key.len() + 4);
+    for (i, c) in key.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                result.push('_');
+            }
+            result.push(c.to_ascii_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// 数字或字符串字段转换为字符串 (原版部分字段类型与本项目不同)
+fn value_to_string(value: Option<Value>) -> Option<String> {
+    match value? {
+        Value::String(s) => Some(s),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+// ========== 原版数据模型 (键已统一为snake_case) ==========
+
+#[derive(Deserialize)]
+struct LegacyRelationTag {
+    #[serde(alias = "tag_id")]
+    tagid: i64,
+    name: String,
+    count: Option<usize>,
+    tip: Option<String>,
+}
+
+impl From<LegacyRelationTag> for RelationTag {
+    fn from(tag: LegacyRelationTag) -> Self {
+        Self {
+            tag_id: tag.tagid,
+            name: tag.name,
+            count: tag.count,
+            tip: tag.tip,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LegacyVip {
+    vip_type: Option<i32>,
+    vip_due_date: Option<i64>,
+    due_remark: Option<String>,
+    vip_status: Option<i32>,
+    nickname_color: Option<String>,
+}
+
+impl From<LegacyVip> for Vip {
+    fn from(vip: LegacyVip) -> Self {
+        Self {
+            vip_type: vip.vip_type,
+            vip_due_date: vip.vip_due_date,
+            due_remark: vip.due_remark,
+            vip_status: vip.vip_status,
+            nickname_color: vip.nickname_color,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LegacyRelation {
+    mid: u64,
+    #[serde(default, alias = "name")]
+    uname: String,
+    #[serde(default)]
+    face: String,
+    sign: Option<String>,
+    #[serde(default)]
+    mtime: i64,
+    attribute: Option<i32>,
+    special: Option<i32>,
+    tag: Option<Vec<i64>>,
+    vip: Option<LegacyVip>,
+}
+
+impl From<LegacyRelation> for Relation {
+    fn from(relation: LegacyRelation) -> Self {
+        Self {
+            mid: relation.mid,
+            uname: relation.uname,
+            face: relation.face,
+            sign: relation.sign,
+            mtime: relation.mtime,
+            attribute: relation.attribute,
+            special: relation.special,
+            tag: relation.tag,
+            vip: relation.vip.map(Into::into),
+        }
+    }
+}
+
+/// 原版黑名单与关注使用相同的结构
+impl From<LegacyRelation> for User {
+    fn from(relation: LegacyRelation) -> Self {
+        Self {
+            mid: relation.mid,
+            uname: relation.uname,
+            face: relation.face,
+            sign: relation.sign,
+            sex: None,
+            level: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LegacyUpper {
+    mid: u64,
+    #[serde(default, alias = "uname")]
+    name: String,
+    #[serde(default)]
+    face: String,
+}
+
+impl From<LegacyUpper> for Upper {
+    fn from(upper: LegacyUpper) -> Self {
+        Self {
+            mid: upper.mid,
+            name: upper.name,
+            face: upper.face,
+            sex: None,
+            level: None,
+            no_face: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LegacyCntInfo {
+    #[serde(default)]
+    collect: i32,
+    #[serde(default)]
+    play: i32,
+    #[serde(default)]
+    danmaku: i32,
+    share: Option<i32>,
+    #[serde(default)]
+    thumb_up: i32,
+}
+
+impl From<LegacyCntInfo> for CntInfo {
+    fn from(cnt: LegacyCntInfo) -> Self {
+        Self {
+            collect: cnt.collect,
+            play: cnt.play,
+            danmaku: cnt.danmaku,
+            share: cnt.share,
+            thumb_up: cnt.thumb_up,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LegacyMedia {
+    id: u64,
+    #[serde(rename = "type", default = "default_media_type")]
+    item_type: u32,
+    #[serde(default)]
+    title: String,
+    cover: Option<String>,
+    intro: Option<String>,
+    page: Option<i32>,
+    duration: Option<i32>,
+    upper: Option<LegacyUpper>,
+    attr: Option<i32>,
+    cnt_info: Option<LegacyCntInfo>,
+    link: Option<String>,
+    ctime: Option<i64>,
+    pubtime: Option<i64>,
+    fav_time: Option<i64>,
+    #[serde(alias = "bv_id")]
+    bvid: Option<String>,
+}
+
+/// 原版缺少类型时默认为视频
+fn default_media_type() -> u32 {
+    2
+}
+
+impl From<LegacyMedia> for Media {
+    fn from(media: LegacyMedia) -> Self {
+        Self {
+            id: media.id,
+            item_type: media.item_type,
+            title: media.title,
+            cover: media.cover,
+            intro: media.intro,
+            page: media.page,
+            duration: media.duration,
+            upper: media.upper.map(Into::into),
+            attr: media.attr,
+            cnt_info: media.cnt_info.map(Into::into),
+            link: media.link,
+            ctime: media.ctime,
+            pubtime: media.pubtime,
+            fav_time: media.fav_time,
+            bv_id: media.bvid.clone(),
+            bvid: media.bvid,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LegacyFavFolder {
+    id: u64,
+    fid: Option<u64>,
+    #[serde(default)]
+    mid: u64,
+    #[serde(default)]
+    attr: i32,
+    title: String,
+    cover: Option<String>,
+    ctime: Option<i64>,
+    #[serde(default)]
+    media_count: u32,
+    intro: Option<String>,
+    #[serde(default, alias = "media_list")]
+    medias: Option<Vec<LegacyMedia>>,
+}
+
+impl From<LegacyFavFolder> for FavFolderWithMedia {
+    fn from(folder: LegacyFavFolder) -> Self {
+        let media_list: Vec<Media> = folder
+            .medias
+            .unwrap_or_default()
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        Self {
+            folder: FavInfo {
+                id: folder.id,
+                fid: folder.fid,
+                mid: folder.mid,
+                attr: folder.attr,
+                title: folder.title,
+                cover: folder.cover,
+                ctime: folder.ctime,
+                media_count: folder.media_count.max(media_list.len() as u32),
+            },
+            intro: folder.intro,
+            media_list,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LegacyHistoryItem {
+    #[serde(default)]
+    oid: u64,
+    epid: Option<u64>,
+    bvid: Option<String>,
+    page: Option<i32>,
+    cid: Option<u64>,
+    part: Option<String>,
+    business: Option<String>,
+    dt: Option<i32>,
+}
+
+impl From<LegacyHistoryItem> for HistoryItem {
+    fn from(item: LegacyHistoryItem) -> Self {
+        Self {
+            oid: item.oid,
+            epid: item.epid,
+            bvid: item.bvid,
+            page: item.page,
+            cid: item.cid,
+            part: item.part,
+            business: item.business,
+            dt: item.dt,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LegacyHistory {
+    #[serde(default)]
+    title: String,
+    cover: Option<String>,
+    uri: Option<String>,
+    history: Option<LegacyHistoryItem>,
+    videos: Option<i32>,
+    author_name: Option<String>,
+    author_mid: Option<u64>,
+    view_at: Option<i64>,
+    progress: Option<i32>,
+    show_title: Option<String>,
+    duration: Option<i32>,
+    kid: Option<u64>,
+}
+
+impl From<LegacyHistory> for History {
+    fn from(history: LegacyHistory) -> Self {
+        Self {
+            title: history.title,
+            cover: history.cover,
+            uri: history.uri,
+            history: history.history.map(Into::into),
+            videos: history.videos,
+            author_name: history.author_name,
+            author_mid: history.author_mid,
+            view_at: history.view_at,
+            progress: history.progress,
+            show_title: history.show_title,
+            duration: history.duration,
+            kid: history.kid,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LegacyBangumi {
+    season_id: u64,
+    #[serde(default)]
+    media_id: u64,
+    season_type: Option<Value>,
+    season_type_name: Option<String>,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    cover: String,
+    total_count: Option<i32>,
+    badge: Option<String>,
+    badge_type: Option<i32>,
+    url: Option<String>,
+    follow_status: Option<i32>,
+}
+
+impl From<LegacyBangumi> for Bangumi {
+    fn from(bangumi: LegacyBangumi) -> Self {
+        Self {
+            season_id: bangumi.season_id,
+            media_id: bangumi.media_id,
+            season_type: value_to_string(bangumi.season_type),
+            season_type_name: bangumi.season_type_name,
+            title: bangumi.title,
+            cover: bangumi.cover,
+            total_count: bangumi.total_count,
+            badge: bangumi.badge,
+            badge_type: bangumi.badge_type,
+            url: bangumi.url,
+            follow_status: bangumi.follow_status,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LegacyToView {
+    aid: u64,
+    bvid: Option<String>,
+    #[serde(default)]
+    cid: u64,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    pic: String,
+    owner: Option<LegacyUpper>,
+    add_at: Option<i64>,
+    duration: Option<i32>,
+    state: Option<i32>,
+    videos: Option<i32>,
+}
+
+impl From<LegacyToView> for ToView {
+    fn from(video: LegacyToView) -> Self {
+        Self {
+            aid: video.aid,
+            bvid: video.bvid,
+            cid: video.cid,
+            title: video.title,
+            pic: video.pic,
+            owner: video.owner.map(Into::into),
+            add_at: video.add_at,
+            duration: video.duration,
+            state: video.state,
+            videos: video.videos,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn file(path: &str, value: Value) -> (String, Vec<u8>) {
+        (path.to_string(), serde_json::to_vec(&value).unwrap())
+    }
+
+    #[test]
+    fn test_module_for_file() {
+        assert_eq!(
+            module_for_file("关注/关注分组.json"),
+            Some(BackupModule::RelationTags)
+        );
+        assert_eq!(
+            module_for_file("关注/关注.json"),
+            Some(BackupModule::Following)
+        );
+        assert_eq!(
+            module_for_file("追番追剧/追剧.json"),
+            Some(BackupModule::Bangumi)
+        );
+        assert_eq!(
+            module_for_file("backup/ToView.JSON"),
+            Some(BackupModule::ToView)
+        );
+        assert_eq!(module_for_file("设置.json"), None);
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("mediaCount"), "media_count");
+        assert_eq!(to_snake_case("seasonId"), "season_id");
+        assert_eq!(to_snake_case("media_count"), "media_count");
+    }
+
+    #[test]
+    fn test_parse_uid() {
+        assert_eq!(parse_uid("123456_用户名"), Some(123456));
+        assert_eq!(parse_uid("用户名"), None);
+    }
+
+    #[test]
+    fn test_convert_files() {
+        let files = vec![
+            file(
+                "关注/关注分组.json",
+                json!([{"tagid": 1, "name": "游戏", "count": 1}]),
+            ),
+            file(
+                "关注/关注.json",
+                json!([{"mid": 2, "uname": "UP主", "face": "", "mtime": 1, "tag": [1]}]),
+            ),
+            file(
+                "收藏夹/创建的收藏夹.json",
+                json!([{
+                    "id": 10, "mid": 3, "attr": 0, "title": "默认收藏夹", "mediaCount": 0,
+                    "medias": [{"id": 100, "type": 2, "title": "视频", "bvid": "BV1xx", "cntInfo": {"play": 5}}]
+                }]),
+            ),
+            file(
+                "追番追剧/追番.json",
+                json!([{"season_id": 1, "title": "番剧", "season_type": 1}]),
+            ),
+            file(
+                "追番追剧/追剧.json",
+                json!({"list": [{"seasonId": 2, "title": "剧集"}]}),
+            ),
+            file("设置.json", json!({})),
+        ];
+
+        let imported = convert_files("123456_用户名", files).unwrap();
+        let data = &imported.archive.data;
+
+        assert_eq!(imported.archive.source.uid, 123456);
+        assert_eq!(imported.imported_files.len(), 5);
+        assert_eq!(imported.skipped_files, vec!["设置.json".to_string()]);
+        assert_eq!(data.relation_tags.as_ref().unwrap()[0].name, "游戏");
+        assert_eq!(data.following.as_ref().unwrap()[0].tag, Some(vec![1]));
+
+        let folder = &data.favorites.as_ref().unwrap()[0];
+        assert_eq!(folder.folder.media_count, 1);
+        assert_eq!(folder.media_list[0].bvid.as_deref(), Some("BV1xx"));
+        assert_eq!(folder.media_list[0].cnt_info.as_ref().unwrap().play, 5);
+
+        let bangumi = data.bangumi.as_ref().unwrap();
+        assert_eq!(bangumi.len(), 2);
+        assert_eq!(bangumi[0].season_type.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_convert_files_without_known_files() {
+        let files = vec![file("设置.json", json!({}))];
+        assert!(convert_files("backup", files).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_legacy_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("稍后再看")).unwrap();
+        std::fs::write(
+            dir.path().join("稍后再看/稍后再看.json"),
+            serde_json::to_vec(&json!([{"aid": 1, "title": "视频", "addAt": 1700000000}])).unwrap(),
+        )
+        .unwrap();
+
+        let imported = import_legacy_backup(dir.path()).await.unwrap();
+        let toview = imported.archive.data.toview.unwrap();
+        assert_eq!(toview[0].add_at, Some(1700000000));
+        assert_eq!(
+            imported.imported_files,
+            vec!["稍后再看/稍后再看.json".to_string()]
+        );
+    }
+}
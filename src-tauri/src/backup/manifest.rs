@@ -0,0 +1,232 @@
+use crate::api::pagination::Completeness;
+use serde::{Deserialize, Serialize};
+
+/// 当前备份归档格式版本
+///
+/// 版本历史:
+/// - 1、2: 与数据载荷版本一致
+/// - 3: 视频元数据提取到 `videos.json`,模块条目只保留与规范记录不同的字段
+pub const ARCHIVE_SCHEMA_VERSION: u32 = 3;
+
+/// 清单在归档中的文件名
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// 视频元数据目录在归档中的文件名
+pub const VIDEOS_FILE_NAME: &str = "videos.json";
+
+/// 本地图片索引在归档中的文件名
+pub const IMAGES_FILE_NAME: &str = "images.json";
+
+/// 备份模块
+///
+/// 归档中每个模块对应一个条目文件。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupModule {
+    /// 关注分组
+    RelationTags,
+    /// 关注列表
+    Following,
+    /// 粉丝列表
+    Followers,
+    /// 黑名单
+    Blacklist,
+    /// 收藏夹
+    Favorites,
+    /// 历史记录
+    History,
+    /// 追番追剧
+    Bangumi,
+    /// 稍后再看
+    #[serde(rename = "toview")]
+    ToView,
+}
+
+impl BackupModule {
+    /// 所有模块 (按归档中的顺序)
+    pub const ALL: [BackupModule; 8] = [
+        BackupModule::RelationTags,
+        BackupModule::Following,
+        BackupModule::Followers,
+        BackupModule::Blacklist,
+        BackupModule::Favorites,
+        BackupModule::History,
+        BackupModule::Bangumi,
+        BackupModule::ToView,
+    ];
+
+    /// 模块标识 (与序列化名称一致)
+    pub fn key(&self) -> &'static str {
+        match self {
+            BackupModule::RelationTags => "relation_tags",
+            BackupModule::Following => "following",
+            BackupModule::Followers => "followers",
+            BackupModule::Blacklist => "blacklist",
+            BackupModule::Favorites => "favorites",
+            BackupModule::History => "history",
+            BackupModule::Bangumi => "bangumi",
+            BackupModule::ToView => "toview",
+        }
+    }
+
+    /// 模块在归档中的条目文件名
+    pub fn file_name(&self) -> String {
+        format!("{}.json", self.key())
+    }
+
+    /// 模块显示名称
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            BackupModule::RelationTags => "关注分组",
+            BackupModule::Following => "关注列表",
+            BackupModule::Followers => "粉丝列表",
+            BackupModule::Blacklist => "黑名单",
+            BackupModule::Favorites => "收藏夹",
+            BackupModule::History => "历史记录",
+            BackupModule::Bangumi => "追番追剧",
+            BackupModule::ToView => "稍后再看",
+        }
+    }
+
+    /// 是否可以还原到账号
+    ///
+    /// 粉丝由其他用户的关注产生,历史记录没有写入接口,二者只能备份。
+    pub fn is_restorable(&self) -> bool {
+        !matches!(self, BackupModule::Followers | BackupModule::History)
+    }
+}
+
+/// 备份来源账号
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupSource {
+    /// 用户ID
+    pub uid: u64,
+    /// 用户名
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uname: Option<String>,
+}
+
+/// 清单中的模块条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// 所属模块
+    pub module: BackupModule,
+    /// 条目文件名
+    pub path: String,
+    /// 数据项数量
+    pub count: usize,
+    /// 完整性统计 (获取时记录的预期数量与截断标记)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completeness: Option<Completeness>,
+    /// 条目内容的SHA-256 (十六进制)
+    pub sha256: String,
+    /// 条目内容大小 (字节)
+    pub size: u64,
+}
+
+impl ManifestEntry {
+    /// 条目是否被截断
+    pub fn is_truncated(&self) -> bool {
+        self.completeness.as_ref().is_some_and(|c| c.truncated)
+    }
+}
+
+/// 清单中的目录条目 (视频元数据目录、本地图片索引)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    /// 条目文件名
+    pub path: String,
+    /// 记录数量
+    pub count: usize,
+    /// 条目内容的SHA-256 (十六进制)
+    pub sha256: String,
+    /// 条目内容大小 (字节)
+    pub size: u64,
+}
+
+/// 备份清单
+///
+/// 记录归档的格式版本、来源账号、时间戳以及每个模块条目的数量和校验和。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// 归档格式版本
+    pub schema_version: u32,
+    /// 写入归档的应用版本
+    pub app_version: String,
+    /// 来源账号
+    pub source: BackupSource,
+    /// 备份开始时间 (时间戳,秒)
+    pub created_at: i64,
+    /// 归档写入时间 (时间戳,秒)
+    pub written_at: i64,
+    /// 模块条目
+    pub entries: Vec<ManifestEntry>,
+    /// 视频元数据目录条目 (第3版起)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub videos: Option<CatalogEntry>,
+    /// 本地图片索引条目 (只有归档过图片的备份才有)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<CatalogEntry>,
+}
+
+impl BackupManifest {
+    /// 查找模块条目
+    pub fn entry(&self, module: BackupModule) -> Option<&ManifestEntry> {
+        self.entries.iter().find(|e| e.module == module)
+    }
+
+    /// 被截断的模块条目
+    pub fn truncated_entries(&self) -> impl Iterator<Item = &ManifestEntry> {
+        self.entries.iter().filter(|e| e.is_truncated())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_module_key_matches_serde() {
+        for module in BackupModule::ALL {
+            let json = serde_json::to_string(&module).unwrap();
+            assert_eq!(json, format!("\"{}\"", module.key()));
+        }
+        assert_eq!(BackupModule::ToView.file_name(), "toview.json");
+    }
+
+    #[test]
+    fn test_manifest_truncated_entries() {
+        let manifest = BackupManifest {
+            schema_version: ARCHIVE_SCHEMA_VERSION,
+            app_version: "2.2.0".to_string(),
+            source: BackupSource::default(),
+            created_at: 0,
+            written_at: 0,
+            entries: vec![
+                ManifestEntry {
+                    module: BackupModule::Following,
+                    path: BackupModule::Following.file_name(),
+                    count: 250,
+                    completeness: Some(Completeness::new(Some(300), 250)),
+                    sha256: String::new(),
+                    size: 0,
+                },
+                ManifestEntry {
+                    module: BackupModule::History,
+                    path: BackupModule::History.file_name(),
+                    count: 10,
+                    completeness: None,
+                    sha256: String::new(),
+                    size: 0,
+                },
+            ],
+            videos: None,
+            images: None,
+        };
+
+        let truncated: Vec<_> = manifest.truncated_entries().map(|e| e.module).collect();
+        assert_eq!(truncated, vec![BackupModule::Following]);
+        assert!(manifest.entry(BackupModule::History).is_some());
+        assert!(manifest.entry(BackupModule::Favorites).is_none());
+    }
+}
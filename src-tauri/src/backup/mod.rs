@@ -0,0 +1,59 @@
+//! 备份文件格式模块
+//!
+//! 该模块负责备份数据的持久化格式和本地存储，各子模块的职责见下方说明。
+
+/// 备份清单
+pub mod manifest;
+
+/// 单文件备份归档
+pub mod archive;
+
+/// 数据格式版本与迁移
+pub mod schema;
+
+/// 原版 bilibili-backup 备份导入
+pub mod legacy;
+
+/// 本地SQLite快照库
+pub mod store;
+
+/// 增量备份
+pub mod incremental;
+
+/// 备份差异比较
+pub mod diff;
+
+/// 备份文件加密
+pub mod crypto;
+
+/// JSON Lines 流式导出
+pub mod jsonl;
+
+/// 备份文件完整性校验
+pub mod verify;
+
+/// 视频元数据目录
+pub mod videos;
+
+/// 本地图片归档
+pub mod images;
+
+/// 快照保留策略
+pub mod retention;
+
+// 导出常用类型
+pub use archive::{read_archive, read_manifest, write_archive, BackupArchive, BackupData};
+pub use diff::{diff_backups, BackupDiff, BackupDiffReport};
+pub use images::{
+    default_image_dir, ImageArchiveReport, ImageArchiveResult, ImageArchiver, ImageIndex, ImageRef,
+};
+pub use jsonl::{export_jsonl, import_jsonl, JsonlHeader, JsonlItem, JsonlReader};
+pub use legacy::{import_legacy_backup, LegacyImport};
+pub use manifest::{BackupManifest, BackupModule, BackupSource, ManifestEntry};
+pub use retention::{RetentionPlan, RetentionPolicy, RetentionReason};
+pub use schema::{decode_payload, decode_value, encode_payload, SCHEMA_VERSION};
+pub use store::{ChainVerification, FavoriteRecord, SnapshotInfo, SnapshotStore};
+pub use verify::{
+    verify_backup, BackupFileKind, EntryReport, IssueKind, VerifyIssue, VerifyReport,
+};
+pub use videos::{VideoCatalog, VideoRecord};
@@ -0,0 +1,320 @@
+//! 快照保留策略
+//!
+//! 定时备份会让快照库无限增长。保留策略按账号决定哪些快照需要保留:
+//! 最近N个、最近D天每天一个、最近W周每周一个、以及每月一个 (永久保留),
+//! 其余快照可以被清理。固定 (pin) 的快照永远不会被清理,
+//! 被保留的增量快照所依赖的快照链也会一并保留。快照库限制了增量快照链的长度
+//! (定期保存完整快照),因此只要较新的快照不再依赖旧的快照链,旧的快照链就会被整条清理。
+
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// 快照保留策略
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// 保留最近的快照数量
+    #[serde(default = "default_keep_last")]
+    pub keep_last: usize,
+    /// 最近多少天内每天保留一个快照
+    #[serde(default = "default_keep_daily")]
+    pub keep_daily: u32,
+    /// 最近多少周内每周保留一个快照
+    #[serde(default = "default_keep_weekly")]
+    pub keep_weekly: u32,
+    /// 是否每月保留一个快照 (永久)
+    #[serde(default = "default_keep_monthly")]
+    pub keep_monthly: bool,
+}
+
+fn default_keep_last() -> usize {
+    10
+}
+
+fn default_keep_daily() -> u32 {
+    7
+}
+
+fn default_keep_weekly() -> u32 {
+    4
+}
+
+fn default_keep_monthly() -> bool {
+    true
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_last: default_keep_last(),
+            keep_daily: default_keep_daily(),
+            keep_weekly: default_keep_weekly(),
+            keep_monthly: default_keep_monthly(),
+        }
+    }
+}
+
+/// 快照被保留的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionReason {
+    /// 用户固定的快照
+    Pinned,
+    /// 最近的N个快照之一
+    Last,
+    /// 当天最新的快照
+    Daily,
+    /// 当周最新的快照
+    Weekly,
+    /// 当月最新的快照
+    Monthly,
+    /// 被保留的增量快照所依赖
+    Base,
+}
+
+/// 参与保留计算的快照
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionCandidate {
+    /// 快照ID
+    pub id: i64,
+    /// 备份时间 (Unix时间戳, 秒)
+    pub created_at: i64,
+    /// 增量快照所基于的快照ID
+    pub base_id: Option<i64>,
+    /// 是否已固定
+    pub pinned: bool,
+}
+
+/// 保留的快照
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetainedSnapshot {
+    /// 快照ID
+    pub id: i64,
+    /// 备份时间
+    pub created_at: i64,
+    /// 保留原因
+    pub reasons: Vec<RetentionReason>,
+}
+
+/// 将被清理的快照
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrunedSnapshot {
+    /// 快照ID
+    pub id: i64,
+    /// 备份时间
+    pub created_at: i64,
+}
+
+/// 保留计划
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetentionPlan {
+    /// 保留的快照 (按备份时间倒序)
+    pub keep: Vec<RetainedSnapshot>,
+    /// 清理的快照 (按备份时间倒序)
+    pub prune: Vec<PrunedSnapshot>,
+}
+
+/// 计算保留计划
+///
+/// # 参数
+///
+/// * `snapshots` - 同一账号的所有快照
+/// * `policy` - 保留策略
+/// * `now` - 当前时间 (Unix时间戳, 秒),用于计算按天、按周的时间窗口
+pub fn plan_retention(
+    snapshots: &[RetentionCandidate],
+    policy: &RetentionPolicy,
+    now: i64,
+) -> RetentionPlan {
+    let mut sorted: Vec<&RetentionCandidate> = snapshots.iter().collect();
+    sorted.sort_by_key(|s| std::cmp::Reverse((s.created_at, s.id)));
+
+    let now = local_time(now);
+    let daily_since = now - Duration::days(i64::from(policy.keep_daily));
+    let weekly_since = now - Duration::weeks(i64::from(policy.keep_weekly));
+
+    let mut reasons: BTreeMap<i64, BTreeSet<RetentionReason>> = BTreeMap::new();
+    let mut days = BTreeSet::new();
+    let mut weeks = BTreeSet::new();
+    let mut months = BTreeSet::new();
+
+    for (index, snapshot) in sorted.iter().enumerate() {
+        let time = local_time(snapshot.created_at);
+        let keep = reasons.entry(snapshot.id).or_default();
+
+        if snapshot.pinned {
+            keep.insert(RetentionReason::Pinned);
+        }
+        if index < policy.keep_last {
+            keep.insert(RetentionReason::Last);
+        }
+        // 快照按时间倒序遍历,每个时间段第一次出现的就是该时间段最新的快照
+        if time > daily_since && days.insert(time.date_naive()) {
+            keep.insert(RetentionReason::Daily);
+        }
+        let week = time.iso_week();
+        if time > weekly_since && weeks.insert((week.year(), week.week())) {
+            keep.insert(RetentionReason::Weekly);
+        }
+        if policy.keep_monthly && months.insert((time.year(), time.month())) {
+            keep.insert(RetentionReason::Monthly);
+        }
+    }
+
+    // 保留增量快照所依赖的整条快照链
+    let by_id: HashMap<i64, &RetentionCandidate> = sorted.iter().map(|s| (s.id, *s)).collect();
+    let kept: Vec<i64> = reasons
+        .iter()
+        .filter(|(_, r)| !r.is_empty())
+        .map(|(id, _)| *id)
+        .collect();
+    for id in kept {
+        let mut base = by_id.get(&id).and_then(|s| s.base_id);
+        while let Some(base_id) = base {
+            let Some(entry) = reasons.get_mut(&base_id) else {
+                break;
+            };
+            if !entry.insert(RetentionReason::Base) {
+                break;
+            }
+            base = by_id.get(&base_id).and_then(|s| s.base_id);
+        }
+    }
+
+    let mut plan = RetentionPlan::default();
+    for snapshot in sorted {
+        let snapshot_reasons = reasons.remove(&snapshot.id).unwrap_or_default();
+        if snapshot_reasons.is_empty() {
+            plan.prune.push(PrunedSnapshot {
+                id: snapshot.id,
+                created_at: snapshot.created_at,
+            });
+        } else {
+            plan.keep.push(RetainedSnapshot {
+                id: snapshot.id,
+                created_at: snapshot.created_at,
+                reasons: snapshot_reasons.into_iter().collect(),
+            });
+        }
+    }
+    plan
+}
+
+fn local_time(timestamp: i64) -> DateTime<Local> {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .unwrap_or_else(|| Local.timestamp_opt(0, 0).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 3600;
+
+    fn candidate(id: i64, created_at: i64, base_id: Option<i64>) -> RetentionCandidate {
+        RetentionCandidate {
+            id,
+            created_at,
+            base_id,
+            pinned: false,
+        }
+    }
+
+    fn ids(plan: &RetentionPlan) -> (Vec<i64>, Vec<i64>) {
+        (
+            plan.keep.iter().map(|s| s.id).collect(),
+            plan.prune.iter().map(|s| s.id).collect(),
+        )
+    }
+
+    #[test]
+    fn test_keep_last_and_pinned() {
+        let now = Local
+            .with_ymd_and_hms(2024, 6, 15, 12, 0, 0)
+            .unwrap()
+            .timestamp();
+        let mut snapshots: Vec<_> = (1..=5)
+            .map(|i| candidate(i, now - 400 * DAY + i, None))
+            .collect();
+        snapshots[0].pinned = true;
+        let policy = RetentionPolicy {
+            keep_last: 2,
+            keep_daily: 0,
+            keep_weekly: 0,
+            keep_monthly: false,
+        };
+
+        let plan = plan_retention(&snapshots, &policy, now);
+        assert_eq!(ids(&plan), (vec![5, 4, 1], vec![3, 2]));
+        assert_eq!(plan.keep[2].reasons, vec![RetentionReason::Pinned]);
+    }
+
+    #[test]
+    fn test_daily_weekly_monthly_buckets() {
+        let now = Local
+            .with_ymd_and_hms(2024, 6, 15, 12, 0, 0)
+            .unwrap()
+            .timestamp();
+        let snapshots = vec![
+            // 同一天的两个快照只保留较新的一个
+            candidate(1, now - 2 * 3600, None),
+            candidate(2, now - 3 * 3600, None),
+            candidate(3, now - DAY, None),
+            // 超出按天窗口,但在按周窗口内
+            candidate(4, now - 10 * DAY, None),
+            candidate(5, now - 10 * DAY - 3600, None),
+            // 很久以前的快照按月保留
+            candidate(
+                6,
+                Local
+                    .with_ymd_and_hms(2023, 1, 20, 0, 0, 0)
+                    .unwrap()
+                    .timestamp(),
+                None,
+            ),
+            candidate(
+                7,
+                Local
+                    .with_ymd_and_hms(2023, 1, 10, 0, 0, 0)
+                    .unwrap()
+                    .timestamp(),
+                None,
+            ),
+        ];
+        let policy = RetentionPolicy {
+            keep_last: 0,
+            keep_daily: 3,
+            keep_weekly: 4,
+            keep_monthly: true,
+        };
+
+        let plan = plan_retention(&snapshots, &policy, now);
+        assert_eq!(ids(&plan), (vec![1, 3, 4, 6], vec![2, 5, 7]));
+    }
+
+    #[test]
+    fn test_incremental_chain_is_kept() {
+        let now = Local
+            .with_ymd_and_hms(2024, 6, 15, 12, 0, 0)
+            .unwrap()
+            .timestamp();
+        let snapshots = vec![
+            candidate(1, now - 3 * DAY, None),
+            candidate(2, now - 2 * DAY, Some(1)),
+            candidate(3, now - DAY, Some(2)),
+            candidate(4, now - 5 * DAY, None),
+        ];
+        let policy = RetentionPolicy {
+            keep_last: 1,
+            keep_daily: 0,
+            keep_weekly: 0,
+            keep_monthly: false,
+        };
+
+        let plan = plan_retention(&snapshots, &policy, now);
+        assert_eq!(ids(&plan), (vec![3, 2, 1], vec![4]));
+        assert_eq!(plan.keep[1].reasons, vec![RetentionReason::Base]);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::api::error::{BiliError, Result};
+use crate::backup::manifest::BackupModule;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// 当前数据格式版本
+///
+/// 版本历史:
+/// - 1: 裸JSON数组 (早期 `export_to_file` 导出的文件和第1版归档条目)
+/// - 2: 带版本信息的载荷 `{schema_version, module, items}`
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// 没有版本信息的裸JSON数组视为第1版
+const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+/// 迁移函数: 将某个版本的载荷升级到下一个版本
+type Migration = fn(BackupModule, Value) -> Result<Value>;
+
+/// 迁移链
+///
+/// 第 i 项把版本 `i + 1` 的载荷升级到版本 `i + 2`。
+/// 修改数据模型时,在末尾追加一个迁移函数并递增 [`SCHEMA_VERSION`]。
+const MIGRATIONS: [Migration; (SCHEMA_VERSION - 1) as usize] = [migrate_v1_to_v2];
+
+/// 第1版 → 第2版: 为裸数组加上版本信息
+fn migrate_v1_to_v2(module: BackupModule, value: Value) -> Result<Value> {
+    if !value.is_array() {
+        return Err(BiliError::parse(format!(
+            "第1版{}数据应为JSON数组",
+            module.display_name()
+        )));
+    }
+
+    Ok(json!({
+        "schema_version": 2,
+        "module": module,
+        "items": value,
+    }))
+}
+
+/// 读取载荷的版本 (裸数组视为第1版)
+fn payload_version(value: &Value) -> Result<u32> {
+    if value.is_array() {
+        return Ok(LEGACY_SCHEMA_VERSION);
+    }
+
+    value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .ok_or_else(|| BiliError::parse("数据缺少 schema_version 字段"))
+}
+
+/// 将任意版本的载荷升级到当前版本
+///
+/// # 错误
+///
+/// - 载荷由更新版本的应用写入
+/// - 载荷所属模块与期望的模块不一致
+/// - 迁移失败
+pub fn upgrade(module: BackupModule, mut value: Value) -> Result<Value> {
+    let mut version = payload_version(&value)?;
+
+    if version > SCHEMA_VERSION {
+        return Err(BiliError::newer_version(format!(
+            "{}数据由更新版本的应用创建 (格式版本 {}，当前支持 {})，请升级后再导入",
+            module.display_name(),
+            version,
+            SCHEMA_VERSION
+        )));
+    }
+    if version == 0 {
+        return Err(BiliError::parse("无效的格式版本: 0"));
+    }
+
+    while version < SCHEMA_VERSION {
+        let migrate = MIGRATIONS[(version - 1) as usize];
+        value = migrate(module, value)?;
+        version += 1;
+        tracing::debug!("{}数据已升级到第 {} 版", module.display_name(), version);
+    }
+
+    let actual = value.get("module").cloned().unwrap_or(Value::Null);
+    if actual != json!(module) {
+        return Err(BiliError::parse(format!(
+            "数据模块不匹配: 期望 {}，实际为 {}",
+            module.key(),
+            actual
+        )));
+    }
+
+    Ok(value)
+}
+
+/// 编码为当前版本的载荷
+pub fn encode_payload<T: Serialize>(module: BackupModule, items: &T) -> Result<Vec<u8>> {
+    let payload = json!({
+        "schema_version": SCHEMA_VERSION,
+        "module": module,
+        "items": items,
+    });
+    serde_json::to_vec_pretty(&payload).map_err(|e| BiliError::parse(format!("序列化失败: {}", e)))
+}
+
+/// 解码任意版本的载荷
+///
+/// 先升级到当前版本,再反序列化为当前的数据模型。
+pub fn decode_payload<T: DeserializeOwned>(module: BackupModule, bytes: &[u8]) -> Result<T> {
+    let value: Value = serde_json::from_slice(bytes).map_err(|e| {
+        BiliError::parse(format!(
+            "{}数据不是有效的JSON: {}",
+            module.display_name(),
+            e
+        ))
+    })?;
+    decode_value(module, value)
+}
+
+/// 按指定版本把数据项组装为载荷 (第1版为裸数组)
+///
+/// 用于版本信息与数据分开保存的场合,例如 JSON Lines 文件头和快照库。
+pub fn versioned_payload(module: BackupModule, version: u32, items: Vec<Value>) -> Value {
+    if version == LEGACY_SCHEMA_VERSION {
+        return Value::Array(items);
+    }
+    json!({
+        "schema_version": version,
+        "module": module,
+        "items": items,
+    })
+}
+
+/// 解码指定版本的数据项
+///
+/// 数据项先组装为该版本的载荷,经迁移链升级后再反序列化。
+pub fn decode_items<T: DeserializeOwned>(
+    module: BackupModule,
+    version: u32,
+    items: Vec<Value>,
+) -> Result<Vec<T>> {
+    decode_value(module, versioned_payload(module, version, items))
+}
+
+/// 解码已解析为JSON的任意版本载荷
+pub fn decode_value<T: DeserializeOwned>(module: BackupModule, value: Value) -> Result<T> {
+    let mut value = upgrade(module, value)?;
+    let items = value
+        .get_mut("items")
+        .map(Value::take)
+        .ok_or_else(|| BiliError::parse("数据缺少 items 字段"))?;
+
+    serde_json::from_value(items)
+        .map_err(|e| BiliError::parse(format!("{}数据反序列化失败: {}", module.display_name(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::models::Bangumi;
+
+    #[test]
+    fn test_migrations_cover_all_versions() {
+        assert_eq!(
+            MIGRATIONS.len() as u32,
+            SCHEMA_VERSION - LEGACY_SCHEMA_VERSION
+        );
+    }
+
+    #[test]
+    fn test_decode_legacy_array() {
+        let legacy = r#"[{"seasonId": 1, "mediaId": 2, "title": "番剧", "cover": ""}]"#.as_bytes();
+        let list: Vec<Bangumi> = decode_payload(BackupModule::Bangumi, legacy).unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].season_id, 1);
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let items = vec!["a".to_string(), "b".to_string()];
+        let bytes = encode_payload(BackupModule::History, &items).unwrap();
+        let value: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["module"], "history");
+
+        let decoded: Vec<String> = decode_payload(BackupModule::History, &bytes).unwrap();
+        assert_eq!(decoded, items);
+    }
+
+    #[test]
+    fn test_decode_items_of_legacy_version() {
+        let items = vec![json!({"seasonId": 1, "mediaId": 2, "title": "番剧", "cover": ""})];
+        let list: Vec<Bangumi> =
+            decode_items(BackupModule::Bangumi, LEGACY_SCHEMA_VERSION, items).unwrap();
+        assert_eq!(list[0].season_id, 1);
+    }
+
+    #[test]
+    fn test_reject_newer_version() {
+        let payload = json!({
+            "schema_version": SCHEMA_VERSION + 1,
+            "module": "history",
+            "items": []
+        });
+        let err = upgrade(BackupModule::History, payload).unwrap_err();
+        assert!(err.is_newer_version());
+        assert!(err.to_string().contains("更新版本"));
+    }
+
+    #[test]
+    fn test_reject_module_mismatch() {
+        let bytes = encode_payload(BackupModule::ToView, &Vec::<u64>::new()).unwrap();
+        let err = decode_payload::<Vec<u64>>(BackupModule::History, &bytes).unwrap_err();
+        assert!(err.to_string().contains("模块不匹配"));
+    }
+}
@@ -0,0 +1,2078 @@
+//! 本地SQLite快照库
+//!
+//! 每次备份保存为一个快照,各模块数据按快照ID和账号分表存储。
+//! 每行同时保存用于查询的关键字段和完整的JSON数据,
+//! 因此既可以跨快照查询 (例如"第一次收藏某个视频是什么时候"),
+//! 也可以无损地导出为备份归档。
+
+use crate::api::error::{BiliError, Result};
+use crate::api::models::{Media, INVALID_MEDIA_TITLE};
+use crate::api::pagination::Completeness;
+use crate::backup::archive::{
+    read_archive, run_blocking, write_archive, BackupArchive, BackupData,
+};
+use crate::backup::incremental::{
+    apply_changes, compute_changes, digest, flatten, module_values, unflatten, ChangeOp,
+    ItemChange, KeyedRow,
+};
+use crate::backup::manifest::{BackupManifest, BackupModule, BackupSource};
+use crate::backup::retention::{self, RetentionCandidate, RetentionPlan, RetentionPolicy};
+use crate::backup::schema::{decode_items, SCHEMA_VERSION};
+use crate::backup::videos::{VideoCatalog, VideoRecord};
+use crate::services::favorites::FavFolderWithMedia;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 数据库结构迁移 (第 i 项将 `user_version` 从 i 升级到 i + 1)
+const STORE_MIGRATIONS: &[&str] = &[
+    r#"
+    CREATE TABLE snapshots (
+        id              INTEGER PRIMARY KEY AUTOINCREMENT,
+        uid             INTEGER NOT NULL,
+        uname           TEXT,
+        created_at      INTEGER NOT NULL,
+        saved_at        INTEGER NOT NULL,
+        schema_version  INTEGER NOT NULL
+    );
+    CREATE INDEX idx_snapshots_uid ON snapshots (uid, created_at);
+
+    CREATE TABLE snapshot_modules (
+        snapshot_id  INTEGER NOT NULL REFERENCES snapshots (id) ON DELETE CASCADE,
+        module       TEXT NOT NULL,
+        count        INTEGER NOT NULL,
+        completeness TEXT,
+        PRIMARY KEY (snapshot_id, module)
+    );
+
+    CREATE TABLE relation_tags (
+        snapshot_id  INTEGER NOT NULL REFERENCES snapshots (id) ON DELETE CASCADE,
+        position     INTEGER NOT NULL,
+        tag_id       INTEGER NOT NULL,
+        name         TEXT NOT NULL,
+        data         TEXT NOT NULL
+    );
+
+    CREATE TABLE relations (
+        snapshot_id  INTEGER NOT NULL REFERENCES snapshots (id) ON DELETE CASCADE,
+        module       TEXT NOT NULL,
+        position     INTEGER NOT NULL,
+        mid          INTEGER NOT NULL,
+        uname        TEXT NOT NULL,
+        data         TEXT NOT NULL
+    );
+    CREATE INDEX idx_relations_mid ON relations (mid);
+
+    CREATE TABLE fav_folders (
+        snapshot_id  INTEGER NOT NULL REFERENCES snapshots (id) ON DELETE CASCADE,
+        position     INTEGER NOT NULL,
+        folder_id    INTEGER NOT NULL,
+        title        TEXT NOT NULL,
+        data         TEXT NOT NULL
+    );
+
+    CREATE TABLE fav_media (
+        snapshot_id  INTEGER NOT NULL REFERENCES snapshots (id) ON DELETE CASCADE,
+        folder_id    INTEGER NOT NULL,
+        position     INTEGER NOT NULL,
+        media_id     INTEGER NOT NULL,
+        bvid         TEXT,
+        title        TEXT NOT NULL,
+        fav_time     INTEGER,
+        data         TEXT NOT NULL
+    );
+    CREATE INDEX idx_fav_media_bvid ON fav_media (bvid);
+    CREATE INDEX idx_fav_media_id ON fav_media (media_id);
+
+    CREATE TABLE history (
+        snapshot_id  INTEGER NOT NULL REFERENCES snapshots (id) ON DELETE CASCADE,
+        position     INTEGER NOT NULL,
+        bvid         TEXT,
+        title        TEXT NOT NULL,
+        view_at      INTEGER,
+        data         TEXT NOT NULL
+    );
+
+    CREATE TABLE bangumi (
+        snapshot_id  INTEGER NOT NULL REFERENCES snapshots (id) ON DELETE CASCADE,
+        position     INTEGER NOT NULL,
+        season_id    INTEGER NOT NULL,
+        title        TEXT NOT NULL,
+        data         TEXT NOT NULL
+    );
+
+    CREATE TABLE toview (
+        snapshot_id  INTEGER NOT NULL REFERENCES snapshots (id) ON DELETE CASCADE,
+        position     INTEGER NOT NULL,
+        aid          INTEGER NOT NULL,
+        bvid         TEXT,
+        title        TEXT NOT NULL,
+        add_at       INTEGER,
+        data         TEXT NOT NULL
+    );
+"#,
+    r#"
+    ALTER TABLE snapshots ADD COLUMN base_id INTEGER REFERENCES snapshots (id);
+    ALTER TABLE snapshot_modules ADD COLUMN sha256 TEXT;
+
+    CREATE TABLE snapshot_changes (
+        snapshot_id  INTEGER NOT NULL REFERENCES snapshots (id) ON DELETE CASCADE,
+        module       TEXT NOT NULL,
+        seq          INTEGER NOT NULL,
+        op           TEXT NOT NULL,
+        item_key     TEXT NOT NULL,
+        position     INTEGER NOT NULL,
+        data         TEXT
+    );
+    CREATE INDEX idx_snapshot_changes ON snapshot_changes (snapshot_id, module, seq);
+"#,
+    r#"
+    CREATE TABLE videos (
+        aid          INTEGER PRIMARY KEY,
+        bvid         TEXT,
+        title        TEXT NOT NULL,
+        data         TEXT NOT NULL,
+        updated_at   INTEGER NOT NULL
+    );
+    CREATE INDEX idx_videos_bvid ON videos (bvid);
+
+    ALTER TABLE history ADD COLUMN aid INTEGER;
+"#,
+    r#"
+    ALTER TABLE snapshots ADD COLUMN images TEXT;
+"#,
+    r#"
+    ALTER TABLE snapshots ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+"#,
+    r#"
+    ALTER TABLE snapshots ADD COLUMN schedule_id INTEGER;
+"#,
+    r#"
+    CREATE TABLE retention_policies (
+        uid          INTEGER PRIMARY KEY,
+        policy       TEXT NOT NULL
+    );
+"#,
+];
+
+/// 增量快照链的最大长度 (包括开头的完整快照)
+///
+/// 达到后下一次保存完整快照,这样保存增量快照时重建基础快照的开销有上限,
+/// 保留策略也可以清理整条旧的快照链。
+const MAX_CHAIN_LENGTH: usize = 10;
+
+/// 保留策略考虑的快照范围
+#[derive(Debug, Clone, Copy)]
+enum RetentionScope {
+    /// 账号的全部快照
+    All,
+    /// 手动保存的快照
+    Manual,
+    /// 某个定时备份计划创建的快照
+    Schedule(u64),
+}
+
+impl RetentionScope {
+    /// 查询参数: 是否包含全部快照,以及要匹配的计划ID
+    fn params(self) -> (bool, Option<i64>) {
+        match self {
+            Self::All => (true, None),
+            Self::Manual => (false, None),
+            Self::Schedule(id) => (false, Some(id as i64)),
+        }
+    }
+}
+
+/// 快照概要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    /// 快照ID
+    pub id: i64,
+    /// 来源账号
+    pub source: BackupSource,
+    /// 备份时间 (Unix时间戳, 秒)
+    pub created_at: i64,
+    /// 存入快照库的时间 (Unix时间戳, 秒)
+    pub saved_at: i64,
+    /// 增量快照所基于的快照ID (完整快照为None)
+    pub base_id: Option<i64>,
+    /// 是否已固定 (固定的快照不会被保留策略清理)
+    pub pinned: bool,
+    /// 创建快照的定时备份计划ID (手动保存的快照为None)
+    pub schedule_id: Option<u64>,
+    /// 各模块的条目数
+    pub counts: BTreeMap<BackupModule, usize>,
+}
+
+/// 快照链校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainVerification {
+    /// 快照链 (从完整快照到被校验的快照)
+    pub chain: Vec<i64>,
+    /// 发现的问题
+    pub problems: Vec<String>,
+}
+
+impl ChainVerification {
+    /// 是否全部通过
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// 快照中单个模块的记录
+struct ModuleRecord {
+    count: usize,
+    completeness: Option<Completeness>,
+    sha256: Option<String>,
+}
+
+/// 快照的基本信息
+struct SnapshotHeader {
+    source: BackupSource,
+    created_at: i64,
+    version: u32,
+    base_id: Option<i64>,
+}
+
+/// 收藏记录 (跨快照查询结果)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavoriteRecord {
+    /// 快照ID
+    pub snapshot_id: i64,
+    /// 快照的备份时间
+    pub snapshot_created_at: i64,
+    /// 收藏夹ID
+    pub folder_id: u64,
+    /// 收藏夹标题
+    pub folder_title: String,
+    /// 视频标题
+    pub title: String,
+    /// 收藏时间 (B站记录的时间,可能缺失)
+    pub fav_time: Option<i64>,
+}
+
+/// 本地SQLite快照库
+///
+/// 内部持有一个共享连接,所有数据库操作在阻塞线程池中执行。
+#[derive(Clone)]
+pub struct SnapshotStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SnapshotStore {
+    /// 打开 (必要时创建) 快照库
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 数据库文件路径
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| BiliError::io(format!("创建快照库目录失败: {}", e)))?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    /// 打开内存中的快照库 (用于测试)
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(mut conn: Connection) -> Result<Self> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        migrate(&mut conn)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// 在阻塞线程池中使用数据库连接
+    async fn with_conn<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = self.conn.clone();
+        run_blocking(move || {
+            let mut conn = conn
+                .lock()
+                .map_err(|_| BiliError::business("快照库连接已损坏"))?;
+            f(&mut conn)
+        })
+        .await
+    }
+
+    /// 保存一次备份为新快照
+    ///
+    /// # 返回
+    ///
+    /// 新快照的ID
+    pub async fn save_snapshot(&self, archive: BackupArchive) -> Result<i64> {
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let id = insert_snapshot(&tx, &archive, None)?;
+            tx.commit()?;
+
+            tracing::info!("已保存快照 #{} (UID {})", id, archive.source.uid);
+            Ok(id)
+        })
+        .await
+    }
+
+    /// 以增量方式保存一次备份
+    ///
+    /// 与同一账号最近的快照比较,只保存新增、删除、修改和移动的条目。
+    /// 没有可用的基础快照,或快照链已达到最大长度时保存完整快照。
+    ///
+    /// # 返回
+    ///
+    /// 新快照的ID
+    pub async fn save_incremental(&self, archive: BackupArchive) -> Result<i64> {
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let id = insert_incremental(&tx, &archive, None)?;
+            tx.commit()?;
+            Ok(id)
+        })
+        .await
+    }
+
+    /// 保存定时备份计划创建的快照
+    ///
+    /// 快照会记录所属的计划: 增量保存时只以同一计划的快照为基础,
+    /// 计划的保留策略也只清理该计划创建的快照 (见 [`SnapshotStore::apply_schedule_retention`])。
+    ///
+    /// # 参数
+    ///
+    /// * `archive` - 备份归档
+    /// * `schedule_id` - 计划ID
+    /// * `incremental` - 是否以增量方式保存
+    ///
+    /// # 返回
+    ///
+    /// 新快照的ID
+    pub async fn save_scheduled(
+        &self,
+        archive: BackupArchive,
+        schedule_id: u64,
+        incremental: bool,
+    ) -> Result<i64> {
+        self.with_conn(move |conn| {
+            let tx = conn.transaction()?;
+            let id = if incremental {
+                insert_incremental(&tx, &archive, Some(schedule_id))?
+            } else {
+                insert_snapshot(&tx, &archive, Some(schedule_id))?
+            };
+            tx.commit()?;
+            Ok(id)
+        })
+        .await
+    }
+
+    /// 校验快照链
+    ///
+    /// 从完整快照开始依次重建链上的每个快照,
+    /// 检查各模块的条目数和摘要是否与保存时一致。
+    pub async fn verify_chain(&self, id: i64) -> Result<ChainVerification> {
+        self.with_conn(move |conn| verify_chain(conn, id)).await
+    }
+
+    /// 列出快照 (按备份时间倒序)
+    ///
+    /// # 参数
+    ///
+    /// * `uid` - 只列出该账号的快照,None表示全部
+    pub async fn list_snapshots(&self, uid: Option<u64>) -> Result<Vec<SnapshotInfo>> {
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, uid, uname, created_at, saved_at, base_id, pinned, schedule_id
+                 FROM snapshots
+                 WHERE ?1 IS NULL OR uid = ?1
+                 ORDER BY created_at DESC, id DESC",
+            )?;
+            let rows = stmt.query_map(params![uid.map(|uid| uid as i64)], |row| {
+                Ok(SnapshotInfo {
+                    id: row.get(0)?,
+                    source: BackupSource {
+                        uid: row.get::<_, i64>(1)? as u64,
+                        uname: row.get(2)?,
+                    },
+                    created_at: row.get(3)?,
+                    saved_at: row.get(4)?,
+                    base_id: row.get(5)?,
+                    pinned: row.get(6)?,
+                    schedule_id: row.get::<_, Option<i64>>(7)?.map(|id| id as u64),
+                    counts: BTreeMap::new(),
+                })
+            })?;
+            let mut snapshots = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+
+            for snapshot in &mut snapshots {
+                for (module, record) in load_modules(conn, snapshot.id)? {
+                    snapshot.counts.insert(module, record.count);
+                }
+            }
+            Ok(snapshots)
+        })
+        .await
+    }
+
+    /// 加载快照为备份归档
+    pub async fn load_snapshot(&self, id: i64) -> Result<BackupArchive> {
+        self.with_conn(move |conn| load_snapshot(conn, id)).await
+    }
+
+    /// 删除快照
+    pub async fn delete_snapshot(&self, id: i64) -> Result<()> {
+        self.with_conn(move |conn| {
+            let dependents: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM snapshots WHERE base_id = ?1",
+                params![id],
+                |row| row.get(0),
+            )?;
+            if dependents > 0 {
+                return Err(BiliError::business(format!(
+                    "快照 #{} 是 {} 个增量快照的基础，不能单独删除",
+                    id, dependents
+                )));
+            }
+
+            let deleted = conn.execute("DELETE FROM snapshots WHERE id = ?1", params![id])?;
+            if deleted == 0 {
+                return Err(BiliError::param(format!("快照 #{} 不存在", id)));
+            }
+            tracing::info!("已删除快照 #{}", id);
+            Ok(())
+        })
+        .await
+    }
+
+    /// 固定或取消固定快照
+    ///
+    /// 固定的快照不会被保留策略清理。
+    pub async fn set_pinned(&self, id: i64, pinned: bool) -> Result<()> {
+        self.with_conn(move |conn| {
+            let updated = conn.execute(
+                "UPDATE snapshots SET pinned = ?1 WHERE id = ?2",
+                params![pinned, id],
+            )?;
+            if updated == 0 {
+                return Err(BiliError::param(format!("快照 #{} 不存在", id)));
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// 预览保留策略 (不删除任何快照)
+    ///
+    /// # 参数
+    ///
+    /// * `uid` - 账号UID
+    /// * `policy` - 保留策略
+    pub async fn plan_retention(&self, uid: u64, policy: RetentionPolicy) -> Result<RetentionPlan> {
+        self.with_conn(move |conn| retention_plan(conn, uid, RetentionScope::All, &policy))
+            .await
+    }
+
+    /// 按保留策略清理账号的快照
+    ///
+    /// 计划的计算和所有删除在同一个事务中完成,
+    /// 中途崩溃或出错时不会留下只删除了一部分的快照链。
+    ///
+    /// # 参数
+    ///
+    /// * `uid` - 账号UID
+    /// * `policy` - 保留策略
+    ///
+    /// # 返回
+    ///
+    /// 执行的保留计划
+    pub async fn apply_retention(
+        &self,
+        uid: u64,
+        policy: RetentionPolicy,
+    ) -> Result<RetentionPlan> {
+        self.with_conn(move |conn| prune_snapshots(conn, uid, RetentionScope::All, &policy))
+            .await
+    }
+
+    /// 按定时备份计划的保留策略清理该计划创建的快照
+    ///
+    /// 手动保存的快照和其他计划创建的快照不受影响。
+    ///
+    /// # 参数
+    ///
+    /// * `uid` - 账号UID
+    /// * `schedule_id` - 计划ID
+    /// * `policy` - 计划的保留策略
+    ///
+    /// # 返回
+    ///
+    /// 执行的保留计划
+    pub async fn apply_schedule_retention(
+        &self,
+        uid: u64,
+        schedule_id: u64,
+        policy: RetentionPolicy,
+    ) -> Result<RetentionPlan> {
+        self.with_conn(move |conn| {
+            prune_snapshots(conn, uid, RetentionScope::Schedule(schedule_id), &policy)
+        })
+        .await
+    }
+
+    /// 读取账号的保留策略 (未设置时返回None)
+    pub async fn retention_policy(&self, uid: u64) -> Result<Option<RetentionPolicy>> {
+        self.with_conn(move |conn| {
+            let policy: Option<String> = conn
+                .query_row(
+                    "SELECT policy FROM retention_policies WHERE uid = ?1",
+                    params![uid as i64],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            policy
+                .map(|policy| {
+                    serde_json::from_str(&policy)
+                        .map_err(|e| BiliError::parse(format!("保留策略解析失败: {}", e)))
+                })
+                .transpose()
+        })
+        .await
+    }
+
+    /// 设置账号的保留策略
+    ///
+    /// 设置后每次手动保存快照都会按该策略清理账号手动保存的快照。
+    ///
+    /// # 参数
+    ///
+    /// * `uid` - 账号UID
+    /// * `policy` - 保留策略,None表示取消 (不再自动清理)
+    pub async fn set_retention_policy(
+        &self,
+        uid: u64,
+        policy: Option<RetentionPolicy>,
+    ) -> Result<()> {
+        self.with_conn(move |conn| {
+            match policy {
+                Some(policy) => conn.execute(
+                    "INSERT INTO retention_policies (uid, policy) VALUES (?1, ?2)
+                     ON CONFLICT (uid) DO UPDATE SET policy = excluded.policy",
+                    params![uid as i64, to_json(&policy)?],
+                )?,
+                None => conn.execute(
+                    "DELETE FROM retention_policies WHERE uid = ?1",
+                    params![uid as i64],
+                )?,
+            };
+            Ok(())
+        })
+        .await
+    }
+
+    /// 按账号的保留策略清理手动保存的快照
+    ///
+    /// 在手动保存快照成功后调用。定时备份计划创建的快照由计划自己的保留策略清理,
+    /// 不受影响。
+    ///
+    /// # 返回
+    ///
+    /// 执行的保留计划,账号未设置保留策略时返回None
+    pub async fn apply_account_retention(&self, uid: u64) -> Result<Option<RetentionPlan>> {
+        let Some(policy) = self.retention_policy(uid).await? else {
+            return Ok(None);
+        };
+        self.with_conn(move |conn| prune_snapshots(conn, uid, RetentionScope::Manual, &policy))
+            .await
+            .map(Some)
+    }
+
+    /// 将快照导出为备份归档文件
+    ///
+    /// 提供口令时导出的归档会被加密。
+    pub async fn export_snapshot(
+        &self,
+        id: i64,
+        path: impl AsRef<Path>,
+        passphrase: Option<&str>,
+    ) -> Result<BackupManifest> {
+        let archive = self.load_snapshot(id).await?;
+        write_archive(path, &archive, passphrase).await
+    }
+
+    /// 将备份归档文件导入为新快照
+    ///
+    /// # 返回
+    ///
+    /// 新快照的ID
+    pub async fn import_archive(
+        &self,
+        path: impl AsRef<Path>,
+        passphrase: Option<&str>,
+    ) -> Result<i64> {
+        let archive = read_archive(path, passphrase).await?;
+        self.save_snapshot(archive).await
+    }
+
+    /// 查询某个视频第一次出现在收藏夹中的记录
+    ///
+    /// 优先按B站记录的收藏时间排序,缺失时按快照的备份时间排序。
+    ///
+    /// # 参数
+    ///
+    /// * `uid` - 账号UID
+    /// * `bvid` - 视频BV号
+    pub async fn first_favorited(&self, uid: u64, bvid: String) -> Result<Option<FavoriteRecord>> {
+        self.with_conn(move |conn| first_favorited(conn, uid, &bvid))
+            .await
+    }
+
+    /// 查询视频的规范记录
+    ///
+    /// 记录合并了所有快照中见到的该视频的元数据。
+    ///
+    /// # 参数
+    ///
+    /// * `id` - 视频的bvid (`BV...`) 或aid (可带 `av` 前缀)
+    pub async fn get_video(&self, id: String) -> Result<Option<VideoRecord>> {
+        self.with_conn(move |conn| {
+            let data: Option<String> = if id.starts_with("BV") {
+                conn.query_row(
+                    "SELECT data FROM videos WHERE bvid = ?1",
+                    params![id],
+                    |row| row.get(0),
+                )
+                .optional()?
+            } else {
+                let digits = id
+                    .strip_prefix("av")
+                    .or_else(|| id.strip_prefix("AV"))
+                    .unwrap_or(&id);
+                let aid: i64 = digits
+                    .parse()
+                    .map_err(|_| BiliError::param(format!("无效的视频ID: {}", id)))?;
+                conn.query_row(
+                    "SELECT data FROM videos WHERE aid = ?1",
+                    params![aid],
+                    |row| row.get(0),
+                )
+                .optional()?
+            };
+
+            data.map(|data| {
+                serde_json::from_str(&data)
+                    .map_err(|e| BiliError::parse(format!("视频记录解析失败: {}", e)))
+            })
+            .transpose()
+        })
+        .await
+    }
+
+    /// 批量查询视频最后已知的真实标题
+    ///
+    /// 未收录或只见过失效占位标题的视频不会出现在结果中。
+    ///
+    /// # 参数
+    ///
+    /// * `aids` - 视频aid列表
+    pub async fn known_titles(&self, aids: Vec<u64>) -> Result<HashMap<u64, String>> {
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare("SELECT title FROM videos WHERE aid = ?1")?;
+            let mut titles = HashMap::new();
+            for aid in aids {
+                let title: Option<String> = stmt
+                    .query_row(params![aid as i64], |row| row.get(0))
+                    .optional()?;
+                if let Some(title) = title.filter(|t| !t.is_empty() && t != INVALID_MEDIA_TITLE) {
+                    titles.insert(aid, title);
+                }
+            }
+            Ok(titles)
+        })
+        .await
+    }
+}
+
+/// 计算账号快照的保留计划
+///
+/// 只考虑 `scope` 范围内的快照。
+fn retention_plan(
+    conn: &Connection,
+    uid: u64,
+    scope: RetentionScope,
+    policy: &RetentionPolicy,
+) -> Result<RetentionPlan> {
+    let (all, schedule_id) = scope.params();
+    let mut stmt = conn.prepare(
+        "SELECT id, created_at, base_id, pinned FROM snapshots
+         WHERE uid = ?1 AND (?2 OR schedule_id IS ?3)",
+    )?;
+    let candidates = stmt
+        .query_map(params![uid as i64, all, schedule_id], |row| {
+            Ok(RetentionCandidate {
+                id: row.get(0)?,
+                created_at: row.get(1)?,
+                base_id: row.get(2)?,
+                pinned: row.get(3)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(retention::plan_retention(
+        &candidates,
+        policy,
+        chrono::Utc::now().timestamp(),
+    ))
+}
+
+/// 在同一个事务中计算保留计划并删除要清理的快照
+fn prune_snapshots(
+    conn: &mut Connection,
+    uid: u64,
+    scope: RetentionScope,
+    policy: &RetentionPolicy,
+) -> Result<RetentionPlan> {
+    let tx = conn.transaction()?;
+    let plan = retention_plan(&tx, uid, scope, policy)?;
+    // 同一事务内删除的快照可能互相引用,外键检查推迟到提交时进行
+    tx.pragma_update(None, "defer_foreign_keys", true)?;
+    for snapshot in &plan.prune {
+        tx.execute("DELETE FROM snapshots WHERE id = ?1", params![snapshot.id])?;
+    }
+    tx.commit()?;
+
+    if !plan.prune.is_empty() {
+        tracing::info!(
+            "账号 {} 按保留策略清理了 {} 个快照，保留 {} 个",
+            uid,
+            plan.prune.len(),
+            plan.keep.len()
+        );
+    }
+    Ok(plan)
+}
+
+/// 默认的快照库文件路径
+pub fn default_store_path(data_dir: impl AsRef<Path>) -> PathBuf {
+    data_dir.as_ref().join("snapshots.db")
+}
+
+/// 执行数据库结构迁移
+fn migrate(conn: &mut Connection) -> Result<()> {
+    let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version > STORE_MIGRATIONS.len() {
+        return Err(BiliError::newer_version(format!(
+            "快照库由更新版本的应用创建 (结构版本 {}，当前支持 {})",
+            version,
+            STORE_MIGRATIONS.len()
+        )));
+    }
+
+    for (i, sql) in STORE_MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", i + 1)?;
+        tx.commit()?;
+        tracing::debug!("快照库结构已升级到第 {} 版", i + 1);
+    }
+    Ok(())
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| BiliError::parse(format!("序列化失败: {}", e)))
+}
+
+/// 写入快照记录及其模块列表
+fn insert_snapshot_row(
+    tx: &Transaction,
+    archive: &BackupArchive,
+    base_id: Option<i64>,
+    schedule_id: Option<u64>,
+    digests: &BTreeMap<BackupModule, String>,
+) -> Result<i64> {
+    let images = if archive.images.is_empty() {
+        None
+    } else {
+        Some(to_json(&archive.images)?)
+    };
+    tx.execute(
+        "INSERT INTO snapshots
+            (uid, uname, created_at, saved_at, schema_version, base_id, images, schedule_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            archive.source.uid as i64,
+            archive.source.uname,
+            archive.created_at,
+            chrono::Utc::now().timestamp(),
+            SCHEMA_VERSION,
+            base_id,
+            images,
+            schedule_id.map(|id| id as i64)
+        ],
+    )?;
+    let id = tx.last_insert_rowid();
+    upsert_videos(tx, &VideoCatalog::from_data(&archive.data))?;
+
+    for module in archive.data.modules() {
+        let completeness = archive.completeness.get(&module).map(to_json).transpose()?;
+        tx.execute(
+            "INSERT INTO snapshot_modules (snapshot_id, module, count, completeness, sha256)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                id,
+                module.key(),
+                archive.data.count(module).unwrap_or(0),
+                completeness,
+                digests.get(&module)
+            ],
+        )?;
+    }
+    Ok(id)
+}
+
+/// 合并视频元数据到视频表
+///
+/// 视频表由所有快照共享,删除快照不会删除其中的记录。
+fn upsert_videos(tx: &Transaction, catalog: &VideoCatalog) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+    let mut select = tx.prepare("SELECT data FROM videos WHERE aid = ?1")?;
+    let mut upsert = tx.prepare(
+        "INSERT INTO videos (aid, bvid, title, data, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT (aid) DO UPDATE SET
+             bvid = excluded.bvid, title = excluded.title,
+             data = excluded.data, updated_at = excluded.updated_at",
+    )?;
+
+    for record in catalog.iter() {
+        let existing: Option<String> = select
+            .query_row(params![record.aid as i64], |row| row.get(0))
+            .optional()?;
+        let merged = match existing {
+            Some(data) => {
+                let mut merged: VideoRecord = serde_json::from_str(&data)
+                    .map_err(|e| BiliError::parse(format!("视频记录解析失败: {}", e)))?;
+                merged.merge(record.clone());
+                merged
+            }
+            None => record.clone(),
+        };
+        upsert.execute(params![
+            merged.aid as i64,
+            merged.bvid,
+            merged.title,
+            to_json(&merged)?,
+            now
+        ])?;
+    }
+    Ok(())
+}
+
+/// 计算各模块数据的摘要
+fn module_digests(data: &BackupData) -> Result<BTreeMap<BackupModule, String>> {
+    let mut digests = BTreeMap::new();
+    for module in data.modules() {
+        if let Some(values) = module_values(data, module)? {
+            digests.insert(module, digest(&values));
+        }
+    }
+    Ok(digests)
+}
+
+/// 写入完整快照及其全部模块数据
+fn insert_snapshot(
+    tx: &Transaction,
+    archive: &BackupArchive,
+    schedule_id: Option<u64>,
+) -> Result<i64> {
+    let digests = module_digests(&archive.data)?;
+    let id = insert_snapshot_row(tx, archive, None, schedule_id, &digests)?;
+    let data = &archive.data;
+
+    if let Some(tags) = &data.relation_tags {
+        let mut stmt = tx.prepare(
+            "INSERT INTO relation_tags (snapshot_id, position, tag_id, name, data)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for (i, tag) in tags.iter().enumerate() {
+            stmt.execute(params![id, i, tag.tag_id, tag.name, to_json(tag)?])?;
+        }
+    }
+
+    let mut stmt = tx.prepare(
+        "INSERT INTO relations (snapshot_id, module, position, mid, uname, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for (module, relations) in [
+        (BackupModule::Following, &data.following),
+        (BackupModule::Followers, &data.followers),
+    ] {
+        for (i, relation) in relations.iter().flatten().enumerate() {
+            stmt.execute(params![
+                id,
+                module.key(),
+                i,
+                relation.mid as i64,
+                relation.uname,
+                to_json(relation)?
+            ])?;
+        }
+    }
+    for (i, user) in data.blacklist.iter().flatten().enumerate() {
+        stmt.execute(params![
+            id,
+            BackupModule::Blacklist.key(),
+            i,
+            user.mid as i64,
+            user.uname,
+            to_json(user)?
+        ])?;
+    }
+
+    if let Some(folders) = &data.favorites {
+        let mut folder_stmt = tx.prepare(
+            "INSERT INTO fav_folders (snapshot_id, position, folder_id, title, data)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        let mut media_stmt = tx.prepare(
+            "INSERT INTO fav_media
+                (snapshot_id, folder_id, position, media_id, bvid, title, fav_time, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for (i, folder) in folders.iter().enumerate() {
+            // 媒体单独存放,收藏夹行只保存基础信息
+            let info = FavFolderWithMedia {
+                folder: folder.folder.clone(),
+                intro: folder.intro.clone(),
+                media_list: Vec::new(),
+            };
+            folder_stmt.execute(params![
+                id,
+                i,
+                folder.folder.id as i64,
+                folder.folder.title,
+                to_json(&info)?
+            ])?;
+
+            for (j, media) in folder.media_list.iter().enumerate() {
+                media_stmt.execute(params![
+                    id,
+                    folder.folder.id as i64,
+                    j,
+                    media.id as i64,
+                    media.bvid.as_ref().or(media.bv_id.as_ref()),
+                    media.title,
+                    media.fav_time,
+                    to_json(media)?
+                ])?;
+            }
+        }
+    }
+
+    if let Some(history) = &data.history {
+        let mut stmt = tx.prepare(
+            "INSERT INTO history (snapshot_id, position, aid, bvid, title, view_at, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        for (i, item) in history.iter().enumerate() {
+            let bvid = item.history.as_ref().and_then(|h| h.bvid.as_ref());
+            let aid = VideoRecord::from_history(item).map(|record| record.aid as i64);
+            stmt.execute(params![
+                id,
+                i,
+                aid,
+                bvid,
+                item.title,
+                item.view_at,
+                to_json(item)?
+            ])?;
+        }
+    }
+
+    if let Some(bangumi) = &data.bangumi {
+        let mut stmt = tx.prepare(
+            "INSERT INTO bangumi (snapshot_id, position, season_id, title, data)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for (i, item) in bangumi.iter().enumerate() {
+            stmt.execute(params![
+                id,
+                i,
+                item.season_id as i64,
+                item.title,
+                to_json(item)?
+            ])?;
+        }
+    }
+
+    if let Some(toview) = &data.toview {
+        let mut stmt = tx.prepare(
+            "INSERT INTO toview (snapshot_id, position, aid, bvid, title, add_at, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        for (i, item) in toview.iter().enumerate() {
+            stmt.execute(params![
+                id,
+                i,
+                item.aid as i64,
+                item.bvid,
+                item.title,
+                item.add_at,
+                to_json(item)?
+            ])?;
+        }
+    }
+
+    Ok(id)
+}
+
+/// 以增量方式写入快照 (以同一账号、同一来源最近的快照为基础)
+fn insert_incremental(
+    tx: &Transaction,
+    archive: &BackupArchive,
+    schedule_id: Option<u64>,
+) -> Result<i64> {
+    let base = tx
+        .query_row(
+            "SELECT id, schema_version FROM snapshots WHERE uid = ?1 AND schedule_id IS ?2
+             ORDER BY created_at DESC, id DESC LIMIT 1",
+            params![archive.source.uid as i64, schedule_id.map(|id| id as i64)],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, u32>(1)?)),
+        )
+        .optional()?;
+
+    // 同一条链上的快照必须使用相同的数据格式版本
+    let Some((base_id, _)) = base.filter(|(_, version)| *version == SCHEMA_VERSION) else {
+        tracing::info!("没有可用的基础快照，保存完整快照");
+        return insert_snapshot(tx, archive, schedule_id);
+    };
+    if snapshot_chain(tx, base_id)?.len() >= MAX_CHAIN_LENGTH {
+        tracing::info!("快照链已有 {} 个快照，保存完整快照", MAX_CHAIN_LENGTH);
+        return insert_snapshot(tx, archive, schedule_id);
+    }
+
+    let mut base_state = reconstruct(tx, base_id, |_, _, _| Ok(()))?;
+    let mut digests = BTreeMap::new();
+    let mut module_changes = Vec::new();
+
+    for module in archive.data.modules() {
+        let values = module_values(&archive.data, module)?.unwrap_or_default();
+        digests.insert(module, digest(&values));
+
+        let current = flatten(module, values);
+        let base_rows = base_state.remove(&module).unwrap_or_default();
+        let changes = compute_changes(&base_rows, &current);
+        if apply_changes(base_rows, changes.clone())? != current {
+            tracing::warn!("{}的变更无法重建当前数据，保存完整快照", module.display_name());
+            return insert_snapshot(tx, archive, schedule_id);
+        }
+        module_changes.push((module, changes));
+    }
+
+    let id = insert_snapshot_row(tx, archive, Some(base_id), schedule_id, &digests)?;
+    let mut stmt = tx.prepare(
+        "INSERT INTO snapshot_changes (snapshot_id, module, seq, op, item_key, position, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    )?;
+    let mut total = 0;
+    for (module, changes) in &module_changes {
+        for (seq, change) in changes.iter().enumerate() {
+            stmt.execute(params![
+                id,
+                module.key(),
+                seq,
+                change.op.key(),
+                change.key,
+                change.position,
+                change.data.as_ref().map(to_json).transpose()?
+            ])?;
+        }
+        total += changes.len();
+    }
+
+    tracing::info!(
+        "已保存增量快照 #{} (基于 #{}，{} 条变更)",
+        id,
+        base_id,
+        total
+    );
+    Ok(id)
+}
+
+/// 解析模块标识
+fn parse_module(key: &str) -> Result<BackupModule> {
+    BackupModule::ALL
+        .into_iter()
+        .find(|m| m.key() == key)
+        .ok_or_else(|| BiliError::parse(format!("未知的模块: {}", key)))
+}
+
+/// 读取快照包含的模块及其条目数、完整性统计和摘要
+fn load_modules(conn: &Connection, id: i64) -> Result<BTreeMap<BackupModule, ModuleRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT module, count, completeness, sha256 FROM snapshot_modules WHERE snapshot_id = ?1",
+    )?;
+    let rows = stmt.query_map(params![id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, usize>(1)?,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, Option<String>>(3)?,
+        ))
+    })?;
+
+    let mut modules = BTreeMap::new();
+    for row in rows {
+        let (key, count, completeness, sha256) = row?;
+        let module = parse_module(&key)?;
+        let completeness = completeness
+            .map(|c| serde_json::from_str(&c))
+            .transpose()
+            .map_err(|e| BiliError::parse(format!("完整性统计解析失败: {}", e)))?;
+        modules.insert(
+            module,
+            ModuleRecord {
+                count,
+                completeness,
+                sha256,
+            },
+        );
+    }
+    Ok(modules)
+}
+
+/// 按保存顺序读取某张表中的JSON数据
+fn load_rows(conn: &Connection, sql: &str, args: impl rusqlite::Params) -> Result<Vec<Value>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(args, |row| row.get::<_, String>(0))?;
+    rows.map(|data| {
+        serde_json::from_str(&data?).map_err(|e| BiliError::parse(format!("快照数据损坏: {}", e)))
+    })
+    .collect()
+}
+
+/// 读取快照的基本信息
+fn load_header(conn: &Connection, id: i64) -> Result<SnapshotHeader> {
+    conn.query_row(
+        "SELECT uid, uname, created_at, schema_version, base_id FROM snapshots WHERE id = ?1",
+        params![id],
+        |row| {
+            Ok(SnapshotHeader {
+                source: BackupSource {
+                    uid: row.get::<_, i64>(0)? as u64,
+                    uname: row.get(1)?,
+                },
+                created_at: row.get(2)?,
+                version: row.get(3)?,
+                base_id: row.get(4)?,
+            })
+        },
+    )
+    .optional()?
+    .ok_or_else(|| BiliError::param(format!("快照 #{} 不存在", id)))
+}
+
+/// 快照链 (从完整快照到指定快照)
+fn snapshot_chain(conn: &Connection, id: i64) -> Result<Vec<i64>> {
+    let mut chain = vec![id];
+    let mut visited = HashSet::from([id]);
+    let mut current = id;
+
+    while let Some(base_id) = load_header(conn, current)?.base_id {
+        if !visited.insert(base_id) {
+            return Err(BiliError::parse(format!("快照 #{} 的快照链存在循环", id)));
+        }
+        chain.push(base_id);
+        current = base_id;
+    }
+
+    chain.reverse();
+    Ok(chain)
+}
+
+/// 读取完整快照中某个模块的数据
+fn load_full_items(conn: &Connection, id: i64, module: BackupModule) -> Result<Vec<Value>> {
+    match module {
+        BackupModule::RelationTags => load_rows(
+            conn,
+            "SELECT data FROM relation_tags WHERE snapshot_id = ?1 ORDER BY position",
+            params![id],
+        ),
+        BackupModule::Following | BackupModule::Followers | BackupModule::Blacklist => load_rows(
+            conn,
+            "SELECT data FROM relations WHERE snapshot_id = ?1 AND module = ?2
+             ORDER BY position",
+            params![id, module.key()],
+        ),
+        BackupModule::Favorites => load_folders(conn, id),
+        BackupModule::History => load_rows(
+            conn,
+            "SELECT data FROM history WHERE snapshot_id = ?1 ORDER BY position",
+            params![id],
+        ),
+        BackupModule::Bangumi => load_rows(
+            conn,
+            "SELECT data FROM bangumi WHERE snapshot_id = ?1 ORDER BY position",
+            params![id],
+        ),
+        BackupModule::ToView => load_rows(
+            conn,
+            "SELECT data FROM toview WHERE snapshot_id = ?1 ORDER BY position",
+            params![id],
+        ),
+    }
+}
+
+/// 读取增量快照中某个模块的变更
+fn load_changes(conn: &Connection, id: i64, module: BackupModule) -> Result<Vec<ItemChange>> {
+    let mut stmt = conn.prepare(
+        "SELECT op, item_key, position, data FROM snapshot_changes
+         WHERE snapshot_id = ?1 AND module = ?2 ORDER BY seq",
+    )?;
+    let rows = stmt.query_map(params![id, module.key()], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, usize>(2)?,
+            row.get::<_, Option<String>>(3)?,
+        ))
+    })?;
+
+    let mut changes = Vec::new();
+    for row in rows {
+        let (op, key, position, data) = row?;
+        let op = ChangeOp::from_key(&op)
+            .ok_or_else(|| BiliError::parse(format!("未知的变更类型: {}", op)))?;
+        let data = data
+            .map(|d| serde_json::from_str(&d))
+            .transpose()
+            .map_err(|e| BiliError::parse(format!("快照数据损坏: {}", e)))?;
+        changes.push(ItemChange {
+            op,
+            key,
+            position,
+            data,
+        });
+    }
+    Ok(changes)
+}
+
+/// 沿快照链重建快照的完整视图
+///
+/// 每重建完链上的一个快照都会调用一次 `visit`。
+fn reconstruct<F>(
+    conn: &Connection,
+    id: i64,
+    mut visit: F,
+) -> Result<BTreeMap<BackupModule, Vec<KeyedRow>>>
+where
+    F: FnMut(
+        i64,
+        &BTreeMap<BackupModule, ModuleRecord>,
+        &BTreeMap<BackupModule, Vec<KeyedRow>>,
+    ) -> Result<()>,
+{
+    let mut state: BTreeMap<BackupModule, Vec<KeyedRow>> = BTreeMap::new();
+
+    for snapshot_id in snapshot_chain(conn, id)? {
+        let header = load_header(conn, snapshot_id)?;
+        let modules = load_modules(conn, snapshot_id)?;
+        let mut next = BTreeMap::new();
+
+        for &module in modules.keys() {
+            let rows = match header.base_id {
+                None => flatten(module, load_full_items(conn, snapshot_id, module)?),
+                Some(_) => apply_changes(
+                    state.remove(&module).unwrap_or_default(),
+                    load_changes(conn, snapshot_id, module)?,
+                )?,
+            };
+            next.insert(module, rows);
+        }
+
+        state = next;
+        visit(snapshot_id, &modules, &state)?;
+    }
+
+    Ok(state)
+}
+
+/// 读取快照为备份归档
+fn load_snapshot(conn: &Connection, id: i64) -> Result<BackupArchive> {
+    let header = load_header(conn, id)?;
+    let modules = load_modules(conn, id)?;
+    let mut state = reconstruct(conn, id, |_, _, _| Ok(()))?;
+
+    let version = header.version;
+    let mut data = BackupData::default();
+    let mut completeness = BTreeMap::new();
+
+    for (module, record) in modules {
+        if let Some(c) = record.completeness {
+            completeness.insert(module, c);
+        }
+
+        let items = unflatten(module, state.remove(&module).unwrap_or_default())?;
+        match module {
+            BackupModule::RelationTags => {
+                data.relation_tags = Some(decode_items(module, version, items)?)
+            }
+            BackupModule::Following => data.following = Some(decode_items(module, version, items)?),
+            BackupModule::Followers => data.followers = Some(decode_items(module, version, items)?),
+            BackupModule::Blacklist => data.blacklist = Some(decode_items(module, version, items)?),
+            BackupModule::Favorites => data.favorites = Some(decode_items(module, version, items)?),
+            BackupModule::History => data.history = Some(decode_items(module, version, items)?),
+            BackupModule::Bangumi => data.bangumi = Some(decode_items(module, version, items)?),
+            BackupModule::ToView => data.toview = Some(decode_items(module, version, items)?),
+        }
+    }
+
+    let images: Option<String> = conn.query_row(
+        "SELECT images FROM snapshots WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    )?;
+    let images = images
+        .map(|images| {
+            serde_json::from_str(&images)
+                .map_err(|e| BiliError::parse(format!("图片索引解析失败: {}", e)))
+        })
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(BackupArchive {
+        source: header.source,
+        created_at: header.created_at,
+        data,
+        completeness,
+        images,
+    })
+}
+
+/// 校验快照链上每个快照的重建结果
+fn verify_chain(conn: &Connection, id: i64) -> Result<ChainVerification> {
+    let chain = snapshot_chain(conn, id)?;
+    let mut problems = Vec::new();
+
+    let result = reconstruct(conn, id, |snapshot_id, modules, state| {
+        for (module, record) in modules {
+            let items = unflatten(*module, state.get(module).cloned().unwrap_or_default())?;
+            if items.len() != record.count {
+                problems.push(format!(
+                    "快照 #{} 的{}条目数不一致: 记录 {}，重建 {}",
+                    snapshot_id,
+                    module.display_name(),
+                    record.count,
+                    items.len()
+                ));
+            }
+            if let Some(expected) = &record.sha256 {
+                if *expected != digest(&items) {
+                    problems.push(format!(
+                        "快照 #{} 的{}数据摘要不一致",
+                        snapshot_id,
+                        module.display_name()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        problems.push(format!("重建快照失败: {}", e));
+    }
+
+    if problems.is_empty() {
+        tracing::info!("快照链校验通过: {:?}", chain);
+    }
+    Ok(ChainVerification { chain, problems })
+}
+
+/// 查询视频第一次出现在收藏夹中的记录
+///
+/// 完整快照的媒体保存在 `fav_media` 表中。增量快照只保存变更,
+/// 视频出现在增量快照中而不在其基础快照中时必然有一条该媒体的新增记录,
+/// 因此只需要查找新增记录,所属收藏夹通过重建该快照得到。
+fn first_favorited(conn: &Connection, uid: u64, bvid: &str) -> Result<Option<FavoriteRecord>> {
+    let order = |r: &FavoriteRecord| {
+        (
+            r.fav_time.unwrap_or(r.snapshot_created_at),
+            r.snapshot_created_at,
+        )
+    };
+
+    let mut first = conn
+        .query_row(
+            "SELECT s.id, s.created_at, m.folder_id, f.title, m.title, m.fav_time
+             FROM fav_media m
+             JOIN snapshots s ON s.id = m.snapshot_id
+             LEFT JOIN fav_folders f
+                ON f.snapshot_id = m.snapshot_id AND f.folder_id = m.folder_id
+             WHERE s.uid = ?1 AND m.bvid = ?2
+             ORDER BY COALESCE(m.fav_time, s.created_at), s.created_at
+             LIMIT 1",
+            params![uid as i64, bvid],
+            |row| {
+                Ok(FavoriteRecord {
+                    snapshot_id: row.get(0)?,
+                    snapshot_created_at: row.get(1)?,
+                    folder_id: row.get::<_, i64>(2)? as u64,
+                    folder_title: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+                    title: row.get(4)?,
+                    fav_time: row.get(5)?,
+                })
+            },
+        )
+        .optional()?;
+    let mut media_key = None;
+
+    // 先按内容粗筛增量快照中新增的媒体,再解析确认BV号
+    let mut stmt = conn.prepare(
+        "SELECT s.id, s.created_at, c.item_key, c.data
+         FROM snapshot_changes c
+         JOIN snapshots s ON s.id = c.snapshot_id
+         WHERE s.uid = ?1 AND c.module = ?2 AND c.op = ?3
+           AND c.item_key LIKE 'media:%' AND instr(c.data, ?4) > 0",
+    )?;
+    let rows = stmt.query_map(
+        params![
+            uid as i64,
+            BackupModule::Favorites.key(),
+            ChangeOp::Add.key(),
+            bvid
+        ],
+        |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        },
+    )?;
+    for row in rows {
+        let (snapshot_id, created_at, key, data) = row?;
+        let Ok(media) = serde_json::from_str::<Media>(&data) else {
+            continue;
+        };
+        if media.bvid.as_deref().or(media.bv_id.as_deref()) != Some(bvid) {
+            continue;
+        }
+        let record = FavoriteRecord {
+            snapshot_id,
+            snapshot_created_at: created_at,
+            folder_id: 0,
+            folder_title: String::new(),
+            title: media.title,
+            fav_time: media.fav_time,
+        };
+        if first.as_ref().is_none_or(|f| order(&record) < order(f)) {
+            first = Some(record);
+            media_key = Some(key);
+        }
+    }
+
+    // 最早的记录来自增量快照时,重建该快照找到媒体所属的收藏夹
+    if let (Some(record), Some(key)) = (first.as_mut(), media_key) {
+        let mut state = reconstruct(conn, record.snapshot_id, |_, _, _| Ok(()))?;
+        let rows = state.remove(&BackupModule::Favorites).unwrap_or_default();
+        let folder = rows
+            .iter()
+            .rev()
+            .filter(|(folder_key, _)| folder_key.starts_with("folder:"))
+            .find(|(folder_key, _)| key.starts_with(&format!("media:{}:", folder_key)))
+            .and_then(|(_, value)| value.get("folder"));
+        if let Some(folder) = folder {
+            record.folder_id = folder.get("id").and_then(Value::as_u64).unwrap_or_default();
+            record.folder_title = folder
+                .get("title")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+        }
+    }
+
+    Ok(first)
+}
+
+/// 读取收藏夹并把媒体放回各自的收藏夹
+fn load_folders(conn: &Connection, id: i64) -> Result<Vec<Value>> {
+    let mut stmt = conn.prepare(
+        "SELECT folder_id, data FROM fav_folders WHERE snapshot_id = ?1 ORDER BY position",
+    )?;
+    let folders = stmt
+        .query_map(params![id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut result = Vec::with_capacity(folders.len());
+    for (folder_id, data) in folders {
+        let mut folder: Value = serde_json::from_str(&data)
+            .map_err(|e| BiliError::parse(format!("快照数据损坏: {}", e)))?;
+        let media = load_rows(
+            conn,
+            "SELECT data FROM fav_media WHERE snapshot_id = ?1 AND folder_id = ?2
+             ORDER BY position",
+            params![id, folder_id],
+        )?;
+        folder["media_list"] = Value::Array(media);
+        result.push(folder);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::api::models::{FavInfo, RelationTag, ToView};
+
+    fn media(id: u64, bvid: &str, fav_time: i64) -> Media {
+        serde_json::from_value(json!({
+            "id": id, "type": 2, "title": format!("视频{}", id),
+            "bvid": bvid, "favTime": fav_time
+        }))
+        .unwrap()
+    }
+
+    fn sample_archive(created_at: i64, medias: Vec<Media>) -> BackupArchive {
+        let mut archive = BackupArchive::new(BackupSource {
+            uid: 42,
+            uname: Some("测试".to_string()),
+        });
+        archive.created_at = created_at;
+        archive.data.relation_tags = Some(vec![RelationTag {
+            tag_id: 1,
+            name: "游戏".to_string(),
+            count: Some(0),
+            tip: None,
+        }]);
+        archive.data.favorites = Some(vec![FavFolderWithMedia {
+            folder: FavInfo {
+                id: 7,
+                fid: None,
+                mid: 42,
+                attr: 0,
+                title: "默认收藏夹".to_string(),
+                cover: None,
+                ctime: None,
+                media_count: medias.len() as u32,
+            },
+            intro: None,
+            media_list: medias,
+        }]);
+        archive.data.toview = Some(Vec::<ToView>::new());
+        archive
+            .completeness
+            .insert(BackupModule::Favorites, Completeness::new(Some(5), 2));
+        archive
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_roundtrip() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        let archive = sample_archive(1000, vec![media(1, "BV1", 900), media(2, "BV2", 950)]);
+        let id = store.save_snapshot(archive.clone()).await.unwrap();
+
+        let loaded = store.load_snapshot(id).await.unwrap();
+        assert_eq!(loaded.source, archive.source);
+        assert_eq!(loaded.created_at, 1000);
+        assert_eq!(loaded.data.modules(), archive.data.modules());
+        assert_eq!(loaded.data.toview.unwrap().len(), 0);
+        assert_eq!(loaded.data.relation_tags.unwrap()[0].name, "游戏");
+
+        let folder = &loaded.data.favorites.unwrap()[0];
+        assert_eq!(folder.folder.title, "默认收藏夹");
+        let bvids: Vec<_> = folder.media_list.iter().map(|m| m.bvid.clone()).collect();
+        assert_eq!(
+            bvids,
+            vec![Some("BV1".to_string()), Some("BV2".to_string())]
+        );
+        assert!(loaded.completeness[&BackupModule::Favorites].truncated);
+
+        let snapshots = store.list_snapshots(Some(42)).await.unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].counts[&BackupModule::Favorites], 1);
+        assert!(store.list_snapshots(Some(1)).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_first_favorited_across_snapshots() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        store
+            .save_snapshot(sample_archive(1000, vec![media(1, "BV1", 900)]))
+            .await
+            .unwrap();
+        let second = store
+            .save_snapshot(sample_archive(
+                2000,
+                vec![media(1, "BV1", 900), media(2, "BV2", 1500)],
+            ))
+            .await
+            .unwrap();
+
+        let first = store
+            .first_favorited(42, "BV1".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.fav_time, Some(900));
+        assert_eq!(first.folder_title, "默认收藏夹");
+
+        let first = store
+            .first_favorited(42, "BV2".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.snapshot_id, second);
+        assert!(store
+            .first_favorited(42, "BV3".to_string())
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_first_favorited_in_incremental_snapshots() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        let base = store
+            .save_incremental(sample_archive(1000, vec![media(1, "BV1", 900)]))
+            .await
+            .unwrap();
+        let id = store
+            .save_incremental(sample_archive(
+                2000,
+                vec![media(1, "BV1", 900), media(2, "BV2", 1500)],
+            ))
+            .await
+            .unwrap();
+        let snapshots = store.list_snapshots(Some(42)).await.unwrap();
+        assert_eq!(snapshots[0].base_id, Some(base));
+
+        let first = store
+            .first_favorited(42, "BV2".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.snapshot_id, id);
+        assert_eq!(first.fav_time, Some(1500));
+        assert_eq!(first.folder_id, 7);
+        assert_eq!(first.folder_title, "默认收藏夹");
+
+        // 基础快照中已有的视频仍然从完整快照中找到
+        let first = store
+            .first_favorited(42, "BV1".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.snapshot_id, base);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_snapshot_stores_no_unchanged_rows() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        let medias = || vec![media(1, "BV1", 900), media(2, "BV2", 950)];
+        store
+            .save_incremental(sample_archive(1000, medias()))
+            .await
+            .unwrap();
+        let id = store
+            .save_incremental(sample_archive(2000, medias()))
+            .await
+            .unwrap();
+
+        let (media_rows, folder_rows, changes): (i64, i64, i64) = store
+            .with_conn(move |conn| {
+                Ok(conn.query_row(
+                    "SELECT
+                        (SELECT COUNT(*) FROM fav_media WHERE snapshot_id = ?1),
+                        (SELECT COUNT(*) FROM fav_folders WHERE snapshot_id = ?1),
+                        (SELECT COUNT(*) FROM snapshot_changes WHERE snapshot_id = ?1)",
+                    params![id],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )?)
+            })
+            .await
+            .unwrap();
+        assert_eq!((media_rows, folder_rows, changes), (0, 0, 0));
+        assert_eq!(
+            store.load_snapshot(id).await.unwrap().data.favorites.unwrap()[0]
+                .media_list
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_videos_merged_across_snapshots() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        store
+            .save_snapshot(sample_archive(1000, vec![media(1, "BV1", 900)]))
+            .await
+            .unwrap();
+
+        let mut richer = media(1, "BV1", 900);
+        richer.duration = Some(120);
+        store
+            .save_snapshot(sample_archive(2000, vec![richer]))
+            .await
+            .unwrap();
+
+        let video = store.get_video("BV1".to_string()).await.unwrap().unwrap();
+        assert_eq!(video.title, "视频1");
+        assert_eq!(video.duration, Some(120));
+        assert!(store.get_video("av1".to_string()).await.unwrap().is_some());
+        assert!(store.get_video("2".to_string()).await.unwrap().is_none());
+        assert!(store.get_video("abc".to_string()).await.is_err());
+
+        let mut invalid = media(1, "BV1", 900);
+        invalid.title = INVALID_MEDIA_TITLE.to_string();
+        store
+            .save_snapshot(sample_archive(3000, vec![invalid]))
+            .await
+            .unwrap();
+        let titles = store.known_titles(vec![1, 2]).await.unwrap();
+        assert_eq!(titles, HashMap::from([(1, "视频1".to_string())]));
+    }
+
+    #[tokio::test]
+    async fn test_retention_keeps_pinned_and_chains() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        let medias = || vec![media(1, "BV1", 900)];
+        let pinned = store
+            .save_snapshot(sample_archive(1000, medias()))
+            .await
+            .unwrap();
+        let pruned = store
+            .save_snapshot(sample_archive(1500, medias()))
+            .await
+            .unwrap();
+        let base = store
+            .save_snapshot(sample_archive(2000, medias()))
+            .await
+            .unwrap();
+        let latest = store
+            .save_incremental(sample_archive(3000, medias()))
+            .await
+            .unwrap();
+        store.set_pinned(pinned, true).await.unwrap();
+
+        let policy = RetentionPolicy {
+            keep_last: 1,
+            keep_daily: 0,
+            keep_weekly: 0,
+            keep_monthly: false,
+        };
+        let plan = store.plan_retention(42, policy.clone()).await.unwrap();
+        let kept: Vec<i64> = plan.keep.iter().map(|s| s.id).collect();
+        assert_eq!(kept, vec![latest, base, pinned]);
+        assert_eq!(plan.prune.len(), 1);
+        assert_eq!(plan.prune[0].id, pruned);
+        assert_eq!(store.list_snapshots(Some(42)).await.unwrap().len(), 4);
+
+        store.apply_retention(42, policy).await.unwrap();
+        let remaining = store.list_snapshots(Some(42)).await.unwrap();
+        assert_eq!(remaining.len(), 3);
+        assert!(remaining.iter().any(|s| s.id == pinned && s.pinned));
+        assert!(store.load_snapshot(latest).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_retention_prunes_old_incremental_chains() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        let mut ids = Vec::new();
+        for i in 1..=30 {
+            let medias = (1..=i).map(|j| media(j, &format!("BV{}", j), 900)).collect();
+            let archive = sample_archive(1000 + i as i64, medias);
+            ids.push(store.save_incremental(archive).await.unwrap());
+        }
+
+        let policy = RetentionPolicy {
+            keep_last: 3,
+            keep_daily: 0,
+            keep_weekly: 0,
+            keep_monthly: false,
+        };
+        let plan = store.apply_retention(42, policy).await.unwrap();
+
+        // 每 MAX_CHAIN_LENGTH 个快照开始一条新的快照链: 最近3个快照及其所依赖的
+        // 快照 (直到最近的完整快照 ids[20]) 被保留,更早的两条快照链被整条清理
+        assert_eq!(MAX_CHAIN_LENGTH, 10);
+        let expected: Vec<i64> = ids[20..].iter().rev().copied().collect();
+        let kept: Vec<i64> = plan.keep.iter().map(|s| s.id).collect();
+        assert_eq!(kept, expected);
+        let pruned: Vec<i64> = plan.prune.iter().map(|s| s.id).collect();
+        assert_eq!(pruned, ids[..20].iter().rev().copied().collect::<Vec<_>>());
+
+        let remaining = store.list_snapshots(Some(42)).await.unwrap();
+        assert_eq!(remaining.iter().map(|s| s.id).collect::<Vec<_>>(), expected);
+        assert_eq!(remaining.last().unwrap().base_id, None);
+        for id in &ids[..20] {
+            assert!(store.load_snapshot(*id).await.is_err());
+        }
+        for id in &ids[27..] {
+            assert!(store.verify_chain(*id).await.unwrap().is_valid());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_schedule_retention_only_prunes_its_own_snapshots() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        let medias = || vec![media(1, "BV1", 900)];
+        let manual = store
+            .save_snapshot(sample_archive(1000, medias()))
+            .await
+            .unwrap();
+        let other = store
+            .save_scheduled(sample_archive(1500, medias()), 2, false)
+            .await
+            .unwrap();
+        let mut scheduled = Vec::new();
+        for i in 0..3 {
+            let archive = sample_archive(2000 + i, medias());
+            scheduled.push(store.save_scheduled(archive, 1, false).await.unwrap());
+        }
+
+        let policy = RetentionPolicy {
+            keep_last: 1,
+            keep_daily: 0,
+            keep_weekly: 0,
+            keep_monthly: false,
+        };
+        let plan = store.apply_schedule_retention(42, 1, policy).await.unwrap();
+        let pruned: Vec<i64> = plan.prune.iter().map(|s| s.id).collect();
+        assert_eq!(pruned, vec![scheduled[1], scheduled[0]]);
+        let remaining: Vec<i64> = store
+            .list_snapshots(Some(42))
+            .await
+            .unwrap()
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(remaining, vec![scheduled[2], other, manual]);
+
+        // 增量快照只以同一计划的快照为基础
+        store.save_snapshot(sample_archive(4000, medias())).await.unwrap();
+        let id = store
+            .save_scheduled(sample_archive(5000, medias()), 1, true)
+            .await
+            .unwrap();
+        let snapshots = store.list_snapshots(Some(42)).await.unwrap();
+        assert_eq!(snapshots[0].id, id);
+        assert_eq!(snapshots[0].base_id, Some(scheduled[2]));
+        assert_eq!(snapshots[0].schedule_id, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_account_retention_only_prunes_manual_snapshots() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        let medias = || vec![media(1, "BV1", 900)];
+        let scheduled = store
+            .save_scheduled(sample_archive(500, medias()), 1, false)
+            .await
+            .unwrap();
+        let mut manual = Vec::new();
+        for i in 0..3 {
+            let archive = sample_archive(1000 + i, medias());
+            manual.push(store.save_snapshot(archive).await.unwrap());
+        }
+        assert!(store.apply_account_retention(42).await.unwrap().is_none());
+
+        let policy = RetentionPolicy {
+            keep_last: 1,
+            keep_daily: 0,
+            keep_weekly: 0,
+            keep_monthly: false,
+        };
+        store.set_retention_policy(42, Some(policy.clone())).await.unwrap();
+        assert_eq!(store.retention_policy(42).await.unwrap(), Some(policy));
+
+        let plan = store.apply_account_retention(42).await.unwrap().unwrap();
+        let pruned: Vec<i64> = plan.prune.iter().map(|s| s.id).collect();
+        assert_eq!(pruned, vec![manual[1], manual[0]]);
+        let remaining: Vec<i64> = store
+            .list_snapshots(Some(42))
+            .await
+            .unwrap()
+            .iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(remaining, vec![manual[2], scheduled]);
+
+        store.set_retention_policy(42, None).await.unwrap();
+        assert!(store.retention_policy(42).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delete_snapshot_cascades() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        let id = store
+            .save_snapshot(sample_archive(1000, vec![media(1, "BV1", 900)]))
+            .await
+            .unwrap();
+
+        store.delete_snapshot(id).await.unwrap();
+        assert!(store.load_snapshot(id).await.is_err());
+        assert!(store
+            .first_favorited(42, "BV1".to_string())
+            .await
+            .unwrap()
+            .is_none());
+        assert!(store.delete_snapshot(id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.zip");
+        let store = SnapshotStore::open(default_store_path(dir.path())).unwrap();
+        let id = store
+            .save_snapshot(sample_archive(1000, vec![media(1, "BV1", 900)]))
+            .await
+            .unwrap();
+
+        let manifest = store.export_snapshot(id, &path, None).await.unwrap();
+        assert_eq!(manifest.source.uid, 42);
+
+        let imported = store.import_archive(&path, None).await.unwrap();
+        assert_ne!(imported, id);
+        let loaded = store.load_snapshot(imported).await.unwrap();
+        assert_eq!(loaded.data.favorites.unwrap()[0].media_list.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_snapshot_chain() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        let first = sample_archive(1000, vec![media(1, "BV1", 900), media(2, "BV2", 950)]);
+        let base = store.save_incremental(first).await.unwrap();
+
+        let mut second = sample_archive(2000, vec![media(3, "BV3", 1900), media(1, "BV1", 900)]);
+        second.data.relation_tags.as_mut().unwrap()[0].name = "游戏区".to_string();
+        let id = store.save_incremental(second.clone()).await.unwrap();
+
+        let snapshots = store.list_snapshots(Some(42)).await.unwrap();
+        assert_eq!(snapshots[0].base_id, Some(base));
+        assert_eq!(snapshots[1].base_id, None);
+
+        let changes: i64 = store
+            .with_conn(move |conn| {
+                Ok(conn.query_row(
+                    "SELECT COUNT(*) FROM snapshot_changes WHERE snapshot_id = ?1",
+                    params![id],
+                    |row| row.get(0),
+                )?)
+            })
+            .await
+            .unwrap();
+        // 标签改名 + 新增BV3 + 删除BV2
+        assert_eq!(changes, 3);
+
+        let loaded = store.load_snapshot(id).await.unwrap();
+        assert_eq!(loaded.data.relation_tags.unwrap()[0].name, "游戏区");
+        let bvids: Vec<_> = loaded.data.favorites.unwrap()[0]
+            .media_list
+            .iter()
+            .map(|m| m.bvid.clone().unwrap())
+            .collect();
+        assert_eq!(bvids, vec!["BV3", "BV1"]);
+
+        let verification = store.verify_chain(id).await.unwrap();
+        assert_eq!(verification.chain, vec![base, id]);
+        assert!(verification.is_valid(), "{:?}", verification.problems);
+
+        assert!(store.delete_snapshot(base).await.is_err());
+        store.delete_snapshot(id).await.unwrap();
+        store.delete_snapshot(base).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_incremental_records_reorder_as_moves() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        let medias: Vec<_> = (1..=20).map(|i| media(i, &format!("BV{}", i), 900)).collect();
+        store
+            .save_incremental(sample_archive(1000, medias.clone()))
+            .await
+            .unwrap();
+
+        // 最后一个视频被移到最前面
+        let mut reordered = medias;
+        reordered.rotate_right(1);
+        let id = store
+            .save_incremental(sample_archive(2000, reordered.clone()))
+            .await
+            .unwrap();
+
+        let ops: Vec<String> = store
+            .with_conn(move |conn| {
+                let mut stmt =
+                    conn.prepare("SELECT op FROM snapshot_changes WHERE snapshot_id = ?1")?;
+                let ops = stmt.query_map(params![id], |row| row.get(0))?;
+                Ok(ops.collect::<rusqlite::Result<Vec<_>>>()?)
+            })
+            .await
+            .unwrap();
+        assert_eq!(ops, vec!["move"]);
+
+        let loaded = store.load_snapshot(id).await.unwrap();
+        let ids: Vec<u64> = loaded.data.favorites.unwrap()[0]
+            .media_list
+            .iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(ids, reordered.iter().map(|m| m.id).collect::<Vec<_>>());
+        assert!(store.verify_chain(id).await.unwrap().is_valid());
+    }
+
+    #[tokio::test]
+    async fn test_incremental_chain_length_is_capped() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        for i in 0..MAX_CHAIN_LENGTH as i64 + 1 {
+            store
+                .save_incremental(sample_archive(1000 + i, vec![media(1, "BV1", 900)]))
+                .await
+                .unwrap();
+        }
+
+        let snapshots = store.list_snapshots(Some(42)).await.unwrap();
+        assert_eq!(snapshots[0].base_id, None);
+        assert!(snapshots[1].base_id.is_some());
+        assert_eq!(snapshots.iter().filter(|s| s.base_id.is_none()).count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_verify_chain_detects_tampering() {
+        let store = SnapshotStore::open_in_memory().unwrap();
+        let base = store
+            .save_incremental(sample_archive(1000, vec![media(1, "BV1", 900)]))
+            .await
+            .unwrap();
+        let id = store
+            .save_incremental(sample_archive(
+                2000,
+                vec![media(1, "BV1", 900), media(2, "BV2", 950)],
+            ))
+            .await
+            .unwrap();
+
+        store
+            .with_conn(move |conn| {
+                conn.execute(
+                    "DELETE FROM fav_media WHERE snapshot_id = ?1",
+                    params![base],
+                )?;
+                Ok(())
+            })
+            .await
+            .unwrap();
+
+        let verification = store.verify_chain(id).await.unwrap();
+        assert!(!verification.is_valid());
+    }
+}
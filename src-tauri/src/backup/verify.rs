@@ -0,0 +1,725 @@
+//! 备份文件完整性校验
+//!
+//! 在还原之前检查备份文件能否完整读取,而不是等到还原进行到一半才发现文件损坏。
+//! 支持单文件备份归档和各模块的导出文件 (如 `export_history` 导出的JSON文件),
+//! 加密的文件需要提供口令。
+
+use crate::api::error::{BiliError, Result};
+use crate::backup::archive::{read_entry, run_blocking, sha256_hex, BackupData};
+use crate::backup::crypto;
+use crate::backup::images::ImageIndex;
+use crate::backup::jsonl::{group_lines, JsonlHeader};
+use crate::backup::manifest::{
+    BackupManifest, BackupModule, CatalogEntry, ARCHIVE_SCHEMA_VERSION, MANIFEST_FILE_NAME,
+};
+use crate::backup::schema::versioned_payload;
+use crate::backup::videos::VideoCatalog;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Cursor;
+use std::path::Path;
+use zip::ZipArchive;
+
+/// zip文件的起始标识
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// 备份文件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupFileKind {
+    /// 单文件备份归档
+    Archive,
+    /// 单个模块的导出文件
+    Export,
+}
+
+/// 问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// 文件无法作为归档打开
+    UnreadableArchive,
+    /// 清单缺失或无法解析
+    InvalidManifest,
+    /// 由更新版本的应用创建
+    NewerVersion,
+    /// 清单中的条目不存在
+    MissingEntry,
+    /// 归档中存在清单未记录的条目
+    UnlistedEntry,
+    /// 校验和不一致
+    ChecksumMismatch,
+    /// 大小与清单不一致
+    SizeMismatch,
+    /// JSON被截断 (文件没有写完)
+    TruncatedJson,
+    /// JSON格式错误
+    CorruptedJson,
+    /// 数据不符合当前的数据格式
+    SchemaMismatch,
+    /// 数据项数量与清单不一致
+    CountMismatch,
+    /// 备份时数据获取不完整
+    IncompleteData,
+    /// 旧版导出文件无法确定所属模块
+    UnknownModule,
+}
+
+impl IssueKind {
+    /// 是否为错误 (否则只是提示,不影响还原)
+    pub fn is_error(&self) -> bool {
+        !matches!(
+            self,
+            IssueKind::UnlistedEntry | IssueKind::IncompleteData | IssueKind::UnknownModule
+        )
+    }
+}
+
+/// 校验发现的问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifyIssue {
+    /// 问题类型
+    pub kind: IssueKind,
+    /// 是否为错误
+    pub error: bool,
+    /// 问题描述
+    pub message: String,
+}
+
+impl VerifyIssue {
+    fn new(kind: IssueKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            error: kind.is_error(),
+            message: message.into(),
+        }
+    }
+}
+
+/// 单个条目的校验结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryReport {
+    /// 条目文件名
+    pub path: String,
+    /// 所属模块 (无法确定时为None)
+    pub module: Option<BackupModule>,
+    /// 清单记录的数据项数量
+    pub expected_count: Option<usize>,
+    /// 实际读取到的数据项数量
+    pub actual_count: Option<usize>,
+    /// 条目大小 (字节)
+    pub size: u64,
+    /// 发现的问题
+    pub issues: Vec<VerifyIssue>,
+}
+
+impl EntryReport {
+    fn new(path: impl Into<String>, module: Option<BackupModule>) -> Self {
+        Self {
+            path: path.into(),
+            module,
+            expected_count: None,
+            actual_count: None,
+            size: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    /// 条目是否可以完整读取
+    pub fn is_valid(&self) -> bool {
+        self.issues.iter().all(|issue| !issue.error)
+    }
+}
+
+/// 备份文件校验报告
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerifyReport {
+    /// 文件类型
+    pub kind: BackupFileKind,
+    /// 文件是否加密
+    pub encrypted: bool,
+    /// 归档清单 (仅归档且清单可读时存在)
+    pub manifest: Option<BackupManifest>,
+    /// 各条目的校验结果
+    pub entries: Vec<EntryReport>,
+    /// 文件级别的问题
+    pub issues: Vec<VerifyIssue>,
+    /// 文件是否可以完整还原
+    pub valid: bool,
+}
+
+impl VerifyReport {
+    fn new(kind: BackupFileKind, encrypted: bool) -> Self {
+        Self {
+            kind,
+            encrypted,
+            manifest: None,
+            entries: Vec::new(),
+            issues: Vec::new(),
+            valid: false,
+        }
+    }
+
+    fn finish(mut self) -> Self {
+        self.valid = self.issues.iter().all(|issue| !issue.error)
+            && self.entries.iter().all(EntryReport::is_valid);
+        self
+    }
+
+    /// 所有问题 (包括各条目的问题)
+    pub fn all_issues(&self) -> impl Iterator<Item = &VerifyIssue> {
+        self.issues
+            .iter()
+            .chain(self.entries.iter().flat_map(|e| e.issues.iter()))
+    }
+}
+
+/// 校验备份文件
+///
+/// 自动识别归档和模块导出文件,检查:
+/// - 归档能否打开、清单能否解析
+/// - 每个条目的SHA-256和大小是否与清单一致
+/// - 条目JSON是否被截断或损坏
+/// - 条目能否按当前数据格式解析 (旧版本数据会先迁移)
+/// - 数据项数量是否与清单一致
+///
+/// 文件损坏不会返回错误,而是记录在报告中。
+///
+/// # 参数
+///
+/// * `path` - 备份文件路径
+/// * `passphrase` - 解密口令,文件未加密时忽略
+///
+/// # 错误
+///
+/// - 文件无法读取
+/// - 文件已加密但未提供口令或口令错误
+pub async fn verify_backup(
+    path: impl AsRef<Path>,
+    passphrase: Option<&str>,
+) -> Result<VerifyReport> {
+    let path = path.as_ref().to_path_buf();
+    let passphrase = passphrase.map(str::to_string);
+    run_blocking(move || {
+        let mut bytes =
+            std::fs::read(&path).map_err(|e| BiliError::io(format!("读取备份文件失败: {}", e)))?;
+        let encrypted = crypto::is_encrypted(&bytes);
+        if encrypted {
+            let passphrase = passphrase
+                .as_deref()
+                .ok_or_else(|| BiliError::crypto("备份文件已加密，请提供口令"))?;
+            bytes = crypto::decrypt(&bytes, passphrase)?;
+        }
+
+        let report = verify_bytes(bytes, encrypted);
+        let status = if report.valid {
+            "完好"
+        } else {
+            "存在问题"
+        };
+        tracing::info!("校验备份文件: {} ({})", path.display(), status);
+        Ok(report)
+    })
+    .await
+}
+
+/// 校验已解密的文件内容
+fn verify_bytes(bytes: Vec<u8>, encrypted: bool) -> VerifyReport {
+    let first_line = bytes.split(|b| *b == b'\n').next().unwrap_or_default();
+    if bytes.starts_with(ZIP_MAGIC) {
+        verify_archive(bytes, encrypted)
+    } else if let Some(header) = std::str::from_utf8(first_line)
+        .ok()
+        .and_then(JsonlHeader::parse)
+    {
+        verify_jsonl(&bytes, header, encrypted)
+    } else {
+        verify_export(&bytes, encrypted)
+    }
+}
+
+/// 校验备份归档
+fn verify_archive(bytes: Vec<u8>, encrypted: bool) -> VerifyReport {
+    let mut report = VerifyReport::new(BackupFileKind::Archive, encrypted);
+
+    let mut zip = match ZipArchive::new(Cursor::new(bytes)) {
+        Ok(zip) => zip,
+        Err(e) => {
+            report.issues.push(VerifyIssue::new(
+                IssueKind::UnreadableArchive,
+                format!("归档无法打开，文件可能不完整: {}", e),
+            ));
+            return report.finish();
+        }
+    };
+
+    let manifest = match read_entry(&mut zip, MANIFEST_FILE_NAME).and_then(|bytes| {
+        serde_json::from_slice::<BackupManifest>(&bytes)
+            .map_err(|e| BiliError::parse(format!("清单解析失败: {}", e)))
+    }) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            report
+                .issues
+                .push(VerifyIssue::new(IssueKind::InvalidManifest, e.to_string()));
+            return report.finish();
+        }
+    };
+
+    if manifest.schema_version > ARCHIVE_SCHEMA_VERSION {
+        report.issues.push(VerifyIssue::new(
+            IssueKind::NewerVersion,
+            format!(
+                "归档由更新版本 ({}) 创建 (格式版本 {}，当前支持 {})",
+                manifest.app_version, manifest.schema_version, ARCHIVE_SCHEMA_VERSION
+            ),
+        ));
+    }
+
+    let mut catalog = VideoCatalog::default();
+    if let Some(ref entry) = manifest.videos {
+        let (entry_report, loaded) =
+            check_catalog_entry(&mut zip, entry, "视频目录", VideoCatalog::len);
+        catalog = loaded.unwrap_or_default();
+        report.entries.push(entry_report);
+    }
+    if let Some(ref entry) = manifest.images {
+        let (entry_report, _) = check_catalog_entry(&mut zip, entry, "图片索引", ImageIndex::len);
+        report.entries.push(entry_report);
+    }
+
+    for entry in &manifest.entries {
+        let mut entry_report = EntryReport::new(&entry.path, Some(entry.module));
+        entry_report.expected_count = Some(entry.count);
+
+        let bytes = match read_entry(&mut zip, &entry.path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                entry_report
+                    .issues
+                    .push(VerifyIssue::new(IssueKind::MissingEntry, e.to_string()));
+                report.entries.push(entry_report);
+                continue;
+            }
+        };
+        entry_report.size = bytes.len() as u64;
+
+        if sha256_hex(&bytes) != entry.sha256 {
+            entry_report.issues.push(VerifyIssue::new(
+                IssueKind::ChecksumMismatch,
+                format!("{} 的校验和与清单不一致", entry.path),
+            ));
+        }
+        if entry_report.size != entry.size {
+            entry_report.issues.push(VerifyIssue::new(
+                IssueKind::SizeMismatch,
+                format!(
+                    "{} 的大小为 {} 字节，清单记录为 {} 字节",
+                    entry.path, entry_report.size, entry.size
+                ),
+            ));
+        }
+
+        check_payload(&mut entry_report, entry.module, bytes, &catalog);
+        if let Some(actual) = entry_report.actual_count {
+            if actual != entry.count {
+                entry_report.issues.push(VerifyIssue::new(
+                    IssueKind::CountMismatch,
+                    format!(
+                        "{} 包含 {} 项，清单记录为 {} 项",
+                        entry.path, actual, entry.count
+                    ),
+                ));
+            }
+        }
+        if entry.is_truncated() {
+            entry_report.issues.push(VerifyIssue::new(
+                IssueKind::IncompleteData,
+                format!("{}在备份时未能完整获取", entry.module.display_name()),
+            ));
+        }
+
+        report.entries.push(entry_report);
+    }
+
+    for name in zip.file_names() {
+        let listed = name == MANIFEST_FILE_NAME
+            || manifest.videos.as_ref().is_some_and(|v| v.path == name)
+            || manifest.images.as_ref().is_some_and(|v| v.path == name)
+            || manifest.entries.iter().any(|e| e.path == name);
+        if !listed {
+            report.issues.push(VerifyIssue::new(
+                IssueKind::UnlistedEntry,
+                format!("归档中的 {} 未记录在清单中，将被忽略", name),
+            ));
+        }
+    }
+
+    report.manifest = Some(manifest);
+    report.finish()
+}
+
+/// 校验清单中的目录条目 (视频目录、图片索引)
+fn check_catalog_entry<T: DeserializeOwned>(
+    zip: &mut ZipArchive<Cursor<Vec<u8>>>,
+    entry: &CatalogEntry,
+    label: &str,
+    len: fn(&T) -> usize,
+) -> (EntryReport, Option<T>) {
+    let mut entry_report = EntryReport::new(&entry.path, None);
+    entry_report.expected_count = Some(entry.count);
+
+    let bytes = match read_entry(zip, &entry.path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            entry_report
+                .issues
+                .push(VerifyIssue::new(IssueKind::MissingEntry, e.to_string()));
+            return (entry_report, None);
+        }
+    };
+    entry_report.size = bytes.len() as u64;
+    if sha256_hex(&bytes) != entry.sha256 {
+        entry_report.issues.push(VerifyIssue::new(
+            IssueKind::ChecksumMismatch,
+            format!("{} 的校验和与清单不一致", entry.path),
+        ));
+    }
+    if check_json(&mut entry_report, &bytes).is_none() {
+        return (entry_report, None);
+    }
+
+    match serde_json::from_slice::<T>(&bytes) {
+        Ok(loaded) => {
+            entry_report.actual_count = Some(len(&loaded));
+            (entry_report, Some(loaded))
+        }
+        Err(e) => {
+            entry_report.issues.push(VerifyIssue::new(
+                IssueKind::SchemaMismatch,
+                format!("{}解析失败: {}", label, e),
+            ));
+            (entry_report, None)
+        }
+    }
+}
+
+/// 校验模块导出文件
+fn verify_export(bytes: &[u8], encrypted: bool) -> VerifyReport {
+    let mut report = VerifyReport::new(BackupFileKind::Export, encrypted);
+
+    let module = serde_json::from_slice::<Value>(bytes)
+        .ok()
+        .and_then(|value| value.get("module").cloned())
+        .and_then(|module| serde_json::from_value::<BackupModule>(module).ok());
+
+    let mut entry_report = EntryReport::new("", module);
+    entry_report.size = bytes.len() as u64;
+    match module {
+        Some(module) => {
+            entry_report.path = module.file_name();
+            check_payload(
+                &mut entry_report,
+                module,
+                bytes.to_vec(),
+                &VideoCatalog::default(),
+            );
+        }
+        None => {
+            // 旧版导出文件是不带模块信息的裸数组,只能检查JSON本身
+            if let Some(value) = check_json(&mut entry_report, bytes) {
+                match value.as_array() {
+                    Some(items) => {
+                        entry_report.actual_count = Some(items.len());
+                        entry_report.issues.push(VerifyIssue::new(
+                            IssueKind::UnknownModule,
+                            "旧版导出文件未记录所属模块，仅检查了JSON完整性",
+                        ));
+                    }
+                    None => entry_report.issues.push(VerifyIssue::new(
+                        IssueKind::SchemaMismatch,
+                        "文件不是可识别的备份数据",
+                    )),
+                }
+            }
+        }
+    }
+
+    report.entries.push(entry_report);
+    report.finish()
+}
+
+/// 校验 JSON Lines 格式的导出文件
+///
+/// 逐行检查JSON,最后一行没有换行且无法解析时视为文件没有写完;
+/// 读取到的行数与文件头记录的行数比较,再按数据格式解析全部数据项。
+fn verify_jsonl(bytes: &[u8], header: JsonlHeader, encrypted: bool) -> VerifyReport {
+    let mut report = VerifyReport::new(BackupFileKind::Export, encrypted);
+    let mut entry = EntryReport::new(header.module.file_name(), Some(header.module));
+    entry.size = bytes.len() as u64;
+    entry.expected_count = Some(header.count);
+
+    let mut values = Vec::new();
+    let lines: Vec<&[u8]> = bytes.split(|b| *b == b'\n').collect();
+    for (index, line) in lines.iter().enumerate().skip(1) {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        match serde_json::from_slice::<Value>(line) {
+            Ok(value) => values.push(value),
+            Err(e) => {
+                let (kind, message) = if index == lines.len() - 1 {
+                    (
+                        IssueKind::TruncatedJson,
+                        format!("第 {} 行不完整，文件可能没有写完: {}", index + 1, e),
+                    )
+                } else {
+                    (
+                        IssueKind::CorruptedJson,
+                        format!("第 {} 行不是有效的JSON: {}", index + 1, e),
+                    )
+                };
+                entry.issues.push(VerifyIssue::new(kind, message));
+                report.entries.push(entry);
+                return report.finish();
+            }
+        }
+    }
+
+    let read = values.len();
+    if read < header.count {
+        entry.issues.push(VerifyIssue::new(
+            IssueKind::TruncatedJson,
+            format!(
+                "文件头记录 {} 行，只读取到 {} 行，文件可能没有写完",
+                header.count, read
+            ),
+        ));
+    } else if read != header.count {
+        entry.issues.push(VerifyIssue::new(
+            IssueKind::CountMismatch,
+            format!("文件头记录 {} 行，实际为 {} 行", header.count, read),
+        ));
+    }
+
+    let items = match group_lines(header.module, values) {
+        Ok(items) => items,
+        Err(e) => {
+            entry
+                .issues
+                .push(VerifyIssue::new(IssueKind::SchemaMismatch, e.to_string()));
+            report.entries.push(entry);
+            return report.finish();
+        }
+    };
+    let payload = versioned_payload(header.module, header.schema_version, items);
+    check_payload(
+        &mut entry,
+        header.module,
+        payload.to_string().into_bytes(),
+        &VideoCatalog::default(),
+    );
+
+    report.entries.push(entry);
+    report.finish()
+}
+
+/// 检查JSON能否完整解析,区分截断和损坏
+fn check_json(entry: &mut EntryReport, bytes: &[u8]) -> Option<Value> {
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            let kind = if e.is_eof() {
+                IssueKind::TruncatedJson
+            } else {
+                IssueKind::CorruptedJson
+            };
+            let message = if e.is_eof() {
+                format!("{} 的内容不完整，文件可能没有写完: {}", entry.path, e)
+            } else {
+                format!("{} 不是有效的JSON: {}", entry.path, e)
+            };
+            entry.issues.push(VerifyIssue::new(kind, message));
+            None
+        }
+    }
+}
+
+/// 检查模块载荷的JSON完整性和数据格式,并记录数据项数量
+///
+/// 归档中的视频字段先用视频目录补全,再按数据格式解析。
+fn check_payload(
+    entry: &mut EntryReport,
+    module: BackupModule,
+    bytes: Vec<u8>,
+    catalog: &VideoCatalog,
+) {
+    if check_json(entry, &bytes).is_none() {
+        return;
+    }
+
+    let mut data = BackupData::default();
+    let result = catalog
+        .expand_payload(module, bytes)
+        .and_then(|bytes| data.set_module_json(module, &bytes));
+    match result {
+        Ok(()) => entry.actual_count = data.count(module),
+        Err(e) => {
+            let kind = if e.is_newer_version() {
+                IssueKind::NewerVersion
+            } else {
+                IssueKind::SchemaMismatch
+            };
+            entry.issues.push(VerifyIssue::new(kind, e.to_string()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::models::ToView;
+    use crate::backup::archive::{write_archive, BackupArchive};
+    use crate::backup::jsonl::write_jsonl;
+    use crate::backup::manifest::BackupSource;
+    use crate::backup::schema::encode_payload;
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    fn sample_archive() -> BackupArchive {
+        let mut archive = BackupArchive::new(BackupSource {
+            uid: 1,
+            uname: None,
+        });
+        let video: ToView = serde_json::from_value(serde_json::json!({
+            "aid": 1,
+            "bvid": "BV1xx411c7mD",
+            "cid": 2,
+            "title": "测试视频",
+            "pic": ""
+        }))
+        .unwrap();
+        archive.data.toview = Some(vec![video]);
+        archive.data.blacklist = Some(vec![]);
+        archive
+    }
+
+    #[tokio::test]
+    async fn test_verify_valid_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.zip");
+        write_archive(&path, &sample_archive(), Some("secret"))
+            .await
+            .unwrap();
+
+        assert!(verify_backup(&path, None).await.is_err());
+
+        let report = verify_backup(&path, Some("secret")).await.unwrap();
+        assert!(report.valid, "{:?}", report);
+        assert!(report.encrypted);
+        assert_eq!(report.kind, BackupFileKind::Archive);
+        // 视频目录 + 两个模块条目
+        assert_eq!(report.entries.len(), 3);
+        assert_eq!(report.entries[0].path, "videos.json");
+        assert_eq!(report.entries[2].actual_count, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_verify_detects_tampered_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.zip");
+        let mut manifest = write_archive(&path, &sample_archive(), None).await.unwrap();
+
+        // 黑名单条目写入截断的JSON,稍后再看条目的数量与清单不一致
+        manifest.entries[1].count = 5;
+        manifest.videos = None;
+        let mut zip = ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = FileOptions::default();
+        zip.start_file(manifest.entries[0].path.as_str(), options)
+            .unwrap();
+        std::io::Write::write_all(&mut zip, b"{\"schema_version\": 2, \"items\": [").unwrap();
+        let toview = encode_payload(BackupModule::ToView, &sample_archive().data.toview).unwrap();
+        zip.start_file(manifest.entries[1].path.as_str(), options)
+            .unwrap();
+        std::io::Write::write_all(&mut zip, &toview).unwrap();
+        zip.start_file(MANIFEST_FILE_NAME, options).unwrap();
+        std::io::Write::write_all(&mut zip, &serde_json::to_vec(&manifest).unwrap()).unwrap();
+        zip.finish().unwrap();
+
+        let report = verify_backup(&path, None).await.unwrap();
+        assert!(!report.valid);
+        let kinds: Vec<_> = report.all_issues().map(|i| i.kind).collect();
+        assert!(kinds.contains(&IssueKind::TruncatedJson));
+        assert!(kinds.contains(&IssueKind::ChecksumMismatch));
+        assert!(kinds.contains(&IssueKind::CountMismatch));
+    }
+
+    #[tokio::test]
+    async fn test_verify_truncated_archive_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.zip");
+        write_archive(&path, &sample_archive(), None).await.unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+
+        let report = verify_backup(&path, None).await.unwrap();
+        assert!(!report.valid);
+        assert_eq!(report.issues[0].kind, IssueKind::UnreadableArchive);
+    }
+
+    #[test]
+    fn test_verify_export_files() {
+        let payload = encode_payload(BackupModule::ToView, &sample_archive().data.toview).unwrap();
+        let report = verify_bytes(payload.clone(), false);
+        assert!(report.valid);
+        assert_eq!(report.kind, BackupFileKind::Export);
+        assert_eq!(report.entries[0].module, Some(BackupModule::ToView));
+
+        let report = verify_bytes(payload[..payload.len() - 10].to_vec(), false);
+        assert!(!report.valid);
+        assert_eq!(report.entries[0].issues[0].kind, IssueKind::TruncatedJson);
+
+        let report = verify_bytes(b"[{\"aid\": 1}]".to_vec(), false);
+        assert!(report.valid);
+        assert_eq!(report.entries[0].actual_count, Some(1));
+        assert_eq!(report.entries[0].issues[0].kind, IssueKind::UnknownModule);
+    }
+
+    #[test]
+    fn test_verify_newer_schema_version() {
+        let payload = encode_payload(BackupModule::ToView, &sample_archive().data.toview).unwrap();
+        let mut value: Value = serde_json::from_slice(&payload).unwrap();
+        value["schema_version"] = serde_json::json!(crate::backup::schema::SCHEMA_VERSION + 1);
+
+        let report = verify_bytes(serde_json::to_vec(&value).unwrap(), false);
+        assert!(!report.valid);
+        assert_eq!(report.entries[0].issues[0].kind, IssueKind::NewerVersion);
+    }
+
+    #[test]
+    fn test_verify_jsonl_export() {
+        let mut bytes = Vec::new();
+        write_jsonl(
+            &mut bytes,
+            BackupModule::ToView,
+            &sample_archive().data.toview.unwrap(),
+        )
+        .unwrap();
+        let report = verify_bytes(bytes.clone(), false);
+        assert!(report.valid);
+        assert_eq!(report.entries[0].module, Some(BackupModule::ToView));
+        assert_eq!(report.entries[0].actual_count, Some(1));
+
+        let report = verify_bytes(bytes[..bytes.len() - 5].to_vec(), false);
+        assert!(!report.valid);
+        assert_eq!(report.entries[0].issues[0].kind, IssueKind::TruncatedJson);
+
+        // 只写完了文件头
+        let cut = bytes.iter().position(|b| *b == b'\n').unwrap() + 1;
+        let report = verify_bytes(bytes[..cut].to_vec(), false);
+        assert!(!report.valid);
+        assert_eq!(report.entries[0].actual_count, Some(0));
+        assert_eq!(report.entries[0].issues[0].kind, IssueKind::TruncatedJson);
+    }
+}
@@ -0,0 +1,535 @@
+//! 视频元数据目录
+//!
+//! 同一个视频会以不同的结构出现在多个模块中 (收藏夹的 `Media`、历史记录的 `History`、
+//! 稍后再看的 `ToView` 以及其他接口返回的 `Video`),每处只带有部分元数据。
+//! 目录以aid为键合并各处见到的最完整字段,为每个视频提供唯一的规范记录。
+//!
+//! 写入归档时,模块数据中与规范记录相同的视频字段会被移除,只保留引用的aid,
+//! 读取时再从目录补全。补全时规范记录中有而条目中没有的字段也会被补上。
+
+use crate::api::error::{BiliError, Result};
+use crate::api::models::{CntInfo, History, Media, ToView, Upper, Video, INVALID_MEDIA_TITLE};
+use crate::backup::archive::BackupData;
+use crate::backup::manifest::BackupModule;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// 视频类型的收藏项
+pub(crate) const MEDIA_TYPE_VIDEO: u32 = 2;
+
+/// 稿件视频的历史记录业务类型
+const HISTORY_BUSINESS_ARCHIVE: &str = "archive";
+
+/// 模块条目字段与规范记录字段的对应关系 (条目字段名, 记录字段名)
+type FieldMap = &'static [(&'static str, &'static str)];
+
+const MEDIA_FIELDS: FieldMap = &[
+    ("title", "title"),
+    ("cover", "cover"),
+    ("upper", "upper"),
+    ("duration", "duration"),
+    ("pubtime", "pubtime"),
+    ("cntInfo", "cnt_info"),
+];
+
+const HISTORY_FIELDS: FieldMap = &[
+    ("title", "title"),
+    ("cover", "cover"),
+    ("duration", "duration"),
+];
+
+const TOVIEW_FIELDS: FieldMap = &[
+    ("title", "title"),
+    ("pic", "cover"),
+    ("owner", "upper"),
+    ("duration", "duration"),
+];
+
+/// 视频的规范记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoRecord {
+    /// 稿件avid
+    pub aid: u64,
+    /// 稿件bvid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bvid: Option<String>,
+    /// 标题
+    #[serde(default)]
+    pub title: String,
+    /// 封面URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover: Option<String>,
+    /// UP主信息
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upper: Option<Upper>,
+    /// 时长 (秒)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<i32>,
+    /// 发布时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pubtime: Option<i64>,
+    /// 状态数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cnt_info: Option<CntInfo>,
+}
+
+impl VideoRecord {
+    fn new(aid: u64, bvid: Option<String>, title: String) -> Self {
+        Self {
+            aid,
+            bvid: bvid.filter(|b| !b.is_empty()),
+            title,
+            cover: None,
+            upper: None,
+            duration: None,
+            pubtime: None,
+            cnt_info: None,
+        }
+    }
+
+    /// 从收藏项提取 (非视频类型返回None)
+    pub fn from_media(media: &Media) -> Option<Self> {
+        if media.item_type != MEDIA_TYPE_VIDEO {
+            return None;
+        }
+
+        let bvid = media.bvid.clone().or_else(|| media.bv_id.clone());
+        let mut record = Self::new(media.id, bvid, media.title.clone());
+        record.cover = non_empty(media.cover.clone());
+        record.upper = media.upper.clone();
+        record.duration = media.duration;
+        record.pubtime = media.pubtime;
+        record.cnt_info = media.cnt_info.clone();
+        Some(record)
+    }
+
+    /// 从历史记录提取 (非稿件视频返回None)
+    pub fn from_history(history: &History) -> Option<Self> {
+        let item = history.history.as_ref()?;
+        if item.business.as_deref() != Some(HISTORY_BUSINESS_ARCHIVE) {
+            return None;
+        }
+
+        let mut record = Self::new(item.oid, item.bvid.clone(), history.title.clone());
+        record.cover = non_empty(history.cover.clone());
+        record.duration = history.duration;
+        record.upper = history.author_mid.map(|mid| Upper {
+            mid,
+            name: history.author_name.clone().unwrap_or_default(),
+            face: String::new(),
+            sex: None,
+            level: None,
+            no_face: None,
+        });
+        Some(record)
+    }
+
+    /// 从稍后再看提取
+    pub fn from_toview(video: &ToView) -> Self {
+        let mut record = Self::new(video.aid, video.bvid.clone(), video.title.clone());
+        record.cover = non_empty(Some(video.pic.clone()));
+        record.upper = video.owner.clone();
+        record.duration = video.duration;
+        record
+    }
+
+    /// 从视频稿件提取
+    pub fn from_video(video: &Video) -> Self {
+        let mut record = Self::new(video.aid, Some(video.bvid.clone()), video.title.clone());
+        record.cover = non_empty(Some(video.pic.clone()));
+        record.upper = video.owner.clone();
+        record.duration = video.duration;
+        record.pubtime = video.pubdate;
+        record
+    }
+
+    /// 是否记录了视频的真实标题 (不是空标题或失效占位标题)
+    pub fn has_known_title(&self) -> bool {
+        !self.title.is_empty() && self.title != INVALID_MEDIA_TITLE
+    }
+
+    /// 合并另一条记录中更完整的字段
+    ///
+    /// 已有的非空字段优先保留;失效视频的占位标题会被真实标题替换。
+    /// UP主信息取字段更完整的一方,状态数取播放数更高 (更新) 的一方。
+    pub fn merge(&mut self, other: VideoRecord) {
+        if !self.has_known_title() && other.has_known_title() {
+            self.title = other.title;
+        }
+        if self.bvid.is_none() {
+            self.bvid = other.bvid;
+        }
+        if self.cover.is_none() {
+            self.cover = other.cover;
+        }
+        if upper_richness(other.upper.as_ref()) > upper_richness(self.upper.as_ref()) {
+            self.upper = other.upper;
+        }
+        if self.duration.unwrap_or(0) <= 0 && other.duration.unwrap_or(0) > 0 {
+            self.duration = other.duration;
+        }
+        if self.pubtime.is_none() {
+            self.pubtime = other.pubtime;
+        }
+        match (&self.cnt_info, other.cnt_info) {
+            (None, Some(cnt)) => self.cnt_info = Some(cnt),
+            (Some(current), Some(cnt)) if cnt.play > current.play => self.cnt_info = Some(cnt),
+            _ => {}
+        }
+    }
+}
+
+/// 空字符串视为缺失
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.is_empty())
+}
+
+/// UP主信息的完整程度
+fn upper_richness(upper: Option<&Upper>) -> usize {
+    upper.map_or(0, |u| {
+        1 + usize::from(!u.name.is_empty())
+            + usize::from(!u.face.is_empty())
+            + usize::from(u.sex.is_some())
+            + usize::from(u.level.is_some())
+    })
+}
+
+/// 视频元数据目录
+///
+/// 以aid为键,按aid顺序序列化为规范记录数组。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<VideoRecord>", into = "Vec<VideoRecord>")]
+pub struct VideoCatalog {
+    videos: BTreeMap<u64, VideoRecord>,
+    bvids: HashMap<String, u64>,
+}
+
+impl From<Vec<VideoRecord>> for VideoCatalog {
+    fn from(records: Vec<VideoRecord>) -> Self {
+        let mut catalog = Self::default();
+        for record in records {
+            catalog.insert(record);
+        }
+        catalog
+    }
+}
+
+impl From<VideoCatalog> for Vec<VideoRecord> {
+    fn from(catalog: VideoCatalog) -> Self {
+        catalog.videos.into_values().collect()
+    }
+}
+
+impl VideoCatalog {
+    /// 从备份数据中收集所有视频
+    pub fn from_data(data: &BackupData) -> Self {
+        let mut catalog = Self::default();
+        for folder in data.favorites.iter().flatten() {
+            for media in &folder.media_list {
+                catalog.insert_opt(VideoRecord::from_media(media));
+            }
+        }
+        for history in data.history.iter().flatten() {
+            catalog.insert_opt(VideoRecord::from_history(history));
+        }
+        for video in data.toview.iter().flatten() {
+            catalog.insert(VideoRecord::from_toview(video));
+        }
+        catalog
+    }
+
+    /// 加入一条记录 (已存在时合并)
+    pub fn insert(&mut self, record: VideoRecord) {
+        if record.aid == 0 {
+            return;
+        }
+        if let Some(ref bvid) = record.bvid {
+            self.bvids.insert(bvid.clone(), record.aid);
+        }
+        match self.videos.get_mut(&record.aid) {
+            Some(existing) => existing.merge(record),
+            None => {
+                self.videos.insert(record.aid, record);
+            }
+        }
+    }
+
+    fn insert_opt(&mut self, record: Option<VideoRecord>) {
+        if let Some(record) = record {
+            self.insert(record);
+        }
+    }
+
+    /// 按aid查找
+    pub fn get(&self, aid: u64) -> Option<&VideoRecord> {
+        self.videos.get(&aid)
+    }
+
+    /// 按bvid查找
+    pub fn get_by_bvid(&self, bvid: &str) -> Option<&VideoRecord> {
+        self.bvids.get(bvid).and_then(|aid| self.videos.get(aid))
+    }
+
+    /// 视频数量
+    pub fn len(&self) -> usize {
+        self.videos.len()
+    }
+
+    /// 目录是否为空
+    pub fn is_empty(&self) -> bool {
+        self.videos.is_empty()
+    }
+
+    /// 按aid顺序遍历
+    pub fn iter(&self) -> impl Iterator<Item = &VideoRecord> {
+        self.videos.values()
+    }
+
+    /// 移除模块载荷中与规范记录相同的视频字段
+    ///
+    /// 载荷必须是当前版本的格式 (`{schema_version, module, items}`)。
+    pub fn compact(&self, module: BackupModule, payload: &mut Value) -> Result<()> {
+        let records = self.records_json()?;
+        for_each_video(module, payload, |aid, item, fields| {
+            let Some(record) = records.get(&aid) else {
+                return;
+            };
+            for (item_field, record_field) in fields {
+                if record.get(*record_field).is_some()
+                    && item.get(*item_field) == record.get(*record_field)
+                {
+                    item.remove(*item_field);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// 用规范记录补全模块载荷中缺失的视频字段
+    pub fn expand(&self, module: BackupModule, payload: &mut Value) -> Result<()> {
+        let records = self.records_json()?;
+        for_each_video(module, payload, |aid, item, fields| {
+            let Some(record) = records.get(&aid) else {
+                return;
+            };
+            for (item_field, record_field) in fields {
+                if item.contains_key(*item_field) {
+                    continue;
+                }
+                if let Some(value) = record.get(*record_field) {
+                    item.insert(item_field.to_string(), value.clone());
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// 移除归档条目中与规范记录相同的视频字段 (不含视频的模块原样返回)
+    pub fn compact_payload(&self, module: BackupModule, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        self.rewrite_payload(module, bytes, Self::compact)
+    }
+
+    /// 用规范记录补全归档条目 (不含视频的模块原样返回)
+    pub fn expand_payload(&self, module: BackupModule, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        self.rewrite_payload(module, bytes, Self::expand)
+    }
+
+    fn rewrite_payload(
+        &self,
+        module: BackupModule,
+        bytes: Vec<u8>,
+        rewrite: fn(&Self, BackupModule, &mut Value) -> Result<()>,
+    ) -> Result<Vec<u8>> {
+        if self.is_empty() || !has_videos(module) {
+            return Ok(bytes);
+        }
+
+        let mut payload: Value = serde_json::from_slice(&bytes).map_err(|e| {
+            BiliError::parse(format!(
+                "{}数据不是有效的JSON: {}",
+                module.display_name(),
+                e
+            ))
+        })?;
+        rewrite(self, module, &mut payload)?;
+        serde_json::to_vec_pretty(&payload)
+            .map_err(|e| BiliError::parse(format!("序列化失败: {}", e)))
+    }
+
+    /// 规范记录的JSON形式
+    fn records_json(&self) -> Result<HashMap<u64, Value>> {
+        self.videos
+            .iter()
+            .map(|(aid, record)| {
+                serde_json::to_value(record)
+                    .map(|value| (*aid, value))
+                    .map_err(|e| BiliError::parse(format!("序列化视频记录失败: {}", e)))
+            })
+            .collect()
+    }
+}
+
+/// 模块数据中是否包含视频
+fn has_videos(module: BackupModule) -> bool {
+    matches!(
+        module,
+        BackupModule::Favorites | BackupModule::History | BackupModule::ToView
+    )
+}
+
+/// 遍历模块载荷中的视频条目
+///
+/// 回调参数为 (aid, 条目对象, 字段对应关系)。
+fn for_each_video<F>(module: BackupModule, payload: &mut Value, mut f: F)
+where
+    F: FnMut(u64, &mut Map<String, Value>, FieldMap),
+{
+    let Some(items) = payload.get_mut("items").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    for item in items {
+        match module {
+            BackupModule::Favorites => {
+                let medias = item.get_mut("media_list").and_then(Value::as_array_mut);
+                for media in medias.into_iter().flatten() {
+                    let Some(obj) = media.as_object_mut() else {
+                        continue;
+                    };
+                    if obj.get("type").and_then(Value::as_u64) != Some(MEDIA_TYPE_VIDEO as u64) {
+                        continue;
+                    }
+                    if let Some(aid) = obj.get("id").and_then(Value::as_u64) {
+                        f(aid, obj, MEDIA_FIELDS);
+                    }
+                }
+            }
+            BackupModule::History => {
+                let Some(obj) = item.as_object_mut() else {
+                    continue;
+                };
+                let detail = obj.get("history");
+                if detail
+                    .and_then(|h| h.get("business"))
+                    .and_then(Value::as_str)
+                    != Some(HISTORY_BUSINESS_ARCHIVE)
+                {
+                    continue;
+                }
+                if let Some(aid) = detail.and_then(|h| h.get("oid")).and_then(Value::as_u64) {
+                    f(aid, obj, HISTORY_FIELDS);
+                }
+            }
+            BackupModule::ToView => {
+                let Some(obj) = item.as_object_mut() else {
+                    continue;
+                };
+                if let Some(aid) = obj.get("aid").and_then(Value::as_u64) {
+                    f(aid, obj, TOVIEW_FIELDS);
+                }
+            }
+            _ => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::schema::{decode_payload, encode_payload};
+    use serde_json::json;
+
+    fn media() -> Media {
+        serde_json::from_value(json!({
+            "id": 100,
+            "type": 2,
+            "title": "测试视频",
+            "cover": "http://i0.hdslb.com/cover.jpg",
+            "upper": {"mid": 1, "name": "UP主", "face": "http://face.jpg"},
+            "duration": 300,
+            "pubtime": 1700000000,
+            "cntInfo": {"collect": 1, "play": 500, "danmaku": 2, "thumb_up": 3},
+            "bvid": "BV1xx411c7mD"
+        }))
+        .unwrap()
+    }
+
+    fn toview() -> ToView {
+        serde_json::from_value(json!({
+            "aid": 100,
+            "cid": 7,
+            "title": "测试视频",
+            "pic": "http://i0.hdslb.com/cover.jpg",
+            "owner": {"mid": 1, "name": "UP主", "face": "http://face.jpg"},
+            "add_at": 1700000100
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_merge_richest_fields() {
+        let history: History = serde_json::from_value(json!({
+            "title": "测试视频",
+            "history": {"oid": 100, "bvid": "BV1xx411c7mD", "business": "archive"},
+            "authorName": "UP主",
+            "authorMid": 1,
+            "duration": 0
+        }))
+        .unwrap();
+
+        let mut catalog = VideoCatalog::default();
+        catalog.insert(VideoRecord::from_history(&history).unwrap());
+        catalog.insert(VideoRecord::from_media(&media()).unwrap());
+
+        let record = catalog.get_by_bvid("BV1xx411c7mD").unwrap();
+        assert_eq!(record.duration, Some(300));
+        assert_eq!(record.upper.as_ref().unwrap().face, "http://face.jpg");
+        assert_eq!(record.cnt_info.as_ref().unwrap().play, 500);
+        assert_eq!(catalog.len(), 1);
+
+        let mut invalid = media();
+        invalid.title = INVALID_MEDIA_TITLE.to_string();
+        let mut catalog = VideoCatalog::default();
+        catalog.insert(VideoRecord::from_media(&invalid).unwrap());
+        catalog.insert(VideoRecord::from_history(&history).unwrap());
+        assert_eq!(catalog.get(100).unwrap().title, "测试视频");
+    }
+
+    #[test]
+    fn test_compact_and_expand_roundtrip() {
+        let data = BackupData {
+            toview: Some(vec![toview()]),
+            ..Default::default()
+        };
+        let mut catalog = VideoCatalog::from_data(&data);
+        catalog.insert(VideoRecord::from_media(&media()).unwrap());
+
+        let bytes = encode_payload(BackupModule::ToView, &data.toview).unwrap();
+        let mut payload: Value = serde_json::from_slice(&bytes).unwrap();
+        catalog.compact(BackupModule::ToView, &mut payload).unwrap();
+        let item = &payload["items"][0];
+        assert!(item.get("title").is_none());
+        assert!(item.get("owner").is_none());
+        assert_eq!(item["add_at"], 1700000100);
+
+        catalog.expand(BackupModule::ToView, &mut payload).unwrap();
+        let videos: Vec<ToView> =
+            decode_payload(BackupModule::ToView, &serde_json::to_vec(&payload).unwrap()).unwrap();
+        assert_eq!(videos[0].title, "测试视频");
+        assert_eq!(videos[0].owner.as_ref().unwrap().name, "UP主");
+        // 补全时带上了规范记录中的时长
+        assert_eq!(videos[0].duration, Some(300));
+    }
+
+    #[test]
+    fn test_catalog_serde() {
+        let mut catalog = VideoCatalog::default();
+        catalog.insert(VideoRecord::from_toview(&toview()));
+        let json = serde_json::to_value(&catalog).unwrap();
+        assert!(json.is_array());
+
+        let loaded: VideoCatalog = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.get(100).unwrap().title, "测试视频");
+    }
+}
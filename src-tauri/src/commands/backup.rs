@@ -0,0 +1,244 @@
+use crate::api::BiliClient;
+use crate::backup::{
+    self, BackupArchive, BackupDiffReport, BackupManifest, BackupModule, ImageArchiveResult,
+    ImageArchiver, LegacyImport, VerifyReport,
+};
+use crate::services::{
+    FullBackupReport, FullBackupService, FullRestoreOptions, FullRestoreReport, FullRestoreService,
+};
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+/// 写入备份归档
+///
+/// 将各模块数据写入单个zip归档文件（包含清单和每个模块一个条目）。
+///
+/// # 参数
+///
+/// * `archive` - 备份归档（来源账号、备份时间、各模块数据及完整性统计）
+/// * `file_path` - 归档文件路径
+/// * `passphrase` - 加密口令（可选，提供时归档会被加密）
+///
+/// # 返回
+///
+/// 成功返回写入的清单，失败返回错误信息
+#[tauri::command]
+pub async fn write_backup_archive(
+    archive: BackupArchive,
+    file_path: String,
+    passphrase: Option<String>,
+) -> Result<BackupManifest, String> {
+    backup::write_archive(&file_path, &archive, passphrase.as_deref())
+        .await
+        .map_err(|e| format!("写入备份归档失败: {}", e))
+}
+
+/// 一键完整备份
+///
+/// 按顺序备份选定的模块并写入单个归档文件。单个模块失败时记录错误并继续备份其余模块，
+/// 归档只包含备份成功的模块。
+///
+/// # 参数
+///
+/// * `modules` - 要备份的模块（可选，为空时备份全部模块）
+/// * `file_path` - 归档文件路径
+/// * `passphrase` - 加密口令（可选，提供时归档会被加密）
+///
+/// # 返回
+///
+/// 成功返回各模块的备份结果（数量、耗时、错误）和写入的清单，失败返回错误信息
+#[tauri::command]
+pub async fn run_full_backup(
+    service: State<'_, FullBackupService>,
+    modules: Option<Vec<BackupModule>>,
+    file_path: String,
+    passphrase: Option<String>,
+) -> Result<FullBackupReport, String> {
+    service
+        .run(
+            &modules.unwrap_or_default(),
+            &file_path,
+            passphrase.as_deref(),
+        )
+        .await
+        .map_err(|e| format!("完整备份失败: {}", e))
+}
+
+/// 一键完整还原
+///
+/// 读取备份归档，把选定的模块按依赖顺序还原到当前账号：先还原关注分组再还原关注列表，
+/// 收藏夹先创建收藏夹再添加内容，黑名单最后还原。单个模块出错时记录错误并继续还原其余模块。
+///
+/// # 参数
+///
+/// * `file_path` - 归档文件路径
+/// * `passphrase` - 解密口令（可选，归档加密时必须提供）
+/// * `options` - 还原选项（可选，包含要还原的模块和各模块的还原选项）
+///
+/// # 返回
+///
+/// 成功返回各模块还原结果汇总的报告，归档无法读取时返回错误信息
+#[tauri::command]
+pub async fn run_full_restore(
+    service: State<'_, FullRestoreService>,
+    file_path: String,
+    passphrase: Option<String>,
+    options: Option<FullRestoreOptions>,
+) -> Result<FullRestoreReport, String> {
+    let archive = backup::read_archive(&file_path, passphrase.as_deref())
+        .await
+        .map_err(|e| format!("读取备份归档失败: {}", e))?;
+    Ok(service.run(archive, options.unwrap_or_default()).await)
+}
+
+/// 读取备份归档
+///
+/// # 参数
+///
+/// * `file_path` - 归档文件路径
+/// * `passphrase` - 解密口令（可选，归档加密时必须提供）
+///
+/// # 返回
+///
+/// 成功返回备份归档，失败返回错误信息
+#[tauri::command]
+pub async fn read_backup_archive(
+    file_path: String,
+    passphrase: Option<String>,
+) -> Result<BackupArchive, String> {
+    backup::read_archive(&file_path, passphrase.as_deref())
+        .await
+        .map_err(|e| format!("读取备份归档失败: {}", e))
+}
+
+/// 读取备份归档的清单
+///
+/// 只读取清单，不加载模块数据，用于在导入前展示归档内容。
+///
+/// # 参数
+///
+/// * `file_path` - 归档文件路径
+/// * `passphrase` - 解密口令（可选，归档加密时必须提供）
+///
+/// # 返回
+///
+/// 成功返回清单，失败返回错误信息
+#[tauri::command]
+pub async fn read_backup_manifest(
+    file_path: String,
+    passphrase: Option<String>,
+) -> Result<BackupManifest, String> {
+    backup::read_manifest(&file_path, passphrase.as_deref())
+        .await
+        .map_err(|e| format!("读取备份清单失败: {}", e))
+}
+
+/// 导入原版 bilibili-backup 的备份
+///
+/// 读取原版工具生成的账号备份目录（或其zip压缩包），转换为本项目的备份归档，
+/// 转换结果可直接用于还原或写入新的归档文件。
+///
+/// # 参数
+///
+/// * `path` - 账号备份目录或zip压缩包路径
+///
+/// # 返回
+///
+/// 成功返回转换结果，失败返回错误信息
+#[tauri::command]
+pub async fn import_legacy_backup(path: String) -> Result<LegacyImport, String> {
+    backup::import_legacy_backup(&path)
+        .await
+        .map_err(|e| format!("导入原版备份失败: {}", e))
+}
+
+/// 比较两个备份归档文件
+///
+/// 按稳定ID比较两次备份都包含的模块，报告关注、分组、收藏夹、追番和黑名单的变化。
+///
+/// # 参数
+///
+/// * `old_path` - 较早的归档文件路径
+/// * `new_path` - 较新的归档文件路径
+/// * `passphrase` - 解密口令（可选，任一归档加密时必须提供）
+///
+/// # 返回
+///
+/// 成功返回差异报告（结构化差异和可读摘要），失败返回错误信息
+#[tauri::command]
+pub async fn diff_backup_archives(
+    old_path: String,
+    new_path: String,
+    passphrase: Option<String>,
+) -> Result<BackupDiffReport, String> {
+    let old = backup::read_archive(&old_path, passphrase.as_deref())
+        .await
+        .map_err(|e| format!("读取备份归档失败: {}", e))?;
+    let new = backup::read_archive(&new_path, passphrase.as_deref())
+        .await
+        .map_err(|e| format!("读取备份归档失败: {}", e))?;
+
+    Ok(backup::diff_backups(&old.data, &new.data).into())
+}
+
+/// 校验备份文件的完整性
+///
+/// 支持备份归档和各模块的导出文件。检查条目校验和、清单记录的数量、
+/// 当前数据格式以及被截断或损坏的JSON，文件本身的问题记录在报告中而不作为错误返回。
+///
+/// # 参数
+///
+/// * `file_path` - 备份文件路径
+/// * `passphrase` - 解密口令（可选，文件加密时必须提供）
+///
+/// # 返回
+///
+/// 成功返回校验报告，文件无法读取或口令错误时返回错误信息
+#[tauri::command]
+pub async fn verify_backup(
+    file_path: String,
+    passphrase: Option<String>,
+) -> Result<VerifyReport, String> {
+    backup::verify_backup(&file_path, passphrase.as_deref())
+        .await
+        .map_err(|e| format!("校验备份文件失败: {}", e))
+}
+
+/// 归档备份引用的图片
+///
+/// 下载关注头像、收藏夹和视频封面、追番封面等图片到应用数据目录下按内容寻址的图片目录，
+/// 并在备份归档中记录原始URL与本地文件的对应关系。已归档的图片不会重复下载。
+///
+/// # 参数
+///
+/// * `archive` - 备份归档
+/// * `concurrency` - 同时下载数（可选，默认8）
+///
+/// # 返回
+///
+/// 成功返回记录了本地图片的备份归档和归档统计，失败返回错误信息
+#[tauri::command]
+pub async fn archive_backup_images(
+    app: AppHandle,
+    mut archive: BackupArchive,
+    concurrency: Option<usize>,
+) -> Result<ImageArchiveResult, String> {
+    let mut archiver = ImageArchiver::new(&BiliClient::new(), image_dir(&app)?);
+    if let Some(concurrency) = concurrency {
+        archiver = archiver.with_concurrency(concurrency);
+    }
+
+    let report = archiver
+        .archive(&mut archive)
+        .await
+        .map_err(|e| format!("归档图片失败: {}", e))?;
+    Ok(ImageArchiveResult { archive, report })
+}
+
+/// 应用数据目录下的图片归档目录
+pub(crate) fn image_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path_resolver()
+        .app_data_dir()
+        .map(backup::default_image_dir)
+        .ok_or_else(|| "无法获取应用数据目录".to_string())
+}
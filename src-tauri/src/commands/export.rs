@@ -0,0 +1,137 @@
+use crate::api::models::{Relation, RelationTag};
+use crate::backup::{BackupArchive, BackupData, BackupModule, SnapshotStore};
+use crate::commands::backup::image_dir;
+use crate::export::{
+    self, BookmarkExport, ColumnInfo, HtmlExport, HtmlOptions, OpmlOptions, TableOptions,
+};
+use crate::services::favorites::FavFolderWithMedia;
+use tauri::{AppHandle, State};
+
+/// 获取模块可导出的表格列
+///
+/// # 参数
+///
+/// * `module` - 模块
+///
+/// # 返回
+///
+/// 成功返回列信息（列标识和表头），失败返回错误信息
+#[tauri::command]
+pub async fn list_table_columns(module: BackupModule) -> Result<Vec<ColumnInfo>, String> {
+    Ok(export::available_columns(module))
+}
+
+/// 导出模块数据为表格文件
+///
+/// 支持带UTF-8 BOM的CSV和XLSX，收藏夹按每个收藏项一行导出。
+///
+/// # 参数
+///
+/// * `data` - 备份数据
+/// * `module` - 要导出的模块
+/// * `file_path` - 导出文件路径
+/// * `options` - 导出选项（文件格式、导出的列、是否转换时间戳）
+///
+/// # 返回
+///
+/// 成功返回导出的行数，失败返回错误信息
+#[tauri::command]
+pub async fn export_table(
+    data: BackupData,
+    module: BackupModule,
+    file_path: String,
+    options: TableOptions,
+) -> Result<usize, String> {
+    export::export_table(&data, module, &file_path, &options)
+        .await
+        .map_err(|e| format!("导出表格失败: {}", e))
+}
+
+/// 导出备份为静态HTML浏览页
+///
+/// 生成的目录包含首页、关注、收藏夹、历史记录等页面，可直接用浏览器离线打开。
+///
+/// # 参数
+///
+/// * `archive` - 备份归档
+/// * `dir_path` - 输出目录
+/// * `options` - 导出选项（本地已缓存的图片；未指定图片归档目录时使用应用数据目录下的图片目录）
+///
+/// # 返回
+///
+/// 成功返回导出结果（页面列表和图片统计），失败返回错误信息
+#[tauri::command]
+pub async fn export_html_site(
+    app: AppHandle,
+    archive: BackupArchive,
+    dir_path: String,
+    mut options: HtmlOptions,
+) -> Result<HtmlExport, String> {
+    if options.image_dir.is_none() && !archive.images.is_empty() {
+        options.image_dir = Some(image_dir(&app)?);
+    }
+    export::export_html(&archive, &dir_path, &options)
+        .await
+        .map_err(|e| format!("导出HTML失败: {}", e))
+}
+
+/// 导出关注列表为OPML订阅文件
+///
+/// 每个UP主对应一个RSS订阅，按关注分组归类，可直接导入RSS阅读器。
+///
+/// # 参数
+///
+/// * `relations` - 关注列表
+/// * `tags` - 关注分组
+/// * `file_path` - 导出文件路径
+/// * `options` - 导出选项（RSSHub地址、订阅地址模板）
+///
+/// # 返回
+///
+/// 成功返回导出的UP主数量，失败返回错误信息
+#[tauri::command]
+pub async fn export_following_opml(
+    relations: Vec<Relation>,
+    tags: Vec<RelationTag>,
+    file_path: String,
+    options: OpmlOptions,
+) -> Result<usize, String> {
+    export::export_opml(&relations, &tags, &file_path, &options)
+        .await
+        .map_err(|e| format!("导出OPML失败: {}", e))
+}
+
+/// 导出收藏夹为浏览器书签文件
+///
+/// 生成Netscape书签HTML，每个收藏夹对应一个书签文件夹。
+/// 失效的收藏项归入单独的文件夹，标题取快照库中记录的最后已知标题。
+///
+/// # 参数
+///
+/// * `folders` - 收藏夹列表
+/// * `file_path` - 导出文件路径
+///
+/// # 返回
+///
+/// 成功返回导出统计，失败返回错误信息
+#[tauri::command]
+pub async fn export_favorites_bookmarks(
+    store: State<'_, SnapshotStore>,
+    folders: Vec<FavFolderWithMedia>,
+    file_path: String,
+) -> Result<BookmarkExport, String> {
+    let invalid: Vec<u64> = folders
+        .iter()
+        .flat_map(|f| &f.media_list)
+        .filter(|m| m.is_invalid())
+        .map(|m| m.id)
+        .collect();
+    let known_titles = store
+        .known_titles(invalid)
+        .await
+        .map_err(|e| format!("查询视频标题失败: {}", e))?;
+
+    export::export_bookmarks(&folders, &known_titles, &file_path)
+        .await
+        .map_err(|e| format!("导出书签失败: {}", e))
+}
@@ -61,3 +61,51 @@ pub async fn clear_favorites(service: State<'_, FavoritesService>) -> Result<usi
         .await
         .map_err(|e| e.to_string())
 }
+
+/// 导出收藏夹到文件
+///
+/// # 参数
+///
+/// * `folders` - 收藏夹列表
+/// * `file_path` - 导出文件路径
+/// * `passphrase` - 加密口令（可选，提供时文件会被加密）
+///
+/// # 返回
+///
+/// - 成功：返回空
+/// - 失败：返回错误信息字符串
+#[tauri::command]
+pub async fn export_favorites(
+    service: State<'_, FavoritesService>,
+    folders: Vec<FavFolderWithMedia>,
+    file_path: String,
+    passphrase: Option<String>,
+) -> Result<(), String> {
+    service
+        .export_to_file(&folders, &file_path, passphrase.as_deref())
+        .await
+        .map_err(|e| format!("导出收藏夹失败: {}", e))
+}
+
+/// 从文件导入收藏夹
+///
+/// # 参数
+///
+/// * `file_path` - 导入文件路径
+/// * `passphrase` - 解密口令（可选，文件加密时必须提供）
+///
+/// # 返回
+///
+/// - 成功：返回收藏夹列表
+/// - 失败：返回错误信息字符串
+#[tauri::command]
+pub async fn import_favorites(
+    service: State<'_, FavoritesService>,
+    file_path: String,
+    passphrase: Option<String>,
+) -> Result<Vec<FavFolderWithMedia>, String> {
+    service
+        .import_from_file(&file_path, passphrase.as_deref())
+        .await
+        .map_err(|e| format!("导入收藏夹失败: {}", e))
+}
@@ -40,6 +40,7 @@ pub async fn clear_history(
 ///
 /// * `history` - 历史记录列表
 /// * `file_path` - 导出文件路径
+/// * `passphrase` - 加密口令（可选，提供时文件会被加密）
 ///
 /// # 返回
 ///
@@ -49,9 +50,10 @@ pub async fn export_history(
     service: State<'_, HistoryService>,
     history: Vec<History>,
     file_path: String,
+    passphrase: Option<String>,
 ) -> Result<(), String> {
     service
-        .export_to_file(&history, &file_path)
+        .export_to_file(&history, &file_path, passphrase.as_deref())
         .await
         .map_err(|e| format!("导出历史记录失败: {}", e))
 }
@@ -61,6 +63,7 @@ pub async fn export_history(
 /// # 参数
 ///
 /// * `file_path` - 导入文件路径
+/// * `passphrase` - 解密口令（可选，文件加密时必须提供）
 ///
 /// # 返回
 ///
@@ -69,9 +72,10 @@ pub async fn export_history(
 pub async fn import_history(
     service: State<'_, HistoryService>,
     file_path: String,
+    passphrase: Option<String>,
 ) -> Result<Vec<History>, String> {
     service
-        .import_from_file(&file_path)
+        .import_from_file(&file_path, passphrase.as_deref())
         .await
         .map_err(|e| format!("导入历史记录失败: {}", e))
 }
@@ -144,6 +148,7 @@ pub async fn clear_bangumi(
 ///
 /// * `bangumi_list` - 追番列表
 /// * `file_path` - 导出文件路径
+/// * `passphrase` - 加密口令（可选，提供时文件会被加密）
 ///
 /// # 返回
 ///
@@ -153,9 +158,10 @@ pub async fn export_bangumi(
     service: State<'_, BangumiService>,
     bangumi_list: Vec<Bangumi>,
     file_path: String,
+    passphrase: Option<String>,
 ) -> Result<(), String> {
     service
-        .export_to_file(&bangumi_list, &file_path)
+        .export_to_file(&bangumi_list, &file_path, passphrase.as_deref())
         .await
         .map_err(|e| format!("导出追番列表失败: {}", e))
 }
@@ -165,6 +171,7 @@ pub async fn export_bangumi(
 /// # 参数
 ///
 /// * `file_path` - 导入文件路径
+/// * `passphrase` - 解密口令（可选，文件加密时必须提供）
 ///
 /// # 返回
 ///
@@ -173,9 +180,10 @@ pub async fn export_bangumi(
 pub async fn import_bangumi(
     service: State<'_, BangumiService>,
     file_path: String,
+    passphrase: Option<String>,
 ) -> Result<Vec<Bangumi>, String> {
     service
-        .import_from_file(&file_path)
+        .import_from_file(&file_path, passphrase.as_deref())
         .await
         .map_err(|e| format!("导入追番列表失败: {}", e))
 }
@@ -238,6 +246,7 @@ pub async fn clear_toview(
 ///
 /// * `videos` - 视频列表
 /// * `file_path` - 导出文件路径
+/// * `passphrase` - 加密口令（可选，提供时文件会被加密）
 ///
 /// # 返回
 ///
@@ -247,9 +256,10 @@ pub async fn export_toview(
     service: State<'_, ToViewService>,
     videos: Vec<ToView>,
     file_path: String,
+    passphrase: Option<String>,
 ) -> Result<(), String> {
     service
-        .export_to_file(&videos, &file_path)
+        .export_to_file(&videos, &file_path, passphrase.as_deref())
         .await
         .map_err(|e| format!("导出稍后再看失败: {}", e))
 }
@@ -259,6 +269,7 @@ pub async fn export_toview(
 /// # 参数
 ///
 /// * `file_path` - 导入文件路径
+/// * `passphrase` - 解密口令（可选，文件加密时必须提供）
 ///
 /// # 返回
 ///
@@ -267,9 +278,10 @@ pub async fn export_toview(
 pub async fn import_toview(
     service: State<'_, ToViewService>,
     file_path: String,
+    passphrase: Option<String>,
 ) -> Result<Vec<ToView>, String> {
     service
-        .import_from_file(&file_path)
+        .import_from_file(&file_path, passphrase.as_deref())
         .await
         .map_err(|e| format!("导入稍后再看失败: {}", e))
 }
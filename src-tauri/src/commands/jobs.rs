@@ -0,0 +1,208 @@
+use crate::jobs::{
+    JobEvents, JobInfo, JobLog, JobManager, JobProgress, JobRequest, JournalSummary,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+
+/// 任务进度事件
+const EVENT_PROGRESS: &str = "job-progress";
+/// 任务日志事件
+const EVENT_LOG: &str = "job-log";
+/// 任务结束事件
+const EVENT_FINISHED: &str = "job-finished";
+
+/// 把任务事件转发给前端
+struct TauriJobEvents {
+    app: AppHandle,
+}
+
+impl TauriJobEvents {
+    fn emit<S: serde::Serialize + Clone>(&self, event: &str, payload: S) {
+        if let Err(e) = self.app.emit_all(event, payload) {
+            tracing::warn!("发送任务事件 {} 失败: {}", event, e);
+        }
+    }
+}
+
+impl JobEvents for TauriJobEvents {
+    fn progress(&self, progress: &JobProgress) {
+        self.emit(EVENT_PROGRESS, progress);
+    }
+
+    fn log(&self, log: &JobLog) {
+        self.emit(EVENT_LOG, log);
+    }
+
+    fn finished(&self, job: &JobInfo) {
+        self.emit(EVENT_FINISHED, job);
+    }
+}
+
+/// 启动后台任务
+///
+/// 任务在后台运行，命令立即返回任务信息。运行过程中发送以下事件：
+/// `job-progress`（当前数量、总数和当前项目）、`job-log`（日志）和 `job-finished`（结束时的任务信息）。
+/// 还原类任务会记录操作日志，应用中断后可以通过 `resume_unfinished_job` 恢复。
+///
+/// # 参数
+///
+/// * `request` - 任务请求（备份、完整备份、还原、完整还原、清空或账号迁移）
+///
+/// # 返回
+///
+/// 成功返回任务信息，失败返回错误信息
+#[tauri::command]
+pub async fn start_job(
+    app: AppHandle,
+    manager: State<'_, JobManager>,
+    request: JobRequest,
+) -> Result<JobInfo, String> {
+    let data_dir = app.path_resolver().app_data_dir();
+    let events = Arc::new(TauriJobEvents { app });
+    manager
+        .start(request, data_dir, events)
+        .map_err(|e| format!("启动任务失败: {}", e))
+}
+
+/// 暂停任务
+///
+/// 任务在处理下一项之前停下。
+///
+/// # 参数
+///
+/// * `id` - 任务ID
+///
+/// # 返回
+///
+/// 成功返回任务信息，失败返回错误信息
+#[tauri::command]
+pub async fn pause_job(manager: State<'_, JobManager>, id: u64) -> Result<JobInfo, String> {
+    manager
+        .pause(id)
+        .map_err(|e| format!("暂停任务失败: {}", e))
+}
+
+/// 继续已暂停的任务
+///
+/// # 参数
+///
+/// * `id` - 任务ID
+///
+/// # 返回
+///
+/// 成功返回任务信息，失败返回错误信息
+#[tauri::command]
+pub async fn resume_job(manager: State<'_, JobManager>, id: u64) -> Result<JobInfo, String> {
+    manager
+        .resume(id)
+        .map_err(|e| format!("继续任务失败: {}", e))
+}
+
+/// 取消任务
+///
+/// 任务在处理下一项之前停止，结束时发送 `job-finished` 事件。
+///
+/// # 参数
+///
+/// * `id` - 任务ID
+///
+/// # 返回
+///
+/// 成功返回任务信息，失败返回错误信息
+#[tauri::command]
+pub async fn cancel_job(manager: State<'_, JobManager>, id: u64) -> Result<JobInfo, String> {
+    manager
+        .cancel(id)
+        .map_err(|e| format!("取消任务失败: {}", e))
+}
+
+/// 列出所有任务
+///
+/// # 返回
+///
+/// 按创建顺序排列的任务信息（包括已结束的任务）
+#[tauri::command]
+pub async fn list_jobs(manager: State<'_, JobManager>) -> Result<Vec<JobInfo>, String> {
+    Ok(manager.list())
+}
+
+/// 列出未完成的任务
+///
+/// 应用崩溃或被关闭时中断的还原任务，以及失败或被取消的还原任务。
+/// 应用启动时调用，用于提示用户恢复。
+///
+/// # 返回
+///
+/// 成功返回操作日志概要列表（包括已完成的步骤数和上次的结束状态），失败返回错误信息
+#[tauri::command]
+pub async fn list_unfinished_jobs(
+    app: AppHandle,
+    manager: State<'_, JobManager>,
+) -> Result<Vec<JournalSummary>, String> {
+    manager
+        .unfinished_journals(data_dir(&app)?)
+        .map_err(|e| format!("读取未完成任务失败: {}", e))
+}
+
+/// 恢复未完成的任务
+///
+/// 使用原来的请求重新启动任务，操作日志中已成功的步骤会被跳过。
+///
+/// # 参数
+///
+/// * `journal_id` - 操作日志ID
+/// * `passphrase` - 解密口令（原任务使用了口令时必须提供）
+///
+/// # 返回
+///
+/// 成功返回任务信息，失败返回错误信息
+#[tauri::command]
+pub async fn resume_unfinished_job(
+    app: AppHandle,
+    manager: State<'_, JobManager>,
+    journal_id: String,
+    passphrase: Option<String>,
+) -> Result<JobInfo, String> {
+    let data_dir = data_dir(&app)?;
+    let events = Arc::new(TauriJobEvents { app });
+    manager
+        .resume_journal(data_dir, &journal_id, passphrase, events)
+        .map_err(|e| format!("恢复任务失败: {}", e))
+}
+
+/// 放弃未完成的任务
+///
+/// 删除操作日志。已应用到账号的修改不受影响。
+///
+/// # 参数
+///
+/// * `journal_id` - 操作日志ID
+///
+/// # 返回
+///
+/// 成功返回空，失败返回错误信息
+#[tauri::command]
+pub async fn discard_unfinished_job(
+    app: AppHandle,
+    manager: State<'_, JobManager>,
+    journal_id: String,
+) -> Result<(), String> {
+    manager
+        .discard_journal(data_dir(&app)?, &journal_id)
+        .map_err(|e| format!("放弃任务失败: {}", e))
+}
+
+/// 把任务事件发送给前端的事件接收器
+///
+/// 供应用启动时创建的后台任务 (例如定时备份) 使用。
+pub fn job_events(app: AppHandle) -> Arc<dyn JobEvents> {
+    Arc::new(TauriJobEvents { app })
+}
+
+/// 应用数据目录
+fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path_resolver()
+        .app_data_dir()
+        .ok_or_else(|| "无法获取应用数据目录".to_string())
+}
@@ -0,0 +1,87 @@
+use crate::services::migration::{load_migration_state, migration_dir};
+use crate::services::{AccountMigration, FullRestoreOptions, MigrationState};
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+/// 账号迁移
+///
+/// 使用两个账号的Cookie同时建立会话，把选定的模块从源账号直接迁移到目标账号。
+/// 源账号只读取数据。迁移进度保存在应用数据目录下，同一对账号再次调用时从中断处继续，
+/// 全部模块完成后对比两个账号的数据数量。
+///
+/// # 参数
+///
+/// * `source_cookie` - 源账号的Cookie
+/// * `target_cookie` - 目标账号的Cookie
+/// * `options` - 迁移选项（可选，包含要迁移的模块和各模块的还原选项）
+///
+/// # 返回
+///
+/// 成功返回迁移状态（各模块进度、还原结果和数量对比），失败返回错误信息
+#[tauri::command]
+pub async fn run_account_migration(
+    app: AppHandle,
+    source_cookie: String,
+    target_cookie: String,
+    options: Option<FullRestoreOptions>,
+) -> Result<MigrationState, String> {
+    let migration = AccountMigration::from_cookies(source_cookie, target_cookie, data_dir(&app)?);
+    migration
+        .run(&options.unwrap_or_default())
+        .await
+        .map_err(|e| format!("账号迁移失败: {}", e))
+}
+
+/// 查询账号迁移进度
+///
+/// # 参数
+///
+/// * `source_uid` - 源账号UID
+/// * `target_uid` - 目标账号UID
+///
+/// # 返回
+///
+/// 成功返回迁移状态（没有迁移记录时为空），失败返回错误信息
+#[tauri::command]
+pub async fn get_account_migration(
+    app: AppHandle,
+    source_uid: u64,
+    target_uid: u64,
+) -> Result<Option<MigrationState>, String> {
+    load_migration_state(migration_dir(data_dir(&app)?, source_uid, target_uid))
+        .await
+        .map_err(|e| format!("读取迁移状态失败: {}", e))
+}
+
+/// 放弃账号迁移
+///
+/// 删除迁移进度和缓存的数据，下次迁移将从头开始。已还原到目标账号的数据不受影响。
+///
+/// # 参数
+///
+/// * `source_uid` - 源账号UID
+/// * `target_uid` - 目标账号UID
+///
+/// # 返回
+///
+/// 成功返回空，失败返回错误信息
+#[tauri::command]
+pub async fn discard_account_migration(
+    app: AppHandle,
+    source_uid: u64,
+    target_uid: u64,
+) -> Result<(), String> {
+    let dir = migration_dir(data_dir(&app)?, source_uid, target_uid);
+    match tokio::fs::remove_dir_all(&dir).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("删除迁移记录失败: {}", e)),
+    }
+}
+
+/// 应用数据目录
+fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path_resolver()
+        .app_data_dir()
+        .ok_or_else(|| "无法获取应用数据目录".to_string())
+}
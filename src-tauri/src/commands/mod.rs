@@ -11,6 +11,24 @@ pub mod favorites;
 /// 历史记录、追番追剧、稍后再看相关命令
 pub mod history;
 
+/// 备份归档相关命令
+pub mod backup;
+
+/// 快照库相关命令
+pub mod snapshot;
+
+/// 导出相关命令
+pub mod export;
+
+/// 账号迁移相关命令
+pub mod migration;
+
+/// 后台任务相关命令
+pub mod jobs;
+
+/// 定时备份相关命令
+pub mod schedule;
+
 /// Tauri命令示例：打招呼
 ///
 /// 这是一个简单的示例命令，用于验证前后端通信是否正常。
@@ -30,3 +48,9 @@ pub use auth::*;
 pub use following::*;
 pub use favorites::*;
 pub use history::*;
+pub use backup::*;
+pub use snapshot::*;
+pub use export::*;
+pub use migration::*;
+pub use jobs::*;
+pub use schedule::*;
@@ -0,0 +1,84 @@
+use crate::commands::jobs::job_events;
+use crate::jobs::{BackupSchedule, JobInfo, Scheduler};
+use tauri::{AppHandle, State};
+
+/// 获取所有定时备份计划
+///
+/// # 返回
+///
+/// 成功返回计划列表（包含下一次运行时间和最近的运行记录），失败返回错误信息
+#[tauri::command]
+pub async fn list_backup_schedules(
+    scheduler: State<'_, Scheduler>,
+) -> Result<Vec<BackupSchedule>, String> {
+    scheduler
+        .list()
+        .await
+        .map_err(|e| format!("获取备份计划失败: {}", e))
+}
+
+/// 新增或更新定时备份计划
+///
+/// 计划ID为0时新增计划，否则更新对应的计划（保留原有的运行记录）。
+/// 运行时间支持cron表达式（如 `0 3 * * *`）或固定间隔（分钟）。
+///
+/// # 参数
+///
+/// * `schedule` - 计划（账号、模块、运行时间、是否增量保存和保留策略）
+///
+/// # 返回
+///
+/// 成功返回保存后的计划，失败返回错误信息
+#[tauri::command]
+pub async fn save_backup_schedule(
+    scheduler: State<'_, Scheduler>,
+    schedule: BackupSchedule,
+) -> Result<BackupSchedule, String> {
+    scheduler
+        .save(schedule)
+        .await
+        .map_err(|e| format!("保存备份计划失败: {}", e))
+}
+
+/// 删除定时备份计划
+///
+/// # 参数
+///
+/// * `id` - 计划ID
+///
+/// # 返回
+///
+/// 成功返回空，失败返回错误信息
+#[tauri::command]
+pub async fn delete_backup_schedule(
+    scheduler: State<'_, Scheduler>,
+    id: u64,
+) -> Result<(), String> {
+    scheduler
+        .delete(id)
+        .await
+        .map_err(|e| format!("删除备份计划失败: {}", e))
+}
+
+/// 立即运行定时备份计划
+///
+/// 以后台任务运行，进度和结果通过任务事件发送。需要计划的账号已登录。
+///
+/// # 参数
+///
+/// * `id` - 计划ID
+///
+/// # 返回
+///
+/// 成功返回任务信息，失败返回错误信息
+#[tauri::command]
+pub async fn run_backup_schedule_now(
+    app: AppHandle,
+    scheduler: State<'_, Scheduler>,
+    id: u64,
+) -> Result<JobInfo, String> {
+    scheduler
+        .run_now(id, job_events(app))
+        .await
+        .map_err(|e| format!("运行备份计划失败: {}", e))
+}
@@ -0,0 +1,374 @@
+use crate::backup::{
+    diff_backups, BackupArchive, BackupDiffReport, BackupManifest, ChainVerification,
+    FavoriteRecord, RetentionPlan, RetentionPolicy, SnapshotInfo, SnapshotStore, VideoRecord,
+};
+use tauri::State;
+
+/// 保存备份为快照
+///
+/// 账号设置了保留策略时，保存成功后按策略清理该账号手动保存的快照。
+///
+/// # 参数
+///
+/// * `archive` - 备份归档
+///
+/// # 返回
+///
+/// 成功返回新快照的ID，失败返回错误信息
+#[tauri::command]
+pub async fn save_snapshot(
+    store: State<'_, SnapshotStore>,
+    archive: BackupArchive,
+) -> Result<i64, String> {
+    let uid = archive.source.uid;
+    let id = store
+        .save_snapshot(archive)
+        .await
+        .map_err(|e| format!("保存快照失败: {}", e))?;
+    apply_account_retention(&store, uid).await;
+    Ok(id)
+}
+
+/// 以增量方式保存备份
+///
+/// 只保存与同一账号上一个快照相比新增、删除和修改的条目。
+/// 账号设置了保留策略时，保存成功后按策略清理该账号手动保存的快照。
+///
+/// # 参数
+///
+/// * `archive` - 备份归档
+///
+/// # 返回
+///
+/// 成功返回新快照的ID，失败返回错误信息
+#[tauri::command]
+pub async fn save_incremental_snapshot(
+    store: State<'_, SnapshotStore>,
+    archive: BackupArchive,
+) -> Result<i64, String> {
+    let uid = archive.source.uid;
+    let id = store
+        .save_incremental(archive)
+        .await
+        .map_err(|e| format!("保存增量快照失败: {}", e))?;
+    apply_account_retention(&store, uid).await;
+    Ok(id)
+}
+
+/// 保存快照后执行账号的保留策略
+///
+/// 快照已经保存，保留策略失败只记录日志。
+async fn apply_account_retention(store: &SnapshotStore, uid: u64) {
+    if let Err(e) = store.apply_account_retention(uid).await {
+        tracing::warn!("账号 {} 执行保留策略失败: {}", uid, e);
+    }
+}
+
+/// 校验快照链
+///
+/// # 参数
+///
+/// * `snapshot_id` - 快照ID
+///
+/// # 返回
+///
+/// 成功返回校验结果，失败返回错误信息
+#[tauri::command]
+pub async fn verify_snapshot_chain(
+    store: State<'_, SnapshotStore>,
+    snapshot_id: i64,
+) -> Result<ChainVerification, String> {
+    store
+        .verify_chain(snapshot_id)
+        .await
+        .map_err(|e| format!("校验快照链失败: {}", e))
+}
+
+/// 列出快照
+///
+/// # 参数
+///
+/// * `uid` - 只列出该账号的快照，为空时列出全部
+///
+/// # 返回
+///
+/// 成功返回快照列表（按备份时间倒序），失败返回错误信息
+#[tauri::command]
+pub async fn list_snapshots(
+    store: State<'_, SnapshotStore>,
+    uid: Option<u64>,
+) -> Result<Vec<SnapshotInfo>, String> {
+    store
+        .list_snapshots(uid)
+        .await
+        .map_err(|e| format!("获取快照列表失败: {}", e))
+}
+
+/// 加载快照
+///
+/// # 参数
+///
+/// * `snapshot_id` - 快照ID
+///
+/// # 返回
+///
+/// 成功返回备份归档，失败返回错误信息
+#[tauri::command]
+pub async fn load_snapshot(
+    store: State<'_, SnapshotStore>,
+    snapshot_id: i64,
+) -> Result<BackupArchive, String> {
+    store
+        .load_snapshot(snapshot_id)
+        .await
+        .map_err(|e| format!("加载快照失败: {}", e))
+}
+
+/// 删除快照
+///
+/// # 参数
+///
+/// * `snapshot_id` - 快照ID
+///
+/// # 返回
+///
+/// 成功返回空，失败返回错误信息
+#[tauri::command]
+pub async fn delete_snapshot(
+    store: State<'_, SnapshotStore>,
+    snapshot_id: i64,
+) -> Result<(), String> {
+    store
+        .delete_snapshot(snapshot_id)
+        .await
+        .map_err(|e| format!("删除快照失败: {}", e))
+}
+
+/// 导出快照为备份归档文件
+///
+/// # 参数
+///
+/// * `snapshot_id` - 快照ID
+/// * `file_path` - 归档文件路径
+/// * `passphrase` - 加密口令（可选，提供时归档会被加密）
+///
+/// # 返回
+///
+/// 成功返回写入的清单，失败返回错误信息
+#[tauri::command]
+pub async fn export_snapshot(
+    store: State<'_, SnapshotStore>,
+    snapshot_id: i64,
+    file_path: String,
+    passphrase: Option<String>,
+) -> Result<BackupManifest, String> {
+    store
+        .export_snapshot(snapshot_id, &file_path, passphrase.as_deref())
+        .await
+        .map_err(|e| format!("导出快照失败: {}", e))
+}
+
+/// 将备份归档文件导入快照库
+///
+/// # 参数
+///
+/// * `file_path` - 归档文件路径
+/// * `passphrase` - 解密口令（可选，归档加密时必须提供）
+///
+/// # 返回
+///
+/// 成功返回新快照的ID，失败返回错误信息
+#[tauri::command]
+pub async fn import_snapshot(
+    store: State<'_, SnapshotStore>,
+    file_path: String,
+    passphrase: Option<String>,
+) -> Result<i64, String> {
+    store
+        .import_archive(&file_path, passphrase.as_deref())
+        .await
+        .map_err(|e| format!("导入快照失败: {}", e))
+}
+
+/// 查询视频的规范记录
+///
+/// 返回快照库中合并了各模块、各快照元数据的视频记录。
+///
+/// # 参数
+///
+/// * `video_id` - 视频的bvid或aid
+///
+/// # 返回
+///
+/// 成功返回视频记录（未收录时为空），失败返回错误信息
+#[tauri::command]
+pub async fn get_video_record(
+    store: State<'_, SnapshotStore>,
+    video_id: String,
+) -> Result<Option<VideoRecord>, String> {
+    store
+        .get_video(video_id)
+        .await
+        .map_err(|e| format!("查询视频记录失败: {}", e))
+}
+
+/// 查询视频第一次被收藏的记录
+///
+/// # 参数
+///
+/// * `uid` - 账号UID
+/// * `bvid` - 视频BV号
+///
+/// # 返回
+///
+/// 成功返回最早的收藏记录（从未收藏时为空），失败返回错误信息
+#[tauri::command]
+pub async fn find_first_favorited(
+    store: State<'_, SnapshotStore>,
+    uid: u64,
+    bvid: String,
+) -> Result<Option<FavoriteRecord>, String> {
+    store
+        .first_favorited(uid, bvid)
+        .await
+        .map_err(|e| format!("查询收藏记录失败: {}", e))
+}
+
+/// 比较两个快照
+///
+/// # 参数
+///
+/// * `old_id` - 较早的快照ID
+/// * `new_id` - 较新的快照ID
+///
+/// # 返回
+///
+/// 成功返回差异报告（结构化差异和可读摘要），失败返回错误信息
+#[tauri::command]
+pub async fn diff_snapshots(
+    store: State<'_, SnapshotStore>,
+    old_id: i64,
+    new_id: i64,
+) -> Result<BackupDiffReport, String> {
+    let old = store
+        .load_snapshot(old_id)
+        .await
+        .map_err(|e| format!("加载快照失败: {}", e))?;
+    let new = store
+        .load_snapshot(new_id)
+        .await
+        .map_err(|e| format!("加载快照失败: {}", e))?;
+
+    Ok(diff_backups(&old.data, &new.data).into())
+}
+
+/// 固定或取消固定快照
+///
+/// 固定的快照不会被保留策略清理。
+///
+/// # 参数
+///
+/// * `snapshot_id` - 快照ID
+/// * `pinned` - 是否固定
+#[tauri::command]
+pub async fn pin_snapshot(
+    store: State<'_, SnapshotStore>,
+    snapshot_id: i64,
+    pinned: bool,
+) -> Result<(), String> {
+    store
+        .set_pinned(snapshot_id, pinned)
+        .await
+        .map_err(|e| format!("固定快照失败: {}", e))
+}
+
+/// 预览保留策略
+///
+/// 只列出将被保留和清理的快照，不删除任何数据。
+///
+/// # 参数
+///
+/// * `uid` - 账号UID
+/// * `policy` - 保留策略（最近N个、按天、按周、按月）
+///
+/// # 返回
+///
+/// 成功返回保留计划，失败返回错误信息
+#[tauri::command]
+pub async fn preview_snapshot_retention(
+    store: State<'_, SnapshotStore>,
+    uid: u64,
+    policy: RetentionPolicy,
+) -> Result<RetentionPlan, String> {
+    store
+        .plan_retention(uid, policy)
+        .await
+        .map_err(|e| format!("计算保留计划失败: {}", e))
+}
+
+/// 按保留策略清理快照
+///
+/// # 参数
+///
+/// * `uid` - 账号UID
+/// * `policy` - 保留策略（最近N个、按天、按周、按月）
+///
+/// # 返回
+///
+/// 成功返回执行的保留计划，失败返回错误信息
+#[tauri::command]
+pub async fn apply_snapshot_retention(
+    store: State<'_, SnapshotStore>,
+    uid: u64,
+    policy: RetentionPolicy,
+) -> Result<RetentionPlan, String> {
+    store
+        .apply_retention(uid, policy)
+        .await
+        .map_err(|e| format!("清理快照失败: {}", e))
+}
+
+/// 获取账号的保留策略
+///
+/// # 参数
+///
+/// * `uid` - 账号UID
+///
+/// # 返回
+///
+/// 成功返回保留策略（未设置时为空），失败返回错误信息
+#[tauri::command]
+pub async fn get_snapshot_retention_policy(
+    store: State<'_, SnapshotStore>,
+    uid: u64,
+) -> Result<Option<RetentionPolicy>, String> {
+    store
+        .retention_policy(uid)
+        .await
+        .map_err(|e| format!("获取保留策略失败: {}", e))
+}
+
+/// 设置账号的保留策略
+///
+/// 设置后每次手动保存快照都会按该策略清理账号手动保存的快照，
+/// 定时备份创建的快照由各计划自己的保留策略清理。
+///
+/// # 参数
+///
+/// * `uid` - 账号UID
+/// * `policy` - 保留策略（为空时取消自动清理）
+///
+/// # 返回
+///
+/// 成功返回空，失败返回错误信息
+#[tauri::command]
+pub async fn set_snapshot_retention_policy(
+    store: State<'_, SnapshotStore>,
+    uid: u64,
+    policy: Option<RetentionPolicy>,
+) -> Result<(), String> {
+    store
+        .set_retention_policy(uid, policy)
+        .await
+        .map_err(|e| format!("设置保留策略失败: {}", e))
+}
@@ -0,0 +1,232 @@
+//! 浏览器书签导出
+//!
+//! 把收藏夹导出为Netscape书签HTML格式,可直接导入Firefox、Chrome等浏览器。
+//! 每个收藏夹对应一个书签文件夹,失效的收藏项单独归入一个文件夹,
+//! 并标注最后已知的标题。
+
+use crate::api::error::Result;
+use crate::api::models::Media;
+use crate::backup::archive::{run_blocking, write_file_atomic};
+use crate::backup::videos::MEDIA_TYPE_VIDEO;
+use crate::export::html::escape;
+use crate::services::favorites::FavFolderWithMedia;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 顶层书签文件夹名
+const ROOT_FOLDER: &str = "哔哩哔哩收藏夹";
+
+/// 失效收藏项的书签文件夹名
+const INVALID_FOLDER: &str = "已失效的收藏";
+
+/// 书签导出结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BookmarkExport {
+    /// 导出的书签文件夹数量 (不含失效收藏文件夹)
+    pub folders: usize,
+    /// 导出的有效书签数量
+    pub bookmarks: usize,
+    /// 导出的失效书签数量
+    pub invalid: usize,
+    /// 没有可用链接而跳过的收藏项数量
+    pub skipped: usize,
+}
+
+/// 收藏项的链接 (没有可用链接时返回None)
+///
+/// 失效视频的bvid可能为空,此时按av号生成链接。
+fn media_url(media: &Media) -> Option<String> {
+    let bvid = media
+        .bvid
+        .as_ref()
+        .or(media.bv_id.as_ref())
+        .filter(|b| !b.is_empty());
+    match bvid {
+        Some(bvid) => Some(format!("https://www.bilibili.com/video/{}", bvid)),
+        None => media
+            .link
+            .clone()
+            .filter(|l| l.starts_with("http"))
+            .or_else(|| {
+                (media.item_type == MEDIA_TYPE_VIDEO && media.id > 0)
+                    .then(|| format!("https://www.bilibili.com/video/av{}", media.id))
+            }),
+    }
+}
+
+/// 书签条目
+fn bookmark(indent: &str, url: &str, add_date: Option<i64>, title: &str) -> String {
+    let add_date = add_date
+        .filter(|t| *t > 0)
+        .map(|t| format!(" ADD_DATE=\"{}\"", t))
+        .unwrap_or_default();
+    format!(
+        "{}<DT><A HREF=\"{}\"{}>{}</A>\n",
+        indent,
+        escape(url),
+        add_date,
+        escape(title)
+    )
+}
+
+/// 书签文件夹
+fn folder(indent: &str, title: &str, add_date: Option<i64>, items: &str) -> String {
+    let add_date = add_date
+        .filter(|t| *t > 0)
+        .map(|t| format!(" ADD_DATE=\"{}\"", t))
+        .unwrap_or_default();
+    format!(
+        "{indent}<DT><H3{}>{}</H3>\n{indent}<DL><p>\n{}{indent}</DL><p>\n",
+        add_date,
+        escape(title),
+        items,
+        indent = indent
+    )
+}
+
+/// 生成Netscape书签HTML
+///
+/// 失效收藏项的标题优先取 `known_titles` 中记录的真实标题。
+///
+/// # 参数
+///
+/// * `folders` - 收藏夹列表
+/// * `known_titles` - 视频最后已知的标题 (aid → 标题)
+///
+/// # 返回
+///
+/// 书签HTML和导出统计
+pub fn build_bookmarks(
+    folders: &[FavFolderWithMedia],
+    known_titles: &HashMap<u64, String>,
+) -> (String, BookmarkExport) {
+    let mut result = BookmarkExport::default();
+    let mut body = String::new();
+    let mut invalid = String::new();
+
+    for fav in folders {
+        let mut items = String::new();
+        for media in &fav.media_list {
+            let Some(url) = media_url(media) else {
+                result.skipped += 1;
+                continue;
+            };
+            if media.is_invalid() {
+                let title = known_titles
+                    .get(&media.id)
+                    .map(String::as_str)
+                    .unwrap_or(&media.title);
+                let title = format!("{}（已失效，原收藏夹：{}）", title, fav.folder.title);
+                invalid.push_str(&bookmark("            ", &url, media.fav_time, &title));
+                result.invalid += 1;
+            } else {
+                items.push_str(&bookmark(
+                    "            ",
+                    &url,
+                    media.fav_time,
+                    &media.title,
+                ));
+                result.bookmarks += 1;
+            }
+        }
+        body.push_str(&folder(
+            "        ",
+            &fav.folder.title,
+            fav.folder.ctime,
+            &items,
+        ));
+        result.folders += 1;
+    }
+    if !invalid.is_empty() {
+        body.push_str(&folder("        ", INVALID_FOLDER, None, &invalid));
+    }
+
+    let mut html = String::from(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <!-- This is an automatically generated file.\n     \
+         It will be read and overwritten.\n     \
+         DO NOT EDIT! -->\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>Bookmarks</TITLE>\n\
+         <H1>Bookmarks</H1>\n\
+         <DL><p>\n",
+    );
+    html.push_str(&folder("    ", ROOT_FOLDER, None, &body));
+    html.push_str("</DL><p>\n");
+    (html, result)
+}
+
+/// 导出收藏夹为浏览器书签文件
+///
+/// # 参数
+///
+/// * `folders` - 收藏夹列表
+/// * `known_titles` - 视频最后已知的标题 (aid → 标题),用于标注失效收藏项
+/// * `path` - 导出文件路径
+///
+/// # 返回
+///
+/// 导出统计
+pub async fn export_bookmarks(
+    folders: &[FavFolderWithMedia],
+    known_titles: &HashMap<u64, String>,
+    path: impl AsRef<Path>,
+) -> Result<BookmarkExport> {
+    let (html, result) = build_bookmarks(folders, known_titles);
+    let path = path.as_ref().to_path_buf();
+    run_blocking(move || {
+        write_file_atomic(&path, html.as_bytes())?;
+        tracing::info!(
+            "收藏夹已导出为书签: {} ({} 个书签，{} 个失效，{} 个无链接跳过)",
+            path.display(),
+            result.bookmarks,
+            result.invalid,
+            result.skipped
+        );
+        Ok(result)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn folders() -> Vec<FavFolderWithMedia> {
+        serde_json::from_value(json!([{
+            "folder": {"id": 1, "mid": 42, "attr": 0, "title": "默认收藏夹", "ctime": 1600000000, "mediaCount": 3},
+            "media_list": [
+                {"id": 100, "type": 2, "title": "视频<一>", "favTime": 1700000000, "bvid": "BV1aa"},
+                {"id": 200, "type": 2, "title": "已失效视频", "attr": 9, "favTime": 1700000100, "bvid": "BV1bb"},
+                {"id": 300, "type": 2, "title": "已失效视频", "attr": 1, "bvid": "BV1cc"},
+                {"id": 400, "type": 2, "title": "已失效视频", "attr": 9, "bvid": ""},
+                {"id": 500, "type": 12, "title": "音频", "bvid": ""}
+            ]
+        }]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bookmarks_structure() {
+        let known = HashMap::from([(200, "被删除的视频".to_string())]);
+        let (html, result) = build_bookmarks(&folders(), &known);
+
+        assert_eq!(result.folders, 1);
+        assert_eq!(result.bookmarks, 1);
+        assert_eq!(result.invalid, 3);
+        assert_eq!(result.skipped, 1);
+        assert!(html.starts_with("<!DOCTYPE NETSCAPE-Bookmark-file-1>"));
+        assert!(html.contains(r#"<H3 ADD_DATE="1600000000">默认收藏夹</H3>"#));
+        assert!(html.contains(
+            r#"<A HREF="https://www.bilibili.com/video/BV1aa" ADD_DATE="1700000000">视频&lt;一&gt;</A>"#
+        ));
+
+        let invalid = html.find(INVALID_FOLDER).unwrap();
+        assert!(html[invalid..].contains("被删除的视频（已失效，原收藏夹：默认收藏夹）"));
+        assert!(html[invalid..].contains("已失效视频（已失效，原收藏夹：默认收藏夹）"));
+        assert!(!html[..invalid].contains("BV1bb"));
+        assert!(html[invalid..].contains("https://www.bilibili.com/video/av400"));
+    }
+}
@@ -0,0 +1,757 @@
+//! 静态HTML浏览页导出
+//!
+//! 把一份备份渲染为可离线浏览的静态网站:首页、按分组展示的关注列表、收藏夹、
+//! 历史记录时间线、追番书架和稍后再看等页面。页面由模板和数据模型直接生成,
+//! 不依赖任何脚本;已缓存在本地的图片会复制到输出目录,
+//! 输出目录可以在任何浏览器中直接打开。
+
+use crate::api::error::{BiliError, Result};
+use crate::api::models::{Bangumi, History, Media, Relation, ToView, User};
+use crate::backup::archive::{run_blocking, write_file_atomic, BackupArchive};
+use crate::backup::images::local_paths;
+use crate::backup::BackupModule;
+use crate::export::group_by_tag;
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 图片在输出目录中的子目录
+const IMAGES_DIR: &str = "images";
+
+/// 样式表文件名
+const STYLE_FILE: &str = "style.css";
+
+/// 页面模板
+const PAGE_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{title}} - {{account}}</title>
+<link rel="stylesheet" href="style.css">
+</head>
+<body>
+<nav>{{nav}}</nav>
+<main>
+<h1>{{title}}</h1>
+{{content}}
+</main>
+<footer>{{footer}}</footer>
+</body>
+</html>
+"#;
+
+/// 样式表
+const STYLE: &str = r#"body { margin: 0; font-family: -apple-system, "PingFang SC", "Microsoft YaHei", sans-serif; background: #f6f7f8; color: #18191c; }
+nav { background: #fff; border-bottom: 1px solid #e3e5e7; padding: 12px 24px; }
+nav a { margin-right: 16px; color: #61666d; text-decoration: none; }
+nav a.current { color: #00aeec; font-weight: bold; }
+main { max-width: 1200px; margin: 0 auto; padding: 16px 24px; }
+footer { color: #9499a0; font-size: 12px; text-align: center; padding: 24px; }
+a { color: #00aeec; }
+h2 { border-left: 4px solid #00aeec; padding-left: 8px; }
+.summary td, .summary th { padding: 6px 16px; text-align: left; }
+.grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(200px, 1fr)); gap: 16px; }
+.card { background: #fff; border-radius: 6px; overflow: hidden; box-shadow: 0 1px 3px rgba(0,0,0,.08); }
+.card img { width: 100%; aspect-ratio: 16 / 10; object-fit: cover; background: #e3e5e7; }
+.card.poster img { aspect-ratio: 3 / 4; }
+.card .body { padding: 8px; font-size: 14px; }
+.card .meta, .meta { color: #9499a0; font-size: 12px; }
+.users { list-style: none; padding: 0; display: grid; grid-template-columns: repeat(auto-fill, minmax(260px, 1fr)); gap: 8px; }
+.users li { background: #fff; border-radius: 6px; padding: 8px; display: flex; gap: 8px; align-items: center; }
+.users img { width: 40px; height: 40px; border-radius: 50%; object-fit: cover; background: #e3e5e7; }
+.timeline { list-style: none; padding: 0; }
+.timeline li { background: #fff; margin-bottom: 6px; padding: 8px; border-radius: 6px; }
+.badge { background: #fb7299; color: #fff; border-radius: 3px; font-size: 12px; padding: 0 4px; }
+"#;
+
+/// HTML导出选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HtmlOptions {
+    /// 已缓存在本地的图片 (图片URL → 本地文件路径)
+    ///
+    /// 找到的图片会复制到输出目录,其余图片仍引用原始URL。
+    #[serde(default)]
+    pub images: HashMap<String, PathBuf>,
+    /// 图片归档目录
+    ///
+    /// 设置后备份图片索引中记录的本地图片也会被复制到输出目录。
+    #[serde(default)]
+    pub image_dir: Option<PathBuf>,
+}
+
+/// HTML导出结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HtmlExport {
+    /// 生成的页面文件名
+    pub pages: Vec<String>,
+    /// 复制到输出目录的本地图片数量
+    pub local_images: usize,
+    /// 仍引用原始URL的图片数量
+    pub remote_images: usize,
+}
+
+/// 导出备份为静态HTML浏览页
+///
+/// # 参数
+///
+/// * `archive` - 备份归档
+/// * `dir` - 输出目录 (不存在时创建)
+/// * `options` - 导出选项
+///
+/// # 返回
+///
+/// 导出结果 (页面列表和图片统计)
+pub async fn export_html(
+    archive: &BackupArchive,
+    dir: impl AsRef<Path>,
+    options: &HtmlOptions,
+) -> Result<HtmlExport> {
+    let archive = archive.clone();
+    let dir = dir.as_ref().to_path_buf();
+    let options = options.clone();
+    run_blocking(move || {
+        let result = SiteWriter::new(&archive, &dir, &options).write()?;
+        tracing::info!(
+            "备份已导出为HTML: {} ({} 个页面，{} 张本地图片)",
+            dir.display(),
+            result.pages.len(),
+            result.local_images
+        );
+        Ok(result)
+    })
+    .await
+}
+
+/// 转义HTML特殊字符
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 填充模板中的 `{{name}}` 占位符 (值不做转义)
+fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut output = template.to_string();
+    for (name, value) in vars {
+        output = output.replace(&format!("{{{{{}}}}}", name), value);
+    }
+    output
+}
+
+/// 格式化时间戳为本地时间
+fn format_time(timestamp: Option<i64>, format: &str) -> String {
+    timestamp
+        .filter(|t| *t > 0)
+        .and_then(|t| Local.timestamp_opt(t, 0).single())
+        .map(|t| t.format(format).to_string())
+        .unwrap_or_default()
+}
+
+/// 格式化时长
+fn format_duration(seconds: Option<i32>) -> String {
+    match seconds.filter(|s| *s > 0) {
+        Some(s) if s >= 3600 => format!("{}:{:02}:{:02}", s / 3600, s / 60 % 60, s % 60),
+        Some(s) => format!("{:02}:{:02}", s / 60, s % 60),
+        None => String::new(),
+    }
+}
+
+/// 视频页面链接
+fn video_link(bvid: Option<&String>, aid: u64) -> String {
+    match bvid.filter(|b| !b.is_empty()) {
+        Some(bvid) => format!("https://www.bilibili.com/video/{}", bvid),
+        None => format!("https://www.bilibili.com/video/av{}", aid),
+    }
+}
+
+/// 用户空间链接
+fn space_link(mid: u64) -> String {
+    format!("https://space.bilibili.com/{}", mid)
+}
+
+/// 站点页面
+struct Page {
+    file: &'static str,
+    title: &'static str,
+}
+
+/// 站点生成器
+struct SiteWriter<'a> {
+    archive: &'a BackupArchive,
+    dir: &'a Path,
+    /// 本地可用的图片 (URL → 本地文件路径)
+    images: HashMap<String, PathBuf>,
+    /// 已复制的图片 (URL → 相对路径)
+    copied: HashMap<String, String>,
+    result: HtmlExport,
+}
+
+impl<'a> SiteWriter<'a> {
+    fn new(archive: &'a BackupArchive, dir: &'a Path, options: &HtmlOptions) -> Self {
+        let mut images = options
+            .image_dir
+            .as_deref()
+            .map(|image_dir| local_paths(&archive.images, image_dir))
+            .unwrap_or_default();
+        images.extend(options.images.clone());
+
+        Self {
+            archive,
+            dir,
+            images,
+            copied: HashMap::new(),
+            result: HtmlExport::default(),
+        }
+    }
+
+    /// 站点包含的页面 (首页之外,只包含备份中存在的模块)
+    fn pages(&self) -> Vec<Page> {
+        let data = &self.archive.data;
+        let mut pages = vec![Page {
+            file: "index.html",
+            title: "概览",
+        }];
+        let optional = [
+            (data.following.is_some(), "following.html", "关注"),
+            (data.followers.is_some(), "followers.html", "粉丝"),
+            (data.favorites.is_some(), "favorites.html", "收藏夹"),
+            (data.history.is_some(), "history.html", "历史记录"),
+            (data.bangumi.is_some(), "bangumi.html", "追番追剧"),
+            (data.toview.is_some(), "toview.html", "稍后再看"),
+            (data.blacklist.is_some(), "blacklist.html", "黑名单"),
+        ];
+        for (present, file, title) in optional {
+            if present {
+                pages.push(Page { file, title });
+            }
+        }
+        pages
+    }
+
+    fn write(mut self) -> Result<HtmlExport> {
+        std::fs::create_dir_all(self.dir.join(IMAGES_DIR))
+            .map_err(|e| BiliError::io(format!("创建输出目录失败: {}", e)))?;
+        write_file_atomic(&self.dir.join(STYLE_FILE), STYLE.as_bytes())?;
+
+        let pages = self.pages();
+        for page in &pages {
+            let content = match page.file {
+                "index.html" => self.index_content(&pages),
+                "following.html" => self.following_content(),
+                "followers.html" => {
+                    let users = self.archive.data.followers.clone().unwrap_or_default();
+                    self.relations_list(&users)
+                }
+                "favorites.html" => self.favorites_content(),
+                "history.html" => self.history_content(),
+                "bangumi.html" => self.bangumi_content(),
+                "toview.html" => self.toview_content(),
+                "blacklist.html" => self.blacklist_content(),
+                _ => String::new(),
+            };
+            self.write_page(page, &pages, &content)?;
+        }
+
+        Ok(self.result)
+    }
+
+    fn write_page(&mut self, page: &Page, pages: &[Page], content: &str) -> Result<()> {
+        let nav: String = pages
+            .iter()
+            .map(|p| {
+                let class = if p.file == page.file {
+                    r#" class="current""#
+                } else {
+                    ""
+                };
+                format!(r#"<a href="{}"{}>{}</a>"#, p.file, class, p.title)
+            })
+            .collect();
+        let footer = format!(
+            "备份时间 {} · 由哔哩哔哩账号备份工具 v{} 生成",
+            format_time(Some(self.archive.created_at), "%Y-%m-%d %H:%M"),
+            env!("CARGO_PKG_VERSION")
+        );
+
+        let html = render(
+            PAGE_TEMPLATE,
+            &[
+                ("title", page.title),
+                ("account", &escape(&self.account_name())),
+                ("nav", &nav),
+                ("content", content),
+                ("footer", &footer),
+            ],
+        );
+        write_file_atomic(&self.dir.join(page.file), html.as_bytes())?;
+        self.result.pages.push(page.file.to_string());
+        Ok(())
+    }
+
+    fn account_name(&self) -> String {
+        let source = &self.archive.source;
+        match source.uname {
+            Some(ref uname) => format!("{} (UID {})", uname, source.uid),
+            None => format!("UID {}", source.uid),
+        }
+    }
+
+    /// 图片地址:本地已缓存时复制到输出目录并返回相对路径,否则返回原始URL
+    fn image(&mut self, url: &str) -> String {
+        if url.is_empty() {
+            return String::new();
+        }
+        if let Some(path) = self.copied.get(url) {
+            return path.clone();
+        }
+
+        if let Some(source) = self.images.get(url).filter(|p| p.is_file()) {
+            let file_name = source
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let relative = format!("{}/{}", IMAGES_DIR, file_name);
+            if std::fs::copy(source, self.dir.join(&relative)).is_ok() {
+                self.result.local_images += 1;
+                self.copied.insert(url.to_string(), relative.clone());
+                return relative;
+            }
+            tracing::warn!("复制图片失败: {}", source.display());
+        }
+
+        self.result.remote_images += 1;
+        let remote = if url.starts_with("//") {
+            format!("https:{}", url)
+        } else {
+            url.to_string()
+        };
+        self.copied.insert(url.to_string(), remote.clone());
+        remote
+    }
+
+    fn index_content(&self, pages: &[Page]) -> String {
+        let data = &self.archive.data;
+        let mut rows = String::new();
+        for module in data.modules() {
+            let count = data.count(module).unwrap_or(0);
+            let detail = match module {
+                BackupModule::Favorites => format!(
+                    "{} 个收藏夹，{} 个收藏项",
+                    count,
+                    data.favorites
+                        .iter()
+                        .flatten()
+                        .map(|f| f.media_list.len())
+                        .sum::<usize>()
+                ),
+                _ => count.to_string(),
+            };
+            let truncated = if self
+                .archive
+                .completeness
+                .get(&module)
+                .is_some_and(|c| c.truncated)
+            {
+                r#" <span class="badge">不完整</span>"#
+            } else {
+                ""
+            };
+            rows.push_str(&format!(
+                "<tr><th>{}</th><td>{}{}</td></tr>\n",
+                module.display_name(),
+                detail,
+                truncated
+            ));
+        }
+
+        let links: String = pages
+            .iter()
+            .skip(1)
+            .map(|p| format!(r#"<li><a href="{}">{}</a></li>"#, p.file, p.title))
+            .collect();
+        format!(
+            "<p>账号：{}</p>\n<table class=\"summary\">\n{}</table>\n<ul>{}</ul>",
+            escape(&self.account_name()),
+            rows,
+            links
+        )
+    }
+
+    fn user_item(&mut self, mid: u64, uname: &str, face: &str, extra: &str) -> String {
+        let face = self.image(face);
+        format!(
+            r#"<li><img src="{}" alt="" loading="lazy"><div><a href="{}">{}</a>{}</div></li>"#,
+            escape(&face),
+            space_link(mid),
+            escape(uname),
+            extra
+        )
+    }
+
+    fn relations_list<'a>(&mut self, relations: impl IntoIterator<Item = &'a Relation>) -> String {
+        let mut html = String::from("<ul class=\"users\">\n");
+        for relation in relations {
+            let mut extra = String::new();
+            if relation.special == Some(1) {
+                extra.push_str(r#" <span class="badge">特别关注</span>"#);
+            }
+            if let Some(sign) = relation.sign.as_ref().filter(|s| !s.is_empty()) {
+                extra.push_str(&format!(r#"<div class="meta">{}</div>"#, escape(sign)));
+            }
+            let item = self.user_item(relation.mid, &relation.uname, &relation.face, &extra);
+            html.push_str(&item);
+            html.push('\n');
+        }
+        html.push_str("</ul>");
+        html
+    }
+
+    fn following_content(&mut self) -> String {
+        let data = &self.archive.data;
+        let following = data.following.clone().unwrap_or_default();
+        let tags = data.relation_tags.clone().unwrap_or_default();
+
+        // 按分组归类,一个用户可以属于多个分组
+        let (groups, ungrouped) = group_by_tag(&following, &tags);
+
+        let mut html = String::new();
+        for (tag, relations) in groups.into_iter().filter(|(_, r)| !r.is_empty()) {
+            html.push_str(&format!(
+                "<h2>{} <span class=\"meta\">{} 人</span></h2>\n",
+                escape(&tag.name),
+                relations.len()
+            ));
+            html.push_str(&self.relations_list(relations));
+        }
+        if !ungrouped.is_empty() {
+            html.push_str(&format!(
+                "<h2>未分组 <span class=\"meta\">{} 人</span></h2>\n",
+                ungrouped.len()
+            ));
+            html.push_str(&self.relations_list(ungrouped));
+        }
+        html
+    }
+
+    fn video_card(&mut self, cover: &str, link: &str, title: &str, meta: &str) -> String {
+        let cover = self.image(cover);
+        format!(
+            r#"<div class="card"><a href="{}"><img src="{}" alt="" loading="lazy"></a><div class="body"><a href="{}">{}</a><div class="meta">{}</div></div></div>"#,
+            escape(link),
+            escape(&cover),
+            escape(link),
+            escape(title),
+            meta
+        )
+    }
+
+    fn media_card(&mut self, media: &Media) -> String {
+        let link = video_link(media.bvid.as_ref().or(media.bv_id.as_ref()), media.id);
+        let mut meta = Vec::new();
+        if let Some(ref upper) = media.upper {
+            meta.push(escape(&upper.name));
+        }
+        let duration = format_duration(media.duration);
+        if !duration.is_empty() {
+            meta.push(duration);
+        }
+        let fav_time = format_time(media.fav_time, "%Y-%m-%d");
+        if !fav_time.is_empty() {
+            meta.push(format!("收藏于 {}", fav_time));
+        }
+        self.video_card(
+            media.cover.as_deref().unwrap_or_default(),
+            &link,
+            &media.title,
+            &meta.join(" · "),
+        )
+    }
+
+    fn favorites_content(&mut self) -> String {
+        let folders = self.archive.data.favorites.clone().unwrap_or_default();
+        let mut html = String::from("<ul>\n");
+        for folder in &folders {
+            html.push_str(&format!(
+                "<li><a href=\"#fav-{}\">{}</a> <span class=\"meta\">{} 个</span></li>\n",
+                folder.folder.id,
+                escape(&folder.folder.title),
+                folder.media_list.len()
+            ));
+        }
+        html.push_str("</ul>\n");
+
+        for folder in &folders {
+            html.push_str(&format!(
+                "<h2 id=\"fav-{}\">{} <span class=\"meta\">{} 个</span></h2>\n",
+                folder.folder.id,
+                escape(&folder.folder.title),
+                folder.media_list.len()
+            ));
+            if let Some(intro) = folder.intro.as_ref().filter(|i| !i.is_empty()) {
+                html.push_str(&format!("<p class=\"meta\">{}</p>\n", escape(intro)));
+            }
+            html.push_str("<div class=\"grid\">\n");
+            for media in &folder.media_list {
+                html.push_str(&self.media_card(media));
+                html.push('\n');
+            }
+            html.push_str("</div>\n");
+        }
+        html
+    }
+
+    fn history_item(&mut self, item: &History) -> String {
+        let detail = item.history.as_ref();
+        let link = match detail.and_then(|h| h.bvid.as_ref()) {
+            Some(bvid) if !bvid.is_empty() => video_link(Some(bvid), 0),
+            _ => item.uri.clone().unwrap_or_default(),
+        };
+        let mut meta = vec![format_time(item.view_at, "%H:%M")];
+        if let Some(ref author) = item.author_name {
+            meta.push(escape(author));
+        }
+        if let (Some(progress), Some(duration)) = (item.progress, item.duration) {
+            if duration > 0 {
+                let progress = if progress < 0 {
+                    "已看完".to_string()
+                } else {
+                    format!("看到 {}", format_duration(Some(progress)))
+                };
+                meta.push(format!(
+                    "{} / {}",
+                    progress,
+                    format_duration(Some(duration))
+                ));
+            }
+        }
+
+        let title = if link.is_empty() {
+            escape(&item.title)
+        } else {
+            format!(r#"<a href="{}">{}</a>"#, escape(&link), escape(&item.title))
+        };
+        format!(
+            r#"<li>{} <span class="meta">{}</span></li>"#,
+            title,
+            meta.join(" · ")
+        )
+    }
+
+    fn history_content(&mut self) -> String {
+        let history = self.archive.data.history.clone().unwrap_or_default();
+        let mut html = String::new();
+        let mut current_day = None;
+        for item in &history {
+            let day = format_time(item.view_at, "%Y-%m-%d");
+            if current_day.as_ref() != Some(&day) {
+                if current_day.is_some() {
+                    html.push_str("</ul>\n");
+                }
+                let heading = if day.is_empty() { "未知日期" } else { &day };
+                html.push_str(&format!("<h2>{}</h2>\n<ul class=\"timeline\">\n", heading));
+                current_day = Some(day);
+            }
+            html.push_str(&self.history_item(item));
+            html.push('\n');
+        }
+        if current_day.is_some() {
+            html.push_str("</ul>\n");
+        }
+        html
+    }
+
+    fn bangumi_card(&mut self, bangumi: &Bangumi) -> String {
+        let link = bangumi
+            .url
+            .clone()
+            .filter(|u| !u.is_empty())
+            .unwrap_or_else(|| {
+                format!(
+                    "https://www.bilibili.com/bangumi/media/md{}",
+                    bangumi.media_id
+                )
+            });
+        let cover = self.image(&bangumi.cover);
+        let badge = bangumi
+            .badge
+            .as_ref()
+            .filter(|b| !b.is_empty())
+            .map(|b| format!(r#" <span class="badge">{}</span>"#, escape(b)))
+            .unwrap_or_default();
+        let status = match bangumi.follow_status {
+            Some(1) => "想看",
+            Some(2) => "在看",
+            Some(3) => "看过",
+            _ => "",
+        };
+        let meta = [
+            bangumi.season_type_name.as_deref().unwrap_or_default(),
+            status,
+        ]
+        .iter()
+        .filter(|s| !s.is_empty())
+        .map(|s| escape(s))
+        .collect::<Vec<_>>()
+        .join(" · ");
+
+        format!(
+            r#"<div class="card poster"><a href="{}"><img src="{}" alt="" loading="lazy"></a><div class="body"><a href="{}">{}</a>{}<div class="meta">{}</div></div></div>"#,
+            escape(&link),
+            escape(&cover),
+            escape(&link),
+            escape(&bangumi.title),
+            badge,
+            meta
+        )
+    }
+
+    fn bangumi_content(&mut self) -> String {
+        let bangumi = self.archive.data.bangumi.clone().unwrap_or_default();
+        let mut html = String::from("<div class=\"grid\">\n");
+        for item in &bangumi {
+            html.push_str(&self.bangumi_card(item));
+            html.push('\n');
+        }
+        html.push_str("</div>");
+        html
+    }
+
+    fn toview_card(&mut self, video: &ToView) -> String {
+        let link = video_link(video.bvid.as_ref(), video.aid);
+        let mut meta = Vec::new();
+        if let Some(ref owner) = video.owner {
+            meta.push(escape(&owner.name));
+        }
+        let duration = format_duration(video.duration);
+        if !duration.is_empty() {
+            meta.push(duration);
+        }
+        let add_at = format_time(video.add_at, "%Y-%m-%d");
+        if !add_at.is_empty() {
+            meta.push(format!("添加于 {}", add_at));
+        }
+        self.video_card(&video.pic, &link, &video.title, &meta.join(" · "))
+    }
+
+    fn toview_content(&mut self) -> String {
+        let toview = self.archive.data.toview.clone().unwrap_or_default();
+        let mut html = String::from("<div class=\"grid\">\n");
+        for video in &toview {
+            html.push_str(&self.toview_card(video));
+            html.push('\n');
+        }
+        html.push_str("</div>");
+        html
+    }
+
+    fn blacklist_content(&mut self) -> String {
+        let users: Vec<User> = self.archive.data.blacklist.clone().unwrap_or_default();
+        let mut html = String::from("<ul class=\"users\">\n");
+        for user in &users {
+            let item = self.user_item(user.mid, &user.uname, &user.face, "");
+            html.push_str(&item);
+            html.push('\n');
+        }
+        html.push_str("</ul>");
+        html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::models::RelationTag;
+    use crate::backup::BackupSource;
+    use serde_json::json;
+
+    fn sample_archive() -> BackupArchive {
+        let mut archive = BackupArchive::new(BackupSource {
+            uid: 42,
+            uname: Some("测试<用户>".to_string()),
+        });
+        archive.data.relation_tags = Some(vec![RelationTag::new(1, "游戏区".to_string())]);
+        archive.data.following = Some(vec![
+            serde_json::from_value(json!({
+                "mid": 1, "uname": "UP主A", "face": "http://i0.hdslb.com/a.jpg",
+                "mtime": 1700000000, "tag": [1]
+            }))
+            .unwrap(),
+            serde_json::from_value(json!({
+                "mid": 2, "uname": "UP主B", "face": "http://i0.hdslb.com/b.jpg",
+                "mtime": 1700000000
+            }))
+            .unwrap(),
+        ]);
+        archive.data.toview = Some(vec![serde_json::from_value(json!({
+            "aid": 100, "bvid": "BV1xx411c7mD", "cid": 1, "title": "视频 & 标题",
+            "pic": "http://i0.hdslb.com/cover.jpg"
+        }))
+        .unwrap()]);
+        archive
+    }
+
+    #[test]
+    fn test_render_and_escape() {
+        assert_eq!(
+            render("<p>{{a}}-{{b}}</p>", &[("a", "1"), ("b", "2")]),
+            "<p>1-2</p>"
+        );
+        assert_eq!(
+            escape(r#"<a href="x">&'"#),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;"
+        );
+        assert_eq!(format_duration(Some(3725)), "1:02:05");
+        assert_eq!(format_duration(Some(65)), "01:05");
+    }
+
+    #[tokio::test]
+    async fn test_export_html_site() {
+        let dir = tempfile::tempdir().unwrap();
+        let cached = dir.path().join("cached.jpg");
+        std::fs::write(&cached, b"jpeg").unwrap();
+
+        let options = HtmlOptions {
+            images: HashMap::from([("http://i0.hdslb.com/cover.jpg".to_string(), cached)]),
+            image_dir: None,
+        };
+        let out = dir.path().join("site");
+        let result = export_html(&sample_archive(), &out, &options)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.pages,
+            vec!["index.html", "following.html", "toview.html"]
+        );
+        assert_eq!(result.local_images, 1);
+        assert_eq!(result.remote_images, 2);
+        assert!(out.join("images/cached.jpg").exists());
+        assert!(out.join("style.css").exists());
+
+        let index = std::fs::read_to_string(out.join("index.html")).unwrap();
+        assert!(index.contains("测试&lt;用户&gt;"));
+        assert!(index.contains(r#"<a href="toview.html">稍后再看</a>"#));
+
+        let following = std::fs::read_to_string(out.join("following.html")).unwrap();
+        let group = following.find("游戏区").unwrap();
+        let ungrouped = following.find("未分组").unwrap();
+        assert!(group < following.find("UP主A").unwrap());
+        assert!(ungrouped < following.find("UP主B").unwrap());
+
+        let toview = std::fs::read_to_string(out.join("toview.html")).unwrap();
+        assert!(toview.contains(r#"src="images/cached.jpg""#));
+        assert!(toview.contains("视频 &amp; 标题"));
+        assert!(toview.contains("https://www.bilibili.com/video/BV1xx411c7mD"));
+    }
+}
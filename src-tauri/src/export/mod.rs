@@ -0,0 +1,53 @@
+//! 导出格式模块
+//!
+//! 该模块负责把备份数据转换为便于在其他工具中查看的格式，例如电子表格、离线浏览页、RSS订阅和浏览器书签。
+
+use crate::api::models::{Relation, RelationTag};
+
+/// 浏览器书签导出
+pub mod bookmarks;
+/// 静态HTML浏览页导出
+pub mod html;
+/// OPML订阅导出
+pub mod opml;
+/// 表格导出 (CSV / XLSX)
+pub mod table;
+
+// 导出常用类型
+pub use bookmarks::{build_bookmarks, export_bookmarks, BookmarkExport};
+pub use html::{export_html, HtmlExport, HtmlOptions};
+pub use opml::{build_opml, export_opml, OpmlOptions};
+pub use table::{
+    available_columns, build_table, export_table, Cell, ColumnInfo, Table, TableFormat,
+    TableOptions,
+};
+
+/// 按关注分组归类关注列表
+///
+/// 分组按 `tags` 的顺序返回 (包括没有成员的分组),一个用户属于多个分组时在每个分组中都会出现。
+///
+/// # 返回
+///
+/// 各分组及其成员,以及不属于任何已知分组的用户
+pub(crate) fn group_by_tag<'a>(
+    relations: &'a [Relation],
+    tags: &'a [RelationTag],
+) -> (Vec<(&'a RelationTag, Vec<&'a Relation>)>, Vec<&'a Relation>) {
+    let mut groups: Vec<(&RelationTag, Vec<&Relation>)> =
+        tags.iter().map(|tag| (tag, Vec::new())).collect();
+    let mut ungrouped = Vec::new();
+    for relation in relations {
+        let tag_ids = relation.tag.as_deref().unwrap_or_default();
+        let mut grouped = false;
+        for (tag, members) in groups.iter_mut() {
+            if tag_ids.contains(&tag.tag_id) {
+                members.push(relation);
+                grouped = true;
+            }
+        }
+        if !grouped {
+            ungrouped.push(relation);
+        }
+    }
+    (groups, ungrouped)
+}
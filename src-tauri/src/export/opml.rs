@@ -0,0 +1,215 @@
+//! OPML订阅导出
+//!
+//! 把关注列表转换为OPML文件,每个UP主对应一个RSS订阅 (默认使用RSSHub路由),
+//! 并按关注分组归类,便于导入RSS阅读器。
+
+use crate::api::error::{BiliError, Result};
+use crate::api::models::{Relation, RelationTag};
+use crate::backup::archive::{run_blocking, write_file_atomic};
+use crate::export::group_by_tag;
+use crate::export::html::escape;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// 默认的RSSHub地址
+pub const DEFAULT_RSSHUB_BASE: &str = "https://rsshub.app";
+
+/// 默认的订阅地址模板 (RSSHub UP主投稿路由)
+pub const DEFAULT_FEED_TEMPLATE: &str = "{base}/bilibili/user/video/{uid}";
+
+/// 未分组关注的分组名
+const UNGROUPED: &str = "未分组";
+
+/// OPML导出选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpmlOptions {
+    /// RSSHub地址 (替换模板中的 `{base}`)
+    #[serde(default = "default_base")]
+    pub base_url: String,
+    /// 订阅地址模板
+    ///
+    /// 支持 `{base}`、`{uid}` 两个占位符。
+    #[serde(default = "default_template")]
+    pub feed_template: String,
+    /// 文档标题
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+fn default_base() -> String {
+    DEFAULT_RSSHUB_BASE.to_string()
+}
+
+fn default_template() -> String {
+    DEFAULT_FEED_TEMPLATE.to_string()
+}
+
+impl Default for OpmlOptions {
+    fn default() -> Self {
+        Self {
+            base_url: default_base(),
+            feed_template: default_template(),
+            title: None,
+        }
+    }
+}
+
+impl OpmlOptions {
+    /// UP主的订阅地址
+    pub fn feed_url(&self, mid: u64) -> String {
+        self.feed_template
+            .replace("{base}", self.base_url.trim_end_matches('/'))
+            .replace("{uid}", &mid.to_string())
+    }
+}
+
+/// 生成OPML文档
+///
+/// 按 `tags` 的顺序输出分组,一个UP主属于多个分组时在每个分组中都会出现;
+/// 不属于任何已知分组的UP主归入"未分组"。空分组不输出。
+///
+/// # 参数
+///
+/// * `relations` - 关注列表
+/// * `tags` - 关注分组
+/// * `options` - 导出选项
+///
+/// # 错误
+///
+/// 模板中不包含 `{uid}` 时返回参数错误
+pub fn build_opml(
+    relations: &[Relation],
+    tags: &[RelationTag],
+    options: &OpmlOptions,
+) -> Result<String> {
+    if !options.feed_template.contains("{uid}") {
+        return Err(BiliError::param("订阅地址模板必须包含 {uid}"));
+    }
+
+    let (tag_groups, ungrouped) = group_by_tag(relations, tags);
+    let mut groups: Vec<(&str, Vec<&Relation>)> = tag_groups
+        .into_iter()
+        .map(|(tag, members)| (tag.name.as_str(), members))
+        .collect();
+    groups.push((UNGROUPED, ungrouped));
+
+    let title = options.title.as_deref().unwrap_or("哔哩哔哩关注");
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<opml version=\"2.0\">\n");
+    xml.push_str("  <head>\n");
+    xml.push_str(&format!("    <title>{}</title>\n", escape(title)));
+    xml.push_str(&format!(
+        "    <dateCreated>{}</dateCreated>\n",
+        Utc::now().to_rfc2822()
+    ));
+    xml.push_str("  </head>\n  <body>\n");
+    for (name, members) in groups.iter().filter(|(_, m)| !m.is_empty()) {
+        let name = escape(name);
+        xml.push_str(&format!(
+            "    <outline text=\"{}\" title=\"{}\">\n",
+            name, name
+        ));
+        for relation in members {
+            let uname = escape(&relation.uname);
+            xml.push_str(&format!(
+                "      <outline type=\"rss\" text=\"{}\" title=\"{}\" xmlUrl=\"{}\" htmlUrl=\"https://space.bilibili.com/{}\"/>\n",
+                uname,
+                uname,
+                escape(&options.feed_url(relation.mid)),
+                relation.mid
+            ));
+        }
+        xml.push_str("    </outline>\n");
+    }
+    xml.push_str("  </body>\n</opml>\n");
+    Ok(xml)
+}
+
+/// 导出关注列表为OPML文件
+///
+/// # 参数
+///
+/// * `relations` - 关注列表
+/// * `tags` - 关注分组
+/// * `path` - 导出文件路径
+/// * `options` - 导出选项
+///
+/// # 返回
+///
+/// 导出的UP主数量
+pub async fn export_opml(
+    relations: &[Relation],
+    tags: &[RelationTag],
+    path: impl AsRef<Path>,
+    options: &OpmlOptions,
+) -> Result<usize> {
+    let xml = build_opml(relations, tags, options)?;
+    let path = path.as_ref().to_path_buf();
+    let count = relations.len();
+    run_blocking(move || {
+        write_file_atomic(&path, xml.as_bytes())?;
+        tracing::info!(
+            "关注列表已导出为OPML: {} ({} 个UP主)",
+            path.display(),
+            count
+        );
+        Ok(count)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn relation(mid: u64, uname: &str, tag: Option<Vec<i64>>) -> Relation {
+        serde_json::from_value(json!({
+            "mid": mid, "uname": uname, "face": "", "mtime": 0, "tag": tag
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_feed_url_template() {
+        let options = OpmlOptions {
+            base_url: "https://rss.example.com/".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.feed_url(123),
+            "https://rss.example.com/bilibili/user/video/123"
+        );
+
+        let invalid = OpmlOptions {
+            feed_template: "{base}/feed".to_string(),
+            ..Default::default()
+        };
+        assert!(build_opml(&[], &[], &invalid).is_err());
+    }
+
+    #[test]
+    fn test_grouped_by_tag() {
+        let tags = vec![
+            RelationTag::new(1, "游戏".to_string()),
+            RelationTag::new(2, "空分组".to_string()),
+            RelationTag::new(3, "音乐".to_string()),
+        ];
+        let relations = vec![
+            relation(10, "A & B", Some(vec![1, 3])),
+            relation(20, "C", None),
+            relation(30, "D", Some(vec![3])),
+        ];
+        let xml = build_opml(&relations, &tags, &OpmlOptions::default()).unwrap();
+
+        assert!(!xml.contains("空分组"));
+        assert_eq!(xml.matches("A &amp; B").count(), 4);
+        let game = xml.find("text=\"游戏\"").unwrap();
+        let music = xml.find("text=\"音乐\"").unwrap();
+        let ungrouped = xml.find("text=\"未分组\"").unwrap();
+        assert!(game < music && music < ungrouped);
+        assert!(xml[music..ungrouped].contains("https://rsshub.app/bilibili/user/video/30"));
+        assert!(xml[ungrouped..].contains("https://space.bilibili.com/20"));
+    }
+}
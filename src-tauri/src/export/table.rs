@@ -0,0 +1,971 @@
+//! 表格导出
+//!
+//! 把各模块的数据展开为二维表格,导出为带UTF-8 BOM的CSV (Excel可直接打开而不乱码)
+//! 或XLSX文件。嵌套字段按 `upper.name`、`vip.vip_type` 这样的路径展开为独立的列,
+//! 时间戳可转换为本地时间。
+
+use crate::api::error::{BiliError, Result};
+use crate::api::models::{Bangumi, FavInfo, History, Media, Relation, RelationTag, ToView, User};
+use crate::backup::archive::{run_blocking, write_file_atomic};
+use crate::backup::{BackupData, BackupModule};
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// UTF-8 BOM
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 可读时间格式
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// 多值单元格的分隔符
+const LIST_SEPARATOR: &str = "; ";
+
+/// 表格文件格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TableFormat {
+    /// CSV (UTF-8 BOM)
+    #[default]
+    Csv,
+    /// Excel工作簿
+    Xlsx,
+}
+
+/// 表格导出选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableOptions {
+    /// 文件格式
+    #[serde(default)]
+    pub format: TableFormat,
+    /// 导出的列 (按给定顺序,为空时导出全部列)
+    #[serde(default)]
+    pub columns: Vec<String>,
+    /// 是否把时间戳转换为本地时间
+    #[serde(default = "default_readable_time")]
+    pub readable_time: bool,
+}
+
+fn default_readable_time() -> bool {
+    true
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        Self {
+            format: TableFormat::Csv,
+            columns: Vec::new(),
+            readable_time: true,
+        }
+    }
+}
+
+/// 列信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnInfo {
+    /// 列标识 (嵌套字段用 `.` 连接)
+    pub key: String,
+    /// 表头
+    pub header: String,
+}
+
+/// 单元格
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Cell {
+    /// 空
+    Empty,
+    /// 文本
+    Text(String),
+    /// 整数
+    Number(i64),
+    /// 是/否
+    Bool(bool),
+}
+
+impl Cell {
+    /// 单元格的文本形式
+    pub fn render(&self) -> String {
+        match self {
+            Cell::Empty => String::new(),
+            Cell::Text(text) => text.clone(),
+            Cell::Number(n) => n.to_string(),
+            Cell::Bool(b) => if *b { "是" } else { "否" }.to_string(),
+        }
+    }
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Cell::Text(value)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Cell::Text(value.to_string())
+    }
+}
+
+impl From<&String> for Cell {
+    fn from(value: &String) -> Self {
+        Cell::Text(value.clone())
+    }
+}
+
+impl From<i64> for Cell {
+    fn from(value: i64) -> Self {
+        Cell::Number(value)
+    }
+}
+
+impl From<u64> for Cell {
+    fn from(value: u64) -> Self {
+        Cell::Number(value as i64)
+    }
+}
+
+impl From<i32> for Cell {
+    fn from(value: i32) -> Self {
+        Cell::Number(value as i64)
+    }
+}
+
+impl From<u32> for Cell {
+    fn from(value: u32) -> Self {
+        Cell::Number(value as i64)
+    }
+}
+
+impl From<usize> for Cell {
+    fn from(value: usize) -> Self {
+        Cell::Number(value as i64)
+    }
+}
+
+impl<T: Into<Cell>> From<Option<T>> for Cell {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Cell::Empty, Into::into)
+    }
+}
+
+/// 导出的表格
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Table {
+    /// 列
+    pub columns: Vec<ColumnInfo>,
+    /// 行
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    /// 生成带UTF-8 BOM的CSV
+    pub fn to_csv(&self) -> Result<Vec<u8>> {
+        let mut writer = csv::Writer::from_writer(UTF8_BOM.to_vec());
+        writer
+            .write_record(self.columns.iter().map(|c| c.header.as_str()))
+            .map_err(csv_error)?;
+        for row in &self.rows {
+            writer
+                .write_record(row.iter().map(Cell::render))
+                .map_err(csv_error)?;
+        }
+        writer
+            .into_inner()
+            .map_err(|e| BiliError::io(format!("写入CSV失败: {}", e)))
+    }
+
+    /// 生成XLSX工作簿
+    ///
+    /// # 参数
+    ///
+    /// * `sheet_name` - 工作表名称
+    #[cfg(feature = "xlsx")]
+    pub fn to_xlsx(&self, sheet_name: &str) -> Result<Vec<u8>> {
+        use rust_xlsxwriter::{Format, Workbook};
+
+        let xlsx_error =
+            |e: rust_xlsxwriter::XlsxError| BiliError::io(format!("写入XLSX失败: {}", e));
+
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(sheet_name).map_err(xlsx_error)?;
+
+        let bold = Format::new().set_bold();
+        for (col, column) in self.columns.iter().enumerate() {
+            sheet
+                .write_string_with_format(0, col as u16, &column.header, &bold)
+                .map_err(xlsx_error)?;
+        }
+        sheet.set_freeze_panes(1, 0).map_err(xlsx_error)?;
+
+        for (i, row) in self.rows.iter().enumerate() {
+            let row_index = i as u32 + 1;
+            for (col, cell) in row.iter().enumerate() {
+                let col = col as u16;
+                match cell {
+                    Cell::Empty => {}
+                    Cell::Number(n) => {
+                        sheet
+                            .write_number(row_index, col, *n as f64)
+                            .map_err(xlsx_error)?;
+                    }
+                    other => {
+                        sheet
+                            .write_string(row_index, col, other.render())
+                            .map_err(xlsx_error)?;
+                    }
+                }
+            }
+        }
+
+        workbook.save_to_buffer().map_err(xlsx_error)
+    }
+
+    /// 未启用 `xlsx` 特性时不支持XLSX导出
+    #[cfg(not(feature = "xlsx"))]
+    pub fn to_xlsx(&self, _sheet_name: &str) -> Result<Vec<u8>> {
+        Err(BiliError::param("当前版本未启用XLSX导出"))
+    }
+}
+
+/// 转换CSV错误
+fn csv_error(e: csv::Error) -> BiliError {
+    BiliError::io(format!("写入CSV失败: {}", e))
+}
+
+/// 生成单元格时的上下文
+struct Context<'a> {
+    /// 关注分组ID → 分组名称
+    tags: &'a HashMap<i64, String>,
+    /// 当前收藏夹 (仅收藏夹模块)
+    folder: Option<&'a FavInfo>,
+    /// 是否把时间戳转换为本地时间
+    readable_time: bool,
+}
+
+impl Context<'_> {
+    /// 时间戳单元格
+    fn time(&self, timestamp: Option<i64>) -> Cell {
+        let Some(timestamp) = timestamp.filter(|t| *t > 0) else {
+            return Cell::Empty;
+        };
+        if !self.readable_time {
+            return Cell::Number(timestamp);
+        }
+        match Local.timestamp_opt(timestamp, 0).single() {
+            Some(time) => Cell::Text(time.format(TIME_FORMAT).to_string()),
+            None => Cell::Number(timestamp),
+        }
+    }
+
+    /// 关注分组名称
+    fn tag_names(&self, tags: Option<&Vec<i64>>) -> Cell {
+        let names: Vec<String> = tags
+            .into_iter()
+            .flatten()
+            .map(|id| self.tags.get(id).cloned().unwrap_or_else(|| id.to_string()))
+            .collect();
+        if names.is_empty() {
+            Cell::Empty
+        } else {
+            Cell::Text(names.join(LIST_SEPARATOR))
+        }
+    }
+}
+
+/// 列定义
+struct Column<T> {
+    key: &'static str,
+    header: &'static str,
+    value: fn(&T, &Context) -> Cell,
+}
+
+const TAG_COLUMNS: &[Column<RelationTag>] = &[
+    Column {
+        key: "tag_id",
+        header: "分组ID",
+        value: |t, _| t.tag_id.into(),
+    },
+    Column {
+        key: "name",
+        header: "分组名称",
+        value: |t, _| (&t.name).into(),
+    },
+    Column {
+        key: "count",
+        header: "关注数",
+        value: |t, _| t.count.into(),
+    },
+];
+
+const RELATION_COLUMNS: &[Column<Relation>] = &[
+    Column {
+        key: "mid",
+        header: "用户ID",
+        value: |r, _| r.mid.into(),
+    },
+    Column {
+        key: "uname",
+        header: "用户名",
+        value: |r, _| (&r.uname).into(),
+    },
+    Column {
+        key: "sign",
+        header: "签名",
+        value: |r, _| r.sign.as_ref().into(),
+    },
+    Column {
+        key: "mtime",
+        header: "关注时间",
+        value: |r, ctx| ctx.time(Some(r.mtime)),
+    },
+    Column {
+        key: "special",
+        header: "特别关注",
+        value: |r, _| Cell::Bool(r.special == Some(1)),
+    },
+    Column {
+        key: "tags",
+        header: "分组",
+        value: |r, ctx| ctx.tag_names(r.tag.as_ref()),
+    },
+    Column {
+        key: "vip.vip_type",
+        header: "会员类型",
+        value: |r, _| r.vip.as_ref().and_then(|v| v.vip_type).into(),
+    },
+    Column {
+        key: "vip.vip_status",
+        header: "会员状态",
+        value: |r, _| r.vip.as_ref().and_then(|v| v.vip_status).into(),
+    },
+    Column {
+        key: "face",
+        header: "头像",
+        value: |r, _| (&r.face).into(),
+    },
+];
+
+const USER_COLUMNS: &[Column<User>] = &[
+    Column {
+        key: "mid",
+        header: "用户ID",
+        value: |u, _| u.mid.into(),
+    },
+    Column {
+        key: "uname",
+        header: "用户名",
+        value: |u, _| (&u.uname).into(),
+    },
+    Column {
+        key: "sign",
+        header: "签名",
+        value: |u, _| u.sign.as_ref().into(),
+    },
+    Column {
+        key: "sex",
+        header: "性别",
+        value: |u, _| u.sex.as_ref().into(),
+    },
+    Column {
+        key: "level",
+        header: "等级",
+        value: |u, _| u.level.into(),
+    },
+    Column {
+        key: "face",
+        header: "头像",
+        value: |u, _| (&u.face).into(),
+    },
+];
+
+const MEDIA_COLUMNS: &[Column<Media>] = &[
+    Column {
+        key: "folder.id",
+        header: "收藏夹ID",
+        value: |_, ctx| ctx.folder.map(|f| f.id).into(),
+    },
+    Column {
+        key: "folder.title",
+        header: "收藏夹",
+        value: |_, ctx| ctx.folder.map(|f| f.title.as_str()).into(),
+    },
+    Column {
+        key: "id",
+        header: "视频ID",
+        value: |m, _| m.id.into(),
+    },
+    Column {
+        key: "bvid",
+        header: "BV号",
+        value: |m, _| m.bvid.as_ref().or(m.bv_id.as_ref()).into(),
+    },
+    Column {
+        key: "title",
+        header: "标题",
+        value: |m, _| (&m.title).into(),
+    },
+    Column {
+        key: "upper.mid",
+        header: "UP主ID",
+        value: |m, _| m.upper.as_ref().map(|u| u.mid).into(),
+    },
+    Column {
+        key: "upper.name",
+        header: "UP主",
+        value: |m, _| m.upper.as_ref().map(|u| u.name.as_str()).into(),
+    },
+    Column {
+        key: "duration",
+        header: "时长(秒)",
+        value: |m, _| m.duration.into(),
+    },
+    Column {
+        key: "pubtime",
+        header: "发布时间",
+        value: |m, ctx| ctx.time(m.pubtime),
+    },
+    Column {
+        key: "fav_time",
+        header: "收藏时间",
+        value: |m, ctx| ctx.time(m.fav_time),
+    },
+    Column {
+        key: "cnt_info.play",
+        header: "播放数",
+        value: |m, _| m.cnt_info.as_ref().map(|c| c.play).into(),
+    },
+    Column {
+        key: "cnt_info.collect",
+        header: "收藏数",
+        value: |m, _| m.cnt_info.as_ref().map(|c| c.collect).into(),
+    },
+    Column {
+        key: "cnt_info.danmaku",
+        header: "弹幕数",
+        value: |m, _| m.cnt_info.as_ref().map(|c| c.danmaku).into(),
+    },
+    Column {
+        key: "intro",
+        header: "简介",
+        value: |m, _| m.intro.as_ref().into(),
+    },
+    Column {
+        key: "cover",
+        header: "封面",
+        value: |m, _| m.cover.as_ref().into(),
+    },
+];
+
+const HISTORY_COLUMNS: &[Column<History>] = &[
+    Column {
+        key: "title",
+        header: "标题",
+        value: |h, _| (&h.title).into(),
+    },
+    Column {
+        key: "history.bvid",
+        header: "BV号",
+        value: |h, _| h.history.as_ref().and_then(|i| i.bvid.as_ref()).into(),
+    },
+    Column {
+        key: "history.oid",
+        header: "目标ID",
+        value: |h, _| h.history.as_ref().map(|i| i.oid).into(),
+    },
+    Column {
+        key: "history.business",
+        header: "类型",
+        value: |h, _| h.history.as_ref().and_then(|i| i.business.as_ref()).into(),
+    },
+    Column {
+        key: "author_mid",
+        header: "UP主ID",
+        value: |h, _| h.author_mid.into(),
+    },
+    Column {
+        key: "author_name",
+        header: "UP主",
+        value: |h, _| h.author_name.as_ref().into(),
+    },
+    Column {
+        key: "view_at",
+        header: "观看时间",
+        value: |h, ctx| ctx.time(h.view_at),
+    },
+    Column {
+        key: "progress",
+        header: "观看进度(秒)",
+        value: |h, _| h.progress.into(),
+    },
+    Column {
+        key: "duration",
+        header: "时长(秒)",
+        value: |h, _| h.duration.into(),
+    },
+    Column {
+        key: "show_title",
+        header: "分P标题",
+        value: |h, _| h.show_title.as_ref().into(),
+    },
+    Column {
+        key: "cover",
+        header: "封面",
+        value: |h, _| h.cover.as_ref().into(),
+    },
+];
+
+const BANGUMI_COLUMNS: &[Column<Bangumi>] = &[
+    Column {
+        key: "season_id",
+        header: "剧集ID",
+        value: |b, _| b.season_id.into(),
+    },
+    Column {
+        key: "media_id",
+        header: "媒体ID",
+        value: |b, _| b.media_id.into(),
+    },
+    Column {
+        key: "title",
+        header: "标题",
+        value: |b, _| (&b.title).into(),
+    },
+    Column {
+        key: "season_type_name",
+        header: "类型",
+        value: |b, _| b.season_type_name.as_ref().into(),
+    },
+    Column {
+        key: "total_count",
+        header: "总集数",
+        value: |b, _| b.total_count.into(),
+    },
+    Column {
+        key: "follow_status",
+        header: "追番状态",
+        value: |b, _| b.follow_status.into(),
+    },
+    Column {
+        key: "badge",
+        header: "徽章",
+        value: |b, _| b.badge.as_ref().into(),
+    },
+    Column {
+        key: "url",
+        header: "链接",
+        value: |b, _| b.url.as_ref().into(),
+    },
+    Column {
+        key: "cover",
+        header: "封面",
+        value: |b, _| (&b.cover).into(),
+    },
+];
+
+const TOVIEW_COLUMNS: &[Column<ToView>] = &[
+    Column {
+        key: "aid",
+        header: "视频ID",
+        value: |v, _| v.aid.into(),
+    },
+    Column {
+        key: "bvid",
+        header: "BV号",
+        value: |v, _| v.bvid.as_ref().into(),
+    },
+    Column {
+        key: "title",
+        header: "标题",
+        value: |v, _| (&v.title).into(),
+    },
+    Column {
+        key: "owner.mid",
+        header: "UP主ID",
+        value: |v, _| v.owner.as_ref().map(|u| u.mid).into(),
+    },
+    Column {
+        key: "owner.name",
+        header: "UP主",
+        value: |v, _| v.owner.as_ref().map(|u| u.name.as_str()).into(),
+    },
+    Column {
+        key: "add_at",
+        header: "添加时间",
+        value: |v, ctx| ctx.time(v.add_at),
+    },
+    Column {
+        key: "duration",
+        header: "时长(秒)",
+        value: |v, _| v.duration.into(),
+    },
+    Column {
+        key: "videos",
+        header: "分P数",
+        value: |v, _| v.videos.into(),
+    },
+    Column {
+        key: "pic",
+        header: "封面",
+        value: |v, _| (&v.pic).into(),
+    },
+];
+
+/// 列定义转换为列信息
+fn column_infos<T>(columns: &[Column<T>]) -> Vec<ColumnInfo> {
+    columns
+        .iter()
+        .map(|c| ColumnInfo {
+            key: c.key.to_string(),
+            header: c.header.to_string(),
+        })
+        .collect()
+}
+
+/// 模块可导出的列
+pub fn available_columns(module: BackupModule) -> Vec<ColumnInfo> {
+    match module {
+        BackupModule::RelationTags => column_infos(TAG_COLUMNS),
+        BackupModule::Following | BackupModule::Followers => column_infos(RELATION_COLUMNS),
+        BackupModule::Blacklist => column_infos(USER_COLUMNS),
+        BackupModule::Favorites => column_infos(MEDIA_COLUMNS),
+        BackupModule::History => column_infos(HISTORY_COLUMNS),
+        BackupModule::Bangumi => column_infos(BANGUMI_COLUMNS),
+        BackupModule::ToView => column_infos(TOVIEW_COLUMNS),
+    }
+}
+
+/// 按选项选出要导出的列
+fn select_columns<'a, T>(
+    columns: &'a [Column<T>],
+    selected: &[String],
+) -> Result<Vec<&'a Column<T>>> {
+    if selected.is_empty() {
+        return Ok(columns.iter().collect());
+    }
+
+    selected
+        .iter()
+        .map(|key| {
+            columns
+                .iter()
+                .find(|c| c.key == key)
+                .ok_or_else(|| BiliError::param(format!("未知的列: {}", key)))
+        })
+        .collect()
+}
+
+/// 表格构建器
+struct TableBuilder<'a, T: 'static> {
+    columns: Vec<&'static Column<T>>,
+    rows: Vec<Vec<Cell>>,
+    tags: &'a HashMap<i64, String>,
+    readable_time: bool,
+}
+
+impl<'a, T: 'static> TableBuilder<'a, T> {
+    fn new(
+        columns: &'static [Column<T>],
+        options: &TableOptions,
+        tags: &'a HashMap<i64, String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            columns: select_columns(columns, &options.columns)?,
+            rows: Vec::new(),
+            tags,
+            readable_time: options.readable_time,
+        })
+    }
+
+    fn push_all(&mut self, items: &[T], folder: Option<&FavInfo>) {
+        let ctx = Context {
+            tags: self.tags,
+            folder,
+            readable_time: self.readable_time,
+        };
+        for item in items {
+            let row = self
+                .columns
+                .iter()
+                .map(|column| (column.value)(item, &ctx))
+                .collect();
+            self.rows.push(row);
+        }
+    }
+
+    fn finish(self) -> Table {
+        Table {
+            columns: self
+                .columns
+                .iter()
+                .map(|c| ColumnInfo {
+                    key: c.key.to_string(),
+                    header: c.header.to_string(),
+                })
+                .collect(),
+            rows: self.rows,
+        }
+    }
+}
+
+/// 构建模块的表格
+///
+/// 收藏夹按每个收藏项一行展开,并带上所属收藏夹的ID和标题;
+/// 关注列表的分组ID会转换为分组名称 (需要同时包含关注分组模块)。
+///
+/// # 参数
+///
+/// * `data` - 备份数据
+/// * `module` - 要导出的模块
+/// * `options` - 导出选项
+///
+/// # 错误
+///
+/// - 备份数据不包含该模块
+/// - 选择了不存在的列
+pub fn build_table(
+    data: &BackupData,
+    module: BackupModule,
+    options: &TableOptions,
+) -> Result<Table> {
+    if data.count(module).is_none() {
+        return Err(BiliError::param(format!(
+            "备份数据中没有{}",
+            module.display_name()
+        )));
+    }
+
+    let tags: HashMap<i64, String> = data
+        .relation_tags
+        .iter()
+        .flatten()
+        .map(|t| (t.tag_id, t.name.clone()))
+        .collect();
+
+    fn simple<T: 'static>(
+        columns: &'static [Column<T>],
+        items: &[T],
+        options: &TableOptions,
+        tags: &HashMap<i64, String>,
+    ) -> Result<Table> {
+        let mut builder = TableBuilder::new(columns, options, tags)?;
+        builder.push_all(items, None);
+        Ok(builder.finish())
+    }
+
+    match module {
+        BackupModule::RelationTags => simple(
+            TAG_COLUMNS,
+            data.relation_tags.as_deref().unwrap_or_default(),
+            options,
+            &tags,
+        ),
+        BackupModule::Following => simple(
+            RELATION_COLUMNS,
+            data.following.as_deref().unwrap_or_default(),
+            options,
+            &tags,
+        ),
+        BackupModule::Followers => simple(
+            RELATION_COLUMNS,
+            data.followers.as_deref().unwrap_or_default(),
+            options,
+            &tags,
+        ),
+        BackupModule::Blacklist => simple(
+            USER_COLUMNS,
+            data.blacklist.as_deref().unwrap_or_default(),
+            options,
+            &tags,
+        ),
+        BackupModule::Favorites => {
+            let mut builder = TableBuilder::new(MEDIA_COLUMNS, options, &tags)?;
+            for folder in data.favorites.iter().flatten() {
+                builder.push_all(&folder.media_list, Some(&folder.folder));
+            }
+            Ok(builder.finish())
+        }
+        BackupModule::History => simple(
+            HISTORY_COLUMNS,
+            data.history.as_deref().unwrap_or_default(),
+            options,
+            &tags,
+        ),
+        BackupModule::Bangumi => simple(
+            BANGUMI_COLUMNS,
+            data.bangumi.as_deref().unwrap_or_default(),
+            options,
+            &tags,
+        ),
+        BackupModule::ToView => simple(
+            TOVIEW_COLUMNS,
+            data.toview.as_deref().unwrap_or_default(),
+            options,
+            &tags,
+        ),
+    }
+}
+
+/// 导出模块数据为表格文件
+///
+/// # 参数
+///
+/// * `data` - 备份数据
+/// * `module` - 要导出的模块
+/// * `path` - 导出文件路径
+/// * `options` - 导出选项
+///
+/// # 返回
+///
+/// 导出的行数
+pub async fn export_table(
+    data: &BackupData,
+    module: BackupModule,
+    path: impl AsRef<Path>,
+    options: &TableOptions,
+) -> Result<usize> {
+    let table = build_table(data, module, options)?;
+    let rows = table.rows.len();
+    let path = path.as_ref().to_path_buf();
+    let format = options.format;
+
+    run_blocking(move || {
+        let bytes = match format {
+            TableFormat::Csv => table.to_csv()?,
+            TableFormat::Xlsx => table.to_xlsx(module.display_name())?,
+        };
+        write_file_atomic(&path, &bytes)?;
+        tracing::info!(
+            "{}已导出为表格: {} ({} 行)",
+            module.display_name(),
+            path.display(),
+            rows
+        );
+        Ok(rows)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::favorites::FavFolderWithMedia;
+    use serde_json::json;
+
+    fn sample_data() -> BackupData {
+        BackupData {
+            relation_tags: Some(vec![RelationTag::new(1, "特别关注".to_string())]),
+            following: Some(vec![serde_json::from_value(json!({
+                "mid": 42,
+                "uname": "UP主, \"测试\"",
+                "face": "",
+                "mtime": 1700000000,
+                "special": 1,
+                "tag": [1, 9],
+                "vip": {"vipType": 2}
+            }))
+            .unwrap()]),
+            favorites: Some(vec![FavFolderWithMedia {
+                folder: serde_json::from_value(json!({
+                    "id": 7, "mid": 42, "attr": 0, "title": "默认收藏夹", "mediaCount": 1
+                }))
+                .unwrap(),
+                intro: None,
+                media_list: vec![serde_json::from_value(json!({
+                    "id": 100, "type": 2, "title": "视频",
+                    "upper": {"mid": 1, "name": "作者", "face": ""}
+                }))
+                .unwrap()],
+            }]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_following_table_flattens_fields() {
+        let options = TableOptions {
+            columns: vec!["uname".into(), "tags".into(), "vip.vip_type".into()],
+            ..Default::default()
+        };
+        let table = build_table(&sample_data(), BackupModule::Following, &options).unwrap();
+        assert_eq!(table.columns[2].header, "会员类型");
+        assert_eq!(
+            table.rows[0],
+            vec![
+                Cell::Text("UP主, \"测试\"".into()),
+                Cell::Text("特别关注; 9".into()),
+                Cell::Number(2),
+            ]
+        );
+
+        let options = TableOptions {
+            columns: vec!["nope".into()],
+            ..Default::default()
+        };
+        assert!(build_table(&sample_data(), BackupModule::Following, &options).is_err());
+        assert!(build_table(&sample_data(), BackupModule::History, &options).is_err());
+    }
+
+    #[test]
+    fn test_readable_time() {
+        let options = TableOptions {
+            columns: vec!["mtime".into()],
+            ..Default::default()
+        };
+        let table = build_table(&sample_data(), BackupModule::Following, &options).unwrap();
+        let expected = Local
+            .timestamp_opt(1700000000, 0)
+            .unwrap()
+            .format(TIME_FORMAT)
+            .to_string();
+        assert_eq!(table.rows[0][0], Cell::Text(expected));
+
+        let options = TableOptions {
+            readable_time: false,
+            ..options
+        };
+        let table = build_table(&sample_data(), BackupModule::Following, &options).unwrap();
+        assert_eq!(table.rows[0][0], Cell::Number(1700000000));
+    }
+
+    #[test]
+    fn test_favorites_one_row_per_media() {
+        let options = TableOptions {
+            columns: vec!["folder.title".into(), "title".into(), "upper.name".into()],
+            ..Default::default()
+        };
+        let table = build_table(&sample_data(), BackupModule::Favorites, &options).unwrap();
+        let csv = String::from_utf8(table.to_csv().unwrap()).unwrap();
+        assert!(csv.starts_with('\u{feff}'));
+        assert_eq!(
+            csv.trim_start_matches('\u{feff}'),
+            "收藏夹,标题,UP主\n默认收藏夹,视频,作者\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_escapes_values() {
+        let options = TableOptions {
+            columns: vec!["uname".into(), "special".into()],
+            ..Default::default()
+        };
+        let table = build_table(&sample_data(), BackupModule::Following, &options).unwrap();
+        let csv = String::from_utf8(table.to_csv().unwrap()).unwrap();
+        assert!(csv.ends_with("\"UP主, \"\"测试\"\"\",是\n"));
+    }
+
+    #[cfg(feature = "xlsx")]
+    #[test]
+    fn test_xlsx_output() {
+        let table = build_table(
+            &sample_data(),
+            BackupModule::Following,
+            &TableOptions::default(),
+        )
+        .unwrap();
+        let bytes = table.to_xlsx("关注列表").unwrap();
+        assert!(bytes.starts_with(b"PK"));
+    }
+}
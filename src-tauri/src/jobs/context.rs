@@ -0,0 +1,174 @@
+//! 当前任务上下文
+//!
+//! 任务在 [`tokio::task_local!`] 作用域中运行,服务层无需传递任何参数即可
+//! 检查暂停和取消、报告进度和日志。在任务之外调用时这些函数不做任何事。
+
+use super::control::JobControl;
+use super::manager::JobInfo;
+use crate::api::client::BiliClient;
+use crate::api::error::Result;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+tokio::task_local! {
+    static CURRENT: Arc<JobContext>;
+}
+
+/// 任务进度
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobProgress {
+    /// 任务ID
+    pub job_id: u64,
+    /// 已处理数量
+    pub current: usize,
+    /// 总数 (未知时为None)
+    pub total: Option<usize>,
+    /// 当前处理的项目
+    pub label: String,
+}
+
+/// 任务日志级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobLogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+/// 任务日志
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobLog {
+    /// 任务ID
+    pub job_id: u64,
+    /// 日志级别
+    pub level: JobLogLevel,
+    /// 日志内容
+    pub message: String,
+    /// 记录时间 (Unix时间戳, 秒)
+    pub time: i64,
+}
+
+/// 任务事件接收者
+///
+/// 任务模块不依赖Tauri,由命令层实现该接口把事件转发给前端。
+pub trait JobEvents: Send + Sync {
+    /// 进度更新
+    fn progress(&self, progress: &JobProgress);
+    /// 日志
+    fn log(&self, log: &JobLog);
+    /// 任务结束 (完成、失败或取消)
+    fn finished(&self, job: &JobInfo);
+}
+
+/// 运行中任务的上下文
+pub struct JobContext {
+    id: u64,
+    control: JobControl,
+    events: Arc<dyn JobEvents>,
+    progress: Mutex<Option<JobProgress>>,
+}
+
+impl JobContext {
+    pub(crate) fn new(id: u64, events: Arc<dyn JobEvents>) -> Self {
+        Self {
+            id,
+            control: JobControl::new(),
+            events,
+            progress: Mutex::new(None),
+        }
+    }
+
+    /// 任务ID
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// 控制句柄
+    pub fn control(&self) -> &JobControl {
+        &self.control
+    }
+
+    /// 最近一次报告的进度
+    pub fn last_progress(&self) -> Option<JobProgress> {
+        self.progress.lock().unwrap().clone()
+    }
+
+    pub(crate) fn events(&self) -> &Arc<dyn JobEvents> {
+        &self.events
+    }
+
+    fn report_progress(&self, current: usize, total: Option<usize>, label: String) {
+        let progress = JobProgress {
+            job_id: self.id,
+            current,
+            total,
+            label,
+        };
+        self.events.progress(&progress);
+        *self.progress.lock().unwrap() = Some(progress);
+    }
+
+    fn report_log(&self, level: JobLogLevel, message: String) {
+        self.events.log(&JobLog {
+            job_id: self.id,
+            level,
+            message,
+            time: chrono::Local::now().timestamp(),
+        });
+    }
+}
+
+/// 在任务上下文中运行
+pub(crate) async fn run<F: Future>(context: Arc<JobContext>, future: F) -> F::Output {
+    CURRENT.scope(context, future).await
+}
+
+/// 检查点: 响应当前任务的暂停和取消
+///
+/// 服务层的循环在处理每一项之前调用。不在任务中时直接返回。
+///
+/// # 错误
+///
+/// - 当前任务已取消 ([`BiliError::Cancelled`](crate::api::BiliError::Cancelled))
+pub async fn checkpoint() -> Result<()> {
+    match CURRENT.try_with(|context| context.control.clone()) {
+        Ok(control) => control.checkpoint().await,
+        Err(_) => Ok(()),
+    }
+}
+
+/// 报告当前任务的进度
+///
+/// # 参数
+///
+/// * `current` - 已处理数量
+/// * `total` - 总数 (未知时为None)
+/// * `label` - 当前处理的项目
+pub fn progress(current: usize, total: Option<usize>, label: impl Into<String>) {
+    let _ = CURRENT.try_with(|context| context.report_progress(current, total, label.into()));
+}
+
+/// 记录当前任务的日志
+pub fn log(level: JobLogLevel, message: impl Into<String>) {
+    let _ = CURRENT.try_with(|context| context.report_log(level, message.into()));
+}
+
+/// 为客户端注入当前任务的检查点
+///
+/// 分页模块在请求每一页之前调用客户端的翻页钩子,注入后长列表的翻页
+/// (包括并发翻页的工作者) 也能响应暂停和取消。不在任务中时原样返回。
+///
+/// # 参数
+///
+/// * `client` - 服务持有的客户端的副本
+pub fn bind_client(client: BiliClient) -> BiliClient {
+    match CURRENT.try_with(|context| context.control.clone()) {
+        Ok(control) => client.with_page_hook(Arc::new(move || {
+            let control = control.clone();
+            Box::pin(async move { control.checkpoint().await })
+        })),
+        Err(_) => client,
+    }
+}
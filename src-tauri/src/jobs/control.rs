@@ -0,0 +1,155 @@
+//! 任务控制: 暂停、继续与取消
+
+use crate::api::error::{BiliError, Result};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// 控制信号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Signal {
+    Run,
+    Pause,
+    Cancel,
+}
+
+/// 任务控制句柄
+///
+/// 克隆的句柄共享同一个状态。任务执行体在循环中调用 [`JobControl::checkpoint`]:
+/// 暂停时在检查点等待,直到继续或取消;取消后检查点返回 [`BiliError::Cancelled`]。
+#[derive(Debug, Clone)]
+pub struct JobControl {
+    signal: Arc<watch::Sender<Signal>>,
+}
+
+impl Default for JobControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobControl {
+    /// 创建处于运行状态的控制句柄
+    pub fn new() -> Self {
+        let (signal, _) = watch::channel(Signal::Run);
+        Self {
+            signal: Arc::new(signal),
+        }
+    }
+
+    /// 暂停 (仅运行中有效)
+    ///
+    /// # 返回
+    ///
+    /// 状态是否发生变化
+    pub fn pause(&self) -> bool {
+        self.transition(Signal::Run, Signal::Pause)
+    }
+
+    /// 继续 (仅暂停中有效)
+    ///
+    /// # 返回
+    ///
+    /// 状态是否发生变化
+    pub fn resume(&self) -> bool {
+        self.transition(Signal::Pause, Signal::Run)
+    }
+
+    /// 取消,暂停中的任务会立即从检查点返回
+    ///
+    /// # 返回
+    ///
+    /// 状态是否发生变化
+    pub fn cancel(&self) -> bool {
+        self.signal.send_if_modified(|signal| {
+            let changed = *signal != Signal::Cancel;
+            *signal = Signal::Cancel;
+            changed
+        })
+    }
+
+    /// 是否已暂停
+    pub fn is_paused(&self) -> bool {
+        *self.signal.borrow() == Signal::Pause
+    }
+
+    /// 是否已取消
+    pub fn is_cancelled(&self) -> bool {
+        *self.signal.borrow() == Signal::Cancel
+    }
+
+    /// 检查点
+    ///
+    /// 运行中直接返回;暂停中等待继续或取消。
+    ///
+    /// # 错误
+    ///
+    /// - 任务已取消
+    pub async fn checkpoint(&self) -> Result<()> {
+        let mut receiver = self.signal.subscribe();
+        loop {
+            let signal = *receiver.borrow_and_update();
+            match signal {
+                Signal::Run => return Ok(()),
+                Signal::Cancel => return Err(BiliError::Cancelled),
+                Signal::Pause => {}
+            }
+            if receiver.changed().await.is_err() {
+                return Err(BiliError::Cancelled);
+            }
+        }
+    }
+
+    fn transition(&self, from: Signal, to: Signal) -> bool {
+        self.signal.send_if_modified(|signal| {
+            if *signal == from {
+                *signal = to;
+                true
+            } else {
+                false
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_pause_resume() {
+        let control = JobControl::new();
+        assert!(control.checkpoint().await.is_ok());
+
+        assert!(control.pause());
+        assert!(!control.pause());
+        assert!(control.is_paused());
+
+        let waiting = tokio::spawn({
+            let control = control.clone();
+            async move { control.checkpoint().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        assert!(control.resume());
+        assert!(waiting.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_while_paused() {
+        let control = JobControl::new();
+        control.pause();
+        let waiting = tokio::spawn({
+            let control = control.clone();
+            async move { control.checkpoint().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert!(control.cancel());
+        assert!(waiting.await.unwrap().unwrap_err().is_cancelled());
+        // 取消后不能再继续
+        assert!(!control.resume());
+        assert!(control.is_cancelled());
+    }
+}
@@ -0,0 +1,480 @@
+//! 操作日志
+//!
+//! 会修改账号数据的任务 (还原、迁移) 把每一步写操作的动作、目标ID和结果追加到日志文件中。
+//! 应用崩溃或被关闭后恢复任务时,日志中已成功的步骤会被跳过,
+//! 避免重复关注、重复收藏浪费请求额度并产生大量重复错误。
+//!
+//! 日志是JSON Lines文件: 第一行为头部 (任务类型、标题和请求),之后每行一个步骤,
+//! 任务失败或取消时追加结束记录,任务完成后删除日志。
+//! 每条记录写入后立即同步到磁盘,崩溃时最多丢失正在写入的一行,读取时忽略不完整的行。
+//!
+//! 服务层通过 [`completed`] 和 [`record`] 使用当前作用域中的日志,不在日志作用域中时不做任何事。
+
+use super::manager::JobStatus;
+use super::request::JobRequest;
+use crate::api::error::{BiliError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::future::Future;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// 日志格式标识
+pub const JOURNAL_FORMAT: &str = "bili-journal";
+
+/// 日志格式版本
+pub const JOURNAL_VERSION: u32 = 1;
+
+/// 日志文件所在的子目录
+const JOURNAL_DIR: &str = "jobs";
+
+tokio::task_local! {
+    static CURRENT: Arc<Journal>;
+}
+
+/// 日志头部
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalHeader {
+    /// 格式标识
+    pub format: String,
+    /// 格式版本
+    pub version: u32,
+    /// 日志ID
+    pub id: String,
+    /// 任务类型
+    pub kind: String,
+    /// 任务标题
+    pub title: String,
+    /// 任务请求 (不含口令,为空时无法从日志恢复)
+    pub request: Option<JobRequest>,
+    /// 恢复时是否需要重新提供口令
+    #[serde(default)]
+    pub needs_passphrase: bool,
+    /// 创建时间 (Unix时间戳, 秒)
+    pub created_at: i64,
+}
+
+impl JournalHeader {
+    /// 创建日志头部
+    pub fn new(id: impl Into<String>, kind: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            format: JOURNAL_FORMAT.to_string(),
+            version: JOURNAL_VERSION,
+            id: id.into(),
+            kind: kind.into(),
+            title: title.into(),
+            request: None,
+            needs_passphrase: false,
+            created_at: chrono::Local::now().timestamp(),
+        }
+    }
+}
+
+/// 单个写操作步骤
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalStep {
+    /// 动作 (如 `follow`、`create_folder`)
+    pub action: String,
+    /// 目标ID
+    pub target: String,
+    /// 是否成功
+    pub ok: bool,
+    /// 操作的返回值 (如新建收藏夹的ID)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// 失败原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 记录时间 (Unix时间戳, 秒)
+    pub time: i64,
+}
+
+/// 日志中的一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum JournalRecord {
+    Header(JournalHeader),
+    Step(JournalStep),
+    Finished { status: JobStatus, time: i64 },
+}
+
+/// 日志概要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalSummary {
+    /// 日志头部
+    pub header: JournalHeader,
+    /// 成功的步骤数
+    pub completed_steps: usize,
+    /// 失败的步骤数 (恢复时会重试)
+    pub failed_steps: usize,
+    /// 最后一条记录的时间
+    pub updated_at: i64,
+    /// 上次运行的结束状态 (进程中断时为None)
+    pub finished: Option<JobStatus>,
+}
+
+/// 操作日志
+pub struct Journal {
+    path: PathBuf,
+    id: String,
+    file: Mutex<File>,
+    completed: Mutex<HashMap<(String, String), Value>>,
+}
+
+impl Journal {
+    /// 创建新的日志文件并写入头部
+    ///
+    /// # 错误
+    ///
+    /// - 文件已存在或无法创建
+    pub fn create(path: impl Into<PathBuf>, header: JournalHeader) -> Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| BiliError::io(format!("创建日志目录失败: {}", e)))?;
+        }
+        let file = OpenOptions::new()
+            .append(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| BiliError::io(format!("创建操作日志失败: {}", e)))?;
+        let journal = Self {
+            id: header.id.clone(),
+            path,
+            file: Mutex::new(file),
+            completed: Mutex::new(HashMap::new()),
+        };
+        journal.append(&JournalRecord::Header(header))?;
+        Ok(journal)
+    }
+
+    /// 打开已有的日志,继续追加记录
+    ///
+    /// # 返回
+    ///
+    /// 日志和日志概要
+    ///
+    /// # 错误
+    ///
+    /// - 文件无法读取或头部无效
+    pub fn open(path: impl Into<PathBuf>) -> Result<(Self, JournalSummary)> {
+        let path = path.into();
+        let (summary, steps) = read_journal(&path)?;
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| BiliError::io(format!("打开操作日志失败: {}", e)))?;
+        // 上次崩溃时可能留下不完整的行,先补上换行,避免与新记录连在一起
+        if file.metadata()?.len() > 0 {
+            let mut last = [0u8; 1];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                file.write_all(b"\n")?;
+            }
+        }
+
+        let completed = steps
+            .into_iter()
+            .filter(|step| step.ok)
+            .map(|step| {
+                (
+                    (step.action, step.target),
+                    step.result.unwrap_or(Value::Null),
+                )
+            })
+            .collect();
+        let journal = Self {
+            id: summary.header.id.clone(),
+            path,
+            file: Mutex::new(file),
+            completed: Mutex::new(completed),
+        };
+        Ok((journal, summary))
+    }
+
+    /// 日志ID
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// 日志文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 查询已成功的步骤
+    ///
+    /// # 返回
+    ///
+    /// 步骤已成功时返回其结果 (没有结果时为 `Value::Null`),否则返回None
+    pub fn completed(&self, action: &str, target: &str) -> Option<Value> {
+        self.completed
+            .lock()
+            .unwrap()
+            .get(&(action.to_string(), target.to_string()))
+            .cloned()
+    }
+
+    /// 记录一个步骤
+    pub fn record(&self, step: JournalStep) -> Result<()> {
+        self.append(&JournalRecord::Step(step.clone()))?;
+        if step.ok {
+            self.completed.lock().unwrap().insert(
+                (step.action, step.target),
+                step.result.unwrap_or(Value::Null),
+            );
+        }
+        Ok(())
+    }
+
+    /// 记录任务的结束状态
+    ///
+    /// 任务完成时删除日志 (不再需要恢复),失败或取消时追加结束记录并保留日志。
+    pub fn finish(&self, status: JobStatus) -> Result<()> {
+        if status == JobStatus::Completed {
+            return match std::fs::remove_file(&self.path) {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(BiliError::io(format!("删除操作日志失败: {}", e))),
+            };
+        }
+        self.append(&JournalRecord::Finished {
+            status,
+            time: chrono::Local::now().timestamp(),
+        })
+    }
+
+    fn append(&self, record: &JournalRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let mut file = self.file.lock().unwrap();
+        file.write_all(&line)
+            .and_then(|_| file.sync_data())
+            .map_err(|e| BiliError::io(format!("写入操作日志失败: {}", e)))
+    }
+}
+
+/// 日志目录
+pub fn journal_dir(data_dir: impl AsRef<Path>) -> PathBuf {
+    data_dir.as_ref().join(JOURNAL_DIR)
+}
+
+/// 日志文件路径
+///
+/// # 错误
+///
+/// - 日志ID包含字母、数字和 `-` 以外的字符
+pub fn journal_path(data_dir: impl AsRef<Path>, id: &str) -> Result<PathBuf> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(BiliError::param(format!("无效的日志ID: {}", id)));
+    }
+    Ok(journal_dir(data_dir).join(format!("{}.jsonl", id)))
+}
+
+/// 读取日志
+///
+/// 无法解析的行 (崩溃时写了一半的记录) 会被忽略。
+///
+/// # 错误
+///
+/// - 文件无法读取
+/// - 第一行不是日志头部,或格式版本比当前程序更新
+fn read_journal(path: &Path) -> Result<(JournalSummary, Vec<JournalStep>)> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| BiliError::io(format!("读取操作日志失败: {}", e)))?;
+    let mut lines = content.lines();
+
+    let header = match lines.next().map(serde_json::from_str::<JournalRecord>) {
+        Some(Ok(JournalRecord::Header(header))) if header.format == JOURNAL_FORMAT => header,
+        _ => return Err(BiliError::parse("不是有效的操作日志")),
+    };
+    if header.version > JOURNAL_VERSION {
+        return Err(BiliError::parse(format!(
+            "操作日志版本 {} 高于当前支持的版本 {}",
+            header.version, JOURNAL_VERSION
+        )));
+    }
+
+    let mut summary = JournalSummary {
+        updated_at: header.created_at,
+        header,
+        completed_steps: 0,
+        failed_steps: 0,
+        finished: None,
+    };
+    let mut steps = Vec::new();
+    for (index, line) in lines.enumerate() {
+        match serde_json::from_str::<JournalRecord>(line) {
+            Ok(JournalRecord::Step(step)) => {
+                summary.updated_at = step.time;
+                summary.finished = None;
+                if step.ok {
+                    summary.completed_steps += 1;
+                } else {
+                    summary.failed_steps += 1;
+                }
+                steps.push(step);
+            }
+            Ok(JournalRecord::Finished { status, time }) => {
+                summary.updated_at = time;
+                summary.finished = Some(status);
+            }
+            Ok(JournalRecord::Header(_)) | Err(_) => {
+                tracing::warn!("忽略操作日志 {:?} 第 {} 行", path, index + 2);
+            }
+        }
+    }
+    Ok((summary, steps))
+}
+
+/// 列出目录中的所有日志 (按创建时间排序)
+///
+/// 无法读取的日志会被跳过。
+pub fn list_journals(dir: impl AsRef<Path>) -> Result<Vec<JournalSummary>> {
+    let entries = match std::fs::read_dir(dir.as_ref()) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(BiliError::io(format!("读取日志目录失败: {}", e))),
+    };
+
+    let mut journals = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("jsonl") {
+            continue;
+        }
+        match read_journal(&path) {
+            Ok((summary, _)) => journals.push(summary),
+            Err(e) => tracing::warn!("跳过操作日志 {:?}: {}", path, e),
+        }
+    }
+    journals.sort_by_key(|j| (j.header.created_at, j.header.id.clone()));
+    Ok(journals)
+}
+
+/// 在日志作用域中运行
+pub async fn scope<F: Future>(journal: Arc<Journal>, future: F) -> F::Output {
+    CURRENT.scope(journal, future).await
+}
+
+/// 查询当前日志中已成功的步骤
+///
+/// 不在日志作用域中时返回None。
+///
+/// # 返回
+///
+/// 步骤已成功时返回其结果 (没有结果时为 `Value::Null`)
+pub fn completed(action: &str, target: impl Display) -> Option<Value> {
+    CURRENT
+        .try_with(|journal| journal.completed(action, &target.to_string()))
+        .ok()
+        .flatten()
+}
+
+/// 把一个写操作的结果记录到当前日志
+///
+/// 成功时记录返回值 (`()` 记为空),失败时记录错误信息。
+/// 不在日志作用域中时不做任何事;写入失败只记录警告,不影响操作本身。
+pub fn record<T: Serialize, E: Display>(
+    action: &str,
+    target: impl Display,
+    result: &std::result::Result<T, E>,
+) {
+    let _ = CURRENT.try_with(|journal| {
+        let (ok, result, error) = match result {
+            Ok(value) => (
+                true,
+                serde_json::to_value(value).ok().filter(|v| !v.is_null()),
+                None,
+            ),
+            Err(e) => (false, None, Some(e.to_string())),
+        };
+        let step = JournalStep {
+            action: action.to_string(),
+            target: target.to_string(),
+            ok,
+            result,
+            error,
+            time: chrono::Local::now().timestamp(),
+        };
+        if let Err(e) = journal.record(step) {
+            tracing::warn!("{}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_and_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = journal_path(dir.path(), "job-1").unwrap();
+        assert!(journal_path(dir.path(), "../job-1").is_err());
+        let journal = Arc::new(
+            Journal::create(&path, JournalHeader::new("job-1", "restore", "还原")).unwrap(),
+        );
+
+        scope(journal.clone(), async {
+            record("follow", 1, &Ok::<_, BiliError>(()));
+            record("follow", 2, &Err::<(), _>(BiliError::api("关注失败")));
+            record("create_folder", "10:0", &Ok::<_, BiliError>(99_i64));
+            assert_eq!(completed("follow", 1), Some(Value::Null));
+            assert_eq!(completed("follow", 2), None);
+        })
+        .await;
+        // 不在作用域中时不记录
+        record("follow", 3, &Ok::<_, BiliError>(()));
+        journal.finish(JobStatus::Cancelled).unwrap();
+        drop(journal);
+
+        // 模拟崩溃时写了一半的行
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(br#"{"record":"step","act"#)
+            .unwrap();
+
+        let (journal, summary) = Journal::open(&path).unwrap();
+        assert_eq!(summary.completed_steps, 2);
+        assert_eq!(summary.failed_steps, 1);
+        assert_eq!(summary.finished, Some(JobStatus::Cancelled));
+        assert_eq!(
+            journal.completed("create_folder", "10:0"),
+            Some(Value::from(99))
+        );
+        assert_eq!(journal.completed("follow", "3"), None);
+
+        // 继续追加后,上次的结束状态被清除
+        let journal = Arc::new(journal);
+        scope(journal.clone(), async {
+            record("follow", 2, &Ok::<_, BiliError>(()));
+        })
+        .await;
+        let journals = list_journals(journal_dir(dir.path())).unwrap();
+        assert_eq!(journals.len(), 1);
+        assert_eq!(journals[0].completed_steps, 3);
+        assert_eq!(journals[0].finished, None);
+
+        journal.finish(JobStatus::Completed).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_open_invalid_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.jsonl");
+        std::fs::write(&path, "{}\n").unwrap();
+        assert!(Journal::open(&path).is_err());
+        assert!(list_journals(dir.path()).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,520 @@
+//! 任务管理器
+//!
+//! 生成任务、记录任务状态,并转发暂停、继续和取消请求。
+//! 已结束的任务保留在列表中,直到应用退出。
+//! 还原类任务在应用数据目录中记录操作日志,应用重启后可以从日志恢复。
+
+use super::context::{self, JobContext, JobEvents, JobProgress};
+use super::journal::{self, Journal, JournalHeader, JournalSummary};
+use super::request::JobRequest;
+use crate::api::{
+    client::BiliClient,
+    error::{BiliError, Result},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// 运行中
+    Running,
+    /// 已暂停 (在下一个检查点停下)
+    Paused,
+    /// 已完成
+    Completed,
+    /// 失败
+    Failed,
+    /// 已取消
+    Cancelled,
+}
+
+impl JobStatus {
+    /// 是否已结束
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// 任务信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobInfo {
+    /// 任务ID
+    pub id: u64,
+    /// 任务类型 (见 [`JobRequest::kind`])
+    pub kind: String,
+    /// 任务标题
+    pub title: String,
+    /// 状态
+    pub status: JobStatus,
+    /// 最近一次报告的进度
+    pub progress: Option<JobProgress>,
+    /// 创建时间 (Unix时间戳, 秒)
+    pub created_at: i64,
+    /// 结束时间
+    pub finished_at: Option<i64>,
+    /// 失败原因
+    pub error: Option<String>,
+    /// 任务结果 (取消的任务可能包含已完成部分的结果)
+    pub result: Option<Value>,
+    /// 操作日志ID (记录操作日志的任务才有)
+    pub journal_id: Option<String>,
+}
+
+struct JobEntry {
+    info: JobInfo,
+    context: Arc<JobContext>,
+}
+
+impl JobEntry {
+    fn snapshot(&self) -> JobInfo {
+        let mut info = self.info.clone();
+        if !info.status.is_finished() {
+            info.progress = self.context.last_progress();
+        }
+        info
+    }
+}
+
+/// 任务管理器
+///
+/// 克隆的管理器共享同一个任务列表。
+#[derive(Clone)]
+pub struct JobManager {
+    client: Arc<RwLock<BiliClient>>,
+    next_id: Arc<AtomicU64>,
+    jobs: Arc<Mutex<BTreeMap<u64, JobEntry>>>,
+}
+
+impl JobManager {
+    /// 创建任务管理器
+    ///
+    /// # 参数
+    ///
+    /// * `client` - 任务使用的HTTP客户端
+    pub fn new(client: Arc<RwLock<BiliClient>>) -> Self {
+        Self {
+            client,
+            next_id: Arc::new(AtomicU64::new(1)),
+            jobs: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    /// 启动任务
+    ///
+    /// 提供数据目录时,还原类任务 ([`JobRequest::is_resumable`]) 会记录操作日志。
+    ///
+    /// # 参数
+    ///
+    /// * `request` - 任务请求
+    /// * `data_dir` - 应用数据目录 (账号迁移和操作日志需要)
+    /// * `events` - 事件接收者
+    ///
+    /// # 返回
+    ///
+    /// 刚启动的任务信息
+    ///
+    /// # 错误
+    ///
+    /// - 无法创建操作日志
+    pub fn start(
+        &self,
+        request: JobRequest,
+        data_dir: Option<PathBuf>,
+        events: Arc<dyn JobEvents>,
+    ) -> Result<JobInfo> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let journal = match data_dir {
+            Some(ref data_dir) if request.is_resumable() => {
+                let journal_id = format!("{}-{}", chrono::Local::now().format("%Y%m%d%H%M%S"), id);
+                let (logged, needs_passphrase) = request.without_secrets();
+                let mut header = JournalHeader::new(&journal_id, request.kind(), request.title());
+                header.request = Some(logged);
+                header.needs_passphrase = needs_passphrase;
+                let path = journal::journal_path(data_dir, &journal_id)?;
+                Some(Arc::new(Journal::create(path, header)?))
+            }
+            _ => None,
+        };
+        Ok(self.launch(id, request, data_dir, events, journal))
+    }
+
+    /// 从操作日志恢复中断的任务
+    ///
+    /// 恢复的任务使用原来的请求,日志中已成功的步骤会被跳过。
+    ///
+    /// # 参数
+    ///
+    /// * `data_dir` - 应用数据目录
+    /// * `journal_id` - 操作日志ID
+    /// * `passphrase` - 口令 (原任务使用了口令时必须提供)
+    /// * `events` - 事件接收者
+    ///
+    /// # 错误
+    ///
+    /// - 日志不存在或无法读取
+    /// - 该日志的任务正在运行
+    /// - 日志中没有任务请求,或缺少口令
+    pub fn resume_journal(
+        &self,
+        data_dir: PathBuf,
+        journal_id: &str,
+        passphrase: Option<String>,
+        events: Arc<dyn JobEvents>,
+    ) -> Result<JobInfo> {
+        if self.is_journal_active(journal_id) {
+            return Err(BiliError::param("该任务正在运行"));
+        }
+        let (journal, summary) = Journal::open(journal::journal_path(&data_dir, journal_id)?)?;
+        let request = summary
+            .header
+            .request
+            .ok_or_else(|| BiliError::param("该任务无法恢复"))?;
+        if summary.header.needs_passphrase && passphrase.is_none() {
+            return Err(BiliError::param("恢复该任务需要提供口令"));
+        }
+        tracing::info!(
+            "从操作日志 {} 恢复任务，跳过 {} 个已完成的步骤",
+            journal_id,
+            summary.completed_steps
+        );
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        Ok(self.launch(
+            id,
+            request.with_passphrase(passphrase),
+            Some(data_dir),
+            events,
+            Some(Arc::new(journal)),
+        ))
+    }
+
+    /// 列出未完成的任务 (应用崩溃、被关闭、失败或取消的还原任务)
+    ///
+    /// 当前正在运行的任务不包括在内。
+    pub fn unfinished_journals(
+        &self,
+        data_dir: impl AsRef<std::path::Path>,
+    ) -> Result<Vec<JournalSummary>> {
+        let journals = journal::list_journals(journal::journal_dir(data_dir))?;
+        Ok(journals
+            .into_iter()
+            .filter(|summary| !self.is_journal_active(&summary.header.id))
+            .collect())
+    }
+
+    /// 放弃未完成的任务,删除其操作日志
+    ///
+    /// # 错误
+    ///
+    /// - 该日志的任务正在运行
+    /// - 删除失败
+    pub fn discard_journal(
+        &self,
+        data_dir: impl AsRef<std::path::Path>,
+        journal_id: &str,
+    ) -> Result<()> {
+        if self.is_journal_active(journal_id) {
+            return Err(BiliError::param("该任务正在运行"));
+        }
+        match std::fs::remove_file(journal::journal_path(data_dir, journal_id)?) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(BiliError::io(format!("删除操作日志失败: {}", e))),
+        }
+    }
+
+    /// 在后台运行任意任务
+    ///
+    /// 任务体在任务上下文中运行,其中的检查点响应该任务的暂停和取消。
+    /// 必须在Tokio运行时中调用。
+    pub fn spawn<F>(
+        &self,
+        kind: impl Into<String>,
+        title: impl Into<String>,
+        events: Arc<dyn JobEvents>,
+        future: F,
+    ) -> JobInfo
+    where
+        F: Future<Output = Result<Value>> + Send + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.spawn_with(id, kind.into(), title.into(), events, None, future)
+    }
+
+    /// 运行任务请求 (有操作日志时在日志作用域中运行)
+    fn launch(
+        &self,
+        id: u64,
+        request: JobRequest,
+        data_dir: Option<PathBuf>,
+        events: Arc<dyn JobEvents>,
+        journal: Option<Arc<Journal>>,
+    ) -> JobInfo {
+        let kind = request.kind().to_string();
+        let title = request.title();
+        let client = self.client.clone();
+        let scoped = journal.clone();
+        self.spawn_with(id, kind, title, events, journal, async move {
+            let future = request.execute(client, data_dir);
+            match scoped {
+                Some(journal) => journal::scope(journal, future).await,
+                None => future.await,
+            }
+        })
+    }
+
+    fn spawn_with<F>(
+        &self,
+        id: u64,
+        kind: String,
+        title: String,
+        events: Arc<dyn JobEvents>,
+        journal: Option<Arc<Journal>>,
+        future: F,
+    ) -> JobInfo
+    where
+        F: Future<Output = Result<Value>> + Send + 'static,
+    {
+        let context = Arc::new(JobContext::new(id, events));
+        let info = JobInfo {
+            id,
+            kind,
+            title,
+            status: JobStatus::Running,
+            progress: None,
+            created_at: chrono::Local::now().timestamp(),
+            finished_at: None,
+            error: None,
+            result: None,
+            journal_id: journal.as_ref().map(|journal| journal.id().to_string()),
+        };
+        self.jobs.lock().unwrap().insert(
+            id,
+            JobEntry {
+                info: info.clone(),
+                context: context.clone(),
+            },
+        );
+        tracing::info!("任务 {} 开始: {}", id, info.title);
+
+        let jobs = self.jobs.clone();
+        tokio::spawn(async move {
+            let result = context::run(context.clone(), future).await;
+            let finished = {
+                let mut jobs = jobs.lock().unwrap();
+                let Some(entry) = jobs.get_mut(&id) else {
+                    return;
+                };
+                finish(&mut entry.info, &entry.context, result);
+                entry.info.clone()
+            };
+            tracing::info!("任务 {} 结束: {:?}", id, finished.status);
+            if let Some(journal) = journal {
+                if let Err(e) = journal.finish(finished.status) {
+                    tracing::warn!("{}", e);
+                }
+            }
+            context.events().finished(&finished);
+        });
+        info
+    }
+
+    /// 暂停任务
+    ///
+    /// # 错误
+    ///
+    /// - 任务不存在或已结束
+    pub fn pause(&self, id: u64) -> Result<JobInfo> {
+        self.update(id, |entry| {
+            entry.context.control().pause();
+            if !entry.context.control().is_cancelled() {
+                entry.info.status = JobStatus::Paused;
+            }
+        })
+    }
+
+    /// 继续已暂停的任务
+    ///
+    /// # 错误
+    ///
+    /// - 任务不存在或已结束
+    pub fn resume(&self, id: u64) -> Result<JobInfo> {
+        self.update(id, |entry| {
+            if entry.context.control().resume() {
+                entry.info.status = JobStatus::Running;
+            }
+        })
+    }
+
+    /// 取消任务
+    ///
+    /// 任务在下一个检查点停止,状态在任务结束时变为已取消。
+    ///
+    /// # 错误
+    ///
+    /// - 任务不存在或已结束
+    pub fn cancel(&self, id: u64) -> Result<JobInfo> {
+        self.update(id, |entry| {
+            entry.context.control().cancel();
+        })
+    }
+
+    /// 获取任务信息
+    pub fn get(&self, id: u64) -> Option<JobInfo> {
+        self.jobs.lock().unwrap().get(&id).map(JobEntry::snapshot)
+    }
+
+    /// 列出所有任务 (按创建顺序)
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .values()
+            .map(JobEntry::snapshot)
+            .collect()
+    }
+
+    /// 是否有未结束的任务正在使用该操作日志
+    fn is_journal_active(&self, journal_id: &str) -> bool {
+        self.jobs.lock().unwrap().values().any(|entry| {
+            !entry.info.status.is_finished() && entry.info.journal_id.as_deref() == Some(journal_id)
+        })
+    }
+
+    fn update(&self, id: u64, apply: impl FnOnce(&mut JobEntry)) -> Result<JobInfo> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let entry = jobs
+            .get_mut(&id)
+            .ok_or_else(|| BiliError::param(format!("任务不存在: {}", id)))?;
+        if entry.info.status.is_finished() {
+            return Err(BiliError::param(format!("任务已结束: {}", id)));
+        }
+        apply(entry);
+        Ok(entry.snapshot())
+    }
+}
+
+/// 根据任务体的结果更新任务信息
+///
+/// 被取消的任务即使返回了结果 (例如已还原部分的报告),状态也记为已取消。
+fn finish(info: &mut JobInfo, context: &JobContext, result: Result<Value>) {
+    info.progress = context.last_progress();
+    info.finished_at = Some(chrono::Local::now().timestamp());
+    match result {
+        Ok(value) => {
+            info.status = if context.control().is_cancelled() {
+                JobStatus::Cancelled
+            } else {
+                JobStatus::Completed
+            };
+            info.result = Some(value);
+        }
+        Err(e) if e.is_cancelled() => info.status = JobStatus::Cancelled,
+        Err(e) => {
+            info.status = JobStatus::Failed;
+            info.error = Some(e.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::{checkpoint, progress, JobLog};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    /// 把结束事件转发到通道的测试接收者
+    struct TestEvents {
+        finished: mpsc::UnboundedSender<JobInfo>,
+    }
+
+    impl JobEvents for TestEvents {
+        fn progress(&self, _progress: &JobProgress) {}
+
+        fn log(&self, _log: &JobLog) {}
+
+        fn finished(&self, job: &JobInfo) {
+            let _ = self.finished.send(job.clone());
+        }
+    }
+
+    fn manager() -> (
+        JobManager,
+        Arc<dyn JobEvents>,
+        mpsc::UnboundedReceiver<JobInfo>,
+    ) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let events: Arc<dyn JobEvents> = Arc::new(TestEvents { finished: sender });
+        let manager = JobManager::new(Arc::new(RwLock::new(BiliClient::new())));
+        (manager, events, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_job_completes_with_progress() {
+        let (manager, events, mut finished) = manager();
+        let info = manager.spawn("test", "测试任务", events, async {
+            for i in 1..=3 {
+                checkpoint().await?;
+                progress(i, Some(3), format!("第 {} 项", i));
+            }
+            Ok(Value::from(3))
+        });
+        assert_eq!(info.status, JobStatus::Running);
+
+        let done = finished.recv().await.unwrap();
+        assert_eq!(done.id, info.id);
+        assert_eq!(done.status, JobStatus::Completed);
+        assert_eq!(done.result, Some(Value::from(3)));
+        assert_eq!(done.progress.as_ref().unwrap().current, 3);
+        assert_eq!(manager.list(), vec![done]);
+    }
+
+    #[tokio::test]
+    async fn test_pause_resume_and_cancel() {
+        let (manager, events, mut finished) = manager();
+        let info = manager.spawn("test", "无限任务", events, async {
+            loop {
+                checkpoint().await?;
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        });
+
+        assert_eq!(manager.pause(info.id).unwrap().status, JobStatus::Paused);
+        assert_eq!(manager.resume(info.id).unwrap().status, JobStatus::Running);
+        manager.pause(info.id).unwrap();
+        manager.cancel(info.id).unwrap();
+
+        let done = finished.recv().await.unwrap();
+        assert_eq!(done.status, JobStatus::Cancelled);
+        assert!(done.error.is_none());
+        assert!(manager.resume(info.id).is_err());
+        assert!(manager.cancel(999).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_job() {
+        let (manager, events, mut finished) = manager();
+        manager.spawn("test", "失败任务", events, async {
+            Err(BiliError::api("请求过于频繁"))
+        });
+
+        let done = finished.recv().await.unwrap();
+        assert_eq!(done.status, JobStatus::Failed);
+        assert!(done.error.unwrap().contains("请求过于频繁"));
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_outside_job() {
+        assert!(checkpoint().await.is_ok());
+        progress(1, None, "无任务时忽略");
+    }
+}
@@ -0,0 +1,28 @@
+//! 后台任务模块
+//!
+//! 备份、还原、清空等耗时操作以任务的形式在后台运行。每个任务有唯一的ID,
+//! 运行过程中通过 [`JobEvents`] 报告进度和日志,并可以暂停、继续或取消。
+//!
+//! 服务层的循环通过 [`checkpoint`] 响应暂停和取消,通过 [`progress`] 和 [`log`] 报告进度。
+//! 翻页由服务用 [`bind_client`] 给客户端注入检查点,分页模块本身不依赖任务模块。
+//! 这些函数在任务之外调用时不做任何事,因此服务在普通命令中的行为不变。
+//! 还原类任务还会记录操作日志 ([`journal`]),应用重启后可以从日志恢复未完成的任务。
+//! 定时备份由 [`Scheduler`] 按 [`schedule`] 中的计划以任务的形式运行。
+
+pub mod context;
+pub mod control;
+pub mod journal;
+pub mod manager;
+pub mod request;
+pub mod schedule;
+pub mod scheduler;
+
+pub use context::{
+    bind_client, checkpoint, log, progress, JobEvents, JobLog, JobLogLevel, JobProgress,
+};
+pub use control::JobControl;
+pub use journal::{Journal, JournalHeader, JournalStep, JournalSummary};
+pub use manager::{JobInfo, JobManager, JobStatus};
+pub use request::JobRequest;
+pub use schedule::{BackupSchedule, ScheduleRun, ScheduleRunStatus, ScheduleSpec};
+pub use scheduler::Scheduler;
@@ -0,0 +1,300 @@
+//! 任务请求
+//!
+//! 前端通过任务请求描述要运行的操作,[`JobRequest::execute`] 调用对应的服务并把结果转换为JSON。
+
+use crate::api::{
+    client::BiliClient,
+    error::{BiliError, Result},
+};
+use crate::backup::{self, BackupData, BackupModule};
+use crate::services::{
+    AccountMigration, BangumiService, BlacklistService, FavoritesService, FollowingService,
+    FullBackupService, FullRestoreOptions, FullRestoreService, HistoryService, ToViewService,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// 清空追番时默认处理的类型 (1:追番 2:追剧)
+const BANGUMI_TYPES: [i32; 2] = [1, 2];
+
+/// 任务请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobRequest {
+    /// 备份单个模块,结果为模块数据和完整性统计
+    Backup {
+        /// 模块
+        module: BackupModule,
+    },
+    /// 一键完整备份
+    FullBackup {
+        /// 要备份的模块,为空时备份全部模块
+        #[serde(default)]
+        modules: Vec<BackupModule>,
+        /// 归档文件路径
+        file_path: String,
+        /// 加密口令
+        #[serde(default)]
+        passphrase: Option<String>,
+    },
+    /// 还原备份数据
+    Restore {
+        /// 备份数据
+        data: Box<BackupData>,
+        /// 还原选项
+        #[serde(default)]
+        options: FullRestoreOptions,
+    },
+    /// 从备份归档完整还原
+    FullRestore {
+        /// 归档文件路径
+        file_path: String,
+        /// 解密口令
+        #[serde(default)]
+        passphrase: Option<String>,
+        /// 还原选项
+        #[serde(default)]
+        options: FullRestoreOptions,
+    },
+    /// 清空模块
+    Clear {
+        /// 模块
+        module: BackupModule,
+        /// 追番类型,为空时清空追番和追剧
+        #[serde(default)]
+        bangumi_type: Option<i32>,
+    },
+    /// 账号迁移
+    Migration {
+        /// 源账号的Cookie
+        source_cookie: String,
+        /// 目标账号的Cookie
+        target_cookie: String,
+        /// 迁移选项
+        #[serde(default)]
+        options: FullRestoreOptions,
+    },
+}
+
+impl JobRequest {
+    /// 任务类型
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Backup { .. } => "backup",
+            Self::FullBackup { .. } => "full_backup",
+            Self::Restore { .. } => "restore",
+            Self::FullRestore { .. } => "full_restore",
+            Self::Clear { .. } => "clear",
+            Self::Migration { .. } => "migration",
+        }
+    }
+
+    /// 任务标题
+    pub fn title(&self) -> String {
+        match self {
+            Self::Backup { module } => format!("备份{}", module.display_name()),
+            Self::FullBackup { .. } => "完整备份".to_string(),
+            Self::Restore { .. } => "还原备份数据".to_string(),
+            Self::FullRestore { .. } => "完整还原".to_string(),
+            Self::Clear { module, .. } => format!("清空{}", module.display_name()),
+            Self::Migration { .. } => "账号迁移".to_string(),
+        }
+    }
+
+    /// 是否记录操作日志 (中断后可以恢复)
+    ///
+    /// 还原类任务会修改账号数据,需要记录每一步以便恢复时跳过。
+    /// 账号迁移在迁移目录中维护自己的日志。
+    pub fn is_resumable(&self) -> bool {
+        matches!(self, Self::Restore { .. } | Self::FullRestore { .. })
+    }
+
+    /// 去掉口令,用于写入操作日志
+    ///
+    /// # 返回
+    ///
+    /// 去掉口令的请求,以及恢复时是否需要重新提供口令
+    pub fn without_secrets(&self) -> (Self, bool) {
+        let mut request = self.clone();
+        let needs_passphrase = match &mut request {
+            Self::FullBackup { passphrase, .. } | Self::FullRestore { passphrase, .. } => {
+                passphrase.take().is_some()
+            }
+            _ => false,
+        };
+        (request, needs_passphrase)
+    }
+
+    /// 填入口令 (恢复任务时使用)
+    pub fn with_passphrase(mut self, value: Option<String>) -> Self {
+        if let Self::FullBackup { passphrase, .. } | Self::FullRestore { passphrase, .. } =
+            &mut self
+        {
+            *passphrase = value;
+        }
+        self
+    }
+
+    /// 执行任务
+    ///
+    /// # 参数
+    ///
+    /// * `client` - HTTP客户端 (账号迁移使用各自的会话)
+    /// * `data_dir` - 应用数据目录 (账号迁移需要)
+    ///
+    /// # 返回
+    ///
+    /// 对应服务的结果 (JSON)
+    ///
+    /// # 错误
+    ///
+    /// - 服务返回错误
+    /// - 模块不支持清空
+    /// - 账号迁移缺少数据目录
+    pub async fn execute(
+        self,
+        client: Arc<RwLock<BiliClient>>,
+        data_dir: Option<PathBuf>,
+    ) -> Result<Value> {
+        match self {
+            Self::Backup { module } => {
+                let fetched = FullBackupService::new(client).fetch_module(module).await?;
+                Ok(json!({
+                    "module": module,
+                    "data": fetched.data,
+                    "completeness": fetched.completeness,
+                }))
+            }
+            Self::FullBackup {
+                modules,
+                file_path,
+                passphrase,
+            } => to_value(
+                FullBackupService::new(client)
+                    .run(&modules, &file_path, passphrase.as_deref())
+                    .await?,
+            ),
+            Self::Restore { data, options } => to_value(
+                FullRestoreService::new(client)
+                    .restore_data(*data, options)
+                    .await,
+            ),
+            Self::FullRestore {
+                file_path,
+                passphrase,
+                options,
+            } => {
+                let archive = backup::read_archive(&file_path, passphrase.as_deref()).await?;
+                to_value(FullRestoreService::new(client).run(archive, options).await)
+            }
+            Self::Clear {
+                module,
+                bangumi_type,
+            } => clear(client, module, bangumi_type).await,
+            Self::Migration {
+                source_cookie,
+                target_cookie,
+                options,
+            } => {
+                let data_dir =
+                    data_dir.ok_or_else(|| BiliError::param("账号迁移需要应用数据目录"))?;
+                to_value(
+                    AccountMigration::from_cookies(source_cookie, target_cookie, data_dir)
+                        .run(&options)
+                        .await?,
+                )
+            }
+        }
+    }
+}
+
+/// 清空单个模块
+async fn clear(
+    client: Arc<RwLock<BiliClient>>,
+    module: BackupModule,
+    bangumi_type: Option<i32>,
+) -> Result<Value> {
+    match module {
+        BackupModule::Following => to_value(FollowingService::new(client).clear_following().await?),
+        BackupModule::Blacklist => to_value(BlacklistService::new(client).clear_blacklist().await?),
+        BackupModule::Favorites => {
+            let cleared = FavoritesService::new(client).clear_all_folders().await?;
+            Ok(json!({ "cleared_count": cleared }))
+        }
+        BackupModule::History => to_value(HistoryService::new(client).clear_history().await?),
+        BackupModule::ToView => to_value(ToViewService::new(client).clear_toview().await?),
+        BackupModule::Bangumi => {
+            let service = BangumiService::new(client);
+            let types = match bangumi_type {
+                Some(type_) => vec![type_],
+                None => BANGUMI_TYPES.to_vec(),
+            };
+            let mut results = Vec::with_capacity(types.len());
+            for type_ in types {
+                results.push(service.clear_bangumi(type_).await?);
+            }
+            to_value(results)
+        }
+        BackupModule::RelationTags | BackupModule::Followers => Err(BiliError::param(format!(
+            "{}不支持清空",
+            module.display_name()
+        ))),
+    }
+}
+
+fn to_value(value: impl Serialize) -> Result<Value> {
+    Ok(serde_json::to_value(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_deserialize() {
+        let request: JobRequest =
+            serde_json::from_str(r#"{"type":"clear","module":"toview"}"#).unwrap();
+        assert_eq!(request.kind(), "clear");
+        assert_eq!(request.title(), "清空稍后再看");
+
+        let request: JobRequest =
+            serde_json::from_str(r#"{"type":"full_backup","file_path":"/tmp/backup.zip"}"#)
+                .unwrap();
+        assert!(matches!(
+            request,
+            JobRequest::FullBackup { ref modules, passphrase: None, .. } if modules.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_without_secrets() {
+        let request = JobRequest::FullRestore {
+            file_path: "/tmp/backup.zip".to_string(),
+            passphrase: Some("secret".to_string()),
+            options: FullRestoreOptions::default(),
+        };
+        assert!(request.is_resumable());
+
+        let (stripped, needs_passphrase) = request.without_secrets();
+        assert!(needs_passphrase);
+        assert!(!serde_json::to_string(&stripped).unwrap().contains("secret"));
+        assert!(matches!(
+            stripped.with_passphrase(Some("secret".to_string())),
+            JobRequest::FullRestore { passphrase: Some(ref p), .. } if p == "secret"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_clear_unsupported_module() {
+        let client = Arc::new(RwLock::new(BiliClient::new()));
+        let request = JobRequest::Clear {
+            module: BackupModule::Followers,
+            bangumi_type: None,
+        };
+        let err = request.execute(client, None).await.unwrap_err();
+        assert!(err.to_string().contains("不支持清空"));
+    }
+}
@@ -0,0 +1,379 @@
+//! 定时备份计划
+//!
+//! 每个计划属于一个账号,记录要备份的模块、运行时间 (cron表达式或固定间隔)
+//! 和快照保留策略。所有计划保存在应用数据目录下的 `schedules.json` 中,
+//! 每个计划保留最近的运行记录。
+
+use crate::api::error::{BiliError, Result};
+use crate::backup::archive::{run_blocking, write_file_atomic};
+use crate::backup::{BackupModule, RetentionPolicy};
+use crate::services::ModuleBackupResult;
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// 计划文件名
+pub const SCHEDULES_FILE_NAME: &str = "schedules.json";
+
+/// 每个计划保留的运行记录数
+const MAX_RUN_HISTORY: usize = 20;
+
+/// 运行时间
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduleSpec {
+    /// 固定间隔 (从上次运行开始计算)
+    Interval {
+        /// 间隔 (分钟)
+        minutes: u32,
+    },
+    /// cron表达式 (本地时间)
+    ///
+    /// 支持常见的5段格式 (分 时 日 月 周),也支持带秒的6段或7段格式。
+    Cron {
+        /// 表达式,例如 `0 3 * * *` 表示每天凌晨3点
+        expression: String,
+    },
+}
+
+impl ScheduleSpec {
+    /// 检查运行时间是否有效
+    ///
+    /// # 错误
+    ///
+    /// - 间隔为0
+    /// - cron表达式无法解析
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Self::Interval { minutes: 0 } => Err(BiliError::param("备份间隔不能为0")),
+            Self::Interval { .. } => Ok(()),
+            Self::Cron { expression } => parse_cron(expression).map(|_| ()),
+        }
+    }
+
+    /// 计算指定时间之后的下一次运行时间
+    ///
+    /// # 参数
+    ///
+    /// * `after` - Unix时间戳 (秒)
+    ///
+    /// # 返回
+    ///
+    /// 下一次运行时间,不会再运行时 (或表达式无效) 为None
+    pub fn next_after(&self, after: i64) -> Option<i64> {
+        match self {
+            Self::Interval { minutes } => Some(after + i64::from(*minutes) * 60),
+            Self::Cron { expression } => {
+                let after = Local.timestamp_opt(after, 0).single()?;
+                let schedule = parse_cron(expression).ok()?;
+                let next = schedule.after(&after).next();
+                next.map(|time| time.timestamp())
+            }
+        }
+    }
+}
+
+/// 解析cron表达式,5段格式补上秒
+fn parse_cron(expression: &str) -> Result<cron::Schedule> {
+    let expression = expression.trim();
+    let normalized = if expression.split_whitespace().count() == 5 {
+        format!("0 {}", expression)
+    } else {
+        expression.to_string()
+    };
+    cron::Schedule::from_str(&normalized)
+        .map_err(|e| BiliError::param(format!("无效的cron表达式 \"{}\": {}", expression, e)))
+}
+
+/// 运行结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleRunStatus {
+    /// 所有模块都备份成功
+    Succeeded,
+    /// 部分模块备份失败 (快照只包含成功的模块)
+    Partial,
+    /// 失败,没有保存快照
+    Failed,
+    /// 被取消
+    Cancelled,
+}
+
+/// 一次运行的记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleRun {
+    /// 计划的运行时间
+    pub scheduled_at: i64,
+    /// 开始时间
+    pub started_at: i64,
+    /// 结束时间
+    pub finished_at: i64,
+    /// 是否为补上的运行 (应用关闭期间错过)
+    pub caught_up: bool,
+    /// 结果
+    pub status: ScheduleRunStatus,
+    /// 保存的快照ID
+    pub snapshot_id: Option<i64>,
+    /// 各模块的备份结果
+    pub modules: Vec<ModuleBackupResult>,
+    /// 按保留策略清理的快照数量
+    pub pruned: Option<usize>,
+    /// 失败原因 (或保留策略执行失败的原因)
+    pub error: Option<String>,
+}
+
+/// 定时备份计划
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupSchedule {
+    /// 计划ID (新建时为0,保存时分配)
+    #[serde(default)]
+    pub id: u64,
+    /// 名称
+    pub name: String,
+    /// 账号UID
+    pub uid: u64,
+    /// 要备份的模块,为空时备份全部模块
+    #[serde(default)]
+    pub modules: Vec<BackupModule>,
+    /// 运行时间
+    pub spec: ScheduleSpec,
+    /// 是否以增量方式保存快照
+    #[serde(default = "default_true")]
+    pub incremental: bool,
+    /// 快照保留策略 (只清理该计划创建的快照)
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    /// 是否启用
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 下一次运行时间 (Unix时间戳, 秒)
+    #[serde(default)]
+    pub next_run: Option<i64>,
+    /// 最近的运行记录 (最新的在前)
+    #[serde(default)]
+    pub runs: Vec<ScheduleRun>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl BackupSchedule {
+    /// 是否已到运行时间
+    pub fn is_due(&self, now: i64) -> bool {
+        self.enabled && self.next_run.is_some_and(|next| next <= now)
+    }
+
+    /// 记录一次运行,并从运行结束时开始计算下一次运行时间
+    pub(crate) fn record_run(&mut self, run: ScheduleRun) {
+        self.next_run = self.spec.next_after(run.finished_at);
+        self.runs.insert(0, run);
+        self.runs.truncate(MAX_RUN_HISTORY);
+    }
+}
+
+/// 计划文件
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScheduleFile {
+    schedules: Vec<BackupSchedule>,
+}
+
+/// 计划文件路径
+pub fn schedules_path(data_dir: impl AsRef<Path>) -> PathBuf {
+    data_dir.as_ref().join(SCHEDULES_FILE_NAME)
+}
+
+/// 读取所有计划 (文件不存在时为空)
+pub async fn load_schedules(path: impl AsRef<Path>) -> Result<Vec<BackupSchedule>> {
+    let path = path.as_ref().to_path_buf();
+    run_blocking(move || {
+        let bytes = match std::fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(BiliError::io(format!("读取备份计划失败: {}", e))),
+        };
+        serde_json::from_slice::<ScheduleFile>(&bytes)
+            .map(|file| file.schedules)
+            .map_err(|e| BiliError::parse(format!("备份计划解析失败: {}", e)))
+    })
+    .await
+}
+
+/// 保存所有计划
+pub async fn save_schedules(path: impl AsRef<Path>, schedules: Vec<BackupSchedule>) -> Result<()> {
+    let path = path.as_ref().to_path_buf();
+    let bytes = serde_json::to_vec_pretty(&ScheduleFile { schedules })?;
+    run_blocking(move || {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| BiliError::io(format!("创建数据目录失败: {}", e)))?;
+        }
+        write_file_atomic(&path, &bytes)
+    })
+    .await
+}
+
+/// 新增或更新计划
+///
+/// 新计划分配ID;更新时保留原有的运行记录,运行时间变化时重新计算下一次运行时间。
+///
+/// # 返回
+///
+/// 保存后的计划
+///
+/// # 错误
+///
+/// - 运行时间无效
+/// - 要更新的计划不存在
+pub(crate) fn upsert_schedule(
+    schedules: &mut Vec<BackupSchedule>,
+    mut schedule: BackupSchedule,
+    now: i64,
+) -> Result<BackupSchedule> {
+    schedule.spec.validate()?;
+
+    if schedule.id == 0 {
+        schedule.id = schedules.iter().map(|s| s.id).max().unwrap_or(0) + 1;
+        schedule.runs.clear();
+        schedule.next_run = schedule.spec.next_after(now);
+        schedules.push(schedule.clone());
+        return Ok(schedule);
+    }
+
+    let existing = schedules
+        .iter_mut()
+        .find(|s| s.id == schedule.id)
+        .ok_or_else(|| BiliError::param(format!("备份计划不存在: {}", schedule.id)))?;
+    schedule.runs = std::mem::take(&mut existing.runs);
+    schedule.next_run = if schedule.spec == existing.spec && existing.next_run.is_some() {
+        existing.next_run
+    } else {
+        schedule.spec.next_after(now)
+    };
+    *existing = schedule.clone();
+    Ok(schedule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> i64 {
+        Local
+            .with_ymd_and_hms(y, m, d, h, min, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    fn schedule(spec: ScheduleSpec) -> BackupSchedule {
+        BackupSchedule {
+            id: 0,
+            name: "每日备份".to_string(),
+            uid: 1,
+            modules: vec![BackupModule::History],
+            spec,
+            incremental: true,
+            retention: RetentionPolicy::default(),
+            enabled: true,
+            next_run: None,
+            runs: Vec::new(),
+        }
+    }
+
+    fn run(finished_at: i64) -> ScheduleRun {
+        ScheduleRun {
+            scheduled_at: finished_at,
+            started_at: finished_at,
+            finished_at,
+            caught_up: false,
+            status: ScheduleRunStatus::Succeeded,
+            snapshot_id: Some(1),
+            modules: Vec::new(),
+            pruned: Some(0),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_next_after() {
+        let now = at(2024, 6, 15, 12, 30);
+        let interval = ScheduleSpec::Interval { minutes: 90 };
+        assert_eq!(interval.next_after(now), Some(now + 90 * 60));
+
+        let daily = ScheduleSpec::Cron {
+            expression: "0 3 * * *".to_string(),
+        };
+        assert!(daily.validate().is_ok());
+        assert_eq!(daily.next_after(now), Some(at(2024, 6, 16, 3, 0)));
+
+        assert!(ScheduleSpec::Interval { minutes: 0 }.validate().is_err());
+        assert!(ScheduleSpec::Cron {
+            expression: "every day".to_string()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_upsert_and_record_run() {
+        let now = at(2024, 6, 15, 12, 0);
+        let mut schedules = Vec::new();
+        let created = upsert_schedule(
+            &mut schedules,
+            schedule(ScheduleSpec::Interval { minutes: 60 }),
+            now,
+        )
+        .unwrap();
+        assert_eq!(created.id, 1);
+        assert_eq!(created.next_run, Some(now + 3600));
+        assert!(!created.is_due(now));
+        assert!(created.is_due(now + 7200));
+
+        // 应用关闭期间错过了多次运行,补上一次后从运行结束时重新计算
+        let finished = now + 10 * 3600;
+        schedules[0].record_run(run(finished));
+        assert_eq!(schedules[0].next_run, Some(finished + 3600));
+
+        // 只改名称时保留运行记录和下一次运行时间
+        let mut renamed = schedules[0].clone();
+        renamed.name = "历史记录".to_string();
+        renamed.runs.clear();
+        let updated = upsert_schedule(&mut schedules, renamed, now).unwrap();
+        assert_eq!(updated.runs.len(), 1);
+        assert_eq!(updated.next_run, Some(finished + 3600));
+
+        let mut missing = schedule(ScheduleSpec::Interval { minutes: 60 });
+        missing.id = 42;
+        assert!(upsert_schedule(&mut schedules, missing, now).is_err());
+    }
+
+    #[test]
+    fn test_run_history_is_capped() {
+        let mut schedule = schedule(ScheduleSpec::Interval { minutes: 60 });
+        for i in 0..(MAX_RUN_HISTORY as i64 + 5) {
+            schedule.record_run(run(i));
+        }
+        assert_eq!(schedule.runs.len(), MAX_RUN_HISTORY);
+        assert_eq!(schedule.runs[0].finished_at, MAX_RUN_HISTORY as i64 + 4);
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = schedules_path(dir.path());
+        assert!(load_schedules(&path).await.unwrap().is_empty());
+
+        let mut schedules = Vec::new();
+        upsert_schedule(
+            &mut schedules,
+            schedule(ScheduleSpec::Cron {
+                expression: "0 3 * * *".to_string(),
+            }),
+            0,
+        )
+        .unwrap();
+        save_schedules(&path, schedules.clone()).await.unwrap();
+        assert_eq!(load_schedules(&path).await.unwrap(), schedules);
+    }
+}
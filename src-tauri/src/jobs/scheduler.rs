@@ -0,0 +1,403 @@
+//! 定时备份调度器
+//!
+//! 应用运行期间每分钟检查一次备份计划,到期的计划以后台任务的形式运行:
+//! 备份选定的模块并保存为快照,按计划的保留策略清理该计划创建的旧快照,
+//! 再把结果记录到计划中。手动保存的快照和其他计划的快照不会被清理。
+//!
+//! 应用关闭期间错过的运行会在下次启动后补上一次 (不会为每个错过的时间点各运行一次)。
+//! 计划所属的账号未登录时,到期的运行会一直等到该账号登录后再执行。
+
+use super::context::{self as job, JobEvents, JobLogLevel};
+use super::manager::{JobInfo, JobManager};
+use super::schedule::{
+    self, load_schedules, save_schedules, upsert_schedule, BackupSchedule, ScheduleRun,
+    ScheduleRunStatus,
+};
+use crate::api::{
+    client::BiliClient,
+    error::{BiliError, Result},
+};
+use crate::backup::SnapshotStore;
+use crate::services::FullBackupService;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// 检查计划的间隔
+const TICK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 超过计划时间多久开始运行算作补上的运行 (秒)
+const CATCH_UP_GRACE_SECS: i64 = 5 * 60;
+
+/// 定时备份调度器
+///
+/// 克隆的调度器共享同一份状态。
+#[derive(Clone)]
+pub struct Scheduler {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    path: PathBuf,
+    client: Arc<RwLock<BiliClient>>,
+    store: SnapshotStore,
+    jobs: JobManager,
+    /// 正在运行的计划
+    running: Mutex<HashSet<u64>>,
+    /// 串行化计划文件的读写
+    file: tokio::sync::Mutex<()>,
+}
+
+impl Scheduler {
+    /// 创建调度器
+    ///
+    /// # 参数
+    ///
+    /// * `data_dir` - 应用数据目录,计划保存在其下的 `schedules.json`
+    /// * `client` - 备份使用的HTTP客户端 (当前登录的账号)
+    /// * `store` - 保存快照的快照库
+    /// * `jobs` - 任务管理器,每次运行作为一个后台任务
+    pub fn new(
+        data_dir: impl AsRef<Path>,
+        client: Arc<RwLock<BiliClient>>,
+        store: SnapshotStore,
+        jobs: JobManager,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                path: schedule::schedules_path(data_dir),
+                client,
+                store,
+                jobs,
+                running: Mutex::new(HashSet::new()),
+                file: tokio::sync::Mutex::new(()),
+            }),
+        }
+    }
+
+    /// 列出所有计划
+    pub async fn list(&self) -> Result<Vec<BackupSchedule>> {
+        let _guard = self.inner.file.lock().await;
+        load_schedules(&self.inner.path).await
+    }
+
+    /// 新增或更新计划
+    ///
+    /// # 返回
+    ///
+    /// 保存后的计划 (包含分配的ID和下一次运行时间)
+    ///
+    /// # 错误
+    ///
+    /// - 运行时间无效
+    /// - 要更新的计划不存在
+    /// - 计划文件无法读写
+    pub async fn save(&self, schedule: BackupSchedule) -> Result<BackupSchedule> {
+        let _guard = self.inner.file.lock().await;
+        let mut schedules = load_schedules(&self.inner.path).await?;
+        let saved = upsert_schedule(&mut schedules, schedule, now())?;
+        save_schedules(&self.inner.path, schedules).await?;
+        tracing::info!("已保存备份计划 #{} ({})", saved.id, saved.name);
+        Ok(saved)
+    }
+
+    /// 删除计划 (正在进行的运行不受影响)
+    pub async fn delete(&self, id: u64) -> Result<()> {
+        let _guard = self.inner.file.lock().await;
+        let mut schedules = load_schedules(&self.inner.path).await?;
+        let count = schedules.len();
+        schedules.retain(|s| s.id != id);
+        if schedules.len() == count {
+            return Err(BiliError::param(format!("备份计划不存在: {}", id)));
+        }
+        save_schedules(&self.inner.path, schedules).await
+    }
+
+    /// 立即运行计划
+    ///
+    /// # 错误
+    ///
+    /// - 计划不存在或正在运行
+    /// - 计划的账号未登录
+    pub async fn run_now(&self, id: u64, events: Arc<dyn JobEvents>) -> Result<JobInfo> {
+        let schedule = self
+            .list()
+            .await?
+            .into_iter()
+            .find(|s| s.id == id)
+            .ok_or_else(|| BiliError::param(format!("备份计划不存在: {}", id)))?;
+        if self.logged_in_uid().await != Some(schedule.uid) {
+            return Err(BiliError::auth("计划的账号未登录"));
+        }
+        self.spawn_run(schedule, now(), events)
+            .ok_or_else(|| BiliError::param("该计划正在运行"))
+    }
+
+    /// 持续检查并运行到期的计划 (不会返回)
+    ///
+    /// 启动后立即检查一次,补上应用关闭期间错过的运行。
+    pub async fn run(self, events: Arc<dyn JobEvents>) {
+        tracing::info!("定时备份调度器已启动");
+        loop {
+            if let Err(e) = self.tick(now(), events.clone()).await {
+                tracing::warn!("检查备份计划失败: {}", e);
+            }
+            tokio::time::sleep(TICK_INTERVAL).await;
+        }
+    }
+
+    /// 检查一次计划,运行所有已到期且账号已登录的计划
+    ///
+    /// # 返回
+    ///
+    /// 本次启动的任务
+    pub async fn tick(&self, now: i64, events: Arc<dyn JobEvents>) -> Result<Vec<JobInfo>> {
+        let schedules = self.list().await?;
+        if !schedules.iter().any(|s| s.is_due(now)) {
+            return Ok(Vec::new());
+        }
+
+        let Some(uid) = self.logged_in_uid().await else {
+            tracing::debug!("有到期的备份计划，等待账号登录");
+            return Ok(Vec::new());
+        };
+        let mut started = Vec::new();
+        for schedule in schedules {
+            if !schedule.is_due(now) || schedule.uid != uid {
+                continue;
+            }
+            let scheduled_at = schedule.next_run.unwrap_or(now);
+            if let Some(info) = self.spawn_run(schedule, scheduled_at, events.clone()) {
+                started.push(info);
+            }
+        }
+        Ok(started)
+    }
+
+    /// 当前登录的账号
+    async fn logged_in_uid(&self) -> Option<u64> {
+        FullBackupService::new(self.inner.client.clone())
+            .source()
+            .await
+            .ok()
+            .map(|source| source.uid)
+    }
+
+    /// 以后台任务运行计划 (计划正在运行时返回None)
+    fn spawn_run(
+        &self,
+        schedule: BackupSchedule,
+        scheduled_at: i64,
+        events: Arc<dyn JobEvents>,
+    ) -> Option<JobInfo> {
+        if !self.inner.running.lock().unwrap().insert(schedule.id) {
+            return None;
+        }
+
+        let inner = self.inner.clone();
+        let title = format!("定时备份: {}", schedule.name);
+        let info = self
+            .inner
+            .jobs
+            .spawn("scheduled_backup", title, events, async move {
+                let run = inner.execute(&schedule, scheduled_at).await;
+                let recorded = inner.record(schedule.id, run.clone()).await;
+                inner.running.lock().unwrap().remove(&schedule.id);
+                recorded?;
+
+                match run.status {
+                    ScheduleRunStatus::Failed => Err(BiliError::business(
+                        run.error.unwrap_or_else(|| "定时备份失败".to_string()),
+                    )),
+                    ScheduleRunStatus::Cancelled => Err(BiliError::Cancelled),
+                    _ => Ok(serde_json::to_value(run)?),
+                }
+            });
+        Some(info)
+    }
+}
+
+impl Inner {
+    /// 运行一次计划,错误记录在运行记录中
+    async fn execute(&self, schedule: &BackupSchedule, scheduled_at: i64) -> ScheduleRun {
+        let started_at = now();
+        let mut run = ScheduleRun {
+            scheduled_at,
+            started_at,
+            finished_at: started_at,
+            caught_up: started_at - scheduled_at > CATCH_UP_GRACE_SECS,
+            status: ScheduleRunStatus::Failed,
+            snapshot_id: None,
+            modules: Vec::new(),
+            pruned: None,
+            error: None,
+        };
+        if run.caught_up {
+            job::log(JobLogLevel::Info, "补上应用关闭期间错过的备份");
+        }
+
+        if let Err(e) = self.backup(schedule, &mut run).await {
+            tracing::warn!("定时备份 #{} 失败: {}", schedule.id, e);
+            run.status = if e.is_cancelled() {
+                ScheduleRunStatus::Cancelled
+            } else {
+                ScheduleRunStatus::Failed
+            };
+            run.error = Some(e.to_string());
+        }
+        run.finished_at = now();
+        run
+    }
+
+    /// 备份、保存快照并执行保留策略
+    async fn backup(&self, schedule: &BackupSchedule, run: &mut ScheduleRun) -> Result<()> {
+        let service = FullBackupService::new(self.client.clone());
+        if service.source().await?.uid != schedule.uid {
+            return Err(BiliError::auth("当前登录的账号不是计划的账号"));
+        }
+
+        let (archive, modules) = service.backup(&schedule.modules).await?;
+        let partial = modules.iter().any(|m| !m.success);
+        run.modules = modules;
+
+        let snapshot_id = self
+            .store
+            .save_scheduled(archive, schedule.id, schedule.incremental)
+            .await?;
+        run.snapshot_id = Some(snapshot_id);
+        run.status = if partial {
+            ScheduleRunStatus::Partial
+        } else {
+            ScheduleRunStatus::Succeeded
+        };
+        job::log(JobLogLevel::Info, format!("已保存快照 #{}", snapshot_id));
+
+        // 快照已经保存,保留策略失败只记录错误
+        match self
+            .store
+            .apply_schedule_retention(schedule.uid, schedule.id, schedule.retention.clone())
+            .await
+        {
+            Ok(plan) => {
+                run.pruned = Some(plan.prune.len());
+                if !plan.prune.is_empty() {
+                    job::log(
+                        JobLogLevel::Info,
+                        format!("按保留策略清理了 {} 个快照", plan.prune.len()),
+                    );
+                }
+            }
+            Err(e) => {
+                tracing::warn!("定时备份 #{} 执行保留策略失败: {}", schedule.id, e);
+                job::log(JobLogLevel::Warn, format!("执行保留策略失败: {}", e));
+                run.error = Some(format!("执行保留策略失败: {}", e));
+            }
+        }
+        Ok(())
+    }
+
+    /// 把运行记录写入计划 (计划已被删除时忽略)
+    async fn record(&self, id: u64, run: ScheduleRun) -> Result<()> {
+        let _guard = self.file.lock().await;
+        let mut schedules = load_schedules(&self.path).await?;
+        let Some(schedule) = schedules.iter_mut().find(|s| s.id == id) else {
+            return Ok(());
+        };
+        schedule.record_run(run);
+        save_schedules(&self.path, schedules).await
+    }
+}
+
+fn now() -> i64 {
+    chrono::Local::now().timestamp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::BackupModule;
+    use crate::jobs::schedule::ScheduleSpec;
+    use crate::jobs::{JobLog, JobProgress};
+
+    struct NoEvents;
+
+    impl JobEvents for NoEvents {
+        fn progress(&self, _progress: &JobProgress) {}
+
+        fn log(&self, _log: &JobLog) {}
+
+        fn finished(&self, _job: &JobInfo) {}
+    }
+
+    fn new_scheduler(dir: &Path, cookie: Option<&str>) -> Scheduler {
+        let mut client = BiliClient::new();
+        if let Some(cookie) = cookie {
+            client.set_cookie(cookie.to_string());
+        }
+        let client = Arc::new(RwLock::new(client));
+        Scheduler::new(
+            dir,
+            client.clone(),
+            SnapshotStore::open_in_memory().unwrap(),
+            JobManager::new(client),
+        )
+    }
+
+    fn schedule(uid: u64) -> BackupSchedule {
+        BackupSchedule {
+            id: 0,
+            name: "历史记录".to_string(),
+            uid,
+            modules: vec![BackupModule::History],
+            spec: ScheduleSpec::Interval { minutes: 60 },
+            incremental: true,
+            retention: Default::default(),
+            enabled: true,
+            next_run: None,
+            runs: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_list_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let scheduler = new_scheduler(dir.path(), None);
+
+        let saved = scheduler.save(schedule(1)).await.unwrap();
+        assert_eq!(saved.id, 1);
+        assert!(saved.next_run.is_some());
+        assert_eq!(scheduler.list().await.unwrap(), vec![saved]);
+
+        scheduler.delete(1).await.unwrap();
+        assert!(scheduler.list().await.unwrap().is_empty());
+        assert!(scheduler.delete(1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_due_run_waits_for_account() {
+        let dir = tempfile::tempdir().unwrap();
+        let events: Arc<dyn JobEvents> = Arc::new(NoEvents);
+
+        // 未登录时到期的计划保持等待
+        let scheduler = new_scheduler(dir.path(), None);
+        let saved = scheduler.save(schedule(1)).await.unwrap();
+        let later = saved.next_run.unwrap() + 3600;
+        assert!(scheduler
+            .tick(later, events.clone())
+            .await
+            .unwrap()
+            .is_empty());
+
+        // 登录的是其他账号时也不运行
+        let scheduler = new_scheduler(dir.path(), Some("DedeUserID=2; SESSDATA=x"));
+        assert!(scheduler
+            .tick(later, events.clone())
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(scheduler.run_now(saved.id, events).await.is_err());
+        assert!(scheduler.list().await.unwrap()[0].is_due(later));
+    }
+}
@@ -2,6 +2,12 @@
 pub mod api;
 /// 业务逻辑层模块
 pub mod services;
+/// 备份文件格式模块
+pub mod backup;
+/// 导出格式模块
+pub mod export;
+/// 后台任务模块
+pub mod jobs;
 /// Tauri命令层模块
 pub mod commands;
 /// 工具函数模块
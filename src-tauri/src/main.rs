@@ -5,18 +5,23 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use bilibili_backup_tauri::{
     api::BiliClient,
+    backup::{store::default_store_path, SnapshotStore},
     services::{
         AuthService,
         FollowingService,
         FollowerService,
         BlacklistService,
         FavoritesService,
+        FullBackupService,
+        FullRestoreService,
         HistoryService,
         BangumiService,
         ToViewService,
     },
     commands,
+    jobs::{JobManager, Scheduler},
 };
+use tauri::Manager;
 use tracing_subscriber::EnvFilter;
 
 fn main() {
@@ -43,6 +48,10 @@ fn main() {
     let history_service = HistoryService::new(client.clone());
     let bangumi_service = BangumiService::new(client.clone());
     let toview_service = ToViewService::new(client.clone());
+    let full_backup_service = FullBackupService::new(client.clone());
+    let full_restore_service = FullRestoreService::new(client.clone());
+    let job_manager = JobManager::new(client.clone());
+    let scheduler_client = client.clone();
 
     // 启动Tauri应用
     tauri::Builder::default()
@@ -55,6 +64,35 @@ fn main() {
         .manage(history_service)
         .manage(bangumi_service)
         .manage(toview_service)
+        .manage(full_backup_service)
+        .manage(full_restore_service)
+        .manage(job_manager)
+
+        // 打开本地快照库（位于应用数据目录），检查可以恢复的未完成任务，并启动定时备份
+        .setup(move |app| {
+            let data_dir = app
+                .path_resolver()
+                .app_data_dir()
+                .ok_or("无法获取应用数据目录")?;
+            match app.state::<JobManager>().unfinished_journals(&data_dir) {
+                Ok(journals) if !journals.is_empty() => {
+                    tracing::info!("有 {} 个未完成的还原任务可以恢复", journals.len());
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("读取未完成任务失败: {}", e),
+            }
+            let store = SnapshotStore::open(default_store_path(&data_dir))?;
+            let scheduler = Scheduler::new(
+                &data_dir,
+                scheduler_client,
+                store.clone(),
+                app.state::<JobManager>().inner().clone(),
+            );
+            tauri::async_runtime::spawn(scheduler.clone().run(commands::job_events(app.handle())));
+            app.manage(store);
+            app.manage(scheduler);
+            Ok(())
+        })
 
         // 注册所有命令
         .invoke_handler(tauri::generate_handler![
@@ -85,10 +123,12 @@ fn main() {
             commands::restore_blacklist,
             commands::clear_blacklist,
 
-            // 收藏管理命令（3个）
+            // 收藏管理命令（5个）
             commands::backup_favorites,
             commands::restore_favorites,
             commands::clear_favorites,
+            commands::export_favorites,
+            commands::import_favorites,
 
             // 历史记录命令（4个）
             commands::backup_history,
@@ -109,6 +149,63 @@ fn main() {
             commands::clear_toview,
             commands::export_toview,
             commands::import_toview,
+
+            // 备份归档命令（9个）
+            commands::run_full_backup,
+            commands::run_full_restore,
+            commands::write_backup_archive,
+            commands::read_backup_archive,
+            commands::read_backup_manifest,
+            commands::import_legacy_backup,
+            commands::diff_backup_archives,
+            commands::verify_backup,
+            commands::archive_backup_images,
+
+            // 快照库命令（16个）
+            commands::save_snapshot,
+            commands::save_incremental_snapshot,
+            commands::verify_snapshot_chain,
+            commands::list_snapshots,
+            commands::load_snapshot,
+            commands::delete_snapshot,
+            commands::export_snapshot,
+            commands::import_snapshot,
+            commands::find_first_favorited,
+            commands::get_video_record,
+            commands::diff_snapshots,
+            commands::pin_snapshot,
+            commands::preview_snapshot_retention,
+            commands::apply_snapshot_retention,
+            commands::get_snapshot_retention_policy,
+            commands::set_snapshot_retention_policy,
+
+            // 导出命令（5个）
+            commands::list_table_columns,
+            commands::export_table,
+            commands::export_html_site,
+            commands::export_following_opml,
+            commands::export_favorites_bookmarks,
+
+            // 账号迁移命令（3个）
+            commands::run_account_migration,
+            commands::get_account_migration,
+            commands::discard_account_migration,
+
+            // 后台任务命令（8个）
+            commands::start_job,
+            commands::pause_job,
+            commands::resume_job,
+            commands::cancel_job,
+            commands::list_jobs,
+            commands::list_unfinished_jobs,
+            commands::resume_unfinished_job,
+            commands::discard_unfinished_job,
+
+            // 定时备份命令（4个）
+            commands::list_backup_schedules,
+            commands::save_backup_schedule,
+            commands::delete_backup_schedule,
+            commands::run_backup_schedule_now,
         ])
         .run(tauri::generate_context!())
         .expect("启动Tauri应用失败");
@@ -3,12 +3,17 @@ use crate::api::{
     endpoints::{API_BANGUMI_FOLLOW, API_BANGUMI_LIST, API_BANGUMI_UNFOLLOW},
     error::BiliError,
     models::{ApiResult, Bangumi, ClearResult, RestoreResult},
+    pagination::{fetch_all_pages_with_outcome, FetchOutcome},
 };
-use serde::Deserialize;
+use crate::backup::{crypto, decode_payload, encode_payload, BackupModule};
+use crate::jobs::{self, journal};
 use serde_json::json;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// 操作日志中追番的动作名
+const JOURNAL_FOLLOW: &str = "bangumi_follow";
+
 /// 追番追剧服务
 ///
 /// 提供追番追剧的备份、还原、清空等功能。
@@ -16,15 +21,6 @@ pub struct BangumiService {
     client: Arc<RwLock<BiliClient>>,
 }
 
-/// 追番列表响应
-#[derive(Debug, Clone, Deserialize)]
-struct BangumiListResponse {
-    /// 列表数据
-    list: Vec<Bangumi>,
-    /// 总数
-    total: usize,
-}
-
 impl BangumiService {
     /// 创建新的追番追剧服务实例
     ///
@@ -77,54 +73,28 @@ impl BangumiService {
     /// # }
     /// ```
     pub async fn backup_bangumi(&self, type_: i32) -> Result<Vec<Bangumi>, BiliError> {
-        let mut all_bangumi = Vec::new();
-        let mut pn = 1;
-        let ps = 20; // 每页20个
-
-        loop {
-            let url = format!(
-                "{}?type={}&follow_status=0&pn={}&ps={}",
-                API_BANGUMI_LIST, type_, pn, ps
-            );
-
-            tracing::info!("获取追番列表 (类型:{}, 页码:{})", type_, pn);
-
-            let client = self.client.read().await;
-            let response = client.get_with_retry(&url).await?;
-            drop(client);
-
-            // 解析响应
-            let result: ApiResult<BangumiListResponse> = response.json().await?;
-            let data = result.into_data()?;
-
-            // 如果没有更多数据，退出循环
-            if data.list.is_empty() {
-                tracing::info!("追番列表获取完成，共 {} 个", all_bangumi.len());
-                break;
-            }
-
-            let count = data.list.len();
-            all_bangumi.extend(data.list);
-
-            tracing::info!(
-                "本次获取 {} 个，累计 {} 个",
-                count,
-                all_bangumi.len()
-            );
-
-            // 如果已经获取到所有数据，退出循环
-            if all_bangumi.len() >= data.total {
-                break;
-            }
+        self.backup_bangumi_with_outcome(type_)
+            .await
+            .map(FetchOutcome::into_items)
+    }
 
-            pn += 1;
+    /// 备份追番追剧列表,并返回完整性统计
+    ///
+    /// # 参数
+    ///
+    /// * `type_` - 类型 (1:番剧 2:电影 3:纪录片 4:国创 5:电视剧 7:综艺)
+    pub async fn backup_bangumi_with_outcome(
+        &self,
+        type_: i32,
+    ) -> Result<FetchOutcome<Bangumi>, BiliError> {
+        tracing::info!("获取追番列表 (类型:{})", type_);
 
-            // 防风控延迟
-            let client = self.client.read().await;
-            client.delay_random().await;
-        }
+        let base_url = format!("{}?type={}&follow_status=0", API_BANGUMI_LIST, type_);
+        let client = jobs::bind_client(self.client.read().await.clone());
+        let outcome = fetch_all_pages_with_outcome::<Bangumi>(&client, &base_url, 20, None).await?;
 
-        Ok(all_bangumi)
+        tracing::info!("追番列表获取完成，{}", outcome.completeness.summary());
+        Ok(outcome)
     }
 
     /// 还原追番追剧列表
@@ -168,8 +138,18 @@ impl BangumiService {
 
         tracing::info!("开始还原 {} 个追番", total);
 
-        for bangumi in bangumi_list {
+        for (index, bangumi) in bangumi_list.into_iter().enumerate() {
+            jobs::checkpoint().await?;
+            jobs::progress(index + 1, Some(total), format!("追番 {}", bangumi.title));
+
+            // 恢复中断的任务时跳过已追的番剧
+            if journal::completed(JOURNAL_FOLLOW, bangumi.season_id).is_some() {
+                success_count += 1;
+                continue;
+            }
+
             let result = self.follow_bangumi(bangumi.season_id).await;
+            journal::record(JOURNAL_FOLLOW, bangumi.season_id, &result);
 
             match result {
                 Ok(_) => {
@@ -252,7 +232,10 @@ impl BangumiService {
 
         let mut cleared_count = 0;
 
-        for bangumi in bangumi_list {
+        for (index, bangumi) in bangumi_list.into_iter().enumerate() {
+            jobs::checkpoint().await?;
+            jobs::progress(index + 1, Some(total), format!("取消追番 {}", bangumi.title));
+
             let result = self.unfollow_bangumi(bangumi.season_id).await;
 
             match result {
@@ -335,10 +318,13 @@ impl BangumiService {
 
     /// 导出追番列表到JSON文件
     ///
+    /// 文件带有格式版本信息,以便日后的版本自动迁移;提供口令时文件会被加密。
+    ///
     /// # 参数
     ///
     /// * `bangumi_list` - 追番列表
     /// * `file_path` - 导出文件路径
+    /// * `passphrase` - 加密口令,None表示不加密
     ///
     /// # 返回
     ///
@@ -347,11 +333,12 @@ impl BangumiService {
         &self,
         bangumi_list: &[Bangumi],
         file_path: &str,
+        passphrase: Option<&str>,
     ) -> Result<(), BiliError> {
-        let json = serde_json::to_string_pretty(bangumi_list)
-            .map_err(|e| BiliError::parse(format!("序列化失败: {}", e)))?;
+        let json = encode_payload(BackupModule::Bangumi, &bangumi_list)?;
+        let bytes = crypto::seal(json, passphrase).await?;
 
-        tokio::fs::write(file_path, json)
+        tokio::fs::write(file_path, bytes)
             .await
             .map_err(|e| BiliError::io(format!("写入文件失败: {}", e)))?;
 
@@ -361,20 +348,28 @@ impl BangumiService {
 
     /// 从JSON文件导入追番列表
     ///
+    /// 支持旧版本导出的文件,读取时会自动迁移到当前格式;
+    /// 由更新版本的应用导出的文件会被拒绝。
+    ///
     /// # 参数
     ///
     /// * `file_path` - 导入文件路径
+    /// * `passphrase` - 解密口令,文件未加密时忽略
     ///
     /// # 返回
     ///
     /// 追番列表
-    pub async fn import_from_file(&self, file_path: &str) -> Result<Vec<Bangumi>, BiliError> {
-        let json = tokio::fs::read_to_string(file_path)
+    pub async fn import_from_file(
+        &self,
+        file_path: &str,
+        passphrase: Option<&str>,
+    ) -> Result<Vec<Bangumi>, BiliError> {
+        let bytes = tokio::fs::read(file_path)
             .await
             .map_err(|e| BiliError::io(format!("读取文件失败: {}", e)))?;
+        let json = crypto::open(bytes, passphrase).await?;
 
-        let bangumi_list: Vec<Bangumi> = serde_json::from_str(&json)
-            .map_err(|e| BiliError::parse(format!("反序列化失败: {}", e)))?;
+        let bangumi_list: Vec<Bangumi> = decode_payload(BackupModule::Bangumi, &json)?;
 
         tracing::info!(
             "从 {} 导入了 {} 个追番",
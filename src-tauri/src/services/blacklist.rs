@@ -15,12 +15,16 @@ use crate::api::{
     endpoints::*,
     error::{BiliError, Result},
     models::*,
-    pagination::fetch_all_pages,
+    pagination::{fetch_all_pages_with_outcome, FetchOutcome},
 };
+use crate::jobs::{self, journal};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// 操作日志中拉黑用户的动作名
+const JOURNAL_BLOCK: &str = "block";
+
 /// 黑名单服务
 pub struct BlacklistService {
     client: Arc<RwLock<BiliClient>>,
@@ -116,13 +120,24 @@ impl BlacklistService {
     /// # }
     /// ```
     pub async fn backup_blacklist(&self) -> Result<Vec<User>> {
-        let client = self.client.read().await;
+        self.backup_blacklist_with_outcome()
+            .await
+            .map(FetchOutcome::into_items)
+    }
 
-        // 分页获取所有黑名单用户
-        let blacklist = fetch_all_pages::<User>(&client, API_BLACK_LIST, 50, None).await?;
+    /// 备份黑名单,并返回完整性统计
+    pub async fn backup_blacklist_with_outcome(&self) -> Result<FetchOutcome<User>> {
+        let client = jobs::bind_client(self.client.read().await.clone());
 
-        tracing::info!("备份了 {} 个黑名单用户", blacklist.len());
-        Ok(blacklist)
+        // 分页获取所有黑名单用户
+        let outcome = fetch_all_pages_with_outcome::<User>(&client, API_BLACK_LIST, 50, None).await?;
+
+        tracing::info!(
+            "备份了 {} 个黑名单用户 ({})",
+            outcome.items.len(),
+            outcome.completeness.summary()
+        );
+        Ok(outcome)
     }
 
     /// 还原黑名单
@@ -154,21 +169,34 @@ impl BlacklistService {
         users: Vec<User>,
         options: BlacklistRestoreOptions,
     ) -> Result<BlacklistRestoreResult> {
-        let mut client = self.client.write().await;
-
-        // 配置延迟
+        // 延迟只配置在副本上,还原期间不占用共享客户端的锁
+        let mut client = self.client.read().await.clone();
         if let Some((min_ms, max_ms)) = options.delay_ms {
-            *client = client.clone().with_delay_range(min_ms, max_ms);
+            client = client.with_delay_range(min_ms, max_ms);
         }
 
         // 批量添加到黑名单
         let mut success_count = 0;
         let mut failed_count = 0;
         let mut failures = Vec::new();
+        let total = users.len();
+        let mut processed = 0;
 
         for chunk in users.chunks(options.batch_size) {
             for user in chunk {
-                match self.add_to_blacklist(&client, user.mid).await {
+                jobs::checkpoint().await?;
+                processed += 1;
+                jobs::progress(processed, Some(total), format!("拉黑 {}", user.uname));
+
+                // 恢复中断的任务时跳过已拉黑的用户
+                if journal::completed(JOURNAL_BLOCK, user.mid).is_some() {
+                    success_count += 1;
+                    continue;
+                }
+
+                let blocked = self.add_to_blacklist(&client, user.mid).await;
+                journal::record(JOURNAL_BLOCK, user.mid, &blocked);
+                match blocked {
                     Ok(_) => success_count += 1,
                     Err(e) => {
                         failed_count += 1;
@@ -217,7 +245,7 @@ impl BlacklistService {
     /// # }
     /// ```
     pub async fn clear_blacklist(&self) -> Result<BlacklistClearResult> {
-        let client = self.client.read().await;
+        let client = self.client.read().await.clone();
 
         // 1. 获取所有黑名单
         let users = self.backup_blacklist().await?;
@@ -226,8 +254,12 @@ impl BlacklistService {
         let mut success_count = 0;
         let mut failed_count = 0;
         let mut failures = Vec::new();
+        let total = users.len();
+
+        for (index, user) in users.into_iter().enumerate() {
+            jobs::checkpoint().await?;
+            jobs::progress(index + 1, Some(total), format!("移出黑名单 {}", user.uname));
 
-        for user in users {
             match self.remove_from_blacklist(&client, user.mid).await {
                 Ok(_) => success_count += 1,
                 Err(e) => {
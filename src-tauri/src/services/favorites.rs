@@ -2,10 +2,20 @@ use crate::api::client::BiliClient;
 use crate::api::endpoints::{API_FAV_BATCH_DEL, API_FAV_COLLECT, API_FAV_CREATE, API_FAV_LIST, API_FAV_RESOURCES};
 use crate::api::error::{BiliError, Result};
 use crate::api::models::{ApiResult, FavInfo, Media, NormalPageData, RestoreResult};
+use crate::api::pagination::{fetch_pages_from, Completeness, FetchOutcome, PageItems};
+use crate::backup::{jsonl, BackupModule};
+use crate::jobs::{self, journal};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// 操作日志中清空现有收藏夹的动作名
+const JOURNAL_CLEAR: &str = "fav_clear";
+/// 操作日志中创建收藏夹的动作名 (结果为新收藏夹ID)
+const JOURNAL_CREATE_FOLDER: &str = "fav_create_folder";
+/// 操作日志中批量添加视频的动作名
+const JOURNAL_ADD: &str = "fav_add";
+
 /// 收藏夹备份数据（包含收藏夹信息和内容）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FavFolderWithMedia {
@@ -50,6 +60,35 @@ impl Default for FavRestoreOptions {
     }
 }
 
+/// 收藏夹内容分页响应
+#[derive(Debug, Clone, Deserialize)]
+struct FavResourcePage {
+    /// 收藏夹信息 (包含总数)
+    #[serde(default)]
+    info: Option<FavResourceInfo>,
+    /// 媒体列表 (没有更多数据时为null)
+    #[serde(default)]
+    medias: Option<Vec<Media>>,
+}
+
+/// 收藏夹内容分页响应中的收藏夹信息
+#[derive(Debug, Clone, Deserialize)]
+struct FavResourceInfo {
+    /// 收藏数量
+    #[serde(default)]
+    media_count: usize,
+}
+
+impl PageItems<Media> for FavResourcePage {
+    fn total(&self) -> Option<usize> {
+        self.info.as_ref().map(|info| info.media_count)
+    }
+
+    fn into_items(self) -> Vec<Media> {
+        self.medias.unwrap_or_default()
+    }
+}
+
 /// 收藏夹服务
 pub struct FavoritesService {
     client: Arc<RwLock<BiliClient>>,
@@ -91,7 +130,17 @@ impl FavoritesService {
     /// - `BiliError::NetworkError`: 网络请求失败
     /// - `BiliError::ApiError`: API返回错误
     pub async fn backup_favorites(&self) -> Result<Vec<FavFolderWithMedia>> {
-        let client = self.client.read().await;
+        self.backup_favorites_with_outcome()
+            .await
+            .map(FetchOutcome::into_items)
+    }
+
+    /// 备份收藏夹,并返回完整性统计
+    ///
+    /// 失效的视频不会出现在收藏夹内容接口中,统计按视频数量汇总所有收藏夹,
+    /// 对比每个收藏夹报告的 `media_count` 与实际获取的数量。
+    pub async fn backup_favorites_with_outcome(&self) -> Result<FetchOutcome<FavFolderWithMedia>> {
+        let client = jobs::bind_client(self.client.read().await.clone());
 
         // 1. 获取用户ID（从Cookie中提取）
         let cookie = client
@@ -111,7 +160,14 @@ impl FavoritesService {
 
         // 3. 遍历每个收藏夹，获取视频列表
         let mut result = Vec::new();
+        let mut completeness = Completeness::new(Some(0), 0);
         for (idx, folder) in folders.iter().enumerate() {
+            jobs::checkpoint().await?;
+            jobs::progress(
+                idx + 1,
+                Some(folders.len()),
+                format!("获取收藏夹 \"{}\"", folder.title),
+            );
             tracing::info!(
                 "正在获取收藏夹 [{}/{}] \"{}\" 的内容...",
                 idx + 1,
@@ -119,12 +175,15 @@ impl FavoritesService {
                 folder.title
             );
 
-            let media_list = self.fetch_folder_media(folder.id, &client).await?;
+            let outcome = self.fetch_folder_media(folder.id, &client).await?;
             tracing::info!(
-                "收藏夹 \"{}\" 包含 {} 个视频",
+                "收藏夹 \"{}\" 包含 {} 个视频 ({})",
                 folder.title,
-                media_list.len()
+                outcome.items.len(),
+                outcome.completeness.summary()
             );
+            completeness.merge(&outcome.completeness);
+            let media_list = outcome.into_items();
 
             result.push(FavFolderWithMedia {
                 folder: folder.clone(),
@@ -136,55 +195,20 @@ impl FavoritesService {
             client.delay_random().await;
         }
 
-        Ok(result)
+        Ok(FetchOutcome {
+            items: result,
+            completeness,
+        })
     }
 
-    /// 获取收藏夹内的所有媒体（分页获取）
-    async fn fetch_folder_media(&self, folder_id: u64, client: &BiliClient) -> Result<Vec<Media>> {
-        let mut all_media = Vec::new();
-        let mut page = 1;
-        let page_size = 20;
-
-        loop {
-            let url = format!(
-                "{}?media_id={}&pn={}&ps={}",
-                API_FAV_RESOURCES, folder_id, page, page_size
-            );
-            let resp = client.get_with_retry(&url).await?;
-            let api_result: ApiResult<serde_json::Value> = resp.json().await?;
-            let data = api_result.into_data()?;
-
-            // 解析媒体列表
-            let medias: Vec<Media> = if let Some(medias_val) = data.get("medias") {
-                if medias_val.is_null() {
-                    // API返回null表示没有更多数据
-                    break;
-                }
-                serde_json::from_value(medias_val.clone())?
-            } else {
-                break;
-            };
-
-            if medias.is_empty() {
-                break;
-            }
-
-            all_media.extend(medias);
-
-            // 检查是否还有更多数据
-            let has_more = data
-                .get("has_more")
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false);
-            if !has_more {
-                break;
-            }
-
-            page += 1;
-            client.delay_random().await;
-        }
-
-        Ok(all_media)
+    /// 获取收藏夹内的所有媒体（分页获取，已知总数时并发获取剩余页）
+    async fn fetch_folder_media(
+        &self,
+        folder_id: u64,
+        client: &BiliClient,
+    ) -> Result<FetchOutcome<Media>> {
+        let base_url = format!("{}?media_id={}", API_FAV_RESOURCES, folder_id);
+        fetch_pages_from::<FavResourcePage, Media>(client, &base_url, 20, 1, None).await
     }
 
     /// 还原收藏夹
@@ -210,7 +234,7 @@ impl FavoritesService {
         folders: Vec<FavFolderWithMedia>,
         options: FavRestoreOptions,
     ) -> Result<RestoreResult> {
-        let client = self.client.read().await;
+        let client = self.client.read().await.clone();
 
         // 验证批量大小
         let batch_size = options.batch_size.min(20);
@@ -220,10 +244,12 @@ impl FavoritesService {
         let mut total_count = 0;
         let mut failed_items = Vec::new();
 
-        // 1. 如果需要清空现有收藏夹
-        if options.clear_existing {
+        // 1. 如果需要清空现有收藏夹 (恢复中断的任务时不再清空已还原的内容)
+        if options.clear_existing && journal::completed(JOURNAL_CLEAR, "all").is_none() {
             tracing::info!("正在清空现有收藏夹...");
-            if let Err(e) = self.clear_all_folders().await {
+            let cleared = self.clear_all_folders().await;
+            journal::record(JOURNAL_CLEAR, "all", &cleared);
+            if let Err(e) = cleared {
                 tracing::warn!("清空收藏夹失败: {}", e);
                 if !options.continue_on_error {
                     return Err(e);
@@ -232,12 +258,16 @@ impl FavoritesService {
         }
 
         // 2. 遍历每个收藏夹进行还原
+        let media_total: usize = folders.iter().map(|f| f.media_list.len()).sum();
         for folder_data in folders {
+            jobs::checkpoint().await?;
             tracing::info!("正在还原收藏夹: \"{}\"", folder_data.folder.title);
 
             // 2.1 创建收藏夹
             let folder_id = match self
-                .create_folder(
+                .create_folder_journaled(
+                    folder_data.folder.id,
+                    0,
                     &folder_data.folder.title,
                     &folder_data.intro.clone().unwrap_or_default(),
                     if (folder_data.folder.attr & 1) == 1 {
@@ -286,7 +316,9 @@ impl FavoritesService {
                     // 超过容量时创建新收藏夹
                     let new_title = format!("{} ({})", folder_data.folder.title, chunk_idx + 1);
                     match self
-                        .create_folder(
+                        .create_folder_journaled(
+                            folder_data.folder.id,
+                            chunk_idx,
                             &new_title,
                             &folder_data.intro.clone().unwrap_or_default(),
                             if (folder_data.folder.attr & 1) == 1 {
@@ -311,11 +343,27 @@ impl FavoritesService {
                 };
 
                 // 2.4 批量添加视频到收藏夹
-                for batch in chunk.chunks(batch_size) {
+                for (batch_idx, batch) in chunk.chunks(batch_size).enumerate() {
+                    jobs::checkpoint().await?;
                     let media_ids: Vec<i64> = batch.iter().map(|m| m.id as i64).collect();
                     total_count += media_ids.len();
+                    jobs::progress(
+                        total_count,
+                        Some(media_total),
+                        format!("还原收藏夹 \"{}\"", folder_data.folder.title),
+                    );
+
+                    // 恢复中断的任务时跳过已添加的批次
+                    let batch_key =
+                        format!("{}:{}:{}", folder_data.folder.id, chunk_idx, batch_idx);
+                    if journal::completed(JOURNAL_ADD, &batch_key).is_some() {
+                        success_count += media_ids.len();
+                        continue;
+                    }
 
-                    match self.add_to_folder(current_folder_id, media_ids.clone()).await {
+                    let added = self.add_to_folder(current_folder_id, media_ids.clone()).await;
+                    journal::record(JOURNAL_ADD, &batch_key, &added);
+                    match added {
                         Ok(_) => {
                             success_count += media_ids.len();
                             tracing::debug!("成功添加 {} 个视频到收藏夹", media_ids.len());
@@ -369,7 +417,7 @@ impl FavoritesService {
     /// - `BiliError::NetworkError`: 网络请求失败
     /// - `BiliError::ApiError`: API返回错误
     pub async fn clear_all_folders(&self) -> Result<usize> {
-        let client = self.client.read().await;
+        let client = jobs::bind_client(self.client.read().await.clone());
 
         // 获取用户ID
         let cookie = client
@@ -387,16 +435,24 @@ impl FavoritesService {
         let mut cleared_count = 0;
 
         // 遍历每个收藏夹清空内容
-        for folder in folders {
+        let folder_count = folders.len();
+        for (idx, folder) in folders.into_iter().enumerate() {
+            jobs::checkpoint().await?;
+            jobs::progress(
+                idx + 1,
+                Some(folder_count),
+                format!("清空收藏夹 \"{}\"", folder.title),
+            );
             tracing::info!("正在清空收藏夹: \"{}\"", folder.title);
 
-            let media_list = self.fetch_folder_media(folder.id, &client).await?;
+            let media_list = self.fetch_folder_media(folder.id, &client).await?.into_items();
             if media_list.is_empty() {
                 continue;
             }
 
             // 批量删除（每次最多20个）
             for batch in media_list.chunks(20) {
+                jobs::checkpoint().await?;
                 let resources: Vec<String> = batch
                     .iter()
                     .map(|m| format!("{}:{}", m.id, m.item_type))
@@ -427,6 +483,45 @@ impl FavoritesService {
         Ok(cleared_count)
     }
 
+    /// 导出收藏夹到JSON Lines文件
+    ///
+    /// 每个收藏夹的基础信息占一行,其中的每个视频再各占一行。提供口令时文件会被加密。
+    ///
+    /// # 参数
+    ///
+    /// * `folders` - 收藏夹列表
+    /// * `file_path` - 导出文件路径
+    /// * `passphrase` - 加密口令,None表示不加密
+    pub async fn export_to_file(
+        &self,
+        folders: &[FavFolderWithMedia],
+        file_path: &str,
+        passphrase: Option<&str>,
+    ) -> Result<()> {
+        jsonl::export_jsonl(file_path, BackupModule::Favorites, folders, passphrase).await?;
+
+        tracing::info!("收藏夹已导出到: {}", file_path);
+        Ok(())
+    }
+
+    /// 从导出文件导入收藏夹
+    ///
+    /// # 参数
+    ///
+    /// * `file_path` - 导入文件路径
+    /// * `passphrase` - 解密口令,文件未加密时忽略
+    pub async fn import_from_file(
+        &self,
+        file_path: &str,
+        passphrase: Option<&str>,
+    ) -> Result<Vec<FavFolderWithMedia>> {
+        let folders: Vec<FavFolderWithMedia> =
+            jsonl::import_jsonl(file_path, BackupModule::Favorites, passphrase).await?;
+
+        tracing::info!("从 {} 导入了 {} 个收藏夹", file_path, folders.len());
+        Ok(folders)
+    }
+
     /// 创建收藏夹
     ///
     /// # 参数
@@ -469,6 +564,33 @@ impl FavoritesService {
         Ok(folder_id)
     }
 
+    /// 创建收藏夹并记录到操作日志
+    ///
+    /// 恢复中断的任务时,已创建的收藏夹直接使用日志中记录的ID,不会重复创建。
+    ///
+    /// # 参数
+    ///
+    /// * `source_id` - 备份中的收藏夹ID
+    /// * `part` - 超过容量上限时的分片序号
+    /// * `title`, `intro`, `privacy` - 同 [`create_folder`](Self::create_folder)
+    async fn create_folder_journaled(
+        &self,
+        source_id: u64,
+        part: usize,
+        title: &str,
+        intro: &str,
+        privacy: i32,
+    ) -> Result<i64> {
+        let key = format!("{}:{}", source_id, part);
+        let created_id = journal::completed(JOURNAL_CREATE_FOLDER, &key).and_then(|id| id.as_i64());
+        if let Some(id) = created_id {
+            return Ok(id);
+        }
+        let created = self.create_folder(title, intro, privacy).await;
+        journal::record(JOURNAL_CREATE_FOLDER, &key, &created);
+        created
+    }
+
     /// 添加视频到收藏夹
     ///
     /// # 参数
@@ -534,6 +656,18 @@ mod tests {
         assert_eq!(options.batch_size, 20);
     }
 
+    #[test]
+    fn test_fav_resource_page_items() {
+        let page: FavResourcePage = serde_json::from_value(serde_json::json!({
+            "info": {"id": 1, "media_count": 45},
+            "medias": null,
+            "has_more": false
+        }))
+        .unwrap();
+        assert_eq!(page.total(), Some(45));
+        assert!(page.into_items().is_empty());
+    }
+
     #[test]
     fn test_fav_folder_with_media_serialization() {
         let folder_data = FavFolderWithMedia {
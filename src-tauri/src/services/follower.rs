@@ -12,8 +12,9 @@ use crate::api::{
     endpoints::*,
     error::{BiliError, Result},
     models::*,
-    pagination::fetch_all_pages,
+    pagination::{fetch_all_pages_with_outcome, FetchOutcome},
 };
+use crate::jobs;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -69,7 +70,16 @@ impl FollowerService {
     /// # }
     /// ```
     pub async fn backup_followers(&self) -> Result<Vec<Relation>> {
-        let client = self.client.read().await;
+        self.backup_followers_with_outcome()
+            .await
+            .map(FetchOutcome::into_items)
+    }
+
+    /// 备份粉丝列表,并返回完整性统计
+    ///
+    /// B站粉丝列表最多只能获取1000个,超出部分会在完整性统计中标记为截断。
+    pub async fn backup_followers_with_outcome(&self) -> Result<FetchOutcome<Relation>> {
+        let client = jobs::bind_client(self.client.read().await.clone());
 
         // 1. 获取当前用户信息
         let nav_info = self.get_user_info(&client).await?;
@@ -77,10 +87,10 @@ impl FollowerService {
 
         // 2. 分页获取所有粉丝
         let base_url = format!("{}?vmid={}", API_FOLLOWER_LIST, mid);
-        let followers = fetch_all_pages::<Relation>(&client, &base_url, 50, None).await?;
+        let outcome = fetch_all_pages_with_outcome::<Relation>(&client, &base_url, 50, None).await?;
 
-        tracing::info!("备份了 {} 个粉丝", followers.len());
-        Ok(followers)
+        tracing::info!("备份了 {} 个粉丝 ({})", outcome.items.len(), outcome.completeness.summary());
+        Ok(outcome)
     }
 
     // ==================== 私有辅助方法 ====================
@@ -17,13 +17,20 @@ use crate::api::{
     endpoints::*,
     error::{BiliError, Result},
     models::*,
-    pagination::fetch_all_pages,
+    pagination::{fetch_all_pages_with_outcome, FetchOutcome},
 };
+use crate::jobs::{self, journal};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// 操作日志中关注用户的动作名
+const JOURNAL_FOLLOW: &str = "follow";
+
+/// 操作日志中设置关注用户分组的动作名
+const JOURNAL_FOLLOW_TAG: &str = "follow_tag";
+
 /// 关注服务
 pub struct FollowingService {
     client: Arc<RwLock<BiliClient>>,
@@ -124,7 +131,17 @@ impl FollowingService {
     /// # }
     /// ```
     pub async fn backup_following(&self) -> Result<Vec<Relation>> {
-        let client = self.client.read().await;
+        self.backup_following_with_outcome()
+            .await
+            .map(FetchOutcome::into_items)
+    }
+
+    /// 备份关注列表,并返回完整性统计
+    ///
+    /// B站会静默截断部分关注列表,结果中的 [`FetchOutcome::completeness`]
+    /// 记录了接口报告的总数与实际获取的数量。
+    pub async fn backup_following_with_outcome(&self) -> Result<FetchOutcome<Relation>> {
+        let client = jobs::bind_client(self.client.read().await.clone());
 
         // 1. 获取当前用户信息
         let nav_info = self.get_user_info(&client).await?;
@@ -132,10 +149,10 @@ impl FollowingService {
 
         // 2. 分页获取所有关注
         let base_url = format!("{}?vmid={}&order=attention", API_FOLLOWING_LIST, mid);
-        let relations = fetch_all_pages::<Relation>(&client, &base_url, 50, None).await?;
+        let outcome = fetch_all_pages_with_outcome::<Relation>(&client, &base_url, 50, None).await?;
 
-        tracing::info!("备份了 {} 个关注", relations.len());
-        Ok(relations)
+        tracing::info!("备份了 {} 个关注 ({})", outcome.items.len(), outcome.completeness.summary());
+        Ok(outcome)
     }
 
     /// 还原关注列表
@@ -168,13 +185,10 @@ impl FollowingService {
         relations: Vec<Relation>,
         options: RestoreOptions,
     ) -> Result<FollowingRestoreResult> {
-        let mut client = self.client.write().await;
-
-        // 配置延迟
+        // 延迟只配置在副本上,还原期间不占用共享客户端的锁
+        let mut client = self.client.read().await.clone();
         if let Some((min_ms, max_ms)) = options.delay_ms {
-            *client = client
-                .clone()
-                .with_delay_range(min_ms, max_ms);
+            client = client.with_delay_range(min_ms, max_ms);
         }
 
         // 1. 获取所有旧分组
@@ -200,14 +214,82 @@ impl FollowingService {
         };
 
         // 3. 批量关注
+        self.follow_all(&client, relations, &options, tag_mapping).await
+    }
+
+    /// 还原关注分组
+    ///
+    /// 按名称匹配当前账号已有的分组,不存在的分组会被创建。
+    /// 用于在还原关注列表之前先还原带有原始名称的分组。
+    ///
+    /// # 参数
+    ///
+    /// * `tags` - 备份的分组列表
+    ///
+    /// # 返回
+    ///
+    /// Result<HashMap<i64, i64>, BiliError> - 分组映射 (旧ID -> 新ID)
+    pub async fn restore_tags(&self, tags: Vec<RelationTag>) -> Result<HashMap<i64, i64>> {
+        let client = self.client.read().await;
+        self.build_tag_mapping(&client, tags).await
+    }
+
+    /// 使用已有的分组映射还原关注列表
+    ///
+    /// 与 [`restore_following`](Self::restore_following) 相同,但不再建立分组映射,
+    /// 而是使用 [`restore_tags`](Self::restore_tags) 返回的映射设置分组。
+    ///
+    /// # 参数
+    ///
+    /// * `relations` - 备份的关注列表
+    /// * `options` - 还原选项
+    /// * `tag_mapping` - 分组映射 (旧ID -> 新ID)
+    pub async fn restore_following_with_mapping(
+        &self,
+        relations: Vec<Relation>,
+        options: RestoreOptions,
+        tag_mapping: HashMap<i64, i64>,
+    ) -> Result<FollowingRestoreResult> {
+        // 延迟只配置在副本上,还原期间不占用共享客户端的锁
+        let mut client = self.client.read().await.clone();
+        if let Some((min_ms, max_ms)) = options.delay_ms {
+            client = client.with_delay_range(min_ms, max_ms);
+        }
+
+        self.follow_all(&client, relations, &options, tag_mapping).await
+    }
+
+    /// 批量关注并设置分组
+    async fn follow_all(
+        &self,
+        client: &BiliClient,
+        relations: Vec<Relation>,
+        options: &RestoreOptions,
+        tag_mapping: HashMap<i64, i64>,
+    ) -> Result<FollowingRestoreResult> {
         let mut success_count = 0;
         let mut failed_count = 0;
         let mut failures = Vec::new();
+        let total = relations.len();
+        let mut processed = 0;
 
         for chunk in relations.chunks(options.batch_size) {
             for relation in chunk {
-                // 关注用户
-                match self.follow_user(&client, relation.mid).await {
+                jobs::checkpoint().await?;
+                processed += 1;
+                jobs::progress(processed, Some(total), format!("关注 {}", relation.uname));
+
+                // 恢复中断的任务时不再重复关注,但仍补上未完成的分组设置
+                let resumed = journal::completed(JOURNAL_FOLLOW, relation.mid).is_some();
+                let mut requested = !resumed;
+                let followed = if resumed {
+                    Ok(())
+                } else {
+                    let followed = self.follow_user(client, relation.mid).await;
+                    journal::record(JOURNAL_FOLLOW, relation.mid, &followed);
+                    followed
+                };
+                match followed {
                     Ok(_) => {
                         success_count += 1;
 
@@ -218,11 +300,15 @@ impl FollowingService {
                                 .filter_map(|old_id| tag_mapping.get(old_id).copied())
                                 .collect();
 
-                            if !new_tag_ids.is_empty() {
-                                if let Err(e) = self
-                                    .add_users_to_tags(&client, vec![relation.mid], new_tag_ids)
-                                    .await
-                                {
+                            if !new_tag_ids.is_empty()
+                                && journal::completed(JOURNAL_FOLLOW_TAG, relation.mid).is_none()
+                            {
+                                requested = true;
+                                let tagged = self
+                                    .add_users_to_tags(client, vec![relation.mid], new_tag_ids)
+                                    .await;
+                                journal::record(JOURNAL_FOLLOW_TAG, relation.mid, &tagged);
+                                if let Err(e) = tagged {
                                     tracing::warn!(
                                         "设置用户 {} 分组失败: {}",
                                         relation.mid,
@@ -249,8 +335,10 @@ impl FollowingService {
                     }
                 }
 
-                // 延迟
-                client.delay_random().await;
+                // 延迟 (没有发出请求时不必等待)
+                if requested {
+                    client.delay_random().await;
+                }
             }
         }
 
@@ -281,7 +369,7 @@ impl FollowingService {
     /// # }
     /// ```
     pub async fn clear_following(&self) -> Result<FollowingClearResult> {
-        let client = self.client.read().await;
+        let client = self.client.read().await.clone();
 
         // 1. 获取所有关注
         let relations = self.backup_following().await?;
@@ -290,8 +378,12 @@ impl FollowingService {
         let mut success_count = 0;
         let mut failed_count = 0;
         let mut failures = Vec::new();
+        let total = relations.len();
+
+        for (index, relation) in relations.into_iter().enumerate() {
+            jobs::checkpoint().await?;
+            jobs::progress(index + 1, Some(total), format!("取消关注 {}", relation.uname));
 
-        for relation in relations {
             match self.unfollow_user(&client, relation.mid).await {
                 Ok(_) => success_count += 1,
                 Err(e) => {
@@ -358,7 +450,15 @@ impl FollowingService {
     /// ```
     pub async fn create_tag(&self, tag_name: &str) -> Result<i64> {
         let client = self.client.read().await;
+        self.create_tag_with(&client, tag_name).await
+    }
+
+    // ==================== 私有辅助方法 ====================
 
+    /// 使用已持有的客户端创建分组
+    ///
+    /// 还原时客户端的锁已被持有,不能再通过 [`create_tag`](Self::create_tag) 重新加锁。
+    async fn create_tag_with(&self, client: &BiliClient, tag_name: &str) -> Result<i64> {
         let form = vec![("tag".to_string(), tag_name.to_string())];
         let response = client.post_form_with_retry(API_TAG_CREATE, &form).await?;
 
@@ -373,8 +473,6 @@ impl FollowingService {
         Ok(tag_id)
     }
 
-    // ==================== 私有辅助方法 ====================
-
     /// 获取用户信息
     async fn get_user_info(&self, client: &BiliClient) -> Result<NavInfo> {
         let response = client.get_with_retry(API_NAV).await?;
@@ -513,7 +611,7 @@ impl FollowingService {
                 tracing::info!("分组映射: {} ({} -> {})", old_tag.name, old_tag.tag_id, new_id);
             } else {
                 // 创建新分组
-                match self.create_tag(&old_tag.name).await {
+                match self.create_tag_with(client, &old_tag.name).await {
                     Ok(new_id) => {
                         mapping.insert(old_tag.tag_id, new_id);
                         tracing::info!(
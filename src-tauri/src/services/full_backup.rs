@@ -0,0 +1,388 @@
+//! 一键完整备份服务
+//!
+//! 按顺序备份选定的模块并写入单个备份归档。
+//! 单个模块失败不会中断整个备份,每个模块的结果 (数量、耗时、错误) 都会记录在报告中。
+
+use crate::api::{
+    client::BiliClient,
+    error::{BiliError, Result},
+    pagination::{Completeness, FetchOutcome},
+};
+use crate::backup::{self, BackupArchive, BackupData, BackupManifest, BackupModule, BackupSource};
+use crate::jobs::{self, JobLogLevel};
+use crate::services::{
+    BangumiService, BlacklistService, FavoritesService, FollowerService, FollowingService,
+    HistoryService, ToViewService,
+};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::RwLock;
+
+/// 完整备份时获取的追番类型 (1:追番 2:追剧)
+const BANGUMI_TYPES: [i32; 2] = [1, 2];
+
+/// 单个模块的备份结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModuleBackupResult {
+    /// 模块
+    pub module: BackupModule,
+    /// 是否成功
+    pub success: bool,
+    /// 备份的数据项数量 (失败时为None)
+    pub count: Option<usize>,
+    /// 完整性统计
+    pub completeness: Option<Completeness>,
+    /// 耗时 (毫秒)
+    pub duration_ms: u64,
+    /// 失败原因
+    pub error: Option<String>,
+}
+
+/// 完整备份报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FullBackupReport {
+    /// 归档文件路径
+    pub file_path: String,
+    /// 写入的清单
+    pub manifest: BackupManifest,
+    /// 各模块的备份结果 (按备份顺序)
+    pub modules: Vec<ModuleBackupResult>,
+    /// 成功的模块数量
+    pub succeeded: usize,
+    /// 失败的模块数量
+    pub failed: usize,
+    /// 总耗时 (毫秒)
+    pub duration_ms: u64,
+}
+
+/// 单个模块获取到的数据
+pub(crate) struct ModuleFetch {
+    pub(crate) data: BackupData,
+    pub(crate) completeness: Option<Completeness>,
+}
+
+impl ModuleFetch {
+    fn new(data: BackupData, completeness: Option<Completeness>) -> Self {
+        Self { data, completeness }
+    }
+}
+
+/// 一键完整备份服务
+pub struct FullBackupService {
+    client: Arc<RwLock<BiliClient>>,
+    following: FollowingService,
+    follower: FollowerService,
+    blacklist: BlacklistService,
+    favorites: FavoritesService,
+    history: HistoryService,
+    bangumi: BangumiService,
+    toview: ToViewService,
+}
+
+impl FullBackupService {
+    /// 创建完整备份服务
+    ///
+    /// 各模块的服务共用同一个HTTP客户端。
+    ///
+    /// # 参数
+    ///
+    /// * `client` - HTTP客户端 (需要已登录)
+    pub fn new(client: Arc<RwLock<BiliClient>>) -> Self {
+        Self {
+            following: FollowingService::new(client.clone()),
+            follower: FollowerService::new(client.clone()),
+            blacklist: BlacklistService::new(client.clone()),
+            favorites: FavoritesService::new(client.clone()),
+            history: HistoryService::new(client.clone()),
+            bangumi: BangumiService::new(client.clone()),
+            toview: ToViewService::new(client.clone()),
+            client,
+        }
+    }
+
+    /// 备份选定的模块并写入归档
+    ///
+    /// 模块按 [`BackupModule::ALL`] 的顺序备份,失败的模块记录错误后继续下一个模块。
+    /// 至少有一个模块成功时写入归档,归档只包含成功的模块。
+    ///
+    /// # 参数
+    ///
+    /// * `modules` - 要备份的模块,为空时备份全部模块
+    /// * `file_path` - 归档文件路径
+    /// * `passphrase` - 加密口令,None表示不加密
+    ///
+    /// # 错误
+    ///
+    /// - 未登录
+    /// - 所有模块都备份失败
+    /// - 写入归档失败
+    /// - 任务已取消
+    pub async fn run(
+        &self,
+        modules: &[BackupModule],
+        file_path: &str,
+        passphrase: Option<&str>,
+    ) -> Result<FullBackupReport> {
+        let started = Instant::now();
+        let (archive, results) = self.backup(modules).await?;
+        let succeeded = results.iter().filter(|r| r.success).count();
+
+        let manifest = backup::write_archive(file_path, &archive, passphrase).await?;
+        let report = FullBackupReport {
+            file_path: file_path.to_string(),
+            manifest,
+            failed: results.len() - succeeded,
+            succeeded,
+            modules: results,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+
+        tracing::info!(
+            "完整备份完成: {} 个模块成功，{} 个模块失败，已写入 {}",
+            report.succeeded,
+            report.failed,
+            file_path
+        );
+        Ok(report)
+    }
+
+    /// 备份选定的模块,返回内存中的归档
+    ///
+    /// 与 [`FullBackupService::run`] 相同,但不写入文件,
+    /// 归档只包含成功的模块,各模块的结果按备份顺序返回。
+    ///
+    /// # 错误
+    ///
+    /// - 未登录
+    /// - 所有模块都备份失败
+    /// - 任务已取消
+    pub async fn backup(
+        &self,
+        modules: &[BackupModule],
+    ) -> Result<(BackupArchive, Vec<ModuleBackupResult>)> {
+        let mut archive = BackupArchive::new(self.source().await?);
+        let modules = ordered_modules(modules);
+
+        tracing::info!("开始完整备份，共 {} 个模块", modules.len());
+        let results = run_modules(&modules, &mut archive, |module| self.fetch_module(module)).await;
+        // 任务被取消时不返回只包含部分模块的归档
+        jobs::checkpoint().await?;
+
+        if results.iter().all(|r| !r.success) {
+            return Err(BiliError::business("所有模块都备份失败"));
+        }
+        Ok((archive, results))
+    }
+
+    /// 当前登录账号
+    pub(crate) async fn source(&self) -> Result<BackupSource> {
+        let client = self.client.read().await;
+        let cookie = client
+            .get_cookie()
+            .ok_or_else(|| BiliError::auth("未登录"))?;
+        let uid = BiliClient::parse_cookie_field(cookie, "DedeUserID")
+            .and_then(|uid| uid.parse().ok())
+            .ok_or_else(|| BiliError::auth("无法从Cookie获取用户ID"))?;
+        Ok(BackupSource { uid, uname: None })
+    }
+
+    /// 获取单个模块的数据
+    pub(crate) async fn fetch_module(&self, module: BackupModule) -> Result<ModuleFetch> {
+        let mut data = BackupData::default();
+        let completeness = match module {
+            BackupModule::RelationTags => {
+                data.relation_tags = Some(self.following.get_relation_tags().await?);
+                None
+            }
+            BackupModule::Following => {
+                let outcome = self.following.backup_following_with_outcome().await?;
+                data.following = Some(outcome.items);
+                Some(outcome.completeness)
+            }
+            BackupModule::Followers => {
+                let outcome = self.follower.backup_followers_with_outcome().await?;
+                data.followers = Some(outcome.items);
+                Some(outcome.completeness)
+            }
+            BackupModule::Blacklist => {
+                let outcome = self.blacklist.backup_blacklist_with_outcome().await?;
+                data.blacklist = Some(outcome.items);
+                Some(outcome.completeness)
+            }
+            BackupModule::Favorites => {
+                let outcome = self.favorites.backup_favorites_with_outcome().await?;
+                data.favorites = Some(outcome.items);
+                Some(outcome.completeness)
+            }
+            BackupModule::History => {
+                let outcome = self.history.backup_history_with_outcome().await?;
+                data.history = Some(outcome.items);
+                Some(outcome.completeness)
+            }
+            BackupModule::Bangumi => {
+                let mut merged = FetchOutcome::new(Vec::new(), Some(0));
+                for type_ in BANGUMI_TYPES {
+                    let outcome = self.bangumi.backup_bangumi_with_outcome(type_).await?;
+                    merged.completeness.merge(&outcome.completeness);
+                    merged.items.extend(outcome.items);
+                }
+                data.bangumi = Some(merged.items);
+                Some(merged.completeness)
+            }
+            BackupModule::ToView => {
+                let outcome = self.toview.backup_toview_with_outcome().await?;
+                data.toview = Some(outcome.items);
+                Some(outcome.completeness)
+            }
+        };
+        Ok(ModuleFetch::new(data, completeness))
+    }
+}
+
+/// 去重并按 [`BackupModule::ALL`] 的顺序排列 (为空时返回全部模块)
+fn ordered_modules(modules: &[BackupModule]) -> Vec<BackupModule> {
+    BackupModule::ALL
+        .into_iter()
+        .filter(|module| modules.is_empty() || modules.contains(module))
+        .collect()
+}
+
+/// 依次备份各模块,失败的模块记录错误后继续
+async fn run_modules<F, Fut>(
+    modules: &[BackupModule],
+    archive: &mut BackupArchive,
+    mut fetch: F,
+) -> Vec<ModuleBackupResult>
+where
+    F: FnMut(BackupModule) -> Fut,
+    Fut: Future<Output = Result<ModuleFetch>>,
+{
+    let mut results = Vec::with_capacity(modules.len());
+    for &module in modules {
+        if jobs::checkpoint().await.is_err() {
+            break;
+        }
+        let started = Instant::now();
+        let result = fetch(module).await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        let result = match result {
+            Ok(fetched) => {
+                let count = fetched.data.count(module);
+                archive.data.merge(fetched.data);
+                if let Some(ref completeness) = fetched.completeness {
+                    archive.completeness.insert(module, completeness.clone());
+                }
+                tracing::info!(
+                    "{}备份成功: {} 项",
+                    module.display_name(),
+                    count.unwrap_or(0)
+                );
+                jobs::log(
+                    JobLogLevel::Info,
+                    format!(
+                        "{}备份成功: {} 项",
+                        module.display_name(),
+                        count.unwrap_or(0)
+                    ),
+                );
+                ModuleBackupResult {
+                    module,
+                    success: true,
+                    count,
+                    completeness: fetched.completeness,
+                    duration_ms,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                tracing::warn!("{}备份失败: {}", module.display_name(), e);
+                jobs::log(
+                    JobLogLevel::Error,
+                    format!("{}备份失败: {}", module.display_name(), e),
+                );
+                ModuleBackupResult {
+                    module,
+                    success: false,
+                    count: None,
+                    completeness: None,
+                    duration_ms,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        results.push(result);
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::models::RelationTag;
+
+    #[test]
+    fn test_ordered_modules() {
+        assert_eq!(ordered_modules(&[]), BackupModule::ALL.to_vec());
+        assert_eq!(
+            ordered_modules(&[
+                BackupModule::ToView,
+                BackupModule::Following,
+                BackupModule::ToView
+            ]),
+            vec![BackupModule::Following, BackupModule::ToView]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_modules_continues_after_failure() {
+        let mut archive = BackupArchive::new(BackupSource {
+            uid: 1,
+            uname: None,
+        });
+        let modules = [
+            BackupModule::RelationTags,
+            BackupModule::Following,
+            BackupModule::ToView,
+        ];
+
+        let results = run_modules(&modules, &mut archive, |module| async move {
+            match module {
+                BackupModule::RelationTags => {
+                    let data = BackupData {
+                        relation_tags: Some(vec![RelationTag::new(1, "特别关注".to_string())]),
+                        ..Default::default()
+                    };
+                    Ok(ModuleFetch::new(data, None))
+                }
+                BackupModule::Following => Err(BiliError::api("请求过于频繁")),
+                _ => {
+                    let data = BackupData {
+                        toview: Some(Vec::new()),
+                        ..Default::default()
+                    };
+                    Ok(ModuleFetch::new(data, Some(Completeness::new(Some(0), 0))))
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(
+            results.iter().map(|r| r.success).collect::<Vec<_>>(),
+            vec![true, false, true]
+        );
+        assert_eq!(results[0].count, Some(1));
+        assert!(results[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("请求过于频繁"));
+        assert_eq!(
+            archive.data.modules(),
+            vec![BackupModule::RelationTags, BackupModule::ToView]
+        );
+        assert!(archive.completeness.contains_key(&BackupModule::ToView));
+    }
+}
@@ -0,0 +1,474 @@
+//! 一键完整还原服务
+//!
+//! 把备份归档中选定的模块还原到当前账号,并汇总为一份报告。
+//! 模块按依赖顺序还原: 先还原关注分组再还原关注列表 (关注时才能设置分组),
+//! 收藏夹先创建收藏夹再添加内容,黑名单最后还原 (拉黑会取消关注)。
+
+use crate::api::{
+    client::BiliClient,
+    error::{BiliError, Result},
+    models::RestoreResult,
+};
+use crate::backup::{BackupArchive, BackupData, BackupModule};
+use crate::jobs::{self, JobLogLevel};
+use crate::services::{
+    BangumiService, BlacklistRestoreOptions, BlacklistRestoreResult, BlacklistService,
+    FavRestoreOptions, FavoritesService, FollowingRestoreResult, FollowingService, RestoreOptions,
+    ToViewService,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::RwLock;
+
+/// 还原顺序
+///
+/// 粉丝和历史记录无法通过接口还原,排在最后并标记为跳过。
+const RESTORE_ORDER: [BackupModule; 8] = [
+    BackupModule::RelationTags,
+    BackupModule::Following,
+    BackupModule::Favorites,
+    BackupModule::ToView,
+    BackupModule::Bangumi,
+    BackupModule::Blacklist,
+    BackupModule::Followers,
+    BackupModule::History,
+];
+
+/// 完整还原选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FullRestoreOptions {
+    /// 要还原的模块,为空时还原归档中的全部模块
+    #[serde(default)]
+    pub modules: Vec<BackupModule>,
+    /// 关注列表还原选项
+    #[serde(default)]
+    pub following: RestoreOptions,
+    /// 收藏夹还原选项
+    #[serde(default)]
+    pub favorites: FavRestoreOptions,
+    /// 黑名单还原选项
+    #[serde(default)]
+    pub blacklist: BlacklistRestoreOptions,
+}
+
+/// 模块还原状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleRestoreStatus {
+    /// 已还原 (单个数据项失败记录在 `failures` 中)
+    Completed,
+    /// 还原中途出错
+    Failed,
+    /// 未还原 (归档中没有该模块,或该模块不支持还原)
+    Skipped,
+}
+
+/// 单个模块的还原结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModuleRestoreResult {
+    /// 模块
+    pub module: BackupModule,
+    /// 还原状态
+    pub status: ModuleRestoreStatus,
+    /// 待还原的数据项数量
+    pub total: usize,
+    /// 还原成功的数量
+    pub success_count: usize,
+    /// 还原失败的数量
+    pub failed_count: usize,
+    /// 失败的数据项及原因
+    pub failures: Vec<String>,
+    /// 耗时 (毫秒)
+    pub duration_ms: u64,
+    /// 出错或跳过的原因
+    pub message: Option<String>,
+}
+
+/// 完整还原报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FullRestoreReport {
+    /// 各模块的还原结果 (按还原顺序)
+    pub modules: Vec<ModuleRestoreResult>,
+    /// 还原成功的数据项总数
+    pub success_count: usize,
+    /// 还原失败的数据项总数
+    pub failed_count: usize,
+    /// 总耗时 (毫秒)
+    pub duration_ms: u64,
+}
+
+/// 各模块还原结果的统一形式
+#[derive(Debug, Default, PartialEq)]
+struct RestoreCounts {
+    total: usize,
+    success_count: usize,
+    failed_count: usize,
+    failures: Vec<String>,
+}
+
+impl RestoreCounts {
+    fn from_following(total: usize, result: FollowingRestoreResult) -> Self {
+        Self {
+            total,
+            success_count: result.success_count,
+            failed_count: result.failed_count,
+            failures: format_failures(result.failures),
+        }
+    }
+
+    fn from_blacklist(total: usize, result: BlacklistRestoreResult) -> Self {
+        Self {
+            total,
+            success_count: result.success_count,
+            failed_count: result.failed_count,
+            failures: format_failures(result.failures),
+        }
+    }
+}
+
+impl From<RestoreResult> for RestoreCounts {
+    fn from(result: RestoreResult) -> Self {
+        Self {
+            total: result.total_count,
+            success_count: result.success_count,
+            failed_count: result.failed_count,
+            failures: result.failed_items.unwrap_or_default(),
+        }
+    }
+}
+
+fn format_failures(failures: Vec<(u64, String)>) -> Vec<String> {
+    failures
+        .into_iter()
+        .map(|(mid, error)| format!("{}: {}", mid, error))
+        .collect()
+}
+
+/// 一键完整还原服务
+pub struct FullRestoreService {
+    following: FollowingService,
+    favorites: FavoritesService,
+    toview: ToViewService,
+    bangumi: BangumiService,
+    blacklist: BlacklistService,
+}
+
+impl FullRestoreService {
+    /// 创建完整还原服务
+    ///
+    /// 各模块的服务共用同一个HTTP客户端。
+    ///
+    /// # 参数
+    ///
+    /// * `client` - HTTP客户端 (需要已登录)
+    pub fn new(client: Arc<RwLock<BiliClient>>) -> Self {
+        Self {
+            following: FollowingService::new(client.clone()),
+            favorites: FavoritesService::new(client.clone()),
+            toview: ToViewService::new(client.clone()),
+            bangumi: BangumiService::new(client.clone()),
+            blacklist: BlacklistService::new(client),
+        }
+    }
+
+    /// 还原归档中选定的模块
+    ///
+    /// 模块按依赖顺序还原,每个模块使用各自的还原选项。
+    /// 单个模块出错时记录错误并继续还原其余模块。
+    /// 关注分组还原成功时,关注列表使用分组的原始名称设置分组。
+    ///
+    /// # 参数
+    ///
+    /// * `archive` - 备份归档
+    /// * `options` - 还原选项
+    pub async fn run(
+        &self,
+        archive: BackupArchive,
+        options: FullRestoreOptions,
+    ) -> FullRestoreReport {
+        self.restore_data(archive.data, options).await
+    }
+
+    /// 还原备份数据中选定的模块
+    ///
+    /// 与 [`FullRestoreService::run`] 相同,但直接使用备份数据。
+    /// 在任务中运行且任务被取消时,不再还原后续模块,报告只包含已处理的模块。
+    ///
+    /// # 参数
+    ///
+    /// * `data` - 备份数据
+    /// * `options` - 还原选项
+    pub async fn restore_data(
+        &self,
+        mut data: BackupData,
+        options: FullRestoreOptions,
+    ) -> FullRestoreReport {
+        let started = Instant::now();
+        let mut tag_mapping: Option<HashMap<i64, i64>> = None;
+        let mut results = Vec::new();
+
+        for module in restore_order(&options.modules) {
+            if jobs::checkpoint().await.is_err() {
+                break;
+            }
+            let result = self
+                .restore_module(module, &mut data, &options, &mut tag_mapping)
+                .await;
+            match result.status {
+                ModuleRestoreStatus::Completed => jobs::log(
+                    JobLogLevel::Info,
+                    format!(
+                        "{}还原完成: 成功 {} 项，失败 {} 项",
+                        module.display_name(),
+                        result.success_count,
+                        result.failed_count
+                    ),
+                ),
+                ModuleRestoreStatus::Failed => jobs::log(
+                    JobLogLevel::Error,
+                    format!(
+                        "{}还原失败: {}",
+                        module.display_name(),
+                        result.message.as_deref().unwrap_or_default()
+                    ),
+                ),
+                ModuleRestoreStatus::Skipped => {}
+            }
+            results.push(result);
+        }
+
+        let report = FullRestoreReport {
+            success_count: results.iter().map(|r| r.success_count).sum(),
+            failed_count: results.iter().map(|r| r.failed_count).sum(),
+            modules: results,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+        tracing::info!(
+            "完整还原完成: 成功 {} 项，失败 {} 项",
+            report.success_count,
+            report.failed_count
+        );
+        report
+    }
+
+    /// 还原单个模块
+    ///
+    /// 模块数据从 `data` 中取出;关注分组还原后的分组映射写入 `tag_mapping`,
+    /// 还原关注列表时使用。映射不会被清除,关注列表中途中断后继续时仍按原分组还原。
+    pub(crate) async fn restore_module(
+        &self,
+        module: BackupModule,
+        data: &mut BackupData,
+        options: &FullRestoreOptions,
+        tag_mapping: &mut Option<HashMap<i64, i64>>,
+    ) -> ModuleRestoreResult {
+        let started = Instant::now();
+        let outcome = match module {
+            BackupModule::RelationTags => match data.relation_tags.take() {
+                Some(tags) => {
+                    let total = tags.len();
+                    let mapping = self.following.restore_tags(tags).await;
+                    Some(mapping.map(|mapping| {
+                        let counts = RestoreCounts {
+                            total,
+                            success_count: mapping.len(),
+                            failed_count: total.saturating_sub(mapping.len()),
+                            failures: Vec::new(),
+                        };
+                        *tag_mapping = Some(mapping);
+                        counts
+                    }))
+                }
+                None => None,
+            },
+            BackupModule::Following => match data.following.take() {
+                Some(relations) => {
+                    let total = relations.len();
+                    let following = options.following.clone();
+                    let result = match tag_mapping.clone() {
+                        Some(mapping) => {
+                            self.following
+                                .restore_following_with_mapping(relations, following, mapping)
+                                .await
+                        }
+                        None => self.following.restore_following(relations, following).await,
+                    };
+                    Some(result.map(|r| RestoreCounts::from_following(total, r)))
+                }
+                None => None,
+            },
+            BackupModule::Favorites => match data.favorites.take() {
+                Some(folders) => Some(
+                    self.favorites
+                        .restore_favorites(folders, options.favorites.clone())
+                        .await
+                        .map(RestoreCounts::from),
+                ),
+                None => None,
+            },
+            BackupModule::ToView => match data.toview.take() {
+                Some(videos) => Some(
+                    self.toview
+                        .restore_toview(videos)
+                        .await
+                        .map(RestoreCounts::from),
+                ),
+                None => None,
+            },
+            BackupModule::Bangumi => match data.bangumi.take() {
+                Some(list) => Some(
+                    self.bangumi
+                        .restore_bangumi(list)
+                        .await
+                        .map(RestoreCounts::from),
+                ),
+                None => None,
+            },
+            BackupModule::Blacklist => match data.blacklist.take() {
+                Some(users) => {
+                    let total = users.len();
+                    Some(
+                        self.blacklist
+                            .restore_blacklist(users, options.blacklist.clone())
+                            .await
+                            .map(|r| RestoreCounts::from_blacklist(total, r)),
+                    )
+                }
+                None => None,
+            },
+            BackupModule::Followers | BackupModule::History => {
+                // 只能备份的模块,归档中包含时在报告中说明
+                if data.count(module).is_some() {
+                    Some(Err(BiliError::business("该模块不支持还原")))
+                } else {
+                    None
+                }
+            }
+        };
+
+        let duration_ms = started.elapsed().as_millis() as u64;
+        let result = module_result(module, outcome, duration_ms);
+        match result.status {
+            ModuleRestoreStatus::Completed => tracing::info!(
+                "{}还原完成: 成功 {} 项，失败 {} 项",
+                module.display_name(),
+                result.success_count,
+                result.failed_count
+            ),
+            _ => tracing::warn!(
+                "{}未还原: {}",
+                module.display_name(),
+                result.message.as_deref().unwrap_or_default()
+            ),
+        }
+        result
+    }
+}
+
+/// 按还原顺序排列选定的模块 (为空时返回全部模块)
+pub(crate) fn restore_order(modules: &[BackupModule]) -> Vec<BackupModule> {
+    RESTORE_ORDER
+        .into_iter()
+        .filter(|module| modules.is_empty() || modules.contains(module))
+        .collect()
+}
+
+/// 生成单个模块的还原结果
+///
+/// `outcome` 为None表示归档中没有该模块的数据。
+fn module_result(
+    module: BackupModule,
+    outcome: Option<Result<RestoreCounts>>,
+    duration_ms: u64,
+) -> ModuleRestoreResult {
+    let (status, counts, message) = match outcome {
+        Some(Ok(counts)) => (ModuleRestoreStatus::Completed, counts, None),
+        Some(Err(e)) if !module.is_restorable() => (
+            ModuleRestoreStatus::Skipped,
+            RestoreCounts::default(),
+            Some(e.to_string()),
+        ),
+        Some(Err(e)) => (
+            ModuleRestoreStatus::Failed,
+            RestoreCounts::default(),
+            Some(e.to_string()),
+        ),
+        None => (
+            ModuleRestoreStatus::Skipped,
+            RestoreCounts::default(),
+            Some("归档中没有该模块的数据".to_string()),
+        ),
+    };
+
+    ModuleRestoreResult {
+        module,
+        status,
+        total: counts.total,
+        success_count: counts.success_count,
+        failed_count: counts.failed_count,
+        failures: counts.failures,
+        duration_ms,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_order() {
+        assert_eq!(restore_order(&[]), RESTORE_ORDER.to_vec());
+        assert_eq!(
+            restore_order(&[
+                BackupModule::Blacklist,
+                BackupModule::Following,
+                BackupModule::RelationTags,
+            ]),
+            vec![
+                BackupModule::RelationTags,
+                BackupModule::Following,
+                BackupModule::Blacklist
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_result_status() {
+        let result = module_result(
+            BackupModule::Following,
+            Some(Ok(RestoreCounts::from_following(
+                3,
+                FollowingRestoreResult {
+                    success_count: 2,
+                    failed_count: 1,
+                    failures: vec![(42, "已注销".to_string())],
+                    tag_mapping: HashMap::new(),
+                },
+            ))),
+            10,
+        );
+        assert_eq!(result.status, ModuleRestoreStatus::Completed);
+        assert_eq!(result.total, 3);
+        assert_eq!(result.failures, vec!["42: 已注销".to_string()]);
+
+        let result = module_result(
+            BackupModule::Favorites,
+            Some(Err(BiliError::auth("未登录"))),
+            0,
+        );
+        assert_eq!(result.status, ModuleRestoreStatus::Failed);
+
+        let result = module_result(
+            BackupModule::History,
+            Some(Err(BiliError::business("该模块不支持还原"))),
+            0,
+        );
+        assert_eq!(result.status, ModuleRestoreStatus::Skipped);
+
+        let result = module_result(BackupModule::ToView, None, 0);
+        assert_eq!(result.status, ModuleRestoreStatus::Skipped);
+    }
+}
@@ -3,7 +3,10 @@ use crate::api::{
     endpoints::{API_HISTORY_CLEAR, API_HISTORY_LIST},
     error::BiliError,
     models::{ApiResult, ClearResult, History},
+    pagination::{fetch_cursor_pages, CursorPage, CursorSpec, FetchOutcome},
 };
+use crate::backup::{jsonl, BackupModule};
+use crate::jobs;
 use serde::Deserialize;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -27,7 +30,7 @@ struct HistoryCursorData {
 }
 
 /// 历史记录游标
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
 struct HistoryCursor {
     /// 最大历史记录ID
     #[serde(default)]
@@ -35,8 +38,43 @@ struct HistoryCursor {
     /// 查看时间戳
     #[serde(default)]
     view_at: i64,
+    /// 业务类型
+    #[serde(default)]
+    business: String,
+}
+
+/// 历史记录游标规则
+///
+/// 下一页通过 `max`、`view_at`、`business` 三个参数共同定位,
+/// `max` 为0表示已经到达末尾。
+struct HistoryCursorSpec;
+
+impl CursorSpec for HistoryCursorSpec {
+    type Data = HistoryCursorData;
+    type Item = History;
+    type Cursor = HistoryCursor;
+
+    fn parse_page(&self, data: HistoryCursorData) -> CursorPage<History, HistoryCursor> {
+        let has_more = !data.list.is_empty() && data.cursor.max != 0;
+        CursorPage {
+            items: data.list,
+            next: Some(data.cursor),
+            has_more,
+        }
+    }
+
+    fn cursor_params(&self, cursor: &HistoryCursor) -> Vec<(String, String)> {
+        vec![
+            ("max".to_string(), cursor.max.to_string()),
+            ("view_at".to_string(), cursor.view_at.to_string()),
+            ("business".to_string(), cursor.business.clone()),
+        ]
+    }
 }
 
+/// 历史记录最大翻页次数 (每页20条)
+const HISTORY_MAX_PAGES: usize = 5000;
+
 impl HistoryService {
     /// 创建新的历史记录服务实例
     ///
@@ -87,58 +125,26 @@ impl HistoryService {
     /// # }
     /// ```
     pub async fn backup_history(&self) -> Result<Vec<History>, BiliError> {
-        let mut all_history = Vec::new();
-        let mut max: Option<u64> = None;
-        let mut view_at: Option<i64> = None;
-
-        loop {
-            // 构建请求URL
-            let url = if let (Some(max_val), Some(view_at_val)) = (max, view_at) {
-                format!(
-                    "{}?max={}&view_at={}",
-                    API_HISTORY_LIST, max_val, view_at_val
-                )
-            } else {
-                API_HISTORY_LIST.to_string()
-            };
-
-            tracing::info!("获取历史记录: {}", url);
-
-            // 发送请求
-            let client = self.client.read().await;
-            let response = client.get_with_retry(&url).await?;
-            drop(client); // 释放读锁
-
-            // 解析响应
-            let result: ApiResult<HistoryCursorData> = response.json().await?;
-            let data = result.into_data()?;
-
-            // 如果没有更多数据，退出循环
-            if data.list.is_empty() {
-                tracing::info!("历史记录获取完成，共 {} 条", all_history.len());
-                break;
-            }
-
-            // 添加到结果集
-            let count = data.list.len();
-            all_history.extend(data.list);
-
-            // 更新游标
-            max = Some(data.cursor.max);
-            view_at = Some(data.cursor.view_at);
+        self.backup_history_with_outcome()
+            .await
+            .map(FetchOutcome::into_items)
+    }
 
-            tracing::info!(
-                "本次获取 {} 条，累计 {} 条",
-                count,
-                all_history.len()
-            );
+    /// 备份历史记录,并返回完整性统计
+    ///
+    /// 历史记录接口不报告总数,统计中的预期数量始终为空;
+    /// 翻页达到上限或游标异常而提前停止时统计会标记为截断。
+    pub async fn backup_history_with_outcome(&self) -> Result<FetchOutcome<History>, BiliError> {
+        tracing::info!("获取历史记录");
 
-            // 防风控延迟
-            let client = self.client.read().await;
-            client.delay_random().await;
-        }
+        // 只在复制客户端时持有锁,翻页期间不阻塞登录等写操作
+        let client = jobs::bind_client(self.client.read().await.clone());
+        let outcome =
+            fetch_cursor_pages(&client, API_HISTORY_LIST, &HistoryCursorSpec, HISTORY_MAX_PAGES)
+                .await?;
 
-        Ok(all_history)
+        tracing::info!("历史记录获取完成，{}", outcome.completeness.summary());
+        Ok(outcome)
     }
 
     /// 清空历史记录
@@ -184,12 +190,15 @@ impl HistoryService {
         })
     }
 
-    /// 导出历史记录到JSON文件
+    /// 导出历史记录到JSON Lines文件
+    ///
+    /// 每条历史记录占一行。提供口令时文件会被加密。
     ///
     /// # 参数
     ///
     /// * `history` - 历史记录列表
     /// * `file_path` - 导出文件路径
+    /// * `passphrase` - 加密口令,None表示不加密
     ///
     /// # 返回
     ///
@@ -206,7 +215,7 @@ impl HistoryService {
     /// # let client = Arc::new(RwLock::new(BiliClient::new()));
     /// let service = HistoryService::new(client);
     /// let history = service.backup_history().await?;
-    /// service.export_to_file(&history, "history.json").await?;
+    /// service.export_to_file(&history, "history.jsonl", None).await?;
     /// # Ok(())
     /// # }
     /// ```
@@ -214,23 +223,20 @@ impl HistoryService {
         &self,
         history: &[History],
         file_path: &str,
+        passphrase: Option<&str>,
     ) -> Result<(), BiliError> {
-        let json = serde_json::to_string_pretty(history)
-            .map_err(|e| BiliError::parse(format!("序列化失败: {}", e)))?;
-
-        tokio::fs::write(file_path, json)
-            .await
-            .map_err(|e| BiliError::io(format!("写入文件失败: {}", e)))?;
+        jsonl::export_jsonl(file_path, BackupModule::History, history, passphrase).await?;
 
         tracing::info!("历史记录已导出到: {}", file_path);
         Ok(())
     }
 
-    /// 从JSON文件导入历史记录
+    /// 从导出文件导入历史记录
     ///
     /// # 参数
     ///
     /// * `file_path` - 导入文件路径
+    /// * `passphrase` - 解密口令,文件未加密时忽略
     ///
     /// # 返回
     ///
@@ -246,17 +252,17 @@ impl HistoryService {
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// # let client = Arc::new(RwLock::new(BiliClient::new()));
     /// let service = HistoryService::new(client);
-    /// let history = service.import_from_file("history.json").await?;
+    /// let history = service.import_from_file("history.jsonl", None).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn import_from_file(&self, file_path: &str) -> Result<Vec<History>, BiliError> {
-        let json = tokio::fs::read_to_string(file_path)
-            .await
-            .map_err(|e| BiliError::io(format!("读取文件失败: {}", e)))?;
-
-        let history: Vec<History> = serde_json::from_str(&json)
-            .map_err(|e| BiliError::parse(format!("反序列化失败: {}", e)))?;
+    pub async fn import_from_file(
+        &self,
+        file_path: &str,
+        passphrase: Option<&str>,
+    ) -> Result<Vec<History>, BiliError> {
+        let history: Vec<History> =
+            jsonl::import_jsonl(file_path, BackupModule::History, passphrase).await?;
 
         tracing::info!("从 {} 导入了 {} 条历史记录", file_path, history.len());
         Ok(history)
@@ -267,6 +273,43 @@ impl HistoryService {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_history_cursor_spec() {
+        let data: HistoryCursorData = serde_json::from_value(serde_json::json!({
+            "cursor": {"max": 123, "view_at": 1700000000, "business": "archive"},
+            "list": [{"title": "测试视频"}]
+        }))
+        .unwrap();
+
+        let page = HistoryCursorSpec.parse_page(data);
+        assert_eq!(page.items.len(), 1);
+        assert!(page.has_more);
+
+        let cursor = page.next.unwrap();
+        let params = HistoryCursorSpec.cursor_params(&cursor);
+        assert_eq!(
+            params,
+            vec![
+                ("max".to_string(), "123".to_string()),
+                ("view_at".to_string(), "1700000000".to_string()),
+                ("business".to_string(), "archive".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_history_cursor_spec_end() {
+        let data: HistoryCursorData = serde_json::from_value(serde_json::json!({
+            "cursor": {"max": 0, "view_at": 0, "business": ""},
+            "list": []
+        }))
+        .unwrap();
+
+        let page = HistoryCursorSpec.parse_page(data);
+        assert!(page.items.is_empty());
+        assert!(!page.has_more);
+    }
+
     #[test]
     fn test_create_history_service() {
         let client = Arc::new(RwLock::new(BiliClient::new()));
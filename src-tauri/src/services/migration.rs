@@ -0,0 +1,564 @@
+//! 账号迁移服务
+//!
+//! 同时持有两个账号的会话,把数据从源账号直接迁移到目标账号。
+//! 源账号只用于读取数据,所有写操作都发生在目标账号上。
+//!
+//! 迁移按模块进行: 先从源账号获取数据并缓存到迁移目录,再还原到目标账号。
+//! 每一步完成后都会保存迁移状态,中断后再次运行会跳过已完成的模块,
+//! 已获取但未还原的模块直接使用缓存的数据。全部模块完成后对比两个账号的数量,
+//! 之后再次运行会开始新的迁移。
+//! 还原过程中的每一步写操作记录在操作日志中,模块中途中断时不会重复已完成的步骤。
+
+use crate::api::client::BiliClient;
+use crate::api::error::{BiliError, Result};
+use crate::backup::archive::{run_blocking, write_file_atomic};
+use crate::backup::{BackupData, BackupModule};
+use crate::jobs::journal::{self, Journal, JournalHeader};
+use crate::jobs::{self, JobLogLevel, JobStatus};
+use crate::services::full_restore::restore_order;
+use crate::services::{
+    FullBackupService, FullRestoreOptions, FullRestoreService, ModuleRestoreResult,
+    ModuleRestoreStatus,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// 迁移状态文件名
+const STATE_FILE_NAME: &str = "state.json";
+
+/// 操作日志文件名
+const JOURNAL_FILE_NAME: &str = "journal.jsonl";
+
+/// 单个模块的迁移进度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStepStatus {
+    /// 尚未开始
+    Pending,
+    /// 已从源账号获取并缓存,尚未还原到目标账号
+    Fetched,
+    /// 已还原到目标账号
+    Completed,
+    /// 获取或还原出错,再次运行时会重试
+    Failed,
+}
+
+/// 单个模块的迁移状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModuleMigrationState {
+    /// 模块
+    pub module: BackupModule,
+    /// 迁移进度
+    pub status: MigrationStepStatus,
+    /// 源账号的数据项数量 (获取后记录)
+    pub source_count: Option<usize>,
+    /// 还原到目标账号的结果
+    pub restore: Option<ModuleRestoreResult>,
+    /// 出错原因
+    pub error: Option<String>,
+}
+
+impl ModuleMigrationState {
+    fn pending(module: BackupModule) -> Self {
+        Self {
+            module,
+            status: MigrationStepStatus::Pending,
+            source_count: None,
+            restore: None,
+            error: None,
+        }
+    }
+}
+
+/// 迁移后的数量对比
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReconcileEntry {
+    /// 模块
+    pub module: BackupModule,
+    /// 源账号的数据项数量
+    pub source_count: usize,
+    /// 目标账号的数据项数量 (获取失败时为None)
+    pub target_count: Option<usize>,
+    /// 目标账号比源账号少的数量
+    pub missing: usize,
+    /// 获取目标账号数据失败的原因
+    pub error: Option<String>,
+}
+
+/// 账号迁移状态
+///
+/// 保存在迁移目录下,用于中断后继续迁移。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationState {
+    /// 源账号UID
+    pub source_uid: u64,
+    /// 目标账号UID
+    pub target_uid: u64,
+    /// 开始迁移的时间 (时间戳,秒)
+    pub created_at: i64,
+    /// 最后更新的时间 (时间戳,秒)
+    pub updated_at: i64,
+    /// 各模块的迁移状态 (按还原顺序)
+    pub modules: Vec<ModuleMigrationState>,
+    /// 目标账号的分组映射 (源分组ID → 目标分组ID),关注列表还原前使用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag_mapping: Option<HashMap<i64, i64>>,
+    /// 数量对比 (全部模块完成后生成)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reconciliation: Option<Vec<ReconcileEntry>>,
+}
+
+impl MigrationState {
+    /// 创建新的迁移状态
+    fn new(source_uid: u64, target_uid: u64) -> Self {
+        let now = chrono::Utc::now().timestamp();
+        Self {
+            source_uid,
+            target_uid,
+            created_at: now,
+            updated_at: now,
+            modules: Vec::new(),
+            tag_mapping: None,
+            reconciliation: None,
+        }
+    }
+
+    /// 是否所有模块都已完成
+    pub fn is_complete(&self) -> bool {
+        self.modules
+            .iter()
+            .all(|m| m.status == MigrationStepStatus::Completed)
+    }
+
+    /// 按选定的模块更新迁移计划
+    ///
+    /// 已有的模块保留原来的进度,新选定的模块从头开始;只能备份的模块不参与迁移。
+    fn plan(&mut self, modules: &[BackupModule]) {
+        let mut existing: HashMap<BackupModule, ModuleMigrationState> = self
+            .modules
+            .drain(..)
+            .map(|state| (state.module, state))
+            .collect();
+        self.modules = restore_order(modules)
+            .into_iter()
+            .filter(BackupModule::is_restorable)
+            .map(|module| {
+                existing
+                    .remove(&module)
+                    .unwrap_or_else(|| ModuleMigrationState::pending(module))
+            })
+            .collect();
+    }
+}
+
+/// 迁移目录
+///
+/// # 参数
+///
+/// * `data_dir` - 应用数据目录
+/// * `source_uid` - 源账号UID
+/// * `target_uid` - 目标账号UID
+pub fn migration_dir(data_dir: impl AsRef<Path>, source_uid: u64, target_uid: u64) -> PathBuf {
+    data_dir
+        .as_ref()
+        .join("migrations")
+        .join(format!("{}-{}", source_uid, target_uid))
+}
+
+/// 读取迁移状态 (没有进行中的迁移时返回None)
+///
+/// # 参数
+///
+/// * `dir` - 迁移目录
+pub async fn load_migration_state(dir: impl AsRef<Path>) -> Result<Option<MigrationState>> {
+    let path = dir.as_ref().join(STATE_FILE_NAME);
+    run_blocking(move || {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes =
+            std::fs::read(&path).map_err(|e| BiliError::io(format!("读取迁移状态失败: {}", e)))?;
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| BiliError::parse(format!("迁移状态解析失败: {}", e)))
+    })
+    .await
+}
+
+/// 账号迁移
+pub struct AccountMigration {
+    source: FullBackupService,
+    target: FullRestoreService,
+    target_reader: FullBackupService,
+    data_dir: PathBuf,
+}
+
+impl AccountMigration {
+    /// 创建账号迁移
+    ///
+    /// # 参数
+    ///
+    /// * `source` - 源账号的HTTP客户端 (只读取数据)
+    /// * `target` - 目标账号的HTTP客户端
+    /// * `data_dir` - 应用数据目录,迁移状态和缓存保存在其下的 `migrations` 目录
+    pub fn new(
+        source: Arc<RwLock<BiliClient>>,
+        target: Arc<RwLock<BiliClient>>,
+        data_dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            source: FullBackupService::new(source),
+            target: FullRestoreService::new(target.clone()),
+            target_reader: FullBackupService::new(target),
+            data_dir: data_dir.into(),
+        }
+    }
+
+    /// 使用两个账号的Cookie创建账号迁移
+    ///
+    /// 两个账号各自使用独立的会话,不影响当前登录的账号。
+    pub fn from_cookies(
+        source_cookie: impl Into<String>,
+        target_cookie: impl Into<String>,
+        data_dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self::new(session(source_cookie), session(target_cookie), data_dir)
+    }
+
+    /// 运行 (或继续) 迁移
+    ///
+    /// 同一对账号已有未完成的迁移时,从中断处继续;上次迁移已完成时重新开始。
+    ///
+    /// # 参数
+    ///
+    /// * `options` - 要迁移的模块和各模块的还原选项
+    ///
+    /// # 返回
+    ///
+    /// 迁移结束后的状态 (包括各模块的结果和数量对比)
+    ///
+    /// # 错误
+    ///
+    /// - 任一账号未登录
+    /// - 源账号和目标账号相同
+    /// - 迁移状态无法读写
+    /// - 任务已取消
+    pub async fn run(&self, options: &FullRestoreOptions) -> Result<MigrationState> {
+        let source_uid = self.source.source().await?.uid;
+        let target_uid = self.target_reader.source().await?.uid;
+        if source_uid == target_uid {
+            return Err(BiliError::param("源账号和目标账号不能相同"));
+        }
+
+        let dir = migration_dir(&self.data_dir, source_uid, target_uid);
+        // 上次迁移已全部完成时重新开始,源账号之后的变化也会被迁移
+        let mut state = match load_migration_state(&dir).await? {
+            Some(state) if !state.is_complete() => {
+                tracing::info!("继续账号迁移: {} → {}", source_uid, target_uid);
+                state
+            }
+            _ => {
+                tracing::info!("开始账号迁移: {} → {}", source_uid, target_uid);
+                remove_file(&dir.join(JOURNAL_FILE_NAME)).await;
+                MigrationState::new(source_uid, target_uid)
+            }
+        };
+        state.plan(&options.modules);
+        state.reconciliation = None;
+        save_state(&dir, &mut state).await?;
+
+        // 操作日志记录已应用到目标账号的每一步,模块中途中断时再次运行会跳过这些步骤
+        let journal = Arc::new(open_journal(&dir, source_uid, target_uid)?);
+        journal::scope(
+            journal.clone(),
+            self.migrate_pending(&dir, &mut state, options),
+        )
+        .await?;
+
+        if state.is_complete() {
+            if let Err(e) = journal.finish(JobStatus::Completed) {
+                tracing::warn!("{}", e);
+            }
+            state.reconciliation = Some(self.reconcile(&state).await);
+        }
+        save_state(&dir, &mut state).await?;
+
+        tracing::info!(
+            "账号迁移结束: {} → {} ({})",
+            source_uid,
+            target_uid,
+            if state.is_complete() {
+                "已完成"
+            } else {
+                "部分模块未完成"
+            }
+        );
+        Ok(state)
+    }
+
+    /// 依次迁移未完成的模块
+    async fn migrate_pending(
+        &self,
+        dir: &Path,
+        state: &mut MigrationState,
+        options: &FullRestoreOptions,
+    ) -> Result<()> {
+        for index in 0..state.modules.len() {
+            if state.modules[index].status == MigrationStepStatus::Completed {
+                continue;
+            }
+            // 取消时迁移状态已保存,再次运行从该模块继续
+            jobs::checkpoint().await?;
+            jobs::log(
+                JobLogLevel::Info,
+                format!("迁移{}", state.modules[index].module.display_name()),
+            );
+            self.migrate_module(dir, state, index, options).await?;
+        }
+        Ok(())
+    }
+
+    /// 迁移单个模块,每一步后保存状态
+    async fn migrate_module(
+        &self,
+        dir: &Path,
+        state: &mut MigrationState,
+        index: usize,
+        options: &FullRestoreOptions,
+    ) -> Result<()> {
+        let module = state.modules[index].module;
+        let cache = dir.join(module.file_name());
+
+        // 1. 从源账号获取 (已缓存时直接读取缓存)
+        let mut data = match read_cache(&cache, module).await? {
+            Some(data) => data,
+            None => match self.source.fetch_module(module).await {
+                Ok(fetched) => {
+                    write_cache(&cache, module, &fetched.data).await?;
+                    let step = &mut state.modules[index];
+                    step.status = MigrationStepStatus::Fetched;
+                    step.source_count = item_count(&fetched.data, module);
+                    step.error = None;
+                    save_state(dir, state).await?;
+                    fetched.data
+                }
+                Err(e) => {
+                    tracing::warn!("从源账号获取{}失败: {}", module.display_name(), e);
+                    let step = &mut state.modules[index];
+                    step.status = MigrationStepStatus::Failed;
+                    step.error = Some(e.to_string());
+                    return save_state(dir, state).await;
+                }
+            },
+        };
+        if state.modules[index].source_count.is_none() {
+            state.modules[index].source_count = item_count(&data, module);
+        }
+
+        // 2. 还原到目标账号
+        let result = self
+            .target
+            .restore_module(module, &mut data, options, &mut state.tag_mapping)
+            .await;
+        let completed = result.status != ModuleRestoreStatus::Failed;
+        let step = &mut state.modules[index];
+        if completed {
+            step.status = MigrationStepStatus::Completed;
+            step.error = None;
+        } else {
+            step.status = MigrationStepStatus::Failed;
+            step.error = result.message.clone();
+        }
+        step.restore = Some(result);
+        save_state(dir, state).await?;
+
+        // 还原失败时保留缓存,再次运行时不必重新获取
+        if completed {
+            remove_file(&cache).await;
+        }
+        Ok(())
+    }
+
+    /// 对比源账号和目标账号的数据项数量
+    async fn reconcile(&self, state: &MigrationState) -> Vec<ReconcileEntry> {
+        let mut entries = Vec::new();
+        for step in &state.modules {
+            let source_count = step.source_count.unwrap_or(0);
+            let entry = match self.target_reader.fetch_module(step.module).await {
+                Ok(fetched) => {
+                    let target_count = item_count(&fetched.data, step.module).unwrap_or(0);
+                    ReconcileEntry {
+                        module: step.module,
+                        source_count,
+                        target_count: Some(target_count),
+                        missing: source_count.saturating_sub(target_count),
+                        error: None,
+                    }
+                }
+                Err(e) => ReconcileEntry {
+                    module: step.module,
+                    source_count,
+                    target_count: None,
+                    missing: 0,
+                    error: Some(e.to_string()),
+                },
+            };
+            if entry.missing > 0 {
+                tracing::warn!(
+                    "{}迁移后目标账号少 {} 项",
+                    step.module.display_name(),
+                    entry.missing
+                );
+            }
+            entries.push(entry);
+        }
+        entries
+    }
+}
+
+/// 数据项数量 (收藏夹按收藏的内容计数)
+fn item_count(data: &BackupData, module: BackupModule) -> Option<usize> {
+    match module {
+        BackupModule::Favorites => data
+            .favorites
+            .as_ref()
+            .map(|folders| folders.iter().map(|f| f.media_list.len()).sum()),
+        _ => data.count(module),
+    }
+}
+
+/// 打开迁移目录中的操作日志,不存在时创建
+fn open_journal(dir: &Path, source_uid: u64, target_uid: u64) -> Result<Journal> {
+    let path = dir.join(JOURNAL_FILE_NAME);
+    if path.exists() {
+        return Journal::open(path).map(|(journal, _)| journal);
+    }
+    let header = JournalHeader::new(
+        format!("{}-{}", source_uid, target_uid),
+        "migration",
+        "账号迁移",
+    );
+    Journal::create(path, header)
+}
+
+/// 使用Cookie创建独立的会话
+fn session(cookie: impl Into<String>) -> Arc<RwLock<BiliClient>> {
+    let mut client = BiliClient::new();
+    client.set_cookie(cookie.into());
+    Arc::new(RwLock::new(client))
+}
+
+/// 保存迁移状态
+async fn save_state(dir: &Path, state: &mut MigrationState) -> Result<()> {
+    state.updated_at = chrono::Utc::now().timestamp();
+    let bytes = serde_json::to_vec_pretty(state)
+        .map_err(|e| BiliError::parse(format!("序列化迁移状态失败: {}", e)))?;
+    let dir = dir.to_path_buf();
+    run_blocking(move || {
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| BiliError::io(format!("创建迁移目录失败: {}", e)))?;
+        write_file_atomic(&dir.join(STATE_FILE_NAME), &bytes)
+    })
+    .await
+}
+
+/// 缓存从源账号获取的模块数据
+async fn write_cache(path: &Path, module: BackupModule, data: &BackupData) -> Result<()> {
+    let Some(bytes) = data.module_to_json(module)? else {
+        return Ok(());
+    };
+    let path = path.to_path_buf();
+    run_blocking(move || write_file_atomic(&path, &bytes)).await
+}
+
+/// 读取缓存的模块数据 (没有缓存时返回None)
+async fn read_cache(path: &Path, module: BackupModule) -> Result<Option<BackupData>> {
+    let path = path.to_path_buf();
+    run_blocking(move || {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes =
+            std::fs::read(&path).map_err(|e| BiliError::io(format!("读取迁移缓存失败: {}", e)))?;
+        let mut data = BackupData::default();
+        data.set_module_json(module, &bytes)?;
+        Ok(Some(data))
+    })
+    .await
+}
+
+/// 删除已还原模块的缓存或上次迁移遗留的文件
+async fn remove_file(path: &Path) {
+    if let Err(e) = tokio::fs::remove_file(path).await {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("删除迁移文件失败: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::models::RelationTag;
+
+    #[test]
+    fn test_plan_keeps_progress_and_order() {
+        let mut state = MigrationState::new(1, 2);
+        state.plan(&[BackupModule::Blacklist, BackupModule::Following]);
+        state.modules[0].status = MigrationStepStatus::Completed;
+
+        state.plan(&[
+            BackupModule::History,
+            BackupModule::Blacklist,
+            BackupModule::RelationTags,
+            BackupModule::Following,
+        ]);
+        assert_eq!(
+            state.modules.iter().map(|m| m.module).collect::<Vec<_>>(),
+            vec![
+                BackupModule::RelationTags,
+                BackupModule::Following,
+                BackupModule::Blacklist
+            ]
+        );
+        assert_eq!(state.modules[1].status, MigrationStepStatus::Completed);
+        assert_eq!(state.modules[0].status, MigrationStepStatus::Pending);
+        assert!(!state.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_state_and_cache_roundtrip() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("1-2");
+        assert!(load_migration_state(&dir).await.unwrap().is_none());
+
+        let mut state = MigrationState::new(1, 2);
+        state.plan(&[]);
+        state.tag_mapping = Some(HashMap::from([(5, 50)]));
+        save_state(&dir, &mut state).await.unwrap();
+        assert_eq!(load_migration_state(&dir).await.unwrap(), Some(state));
+
+        let cache = dir.join(BackupModule::RelationTags.file_name());
+        let data = BackupData {
+            relation_tags: Some(vec![RelationTag::new(5, "特别关注".to_string())]),
+            ..Default::default()
+        };
+        write_cache(&cache, BackupModule::RelationTags, &data)
+            .await
+            .unwrap();
+        let cached = read_cache(&cache, BackupModule::RelationTags)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(item_count(&cached, BackupModule::RelationTags), Some(1));
+
+        remove_file(&cache).await;
+        assert!(read_cache(&cache, BackupModule::RelationTags)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -27,6 +27,15 @@ pub mod blacklist;
 /// 收藏夹管理服务模块
 pub mod favorites;
 
+/// 一键完整备份服务模块
+pub mod full_backup;
+
+/// 一键完整还原服务模块
+pub mod full_restore;
+
+/// 账号迁移服务模块
+pub mod migration;
+
 // 导出常用类型
 pub use auth::{AuthService, AuthUser};
 pub use bangumi::BangumiService;
@@ -36,3 +45,11 @@ pub use following::{FollowingClearResult, FollowingRestoreResult, FollowingServi
 pub use history::HistoryService;
 pub use toview::ToViewService;
 pub use favorites::{FavoritesService, FavFolderWithMedia, FavRestoreOptions};
+pub use full_backup::{FullBackupReport, FullBackupService, ModuleBackupResult};
+pub use full_restore::{
+    FullRestoreOptions, FullRestoreReport, FullRestoreService, ModuleRestoreResult,
+    ModuleRestoreStatus,
+};
+pub use migration::{
+    AccountMigration, MigrationState, MigrationStepStatus, ModuleMigrationState, ReconcileEntry,
+};
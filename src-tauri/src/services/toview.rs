@@ -3,10 +3,16 @@ use crate::api::{
     endpoints::{API_TOVIEW_ADD, API_TOVIEW_CLEAR, API_TOVIEW_DEL, API_TOVIEW_LIST},
     error::BiliError,
     models::{ApiResult, ClearResult, RestoreResult, ToView, ToViewList},
+    pagination::FetchOutcome,
 };
+use crate::backup::{jsonl, BackupModule};
+use crate::jobs::{self, journal};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// 操作日志中添加稍后再看的动作名
+const JOURNAL_ADD: &str = "toview_add";
+
 /// 稍后再看服务
 ///
 /// 提供稍后再看的备份、还原、清空等功能。
@@ -62,6 +68,15 @@ impl ToViewService {
     /// # }
     /// ```
     pub async fn backup_toview(&self) -> Result<Vec<ToView>, BiliError> {
+        self.backup_toview_with_outcome()
+            .await
+            .map(FetchOutcome::into_items)
+    }
+
+    /// 备份稍后再看列表,并返回完整性统计
+    ///
+    /// 失效视频可能不在列表中返回,此时实际数量会少于接口报告的 `count`。
+    pub async fn backup_toview_with_outcome(&self) -> Result<FetchOutcome<ToView>, BiliError> {
         tracing::info!("获取稍后再看列表");
 
         let client = self.client.read().await;
@@ -72,8 +87,10 @@ impl ToViewService {
         let result: ApiResult<ToViewList> = response.json().await?;
         let data = result.into_data()?;
 
-        tracing::info!("获取到 {} 个稍后再看", data.list.len());
-        Ok(data.list)
+        let expected = usize::try_from(data.count).ok();
+        let outcome = FetchOutcome::new(data.list, expected);
+        tracing::info!("获取到 {} 个稍后再看 ({})", outcome.items.len(), outcome.completeness.summary());
+        Ok(outcome)
     }
 
     /// 还原稍后再看列表
@@ -114,8 +131,18 @@ impl ToViewService {
 
         tracing::info!("开始还原 {} 个稍后再看", total);
 
-        for video in videos {
+        for (index, video) in videos.into_iter().enumerate() {
+            jobs::checkpoint().await?;
+            jobs::progress(index + 1, Some(total), format!("添加 {}", video.title));
+
+            // 恢复中断的任务时跳过已添加的视频
+            if journal::completed(JOURNAL_ADD, video.aid).is_some() {
+                success_count += 1;
+                continue;
+            }
+
             let result = self.add_toview(video.aid).await;
+            journal::record(JOURNAL_ADD, video.aid, &result);
 
             match result {
                 Ok(_) => {
@@ -253,12 +280,15 @@ impl ToViewService {
         Ok(())
     }
 
-    /// 导出稍后再看列表到JSON文件
+    /// 导出稍后再看列表到JSON Lines文件
+    ///
+    /// 每个视频占一行。提供口令时文件会被加密。
     ///
     /// # 参数
     ///
     /// * `videos` - 视频列表
     /// * `file_path` - 导出文件路径
+    /// * `passphrase` - 加密口令,None表示不加密
     ///
     /// # 返回
     ///
@@ -267,34 +297,31 @@ impl ToViewService {
         &self,
         videos: &[ToView],
         file_path: &str,
+        passphrase: Option<&str>,
     ) -> Result<(), BiliError> {
-        let json = serde_json::to_string_pretty(videos)
-            .map_err(|e| BiliError::parse(format!("序列化失败: {}", e)))?;
-
-        tokio::fs::write(file_path, json)
-            .await
-            .map_err(|e| BiliError::io(format!("写入文件失败: {}", e)))?;
+        jsonl::export_jsonl(file_path, BackupModule::ToView, videos, passphrase).await?;
 
         tracing::info!("稍后再看列表已导出到: {}", file_path);
         Ok(())
     }
 
-    /// 从JSON文件导入稍后再看列表
+    /// 从导出文件导入稍后再看列表
     ///
     /// # 参数
     ///
     /// * `file_path` - 导入文件路径
+    /// * `passphrase` - 解密口令,文件未加密时忽略
     ///
     /// # 返回
     ///
     /// 视频列表
-    pub async fn import_from_file(&self, file_path: &str) -> Result<Vec<ToView>, BiliError> {
-        let json = tokio::fs::read_to_string(file_path)
-            .await
-            .map_err(|e| BiliError::io(format!("读取文件失败: {}", e)))?;
-
-        let videos: Vec<ToView> = serde_json::from_str(&json)
-            .map_err(|e| BiliError::parse(format!("反序列化失败: {}", e)))?;
+    pub async fn import_from_file(
+        &self,
+        file_path: &str,
+        passphrase: Option<&str>,
+    ) -> Result<Vec<ToView>, BiliError> {
+        let videos: Vec<ToView> =
+            jsonl::import_jsonl(file_path, BackupModule::ToView, passphrase).await?;
 
         tracing::info!("从 {} 导入了 {} 个稍后再看", file_path, videos.len());
         Ok(videos)